@@ -0,0 +1,378 @@
+use super::types::encode_comparable_bytes;
+use super::EncodeValue;
+use crate::errors::MySQLResult;
+use byteorder::{BigEndian, WriteBytesExt};
+use sqlparser::ast::DataType;
+
+/// One bit for every value of a column, the bit is set if the value is NULL.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NullBitmap {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl NullBitmap {
+    pub fn push(&mut self, null: bool) {
+        if self.len % 64 == 0 {
+            self.bits.push(0);
+        }
+        if null {
+            self.bits[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    pub fn is_null(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The bitmap of the values computed from two columns, which are NULL if any input is NULL.
+    pub fn union(&self, other: &NullBitmap) -> NullBitmap {
+        NullBitmap {
+            bits: self
+                .bits
+                .iter()
+                .zip(other.bits.iter())
+                .map(|(a, b)| a | b)
+                .collect(),
+            len: self.len.min(other.len),
+        }
+    }
+}
+
+/// The values of a column. Integers, doubles and strings are stored in typed vectors so that
+/// expressions and aggregations run over them without matching every value, other types are
+/// kept as `EncodeValue`. The slot of a NULL value holds an arbitrary value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnValues {
+    Int(Vec<i64>),
+    Double(Vec<f64>),
+    /// The value `i` is `data[offsets[i]..offsets[i + 1]]`.
+    Bytes {
+        offsets: Vec<usize>,
+        data: Vec<u8>,
+    },
+    Other(Vec<EncodeValue>),
+}
+
+impl ColumnValues {
+    fn with_capacity(data_type: &DataType, capacity: usize) -> ColumnValues {
+        match data_type {
+            DataType::SmallInt | DataType::Int | DataType::BigInt | DataType::Boolean => {
+                ColumnValues::Int(Vec::with_capacity(capacity))
+            }
+            DataType::Double => ColumnValues::Double(Vec::with_capacity(capacity)),
+            DataType::Char(_) | DataType::Varchar(_) | DataType::String | DataType::Text => {
+                let mut offsets = Vec::with_capacity(capacity + 1);
+                offsets.push(0);
+                ColumnValues::Bytes {
+                    offsets,
+                    data: vec![],
+                }
+            }
+            _ => ColumnValues::Other(Vec::with_capacity(capacity)),
+        }
+    }
+
+    fn push_null(&mut self) {
+        match self {
+            ColumnValues::Int(values) => values.push(0),
+            ColumnValues::Double(values) => values.push(0.0),
+            ColumnValues::Bytes { offsets, data } => offsets.push(data.len()),
+            ColumnValues::Other(values) => values.push(EncodeValue::NULL),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    values: ColumnValues,
+    nulls: NullBitmap,
+}
+
+impl Column {
+    /// An empty column whose values are stored as the values of `data_type`. A value of another
+    /// type can still be pushed, then the column falls back to `ColumnValues::Other`.
+    pub fn with_capacity(data_type: &DataType, capacity: usize) -> Column {
+        Column {
+            values: ColumnValues::with_capacity(data_type, capacity),
+            nulls: NullBitmap::default(),
+        }
+    }
+
+    /// A column of the values computed by expressions, the type is decided by the values.
+    pub fn from_values(values: Vec<EncodeValue>) -> Column {
+        let capacity = values.len();
+        let values_of_type = match values.iter().find(|v| !v.is_null()) {
+            Some(EncodeValue::Int(_)) => ColumnValues::Int(Vec::with_capacity(capacity)),
+            Some(EncodeValue::Double(_)) => ColumnValues::Double(Vec::with_capacity(capacity)),
+            Some(EncodeValue::Bytes(_)) => ColumnValues::with_capacity(&DataType::String, capacity),
+            _ => ColumnValues::Other(Vec::with_capacity(capacity)),
+        };
+        let mut column = Column {
+            values: values_of_type,
+            nulls: NullBitmap::default(),
+        };
+        for v in values {
+            column.push(v);
+        }
+        column
+    }
+
+    pub fn from_ints(values: Vec<i64>, nulls: NullBitmap) -> Column {
+        Column {
+            values: ColumnValues::Int(values),
+            nulls,
+        }
+    }
+
+    pub fn from_doubles(values: Vec<f64>, nulls: NullBitmap) -> Column {
+        Column {
+            values: ColumnValues::Double(values),
+            nulls,
+        }
+    }
+
+    /// A column which has the value `v` in all `len` rows.
+    pub fn repeat(v: &EncodeValue, len: usize) -> Column {
+        let mut nulls = NullBitmap::default();
+        for _ in 0..len {
+            nulls.push(v.is_null());
+        }
+        let values = match v {
+            EncodeValue::NULL => ColumnValues::Int(vec![0; len]),
+            EncodeValue::Int(i) => ColumnValues::Int(vec![*i; len]),
+            EncodeValue::Double(f) => ColumnValues::Double(vec![*f; len]),
+            EncodeValue::Bytes(b) => ColumnValues::Bytes {
+                offsets: (0..=len).map(|i| i * b.len()).collect(),
+                data: b.repeat(len),
+            },
+            v => ColumnValues::Other(vec![v.clone(); len]),
+        };
+        Column { values, nulls }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nulls.len()
+    }
+
+    pub fn values(&self) -> &ColumnValues {
+        &self.values
+    }
+
+    pub fn nulls(&self) -> &NullBitmap {
+        &self.nulls
+    }
+
+    pub fn is_null(&self, i: usize) -> bool {
+        self.nulls.is_null(i)
+    }
+
+    /// Whether the value `i` is regarded as true in a condition, as `EncodeValue::is_true`.
+    pub fn is_true(&self, i: usize) -> bool {
+        if self.is_null(i) {
+            return false;
+        }
+        match &self.values {
+            ColumnValues::Int(values) => values[i] != 0,
+            ColumnValues::Double(values) => values[i] != 0.0,
+            _ => self.value(i).is_true(),
+        }
+    }
+
+    pub fn push(&mut self, v: EncodeValue) {
+        let null = v.is_null();
+        let rest = match (&mut self.values, v) {
+            (values, EncodeValue::NULL) => {
+                values.push_null();
+                None
+            }
+            (ColumnValues::Int(values), EncodeValue::Int(i)) => {
+                values.push(i);
+                None
+            }
+            (ColumnValues::Double(values), EncodeValue::Double(f)) => {
+                values.push(f);
+                None
+            }
+            (ColumnValues::Bytes { offsets, data }, EncodeValue::Bytes(b)) => {
+                data.extend_from_slice(&b);
+                offsets.push(data.len());
+                None
+            }
+            (ColumnValues::Other(values), v) => {
+                values.push(v);
+                None
+            }
+            (_, v) => Some(v),
+        };
+        if let Some(v) = rest {
+            let mut values: Vec<EncodeValue> = (0..self.len()).map(|i| self.value(i)).collect();
+            values.push(v);
+            self.values = ColumnValues::Other(values);
+        }
+        self.nulls.push(null);
+    }
+
+    pub fn value(&self, i: usize) -> EncodeValue {
+        if self.is_null(i) {
+            return EncodeValue::NULL;
+        }
+        match &self.values {
+            ColumnValues::Int(values) => EncodeValue::Int(values[i]),
+            ColumnValues::Double(values) => EncodeValue::Double(values[i]),
+            ColumnValues::Bytes { .. } => EncodeValue::Bytes(self.bytes(i).to_vec()),
+            ColumnValues::Other(values) => values[i].clone(),
+        }
+    }
+
+    /// The value `i` of a column of strings.
+    pub fn bytes(&self, i: usize) -> &[u8] {
+        match &self.values {
+            ColumnValues::Bytes { offsets, data } => &data[offsets[i]..offsets[i + 1]],
+            _ => &[],
+        }
+    }
+
+    /// A column of the values at `indices`.
+    pub fn take(&self, indices: &[usize]) -> Column {
+        let values = match &self.values {
+            ColumnValues::Int(values) => {
+                ColumnValues::Int(indices.iter().map(|i| values[*i]).collect())
+            }
+            ColumnValues::Double(values) => {
+                ColumnValues::Double(indices.iter().map(|i| values[*i]).collect())
+            }
+            ColumnValues::Bytes { .. } => {
+                let mut offsets = Vec::with_capacity(indices.len() + 1);
+                let mut data = vec![];
+                offsets.push(0);
+                for i in indices {
+                    data.extend_from_slice(self.bytes(*i));
+                    offsets.push(data.len());
+                }
+                ColumnValues::Bytes { offsets, data }
+            }
+            ColumnValues::Other(values) => {
+                ColumnValues::Other(indices.iter().map(|i| values[*i].clone()).collect())
+            }
+        };
+        let mut nulls = NullBitmap::default();
+        for i in indices {
+            nulls.push(self.is_null(*i));
+        }
+        Column { values, nulls }
+    }
+
+    /// Append the hash key of the value `i`, it is the same as `encode_hash_key` of the value.
+    pub fn encode_hash_key(&self, i: usize, output: &mut Vec<u8>) -> MySQLResult<()> {
+        if self.is_null(i) {
+            output.push(0);
+            return Ok(());
+        }
+        match &self.values {
+            ColumnValues::Int(values) => {
+                output.push(1);
+                output.write_i64::<BigEndian>(values[i])?;
+            }
+            ColumnValues::Double(values) => encode_f64_hash_key(values[i], output)?,
+            ColumnValues::Bytes { .. } => {
+                output.push(3);
+                encode_comparable_bytes(self.bytes(i), output);
+            }
+            ColumnValues::Other(values) => encode_hash_key(&values[i], output)?,
+        }
+        Ok(())
+    }
+}
+
+fn encode_f64_hash_key(f: f64, output: &mut Vec<u8>) -> MySQLResult<()> {
+    if f.fract() == 0.0 && f.abs() < i64::MAX as f64 {
+        output.push(1);
+        output.write_i64::<BigEndian>(f as i64)?;
+    } else {
+        output.push(2);
+        output.write_u64::<BigEndian>(f.to_bits())?;
+    }
+    Ok(())
+}
+
+/// Encode a value so that values which are equal in SQL have the same bytes, it is used as the
+/// key of hash tables. The encoding of a value never is a prefix of another one, so the keys of
+/// many values can be concatenated.
+pub fn encode_hash_key(v: &EncodeValue, output: &mut Vec<u8>) -> MySQLResult<()> {
+    match v {
+        EncodeValue::NULL => output.push(0),
+        EncodeValue::Int(i) => {
+            output.push(1);
+            output.write_i64::<BigEndian>(*i)?;
+        }
+        EncodeValue::Float(_) | EncodeValue::Double(_) => {
+            encode_f64_hash_key(v.as_f64().unwrap(), output)?
+        }
+        v => {
+            output.push(3);
+            v.encode_comparable(output, &DataType::String)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column() {
+        let mut column = Column::with_capacity(&DataType::Int, 4);
+        column.push(EncodeValue::Int(1));
+        column.push(EncodeValue::NULL);
+        column.push(EncodeValue::Int(3));
+        assert!(matches!(column.values(), ColumnValues::Int(_)));
+        assert_eq!(column.value(1), EncodeValue::NULL);
+        assert!(column.is_true(0) && !column.is_true(1));
+
+        // A value of another type turns the column into a column of `EncodeValue`.
+        column.push(EncodeValue::Bytes(b"a".to_vec()));
+        assert!(matches!(column.values(), ColumnValues::Other(_)));
+        let values: Vec<EncodeValue> = (0..column.len()).map(|i| column.value(i)).collect();
+        assert_eq!(
+            values,
+            vec![
+                EncodeValue::Int(1),
+                EncodeValue::NULL,
+                EncodeValue::Int(3),
+                EncodeValue::Bytes(b"a".to_vec()),
+            ]
+        );
+
+        let strings = Column::from_values(vec![
+            EncodeValue::Bytes(b"ab".to_vec()),
+            EncodeValue::NULL,
+            EncodeValue::Bytes(b"c".to_vec()),
+        ]);
+        let taken = strings.take(&[2, 1, 0]);
+        assert_eq!(taken.bytes(0), b"c");
+        assert!(taken.is_null(1));
+        assert_eq!(taken.value(2), EncodeValue::Bytes(b"ab".to_vec()));
+
+        for (i, v) in values.iter().enumerate() {
+            let mut a = vec![];
+            let mut b = vec![];
+            column.encode_hash_key(i, &mut a).unwrap();
+            encode_hash_key(v, &mut b).unwrap();
+            assert_eq!(a, b);
+        }
+        let mut a = vec![];
+        let mut b = vec![];
+        Column::repeat(&EncodeValue::Double(3.0), 1)
+            .encode_hash_key(0, &mut a)
+            .unwrap();
+        encode_hash_key(&EncodeValue::Int(3), &mut b).unwrap();
+        assert_eq!(a, b);
+    }
+}
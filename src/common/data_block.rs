@@ -0,0 +1,110 @@
+use super::{Column, EncodeValue};
+use crate::table::schema::DataSchemaRef;
+
+/// A batch of rows stored by columns. The rows removed by a filter are only dropped from the
+/// selection, so filters never copy the columns, and the rows of a block are the selected ones.
+pub struct DataBlock {
+    pub schema: DataSchemaRef,
+    columns: Vec<Column>,
+    /// The number of values in every column. It is kept apart from the columns since a block
+    /// may have rows without columns, such as the block of `SELECT 1`.
+    num_rows: usize,
+    /// The offsets of the selected rows in the columns in ascending order, all rows are
+    /// selected if it is `None`.
+    selection: Option<Vec<usize>>,
+}
+
+impl DataBlock {
+    pub fn new(schema: DataSchemaRef, columns: Vec<Column>, num_rows: usize) -> DataBlock {
+        DataBlock {
+            schema,
+            columns,
+            num_rows,
+            selection: None,
+        }
+    }
+
+    /// Build a block from rows, the columns are typed by the columns of `schema`.
+    pub fn from_rows(schema: DataSchemaRef, rows: Vec<Vec<EncodeValue>>) -> DataBlock {
+        let num_rows = rows.len();
+        let mut columns: Vec<Column> = schema
+            .columns
+            .iter()
+            .map(|c| Column::with_capacity(&c.data_type, num_rows))
+            .collect();
+        for row in rows {
+            for (column, v) in columns.iter_mut().zip(row) {
+                column.push(v);
+            }
+        }
+        DataBlock::new(schema, columns, num_rows)
+    }
+
+    /// The number of selected rows.
+    pub fn len(&self) -> usize {
+        match &self.selection {
+            Some(selection) => selection.len(),
+            None => self.num_rows,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All values of the columns, including the rows which are not selected.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn selection(&self) -> Option<&[usize]> {
+        self.selection.as_deref()
+    }
+
+    /// The offset in the columns of the selected row `i`.
+    fn offset(&self, i: usize) -> usize {
+        match &self.selection {
+            Some(selection) => selection[i],
+            None => i,
+        }
+    }
+
+    pub fn value(&self, row: usize, column: usize) -> EncodeValue {
+        self.columns[column].value(self.offset(row))
+    }
+
+    pub fn row(&self, i: usize) -> Vec<EncodeValue> {
+        let offset = self.offset(i);
+        self.columns.iter().map(|c| c.value(offset)).collect()
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<EncodeValue>> {
+        (0..self.len()).map(|i| self.row(i)).collect()
+    }
+
+    /// Keep the selected rows for which `mask` is true, `mask` has a value for every selected
+    /// row, as the columns computed by `Expression::eval_column`.
+    pub fn filter(&mut self, mask: &Column) {
+        let selection = (0..self.len())
+            .filter(|i| mask.is_true(*i))
+            .map(|i| self.offset(i))
+            .collect();
+        self.selection = Some(selection);
+    }
+
+    /// Keep `len` selected rows starting from the row `offset`.
+    pub fn slice(&mut self, offset: usize, len: usize) {
+        let selection = (offset..offset + len).map(|i| self.offset(i)).collect();
+        self.selection = Some(selection);
+    }
+
+    /// A block of the columns at `indices` of this block, the selection is kept.
+    pub fn project(self, schema: DataSchemaRef, indices: &[usize]) -> DataBlock {
+        DataBlock {
+            schema,
+            columns: indices.iter().map(|i| self.columns[*i].clone()).collect(),
+            num_rows: self.num_rows,
+            selection: self.selection,
+        }
+    }
+}
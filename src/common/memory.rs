@@ -0,0 +1,147 @@
+use crate::errors::{MySQLError, MySQLResult};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub type MemTrackerRef = Arc<MemTracker>;
+
+/// What a query does when it holds more memory than a quota.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OomAction {
+    /// Fail the query with `MemoryQuotaExceeded`.
+    Cancel,
+    /// Let the operators which can spill write their rows to temporary files, the query only
+    /// fails if memory which can not be spilled exceeds a quota.
+    Spill,
+}
+
+impl OomAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OomAction::Cancel => "CANCEL",
+            OomAction::Spill => "SPILL",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<OomAction> {
+        match name.to_uppercase().as_str() {
+            "CANCEL" => Some(OomAction::Cancel),
+            "SPILL" => Some(OomAction::Spill),
+            _ => None,
+        }
+    }
+}
+
+/// The memory held by the server, a session or a query. Trackers form a tree, the memory
+/// consumed by a tracker is consumed by all its ancestors as well, and each of them may have a
+/// limit of its own.
+pub struct MemTracker {
+    label: &'static str,
+    /// The bytes this tracker may hold, 0 means no limit.
+    limit: AtomicUsize,
+    consumed: AtomicUsize,
+    parent: Option<MemTrackerRef>,
+}
+
+impl MemTracker {
+    pub fn root(label: &'static str, limit: usize) -> MemTrackerRef {
+        Arc::new(MemTracker {
+            label,
+            limit: AtomicUsize::new(limit),
+            consumed: AtomicUsize::new(0),
+            parent: None,
+        })
+    }
+
+    pub fn child(self: &Arc<Self>, label: &'static str, limit: usize) -> MemTrackerRef {
+        Arc::new(MemTracker {
+            label,
+            limit: AtomicUsize::new(limit),
+            consumed: AtomicUsize::new(0),
+            parent: Some(self.clone()),
+        })
+    }
+
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+    }
+
+    pub fn consumed(&self) -> usize {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    pub fn consume(&self, bytes: usize) {
+        let mut tracker = Some(self);
+        while let Some(t) = tracker {
+            t.consumed.fetch_add(bytes, Ordering::Relaxed);
+            tracker = t.parent.as_deref();
+        }
+    }
+
+    pub fn release(&self, bytes: usize) {
+        let mut tracker = Some(self);
+        while let Some(t) = tracker {
+            t.consumed.fetch_sub(bytes, Ordering::Relaxed);
+            tracker = t.parent.as_deref();
+        }
+    }
+
+    /// Fail with `MemoryQuotaExceeded` of the nearest tracker, from this one to the root, which
+    /// holds more than its limit.
+    pub fn check(&self) -> MySQLResult<()> {
+        let mut tracker = Some(self);
+        while let Some(t) = tracker {
+            let limit = t.limit.load(Ordering::Relaxed);
+            if limit > 0 && t.consumed() > limit {
+                return Err(MySQLError::MemoryQuotaExceeded(t.label.to_string(), limit));
+            }
+            tracker = t.parent.as_deref();
+        }
+        Ok(())
+    }
+
+    /// Consume `bytes` only if no quota is exceeded by them.
+    pub fn try_consume(&self, bytes: usize) -> MySQLResult<()> {
+        self.consume(bytes);
+        let result = self.check();
+        if result.is_err() {
+            self.release(bytes);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mem_tracker() {
+        let server = MemTracker::root("server", 100);
+        let session = server.child("session", 0);
+        let query = session.child("query", 50);
+        query.consume(40);
+        assert!(query.check().is_ok());
+        assert_eq!(server.consumed(), 40);
+
+        let other = server.child("session", 0);
+        assert!(other.try_consume(70).is_err());
+        assert_eq!(server.consumed(), 40);
+        other.consume(70);
+        match query.check() {
+            Err(MySQLError::MemoryQuotaExceeded(label, 100)) => assert_eq!(label, "server"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        other.release(70);
+        query.consume(20);
+        match query.check() {
+            Err(MySQLError::MemoryQuotaExceeded(label, 50)) => assert_eq!(label, "query"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        query.set_limit(0);
+        assert!(query.check().is_ok());
+        query.release(60);
+        assert_eq!(server.consumed(), 0);
+    }
+}
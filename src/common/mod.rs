@@ -1,13 +1,17 @@
+mod column;
+mod data_block;
+mod memory;
 mod types;
 
-use crate::table::schema::DataSchemaRef;
+use crate::errors::MySQLResult;
+use futures::stream::Stream;
+use std::pin::Pin;
 
+pub use column::{Column, ColumnValues, NullBitmap};
+pub use data_block::DataBlock;
+pub use memory::{MemTracker, MemTrackerRef, OomAction};
 pub use types::EncodeValue;
 
-pub struct DataBlock {
-    pub schema: DataSchemaRef,
-    pub data: Vec<Vec<EncodeValue>>,
-}
-
-// TODO: replace it with async stream
-pub type SendableDataBlockStream = Vec<DataBlock>;
+/// The blocks produced by an executor. They are pulled by the consumer one by one, so an
+/// executor only reads the next block when the previous one has been taken away.
+pub type SendableDataBlockStream = Pin<Box<dyn Stream<Item = MySQLResult<DataBlock>> + Send>>;
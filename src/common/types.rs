@@ -1,12 +1,14 @@
-use byteorder::{LittleEndian as LE, ReadBytesExt, WriteBytesExt};
+use byteorder::{BigEndian as BE, LittleEndian as LE, ReadBytesExt, WriteBytesExt};
 use sqlparser::ast::DataType;
 use sqlparser::ast::Value;
+use std::cmp::Ordering;
 use std::io;
 use std::io::Read;
 
 const ENC_GROUP_SIZE: u8 = 8;
 const ENC_EMPTY_PADS: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0];
 const ENC_MARKER: u8 = 255;
+const SIGN_MASK: u64 = 0x8000000000000000;
 
 #[derive(Clone, Debug)]
 pub enum EncodeValue {
@@ -21,24 +23,17 @@ pub enum EncodeValue {
     Time(bool, u32, u8, u8, u8, u32),
 }
 
-// TODO: Support compare float and double and date and time.
 impl std::cmp::PartialEq for EncodeValue {
     fn eq(&self, other: &Self) -> bool {
-        match self {
-            EncodeValue::NULL => *other == EncodeValue::NULL,
-            EncodeValue::Int(v) => {
-                if let EncodeValue::Int(o) = other {
-                    *v == *o
-                } else {
-                    false
-                }
-            }
-            EncodeValue::Bytes(v) => {
-                if let EncodeValue::Bytes(o) = other {
-                    v.eq(o)
-                } else {
-                    false
-                }
+        match (self, other) {
+            (EncodeValue::NULL, EncodeValue::NULL) => true,
+            (EncodeValue::Int(v), EncodeValue::Int(o)) => *v == *o,
+            (EncodeValue::Bytes(v), EncodeValue::Bytes(o)) => v.eq(o),
+            (EncodeValue::Float(v), EncodeValue::Float(o)) => *v == *o,
+            (EncodeValue::Double(v), EncodeValue::Double(o)) => *v == *o,
+            (EncodeValue::Date(..), EncodeValue::Date(..))
+            | (EncodeValue::Time(..), EncodeValue::Time(..)) => {
+                self.compare(other) == Some(Ordering::Equal)
             }
             _ => false,
         }
@@ -61,11 +56,12 @@ impl EncodeValue {
             Value::Null => Ok(EncodeValue::NULL),
             Value::NationalStringLiteral(v) => Ok(EncodeValue::Bytes(v.into_bytes())),
             #[cfg(not(feature = "bigdecimal"))]
-            Value::Number(v, _) => {
-                Ok(EncodeValue::Int(v.parse::<i64>().map_err(|_| {
+            Value::Number(v, _) => match v.parse::<i64>() {
+                Ok(v) => Ok(EncodeValue::Int(v)),
+                Err(_) => Ok(EncodeValue::Double(v.parse::<f64>().map_err(|_| {
                     io::Error::new(io::ErrorKind::InvalidInput, "parse error")
-                })?))
-            }
+                })?)),
+            },
             #[cfg(feature = "bigdecimal")]
             Value::Number(v, _) => Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -89,6 +85,102 @@ impl EncodeValue {
         }
     }
 
+    /// Convert the value to a number as MySQL does, a string which is not a number is 0.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            EncodeValue::NULL => None,
+            EncodeValue::Int(v) => Some(*v as f64),
+            EncodeValue::Float(v) => Some(*v as f64),
+            EncodeValue::Double(v) => Some(*v),
+            EncodeValue::Bytes(v) => Some(
+                std::str::from_utf8(v)
+                    .ok()
+                    .and_then(|s| s.trim().parse::<f64>().ok())
+                    .unwrap_or(0.0),
+            ),
+            EncodeValue::Date(year, month, day, hour, minute, second, _) => Some(
+                *year as f64 * 1e10
+                    + *month as f64 * 1e8
+                    + *day as f64 * 1e6
+                    + *hour as f64 * 1e4
+                    + *minute as f64 * 1e2
+                    + *second as f64,
+            ),
+            EncodeValue::Time(negative, days, hour, minute, second, _) => {
+                let v = (*days as f64 * 24.0 + *hour as f64) * 1e4
+                    + *minute as f64 * 1e2
+                    + *second as f64;
+                Some(if *negative { -v } else { v })
+            }
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            EncodeValue::Int(v) => Some(*v),
+            _ => self.as_f64().map(|v| v.round() as i64),
+        }
+    }
+
+    /// Whether the value is regarded as true in a condition, NULL is not true.
+    pub fn is_true(&self) -> bool {
+        match self {
+            EncodeValue::NULL => false,
+            EncodeValue::Int(v) => *v != 0,
+            _ => self.as_f64().map_or(false, |v| v != 0.0),
+        }
+    }
+
+    /// Compare two values, return `None` if any of them is NULL. Values of different types are
+    /// compared as numbers.
+    pub fn compare(&self, other: &EncodeValue) -> Option<Ordering> {
+        match (self, other) {
+            (EncodeValue::NULL, _) | (_, EncodeValue::NULL) => None,
+            (EncodeValue::Int(a), EncodeValue::Int(b)) => Some(a.cmp(b)),
+            (EncodeValue::Bytes(a), EncodeValue::Bytes(b)) => Some(a.cmp(b)),
+            (
+                EncodeValue::Date(y1, m1, d1, h1, mi1, s1, u1),
+                EncodeValue::Date(y2, m2, d2, h2, mi2, s2, u2),
+            ) => Some((y1, m1, d1, h1, mi1, s1, u1).cmp(&(y2, m2, d2, h2, mi2, s2, u2))),
+            (
+                EncodeValue::Time(n1, d1, h1, m1, s1, u1),
+                EncodeValue::Time(n2, d2, h2, m2, s2, u2),
+            ) => {
+                let a = (*d1 as i64 * 86400 + *h1 as i64 * 3600 + *m1 as i64 * 60 + *s1 as i64)
+                    * 1000000
+                    + *u1 as i64;
+                let b = (*d2 as i64 * 86400 + *h2 as i64 * 3600 + *m2 as i64 * 60 + *s2 as i64)
+                    * 1000000
+                    + *u2 as i64;
+                let a = if *n1 { -a } else { a };
+                let b = if *n2 { -b } else { b };
+                Some(a.cmp(&b))
+            }
+            (a, b) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        }
+    }
+
+    /// Convert the value to the type of a column, so that it can be encoded as the column.
+    pub fn convert_to(&self, data_type: &DataType) -> EncodeValue {
+        match (self, data_type) {
+            (EncodeValue::NULL, _) => EncodeValue::NULL,
+            (EncodeValue::Int(_), DataType::SmallInt)
+            | (EncodeValue::Int(_), DataType::Int)
+            | (EncodeValue::Int(_), DataType::BigInt) => self.clone(),
+            (_, DataType::SmallInt) | (_, DataType::Int) | (_, DataType::BigInt) => {
+                EncodeValue::Int(self.as_i64().unwrap_or(0))
+            }
+            (EncodeValue::Double(_), DataType::Double) => self.clone(),
+            (_, DataType::Double) => EncodeValue::Double(self.as_f64().unwrap_or(0.0)),
+            (EncodeValue::Bytes(_), _) => self.clone(),
+            (_, DataType::Char(_))
+            | (_, DataType::Varchar(_))
+            | (_, DataType::String)
+            | (_, DataType::Text) => EncodeValue::Bytes(String::from(self.clone()).into_bytes()),
+            _ => self.clone(),
+        }
+    }
+
     pub fn encode_comparable(
         &self,
         output: &mut Vec<u8>,
@@ -101,29 +193,17 @@ impl EncodeValue {
                 Ok(())
             }
             EncodeValue::Bytes(v) => {
-                let pad_count = v.len() / ENC_GROUP_SIZE as usize;
-                let pad_remain = (v.len() % ENC_GROUP_SIZE as usize) as u8;
-                for i in 0..pad_count {
-                    output.extend_from_slice(
-                        &v[i * ENC_GROUP_SIZE as usize..(i + 1) * ENC_GROUP_SIZE as usize],
-                    );
-                    output.push(ENC_MARKER);
-                }
-                if pad_remain > 0 {
-                    output.extend_from_slice(&v[pad_count * ENC_GROUP_SIZE as usize..]);
-                }
-                output.extend_from_slice(&ENC_EMPTY_PADS[pad_remain as usize..]);
-                output.push(ENC_MARKER - ENC_GROUP_SIZE + pad_remain);
+                encode_comparable_bytes(v, output);
                 Ok(())
             }
             EncodeValue::Float(v) => {
-                output.write_f32::<LE>(*v)?;
+                output.write_u32::<BE>(encode_f32_comparable(*v))?;
                 output.extend_from_slice(&ENC_EMPTY_PADS[4..]);
                 output.push(ENC_MARKER - ENC_GROUP_SIZE + 4);
                 Ok(())
             }
             EncodeValue::Double(v) => {
-                output.write_f64::<LE>(*v)?;
+                output.write_u64::<BE>(encode_f64_comparable(*v))?;
                 output.extend_from_slice(ENC_EMPTY_PADS);
                 output.push(ENC_MARKER - ENC_GROUP_SIZE);
                 Ok(())
@@ -131,7 +211,7 @@ impl EncodeValue {
             EncodeValue::Int(v) => {
                 match column_type {
                     DataType::SmallInt | DataType::Int | DataType::BigInt => {
-                        output.write_i64::<LE>(*v)?
+                        output.write_u64::<BE>(*v as u64 ^ SIGN_MASK)?
                     }
                     _ => {
                         return Err(io::Error::new(
@@ -145,14 +225,14 @@ impl EncodeValue {
                 Ok(())
             }
             EncodeValue::Date(year, month, day, hour, minute, second, micro) => {
-                output.write_u32::<LE>(*year as u32)?;
+                output.write_u32::<BE>(*year as u32)?;
                 output.write_u8(*month)?;
                 output.write_u8(*day)?;
                 output.write_u8(*hour)?;
                 output.write_u8(*minute)?;
                 output.push(ENC_MARKER);
                 output.write_u8(*second)?;
-                output.write_u32::<LE>(*micro)?;
+                output.write_u32::<BE>(*micro)?;
                 output.extend_from_slice(&ENC_EMPTY_PADS[5..]);
                 output.push(ENC_MARKER - ENC_GROUP_SIZE + 5);
                 Ok(())
@@ -163,12 +243,12 @@ impl EncodeValue {
                 } else {
                     output.write_u8(0)?;
                 }
-                output.write_u32::<LE>(*day)?;
+                output.write_u32::<BE>(*day)?;
                 output.write_u8(*hour)?;
                 output.write_u8(*minute)?;
                 output.write_u8(*second)?;
                 output.push(ENC_MARKER);
-                output.write_u32::<LE>(*micro)?;
+                output.write_u32::<BE>(*micro)?;
                 output.extend_from_slice(&ENC_EMPTY_PADS[4..]);
                 output.push(ENC_MARKER - ENC_GROUP_SIZE + 4);
                 Ok(())
@@ -232,12 +312,12 @@ impl EncodeValue {
     pub fn decode_comparable(input: &mut &[u8], column_type: &DataType) -> io::Result<EncodeValue> {
         match column_type {
             DataType::SmallInt | DataType::Int | DataType::BigInt => {
-                let value = EncodeValue::Int(input.read_i64::<LE>()?);
+                let value = EncodeValue::Int((input.read_u64::<BE>()? ^ SIGN_MASK) as i64);
                 check_and_skip_empty_padding(input)?;
                 Ok(value)
             }
             DataType::Double => {
-                let value = EncodeValue::Double(input.read_f64::<LE>()?);
+                let value = EncodeValue::Double(decode_f64_comparable(input.read_u64::<BE>()?));
                 check_and_skip_empty_padding(input)?;
                 Ok(value)
             }
@@ -247,14 +327,14 @@ impl EncodeValue {
                 Ok(EncodeValue::Bytes(output))
             }
             DataType::Date => {
-                let year = input.read_u32::<LE>()? as u16;
+                let year = input.read_u32::<BE>()? as u16;
                 let month = input.read_u8()?;
                 let day = input.read_u8()?;
                 let hour = input.read_u8()?;
                 let minute = input.read_u8()?;
                 input.read_u8()?;
                 let second = input.read_u8()?;
-                let micro_second = input.read_u32::<LE>()?;
+                let micro_second = input.read_u32::<BE>()?;
                 input.read_u24::<LE>()?;
                 if input.read_u8()? != ENC_MARKER - ENC_GROUP_SIZE + 5 {
                     Err(io::Error::new(
@@ -275,12 +355,12 @@ impl EncodeValue {
             }
             DataType::Time => {
                 let is_negative = input.read_u8()? == 1u8;
-                let days = input.read_u32::<LE>()?;
+                let days = input.read_u32::<BE>()?;
                 let hours = input.read_u8()?;
                 let minutes = input.read_u8()?;
                 let seconds = input.read_u8()?;
                 input.read_u8()?;
-                let micro_seconds = input.read_u32::<LE>()?;
+                let micro_seconds = input.read_u32::<BE>()?;
                 input.read_u32::<LE>()?;
                 if input.read_u8()? != ENC_MARKER - ENC_GROUP_SIZE + 4 {
                     Err(io::Error::new(
@@ -390,6 +470,33 @@ impl EncodeValue {
     }
 }
 
+/// Map a float to an unsigned integer which keeps the order of the float values.
+fn encode_f32_comparable(v: f32) -> u32 {
+    let bits = v.to_bits();
+    if v >= 0.0 {
+        bits | (SIGN_MASK >> 32) as u32
+    } else {
+        !bits
+    }
+}
+
+fn encode_f64_comparable(v: f64) -> u64 {
+    let bits = v.to_bits();
+    if v >= 0.0 {
+        bits | SIGN_MASK
+    } else {
+        !bits
+    }
+}
+
+fn decode_f64_comparable(u: u64) -> f64 {
+    if u & SIGN_MASK > 0 {
+        f64::from_bits(u & !SIGN_MASK)
+    } else {
+        f64::from_bits(!u)
+    }
+}
+
 pub fn check_and_skip_empty_padding(input: &mut &[u8]) -> io::Result<()> {
     input.read_i64::<LE>()?;
     if input.read_u8()? != ENC_MARKER - ENC_GROUP_SIZE {
@@ -402,6 +509,21 @@ pub fn check_and_skip_empty_padding(input: &mut &[u8]) -> io::Result<()> {
     }
 }
 
+pub fn encode_comparable_bytes(v: &[u8], output: &mut Vec<u8>) {
+    let pad_count = v.len() / ENC_GROUP_SIZE as usize;
+    let pad_remain = (v.len() % ENC_GROUP_SIZE as usize) as u8;
+    for i in 0..pad_count {
+        output
+            .extend_from_slice(&v[i * ENC_GROUP_SIZE as usize..(i + 1) * ENC_GROUP_SIZE as usize]);
+        output.push(ENC_MARKER);
+    }
+    if pad_remain > 0 {
+        output.extend_from_slice(&v[pad_count * ENC_GROUP_SIZE as usize..]);
+    }
+    output.extend_from_slice(&ENC_EMPTY_PADS[pad_remain as usize..]);
+    output.push(ENC_MARKER - ENC_GROUP_SIZE + pad_remain);
+}
+
 pub fn decode_comparable_bytes(input: &mut &[u8], output: &mut Vec<u8>) -> io::Result<()> {
     let mut buf = vec![0u8; 9];
     while input.len() > ENC_GROUP_SIZE as usize {
@@ -487,4 +609,34 @@ mod tests {
 
         assert_eq!(std::cmp::Ordering::Less, data1.cmp(&data2));
     }
+
+    #[test]
+    fn test_comparable_number() {
+        let ints = [i64::MIN, -300, -1, 0, 1, 256, i64::MAX];
+        let mut last = vec![];
+        for v in ints.iter() {
+            let mut data = vec![];
+            EncodeValue::Int(*v)
+                .encode_comparable(&mut data, &DataType::BigInt)
+                .unwrap();
+            assert!(last < data);
+            let mut input = data.as_slice();
+            let decoded = EncodeValue::decode_comparable(&mut input, &DataType::BigInt).unwrap();
+            assert_eq!(decoded, EncodeValue::Int(*v));
+            last = data;
+        }
+        let doubles = [-1e10, -1.5, 0.0, 0.25, 3.0, 1e10];
+        let mut last = vec![];
+        for v in doubles.iter() {
+            let mut data = vec![];
+            EncodeValue::Double(*v)
+                .encode_comparable(&mut data, &DataType::Double)
+                .unwrap();
+            assert!(last < data);
+            let mut input = data.as_slice();
+            let decoded = EncodeValue::decode_comparable(&mut input, &DataType::Double).unwrap();
+            assert_eq!(decoded, EncodeValue::Double(*v));
+            last = data;
+        }
+    }
 }
@@ -1,6 +1,14 @@
-use crate::store::TiKVConfig;
+use crate::common::{MemTracker, OomAction};
+use crate::planner::DEFAULT_PLAN_CACHE_CAPACITY;
+use crate::session::{ProcessList, SessionOptions, SessionVariables, DEFAULT_MEM_QUOTA_QUERY};
+use crate::store::{DeleteRangeWorker, TiKVConfig};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The default of `delete_range_delay`.
+const DEFAULT_DELETE_RANGE_DELAY: u64 = 10;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -16,6 +24,49 @@ pub struct Config {
     pub tikv: TiKVConfig,
     pub storage: StorageType,
     pub connection_pool_size: usize,
+    #[serde(default = "default_plan_cache_capacity")]
+    pub plan_cache_capacity: usize,
+    /// The directory of the temporary files of sorts, aggregations and joins which spill.
+    #[serde(default = "default_tmp_dir")]
+    pub tmp_dir: PathBuf,
+    /// The default of `naivedb_mem_quota_query`, the bytes which the operators of one query
+    /// may hold, 0 means no limit.
+    #[serde(default = "default_mem_quota_query")]
+    pub mem_quota_query: usize,
+    /// The default of `naivedb_mem_quota_session`.
+    #[serde(default)]
+    pub mem_quota_session: usize,
+    /// The bytes which all sessions together may hold, 0 means no limit.
+    #[serde(default)]
+    pub mem_quota_server: usize,
+    /// The default of `naivedb_mem_oom_action`, whether a query which exceeds a quota fails or
+    /// spills to `tmp_dir`.
+    #[serde(default = "default_oom_action")]
+    pub oom_action: OomAction,
+    /// The seconds the data of a dropped or truncated table is kept in storage before it is
+    /// deleted.
+    #[serde(default = "default_delete_range_delay")]
+    pub delete_range_delay: u64,
+}
+
+fn default_plan_cache_capacity() -> usize {
+    DEFAULT_PLAN_CACHE_CAPACITY
+}
+
+fn default_tmp_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+fn default_mem_quota_query() -> usize {
+    DEFAULT_MEM_QUOTA_QUERY
+}
+
+fn default_oom_action() -> OomAction {
+    OomAction::Spill
+}
+
+fn default_delete_range_delay() -> u64 {
+    DEFAULT_DELETE_RANGE_DELAY
 }
 
 impl Default for Config {
@@ -24,6 +75,35 @@ impl Default for Config {
             tikv: TiKVConfig::default(),
             storage: StorageType::Mem,
             connection_pool_size: 4,
+            plan_cache_capacity: DEFAULT_PLAN_CACHE_CAPACITY,
+            tmp_dir: default_tmp_dir(),
+            mem_quota_query: DEFAULT_MEM_QUOTA_QUERY,
+            mem_quota_session: 0,
+            mem_quota_server: 0,
+            oom_action: default_oom_action(),
+            delete_range_delay: DEFAULT_DELETE_RANGE_DELAY,
+        }
+    }
+}
+
+impl Config {
+    /// The options shared by the sessions of a server, whose memory is tracked by one tracker
+    /// of the server, whose connections are in one process list and whose dropped tables are
+    /// deleted by one worker.
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions {
+            tmp_dir: self.tmp_dir.clone(),
+            variables: SessionVariables {
+                mem_quota_query: self.mem_quota_query,
+                mem_quota_session: self.mem_quota_session,
+                oom_action: self.oom_action,
+                ..SessionVariables::default()
+            },
+            memory: MemTracker::root("server", self.mem_quota_server),
+            processes: ProcessList::new(),
+            delete_ranges: Arc::new(DeleteRangeWorker::new(Duration::from_secs(
+                self.delete_range_delay,
+            ))),
         }
     }
 }
@@ -16,8 +16,8 @@ pub enum MySQLError {
     #[error("io error : {0}")]
     Io(String),
 
-    #[error("primary key has exist")]
-    KeyExist,
+    #[error("duplicate entry '{0}' for key '{1}'")]
+    DupEntry(String, String),
 
     #[error("index has exist")]
     IndexExist,
@@ -25,14 +25,92 @@ pub enum MySQLError {
     #[error("index not exist")]
     NoIndex,
 
+    #[error("multiple primary key defined")]
+    MultiplePrimaryKey,
+
     #[error("column not exist")]
     NoColumn,
 
+    #[error("duplicate column name '{0}'")]
+    DupColumn(String),
+
+    #[error("can't drop column '{0}' which is covered by an index")]
+    DropIndexedColumn(String),
+
+    #[error("cannot drop column '{0}': needed in a foreign key constraint '{1}'")]
+    DropForeignKeyColumn(String, String),
+
+    #[error("failed to add the foreign key constraint '{0}'")]
+    CannotAddForeignKey(String),
+
+    #[error("cannot add or update a child row: a foreign key constraint fails ({0})")]
+    NoReferencedRow(String),
+
+    #[error("cannot delete or update a parent row: a foreign key constraint fails ({0})")]
+    RowIsReferenced(String),
+
+    #[error("check constraint '{0}' uses column '{1}', hence column cannot be dropped or renamed")]
+    DependentByCheck(String, String),
+
+    #[error("duplicate check constraint name '{0}'")]
+    DupCheck(String),
+
+    #[error("check constraint '{0}' is violated")]
+    CheckViolated(String),
+
+    #[error("column '{0}' has a generated column dependency")]
+    DependentByGenerated(String),
+
+    #[error("generated column can refer only to generated columns defined prior to it")]
+    GeneratedColumnNonPrior,
+
+    #[error("the value specified for generated column '{0}' in table '{1}' is not allowed")]
+    GeneratedColumnValue(String, String),
+
+    #[error("column '{0}' cannot be null")]
+    BadNull(String),
+
+    #[error("field '{0}' doesn't have a default value")]
+    NoDefault(String),
+
+    #[error("data too long for column '{0}'")]
+    DataTooLong(String),
+
+    #[error("out of range value for column '{0}'")]
+    DataOutOfRange(String),
+
+    #[error("incorrect {0} value: '{1}' for column '{2}'")]
+    WrongValueForColumn(String, String, String),
+
+    #[error("you can't delete all columns with ALTER TABLE; use DROP TABLE instead")]
+    DropAllColumns,
+
+    #[error("'{0}' is not VIEW")]
+    NotView(String),
+
+    #[error("view's SELECT and view's field list have different column counts")]
+    ViewWrongList,
+
+    #[error("`{0}` contains view recursion")]
+    ViewRecursive(String),
+
+    #[error("the target table {0} of the INSERT is not insertable-into")]
+    NonInsertable(String),
+
     #[error("table {0} doest not exist")]
     NoTable(String),
 
-    #[error("DB not exist")]
-    NoDB,
+    #[error("table '{0}' already exists")]
+    TableExists(String),
+
+    #[error("unknown database '{0}'")]
+    NoDB(String),
+
+    #[error("no database selected")]
+    NoDBSelected,
+
+    #[error("can't create database '{0}'; database exists")]
+    DBExists(String),
 
     #[error("miss column {0}")]
     MissColumn(String),
@@ -43,6 +121,54 @@ pub enum MySQLError {
     #[error("unsupported sql")]
     UnsupportSQL,
 
+    #[error("value is out of range in '{0}'")]
+    OutOfRange(String),
+
+    #[error("column '{0}' in field list is ambiguous")]
+    AmbiguousColumn(String),
+
+    #[error("expression '{0}' is not in GROUP BY clause")]
+    NotInGroupBy(String),
+
+    #[error("invalid use of group function")]
+    InvalidGroupFunc,
+
+    #[error("incorrect arguments to {0}")]
+    WrongArguments(String),
+
+    #[error("unknown prepared statement handler ({0})")]
+    UnknownStatement(u32),
+
+    #[error("incorrect arguments to execute, expect {0} parameters but got {1}")]
+    ParamMissMatch(usize, usize),
+
+    #[error("unknown system variable '{0}'")]
+    UnknownSystemVariable(String),
+
+    #[error("variable '{0}' can't be set to the value of '{1}'")]
+    WrongValueForVariable(String, String),
+
+    #[error("transaction is read only")]
+    ReadOnlyTransaction,
+
+    #[error("out of memory quota: the {0} holds more than {1} bytes")]
+    MemoryQuotaExceeded(String, usize),
+
+    #[error("query execution was interrupted")]
+    QueryInterrupted,
+
+    #[error("query execution was interrupted, maximum statement execution time exceeded")]
+    ExecutionTimeout,
+
+    #[error("connection was killed")]
+    ConnectionKilled,
+
+    #[error("unknown thread id: {0}")]
+    UnknownThread(u64),
+
+    #[error("parallel worker failed: {0}")]
+    WorkerFailed(String),
+
     #[error("TiKV Error")]
     TiKV(KVError),
 }
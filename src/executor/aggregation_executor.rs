@@ -0,0 +1,755 @@
+use super::parallel_executor::WorkerTasks;
+use super::spill::{
+    partition_of, row_size, MemoryReservation, QueryMemory, SpillFile, SPILL_BATCH_SIZE,
+    SPILL_PARTITIONS,
+};
+use crate::common::{Column, ColumnValues, DataBlock, EncodeValue};
+use crate::errors::{MySQLError, MySQLResult};
+use crate::executor::Executor;
+use crate::planner::{AggFunction, AggregateExpr, Expression};
+use crate::table::schema::DataSchemaRef;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// The intermediate result of an aggregate function for one group.
+struct AggState {
+    /// The number of rows whose arguments are not NULL.
+    count: i64,
+    /// The sum of `SUM` and `AVG`, or the current value of `MIN` and `MAX`.
+    value: Option<EncodeValue>,
+    /// The arguments which have been seen, only for `DISTINCT`.
+    seen: Option<HashSet<Vec<u8>>>,
+}
+
+impl AggState {
+    fn new(agg: &AggregateExpr) -> AggState {
+        AggState {
+            count: 0,
+            value: None,
+            seen: if agg.distinct {
+                Some(HashSet::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Update the state by the row `i` of the columns of the arguments.
+    fn update(&mut self, agg: &AggregateExpr, args: &[Column], i: usize) -> MySQLResult<()> {
+        if args.iter().any(|c| c.is_null(i)) {
+            return Ok(());
+        }
+        if let Some(seen) = self.seen.as_mut() {
+            let mut key = vec![];
+            for c in args.iter() {
+                c.encode_hash_key(i, &mut key)?;
+            }
+            if !seen.insert(key) {
+                return Ok(());
+            }
+        }
+        let v = match (agg.func, args.last()) {
+            (AggFunction::Count, _) | (_, None) => {
+                self.count += 1;
+                return Ok(());
+            }
+            (AggFunction::Sum, Some(c)) | (AggFunction::Avg, Some(c)) => {
+                return self.add_number(c.value(i));
+            }
+            (_, Some(c)) => c.value(i),
+        };
+        self.count += 1;
+        self.value = Some(match (agg.func, self.value.take()) {
+            (AggFunction::Min, Some(m)) if v.compare(&m) != Some(Ordering::Less) => m,
+            (AggFunction::Max, Some(m)) if v.compare(&m) != Some(Ordering::Greater) => m,
+            _ => v,
+        });
+        Ok(())
+    }
+
+    /// Add a value which is not NULL to the sum of `SUM` and `AVG`.
+    fn add_number(&mut self, v: EncodeValue) -> MySQLResult<()> {
+        self.count += 1;
+        self.value = Some(match self.value.take() {
+            None => to_number(v),
+            Some(sum) => add(sum, v)?,
+        });
+        Ok(())
+    }
+
+    /// Merge the state of the same group computed from other rows, only for functions without
+    /// `DISTINCT`.
+    fn merge(&mut self, agg: &AggregateExpr, other: AggState) -> MySQLResult<()> {
+        self.count += other.count;
+        let v = match other.value {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        self.value = Some(match (agg.func, self.value.take()) {
+            (_, None) => v,
+            (AggFunction::Sum, Some(sum)) | (AggFunction::Avg, Some(sum)) => add(sum, v)?,
+            (AggFunction::Min, Some(m)) if v.compare(&m) != Some(Ordering::Less) => m,
+            (AggFunction::Max, Some(m)) if v.compare(&m) != Some(Ordering::Greater) => m,
+            _ => v,
+        });
+        Ok(())
+    }
+
+    fn result(&self, agg: &AggregateExpr) -> EncodeValue {
+        match (agg.func, self.value.as_ref()) {
+            (AggFunction::Count, _) => EncodeValue::Int(self.count),
+            (_, None) => EncodeValue::NULL,
+            (AggFunction::Avg, Some(sum)) => {
+                EncodeValue::Double(sum.as_f64().unwrap_or(0.0) / self.count as f64)
+            }
+            (_, Some(v)) => v.convert_to(&agg.data_type()),
+        }
+    }
+}
+
+/// Integers are summed exactly, other values are summed as doubles.
+fn to_number(v: EncodeValue) -> EncodeValue {
+    match v {
+        EncodeValue::Int(_) => v,
+        v => EncodeValue::Double(v.as_f64().unwrap_or(0.0)),
+    }
+}
+
+fn add(sum: EncodeValue, v: EncodeValue) -> MySQLResult<EncodeValue> {
+    match (sum, to_number(v)) {
+        (EncodeValue::Int(a), EncodeValue::Int(b)) => a
+            .checked_add(b)
+            .map(EncodeValue::Int)
+            .ok_or_else(|| MySQLError::OutOfRange(format!("{} + {}", a, b))),
+        (a, b) => Ok(EncodeValue::Double(
+            a.as_f64().unwrap_or(0.0) + b.as_f64().unwrap_or(0.0),
+        )),
+    }
+}
+
+struct Group {
+    /// The values of the `GROUP BY` expressions.
+    values: Vec<EncodeValue>,
+    states: Vec<AggState>,
+}
+
+impl Group {
+    fn into_row(self, aggs: &[AggregateExpr]) -> Vec<EncodeValue> {
+        let mut values = self.values;
+        for (state, agg) in self.states.iter().zip(aggs.iter()) {
+            values.push(state.result(agg));
+        }
+        values
+    }
+}
+
+/// Update the states of the aggregate function `a` by the columns of its arguments computed from
+/// a block, `group_ids` are the groups of the rows. `COUNT`, `SUM` and `AVG` without `DISTINCT`
+/// run over the vectors of the columns, other functions are updated row by row.
+fn update_states(
+    groups: &mut [Group],
+    a: usize,
+    agg: &AggregateExpr,
+    args: &[Column],
+    group_ids: &[usize],
+) -> MySQLResult<()> {
+    if !agg.distinct {
+        match (agg.func, args) {
+            (AggFunction::Count, []) => {
+                for g in group_ids {
+                    groups[*g].states[a].count += 1;
+                }
+                return Ok(());
+            }
+            (AggFunction::Count, [arg]) => {
+                for (i, g) in group_ids.iter().enumerate() {
+                    if !arg.is_null(i) {
+                        groups[*g].states[a].count += 1;
+                    }
+                }
+                return Ok(());
+            }
+            (AggFunction::Sum, [arg]) | (AggFunction::Avg, [arg]) => match arg.values() {
+                ColumnValues::Int(values) => {
+                    for (i, g) in group_ids.iter().enumerate() {
+                        if !arg.is_null(i) {
+                            groups[*g].states[a].add_number(EncodeValue::Int(values[i]))?;
+                        }
+                    }
+                    return Ok(());
+                }
+                ColumnValues::Double(values) => {
+                    for (i, g) in group_ids.iter().enumerate() {
+                        if !arg.is_null(i) {
+                            groups[*g].states[a].add_number(EncodeValue::Double(values[i]))?;
+                        }
+                    }
+                    return Ok(());
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    for (i, g) in group_ids.iter().enumerate() {
+        groups[*g].states[a].update(agg, args, i)?;
+    }
+    Ok(())
+}
+
+/// The input rows of the groups which are not in memory, partitioned by the hash of their
+/// `GROUP BY` values, so every group is in one partition.
+struct SpilledRows {
+    schema: DataSchemaRef,
+    partitions: Vec<Vec<SpillFile>>,
+}
+
+/// The groups built from the rows read so far. The input may be split into parts which are
+/// aggregated by their own aggregators, and the partial groups are merged at last.
+///
+/// Once the query exceeds its memory, no group is added any more, and the rows of other groups
+/// are spilled to partitions, which are aggregated one by one after the input is exhausted.
+struct Aggregator {
+    group_by: Vec<Expression>,
+    aggs: Vec<AggregateExpr>,
+    /// The position in `groups` of every group by the encoded values of `GROUP BY`.
+    index: HashMap<Vec<u8>, usize>,
+    /// The groups ordered by their first row.
+    groups: Vec<Group>,
+    reservation: MemoryReservation,
+    /// Whether the rows may be spilled, it is false for the aggregator of a spilled partition.
+    spillable: bool,
+    spilled: Option<SpilledRows>,
+}
+
+impl Aggregator {
+    fn new(
+        group_by: Vec<Expression>,
+        aggs: Vec<AggregateExpr>,
+        memory: &Arc<QueryMemory>,
+        spillable: bool,
+    ) -> Aggregator {
+        Aggregator {
+            // There is only one group without `GROUP BY`, which never needs to spill.
+            spillable: spillable && !group_by.is_empty(),
+            group_by,
+            aggs,
+            index: HashMap::default(),
+            groups: vec![],
+            reservation: memory.reservation(),
+            spilled: None,
+        }
+    }
+
+    /// Read all blocks of an opened input.
+    async fn consume(&mut self, input: &mut dyn Executor) -> MySQLResult<()> {
+        while let Some(block) = input.next().await? {
+            self.update(block)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, mut block: DataBlock) -> MySQLResult<()> {
+        let mut keys = Vec::with_capacity(self.group_by.len());
+        for e in self.group_by.iter() {
+            keys.push(e.eval_column(&block)?);
+        }
+        let mut group_ids = Vec::with_capacity(block.len());
+        let mut in_memory = Vec::with_capacity(block.len());
+        for i in 0..block.len() {
+            let mut key = vec![];
+            for c in keys.iter() {
+                c.encode_hash_key(i, &mut key)?;
+            }
+            if let Some(id) = self.index.get(&key) {
+                group_ids.push(*id);
+                in_memory.push(EncodeValue::Int(1));
+            } else if let Some(spilled) = self.spilled.as_mut() {
+                let files = &mut spilled.partitions[partition_of(&key)];
+                if files.is_empty() {
+                    files.push(self.reservation.memory().create_file()?);
+                }
+                files.last_mut().unwrap().write_row(&block.row(i))?;
+                in_memory.push(EncodeValue::Int(0));
+            } else {
+                let values: Vec<EncodeValue> = keys.iter().map(|c| c.value(i)).collect();
+                let states_size = self.aggs.len() * size_of::<AggState>();
+                self.reservation
+                    .grow(key.len() * 2 + row_size(&values) + states_size)?;
+                self.groups.push(Group {
+                    values,
+                    states: self.aggs.iter().map(AggState::new).collect(),
+                });
+                self.index.insert(key, self.groups.len() - 1);
+                group_ids.push(self.groups.len() - 1);
+                in_memory.push(EncodeValue::Int(1));
+            }
+        }
+        if group_ids.len() < block.len() {
+            block.filter(&Column::from_values(in_memory));
+        }
+        let mut args = Vec::with_capacity(self.aggs.len());
+        for agg in self.aggs.iter() {
+            let mut columns = Vec::with_capacity(agg.args.len());
+            for e in agg.args.iter() {
+                columns.push(e.eval_column(&block)?);
+            }
+            args.push(columns);
+        }
+        for (a, agg) in self.aggs.iter().enumerate() {
+            update_states(&mut self.groups, a, agg, &args[a], &group_ids)?;
+        }
+        if self.spillable && self.spilled.is_none() && self.reservation.memory().exceeded() {
+            self.spilled = Some(SpilledRows {
+                schema: block.schema.clone(),
+                partitions: (0..SPILL_PARTITIONS).map(|_| vec![]).collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The groups with their encoded `GROUP BY` values, ordered by their first row.
+    fn into_keyed_groups(self) -> Vec<(Vec<u8>, Group)> {
+        let mut keys = vec![vec![]; self.groups.len()];
+        for (key, id) in self.index {
+            keys[id] = key;
+        }
+        keys.into_iter().zip(self.groups).collect()
+    }
+
+    /// Merge a group of the same key into the group at `id`.
+    fn merge_group(&mut self, id: usize, group: Group) -> MySQLResult<()> {
+        let states = self.groups[id].states.iter_mut();
+        for ((state, agg), other) in states.zip(self.aggs.iter()).zip(group.states) {
+            state.merge(agg, other)?;
+        }
+        Ok(())
+    }
+
+    /// Merge the groups of the rows after the rows of this aggregator, the new groups are
+    /// appended in their order, so the groups stay ordered by their first row.
+    fn merge(&mut self, mut other: Aggregator) -> MySQLResult<()> {
+        if let Some(spilled) = other.spilled.take() {
+            match self.spilled.as_mut() {
+                Some(rows) => {
+                    for (files, other) in rows.partitions.iter_mut().zip(spilled.partitions) {
+                        files.extend(other);
+                    }
+                }
+                None => self.spilled = Some(spilled),
+            }
+        }
+        self.reservation.merge(std::mem::replace(
+            &mut other.reservation,
+            self.reservation.memory().reservation(),
+        ));
+        for (key, group) in other.into_keyed_groups() {
+            match self.index.get(&key) {
+                Some(id) => self.merge_group(*id, group)?,
+                None => {
+                    self.groups.push(group);
+                    self.index.insert(key, self.groups.len() - 1);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Aggregate the rows spilled to one partition, and return the rows of the groups which
+    /// are not in memory. The groups in memory may also have rows in the partition only if the
+    /// input was aggregated by parts, which never have `DISTINCT`, so their states are merged.
+    fn aggregate_partition(
+        &mut self,
+        schema: &DataSchemaRef,
+        files: Vec<SpillFile>,
+    ) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+        let memory = self.reservation.memory().clone();
+        let mut partition =
+            Aggregator::new(self.group_by.clone(), self.aggs.clone(), &memory, false);
+        for file in files {
+            let mut reader = file.into_reader()?;
+            loop {
+                let rows = reader.read_rows(SPILL_BATCH_SIZE)?;
+                if rows.is_empty() {
+                    break;
+                }
+                partition.update(DataBlock::from_rows(schema.clone(), rows))?;
+            }
+        }
+        let mut rows = vec![];
+        for (key, group) in partition.into_keyed_groups() {
+            match self.index.get(&key) {
+                Some(id) => self.merge_group(*id, group)?,
+                None => rows.push(group.into_row(&self.aggs)),
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Return one row for every group in memory. Without `GROUP BY` there is always one group,
+    /// even for no rows.
+    fn finish(mut self) -> Vec<Vec<EncodeValue>> {
+        if self.groups.is_empty() && self.group_by.is_empty() {
+            self.groups.push(Group {
+                values: vec![],
+                states: self.aggs.iter().map(AggState::new).collect(),
+            });
+        }
+        let aggs = self.aggs;
+        self.groups
+            .into_iter()
+            .map(|group| group.into_row(&aggs))
+            .collect()
+    }
+}
+
+/// Aggregate a part of the input on its own task.
+async fn aggregate_part(
+    mut input: Box<dyn Executor>,
+    mut aggregator: Aggregator,
+) -> MySQLResult<Aggregator> {
+    input.open().await?;
+    let result = aggregator.consume(input.as_mut()).await;
+    input.close().await?;
+    result.map(|_| aggregator)
+}
+
+pub struct AggregationExecutor {
+    input: Box<dyn Executor>,
+    group_by: Vec<Expression>,
+    aggs: Vec<AggregateExpr>,
+    schema: DataSchemaRef,
+    /// The number of parts which the input is split into to be aggregated concurrently.
+    concurrency: usize,
+    memory: Arc<QueryMemory>,
+    aggregator: Option<Aggregator>,
+    /// The spilled partitions which have not been aggregated.
+    spilled: Option<SpilledRows>,
+}
+
+impl AggregationExecutor {
+    pub fn new(
+        input: Box<dyn Executor>,
+        group_by: Vec<Expression>,
+        aggs: Vec<AggregateExpr>,
+        schema: DataSchemaRef,
+        concurrency: usize,
+        memory: Arc<QueryMemory>,
+    ) -> AggregationExecutor {
+        AggregationExecutor {
+            input,
+            group_by,
+            aggs,
+            schema,
+            concurrency,
+            memory,
+            aggregator: None,
+            spilled: None,
+        }
+    }
+
+    fn aggregator(&self) -> Aggregator {
+        Aggregator::new(self.group_by.clone(), self.aggs.clone(), &self.memory, true)
+    }
+
+    async fn aggregate(&mut self) -> MySQLResult<Aggregator> {
+        let mut aggregator = self.aggregator();
+        self.input.open().await?;
+        let result = aggregator.consume(self.input.as_mut()).await;
+        self.input.close().await?;
+        result.map(|_| aggregator)
+    }
+
+    /// Aggregate every part on a task, and merge the partial groups in the order of the parts.
+    async fn aggregate_parallel(
+        &mut self,
+        parts: Vec<Box<dyn Executor>>,
+    ) -> MySQLResult<Aggregator> {
+        let mut workers = WorkerTasks::new();
+        for part in parts {
+            workers.spawn(aggregate_part(part, self.aggregator()));
+        }
+        let mut aggregator = self.aggregator();
+        // The remaining workers are aborted once one of them fails.
+        while let Some(partial) = workers.join_next().await {
+            aggregator.merge(partial??)?;
+        }
+        Ok(aggregator)
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for AggregationExecutor {
+    fn name(&self) -> &str {
+        "AggregationExecutor"
+    }
+
+    /// The partial groups of `DISTINCT` can not be merged, so it is always aggregated serially.
+    async fn open(&mut self) -> MySQLResult<()> {
+        let parts = if self.concurrency > 1 && self.aggs.iter().all(|agg| !agg.distinct) {
+            self.input.split(self.concurrency).await?
+        } else {
+            None
+        };
+        let mut aggregator = match parts {
+            Some(parts) => self.aggregate_parallel(parts).await?,
+            None => self.aggregate().await?,
+        };
+        self.spilled = aggregator.spilled.take();
+        self.aggregator = Some(aggregator);
+        Ok(())
+    }
+
+    /// The groups of every spilled partition are returned by a block, and the groups in memory
+    /// are returned by the last block.
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let aggregator = match self.aggregator.as_mut() {
+            Some(aggregator) => aggregator,
+            None => return Ok(None),
+        };
+        if let Some(spilled) = self.spilled.as_mut() {
+            while let Some(files) = spilled.partitions.pop() {
+                let rows = aggregator.aggregate_partition(&spilled.schema, files)?;
+                if !rows.is_empty() {
+                    return Ok(Some(DataBlock::from_rows(self.schema.clone(), rows)));
+                }
+            }
+        }
+        let rows = self.aggregator.take().unwrap().finish();
+        Ok(Some(DataBlock::from_rows(self.schema.clone(), rows)))
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.aggregator = None;
+        self.spilled = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{MemTracker, OomAction};
+    use crate::planner::ColumnRef;
+    use crate::table::schema::{ColumnInfo, DataSchema};
+    use sqlparser::ast::DataType;
+    use std::sync::Arc;
+    use tokio::runtime;
+
+    struct RowsExecutor {
+        block: Option<DataBlock>,
+    }
+
+    #[async_trait::async_trait]
+    impl Executor for RowsExecutor {
+        fn name(&self) -> &str {
+            "RowsExecutor"
+        }
+
+        async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+            Ok(self.block.take())
+        }
+    }
+
+    /// An input which returns its blocks in order, or is split into one part for every block.
+    struct PartsExecutor {
+        blocks: Vec<DataBlock>,
+    }
+
+    #[async_trait::async_trait]
+    impl Executor for PartsExecutor {
+        fn name(&self) -> &str {
+            "PartsExecutor"
+        }
+
+        async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+            if self.blocks.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(self.blocks.remove(0)))
+        }
+
+        async fn split(&mut self, _count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+            let parts = self
+                .blocks
+                .drain(..)
+                .map(|block| Box::new(RowsExecutor { block: Some(block) }) as Box<dyn Executor>)
+                .collect();
+            Ok(Some(parts))
+        }
+    }
+
+    fn memory(limit: usize) -> Arc<QueryMemory> {
+        let tracker = MemTracker::root("query", limit);
+        QueryMemory::new(tracker, OomAction::Spill, std::env::temp_dir())
+    }
+
+    fn column(index: usize) -> Expression {
+        Expression::Column(ColumnRef {
+            id: index + 1,
+            index,
+            name: format!("c{}", index),
+            data_type: DataType::Int,
+        })
+    }
+
+    fn aggregate(group_by: Vec<Expression>, rows: Vec<Vec<EncodeValue>>) -> Vec<Vec<EncodeValue>> {
+        let schema = |width: usize| {
+            Arc::new(DataSchema {
+                columns: (0..width)
+                    .map(|i| Arc::new(ColumnInfo::result_column("c", i, DataType::Int)))
+                    .collect(),
+            })
+        };
+        let width = group_by.len() + 4;
+        let input = RowsExecutor {
+            block: Some(DataBlock::from_rows(schema(2), rows)),
+        };
+        let aggs = vec![
+            AggregateExpr {
+                func: AggFunction::Count,
+                args: vec![],
+                distinct: false,
+            },
+            AggregateExpr {
+                func: AggFunction::Sum,
+                args: vec![column(1)],
+                distinct: false,
+            },
+            AggregateExpr {
+                func: AggFunction::Count,
+                args: vec![column(1)],
+                distinct: true,
+            },
+            AggregateExpr {
+                func: AggFunction::Max,
+                args: vec![column(1)],
+                distinct: false,
+            },
+        ];
+        let mut executor =
+            AggregationExecutor::new(Box::new(input), group_by, aggs, schema(width), 1, memory(0));
+        let r = runtime::Runtime::new().unwrap();
+        r.block_on(async {
+            executor.open().await.unwrap();
+            let block = executor.next().await.unwrap().unwrap();
+            assert!(executor.next().await.unwrap().is_none());
+            block.into_rows()
+        })
+    }
+
+    #[test]
+    fn test_hash_aggregation() {
+        let rows = vec![
+            vec![EncodeValue::Int(1), EncodeValue::Int(2)],
+            vec![EncodeValue::Int(2), EncodeValue::NULL],
+            vec![EncodeValue::Int(1), EncodeValue::Int(2)],
+            vec![EncodeValue::Double(1.0), EncodeValue::Int(5)],
+        ];
+        let output = aggregate(vec![column(0)], rows);
+        assert_eq!(
+            output,
+            vec![
+                vec![
+                    EncodeValue::Int(1),
+                    EncodeValue::Int(3),
+                    EncodeValue::Int(9),
+                    EncodeValue::Int(2),
+                    EncodeValue::Int(5),
+                ],
+                vec![
+                    EncodeValue::Int(2),
+                    EncodeValue::Int(1),
+                    EncodeValue::NULL,
+                    EncodeValue::Int(0),
+                    EncodeValue::NULL,
+                ],
+            ]
+        );
+        let output = aggregate(vec![], vec![]);
+        assert_eq!(
+            output,
+            vec![vec![
+                EncodeValue::Int(0),
+                EncodeValue::NULL,
+                EncodeValue::Int(0),
+                EncodeValue::NULL,
+            ]]
+        );
+    }
+
+    /// Aggregate three blocks by the first column, return the rows of all output blocks.
+    fn aggregate_blocks(concurrency: usize, memory_limit: usize) -> Vec<Vec<EncodeValue>> {
+        let schema = |width: usize| {
+            Arc::new(DataSchema {
+                columns: (0..width)
+                    .map(|i| Arc::new(ColumnInfo::result_column("c", i, DataType::Int)))
+                    .collect(),
+            })
+        };
+        let row = |a: i64, b: EncodeValue| vec![EncodeValue::Int(a), b];
+        let blocks = vec![
+            vec![row(1, EncodeValue::Int(2)), row(2, EncodeValue::NULL)],
+            vec![row(3, EncodeValue::Int(4)), row(1, EncodeValue::Int(7))],
+            vec![row(2, EncodeValue::Int(1))],
+        ];
+        let aggs = vec![
+            AggregateExpr {
+                func: AggFunction::Count,
+                args: vec![],
+                distinct: false,
+            },
+            AggregateExpr {
+                func: AggFunction::Sum,
+                args: vec![column(1)],
+                distinct: false,
+            },
+            AggregateExpr {
+                func: AggFunction::Min,
+                args: vec![column(1)],
+                distinct: false,
+            },
+        ];
+        let input = PartsExecutor {
+            blocks: blocks
+                .into_iter()
+                .map(|rows| DataBlock::from_rows(schema(2), rows))
+                .collect(),
+        };
+        let mut executor = AggregationExecutor::new(
+            Box::new(input),
+            vec![column(0)],
+            aggs,
+            schema(4),
+            concurrency,
+            memory(memory_limit),
+        );
+        let r = runtime::Runtime::new().unwrap();
+        r.block_on(async {
+            executor.open().await.unwrap();
+            let mut rows = vec![];
+            while let Some(block) = executor.next().await.unwrap() {
+                rows.extend(block.into_rows());
+            }
+            rows
+        })
+    }
+
+    #[test]
+    fn test_parallel_aggregation() {
+        let int = EncodeValue::Int;
+        let groups = vec![
+            vec![int(1), int(2), int(9), int(2)],
+            vec![int(2), int(2), int(1), int(1)],
+            vec![int(3), int(1), int(4), int(4)],
+        ];
+        assert_eq!(aggregate_blocks(4, 0), groups);
+        assert_eq!(aggregate_blocks(4, 1), groups);
+        // The groups exceed the memory after the first block, so the new group of the second
+        // block is spilled and returned before the groups in memory.
+        let spilled = vec![groups[2].clone(), groups[0].clone(), groups[1].clone()];
+        assert_eq!(aggregate_blocks(1, 1), spilled);
+    }
+}
@@ -0,0 +1,424 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::planner::{AlterTableOperation, AlterTablePlan};
+use crate::session::SessionRef;
+use crate::store::{Storage, TransactionOptions};
+use crate::table::auto_id::AutoIdAllocator;
+use crate::table::schema::{DataSchema, IndexInfo, TableInfo, TableState};
+use crate::table::{EncoderRow, TableSource};
+use crate::transaction::{OptimisticTransactionContext, TransactionContext};
+use std::sync::Arc;
+
+/// The number of records read from storage by one request when a table is copied.
+const REORGANIZE_BATCH_SIZE: usize = 1024;
+
+pub struct AlterTableExecutor {
+    plan: AlterTablePlan,
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+}
+
+impl AlterTableExecutor {
+    pub fn new(plan: AlterTablePlan, session: SessionRef, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            plan,
+            session,
+            storage,
+        }
+    }
+
+    /// Publish `meta` as the next version of the table.
+    fn replace_table(&self, meta: TableInfo) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        session.replace_table(&self.plan.db, meta.name.clone(), meta)
+    }
+
+    /// Publish a version of `meta` for every state which the column `name` passes through.
+    fn replace_with_states(
+        &self,
+        meta: &TableInfo,
+        name: &str,
+        states: &[TableState],
+    ) -> MySQLResult<()> {
+        for state in states {
+            let mut meta = meta.clone();
+            meta.set_column_state(name, state.clone())?;
+            self.replace_table(meta)?;
+        }
+        Ok(())
+    }
+
+    /// Publish a version of `meta` for every state which the index `name` passes through.
+    fn replace_with_index_states(
+        &self,
+        meta: &TableInfo,
+        name: &str,
+        states: &[TableState],
+    ) -> MySQLResult<()> {
+        for state in states {
+            let mut meta = meta.clone();
+            meta.set_index_state(name, state.clone())?;
+            self.replace_table(meta)?;
+        }
+        Ok(())
+    }
+
+    fn transaction_options(&self) -> TransactionOptions {
+        let memory = self.session.lock().unwrap().memory_tracker();
+        TransactionOptions {
+            pessimistic: false,
+            memory: Some(memory),
+        }
+    }
+
+    /// Add an index through the delete-only and write-only states. Once it is write-only, the
+    /// new rows write their entries of it, and the entries of the rows before are filled in
+    /// before it becomes public.
+    async fn add_index(&self, mut meta: TableInfo, index: IndexInfo) -> MySQLResult<()> {
+        let primary = index.primary;
+        let name = meta.add_index(index)?;
+        // The records are stored by the primary key, so they are copied to be stored by it
+        // instead of the hidden `_rowid`.
+        if primary {
+            meta.pk_is_handle = true;
+            return self.reorganize(meta).await;
+        }
+        let states = [TableState::DeleteOnly, TableState::WriteOnly];
+        self.replace_with_index_states(&meta, &name, &states)?;
+        let table = TableSource::new(Arc::new(meta.clone()));
+        let index = meta
+            .indices
+            .iter()
+            .find(|i| i.name == name)
+            .unwrap()
+            .clone();
+        if let Err(e) = self.fill_index(&table, &index).await {
+            // Keep the index id allocated, the entries written while it was write-only are
+            // deleted with the index.
+            let mut origin = self.plan.table.clone_meta();
+            origin.max_index_id = meta.max_index_id;
+            self.replace_table(origin)?;
+            let session = self.session.lock().unwrap();
+            session
+                .delete_ranges()
+                .schedule(self.storage.clone(), table.index_range(&index)?);
+            return Err(e);
+        }
+        self.replace_table(meta)
+    }
+
+    /// Write the entries of `index` for all records of `table`. All columns are decoded, so the
+    /// virtual columns are computed before the keys are built from them.
+    async fn fill_index(&self, table: &TableSource, index: &IndexInfo) -> MySQLResult<()> {
+        let opts = self.transaction_options();
+        let txn = self.storage.new_transaction(&opts).await?;
+        let mut ctx = OptimisticTransactionContext::new(txn);
+        let schema = DataSchema {
+            columns: table.meta().columns.clone(),
+        };
+        let (mut start, end) = table.record_range()?;
+        loop {
+            let (records, next) = table
+                .scan_handle_record_batch(&mut ctx, &start, &end, REORGANIZE_BATCH_SIZE, &schema)
+                .await?;
+            for (handle, record) in records {
+                table
+                    .add_index_entry(&mut ctx, index, &handle, &record)
+                    .await?;
+            }
+            match next {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+        ctx.commit().await
+    }
+
+    /// Remove an index after it passes through the write-only and delete-only states, its
+    /// entries are deleted in background. The records of a table whose primary key is dropped
+    /// are copied to be stored by the hidden `_rowid`.
+    async fn drop_index(&self, meta: TableInfo, name: &str) -> MySQLResult<()> {
+        let mut dropped = meta.clone();
+        let index = dropped.drop_index(name)?;
+        if index.primary {
+            dropped.pk_is_handle = false;
+            return self.reorganize(dropped).await;
+        }
+        let states = [TableState::WriteOnly, TableState::DeleteOnly];
+        self.replace_with_index_states(&meta, name, &states)?;
+        self.replace_table(dropped)?;
+        let range = self.plan.table.index_range(&index)?;
+        let session = self.session.lock().unwrap();
+        session
+            .delete_ranges()
+            .schedule(self.storage.clone(), range);
+        Ok(())
+    }
+
+    /// Copy the rows into a table of a new id which stores them as `meta`, then let it replace
+    /// the old table, whose data is deleted in background. Like the other DDL here, it does not
+    /// wait for the statements of other sessions, the rows they write during the copy are lost.
+    async fn reorganize(&self, mut meta: TableInfo) -> MySQLResult<()> {
+        let table_mgr = self.session.lock().unwrap().get_table_manager();
+        meta.id = table_mgr.write().unwrap().alloc_table_id();
+        let old = self.plan.table.clone();
+        // The counters are stored in the range of the old table, carry them over to the new one.
+        let auto_id_end = old.auto_id_end(&self.storage).await?;
+        meta.auto_id = Arc::new(AutoIdAllocator::new(auto_id_end));
+        let row_id_end = old.row_id_end(&self.storage).await?;
+        meta.row_id = Arc::new(AutoIdAllocator::new(row_id_end));
+        let new = TableSource::new(Arc::new(meta.clone()));
+        let opts = self.transaction_options();
+        let txn = self.storage.new_transaction(&opts).await?;
+        let mut ctx = OptimisticTransactionContext::new(txn);
+        let schema = DataSchema {
+            columns: old.meta().columns.clone(),
+        };
+        let (mut start, end) = old.record_range()?;
+        let mut row = EncoderRow::default();
+        loop {
+            let (records, next) = old
+                .scan_record_batch(&mut ctx, &start, &end, REORGANIZE_BATCH_SIZE, &schema)
+                .await?;
+            for record in records {
+                let row_id = new.alloc_row_id(&self.storage).await?;
+                new.add_record(&mut ctx, &mut row, &new.meta().columns, record, row_id)
+                    .await?;
+                row.clear();
+            }
+            match next {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+        ctx.commit().await?;
+        self.replace_table(meta)?;
+        let session = self.session.lock().unwrap();
+        session.delete_table_data(&old, &self.storage)
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for AlterTableExecutor {
+    fn name(&self) -> &str {
+        "AlterTableExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut meta = self.plan.table.clone_meta();
+        match &self.plan.operation {
+            AlterTableOperation::AddColumn(def) => {
+                meta.add_column(def)?;
+                let name = def.name.value.to_lowercase();
+                let states = [TableState::DeleteOnly, TableState::WriteOnly];
+                self.replace_with_states(&meta, &name, &states)?;
+                self.replace_table(meta)
+            }
+            AlterTableOperation::DropColumn(name) => {
+                let mut dropped = meta.clone();
+                dropped.drop_column(name)?;
+                let states = [TableState::WriteOnly, TableState::DeleteOnly];
+                self.replace_with_states(&meta, name, &states)?;
+                self.replace_table(dropped)
+            }
+            AlterTableOperation::ModifyColumn(def) => {
+                if meta.modify_column(def)? {
+                    self.reorganize(meta).await
+                } else {
+                    self.replace_table(meta)
+                }
+            }
+            AlterTableOperation::RenameColumn(old, new) => {
+                meta.rename_column(old, new)?;
+                self.replace_table(meta)
+            }
+            AlterTableOperation::RenameTable(db, name) => {
+                let mut session = self.session.lock().unwrap();
+                session.rename_table(&self.plan.db, self.plan.table.name(), db, name.clone())
+            }
+            AlterTableOperation::AddIndex(index) => self.add_index(meta, index.clone()).await,
+            AlterTableOperation::DropIndex(name) => self.drop_index(meta, name).await,
+        }
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+    use crate::store::{Storage, TransactionOptions};
+    use crate::table::schema::IndexType;
+    use sqlparser::ast::DataType;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use tokio::runtime;
+
+    #[test]
+    fn test_alter_table() {
+        let (session, _, run) = connect(Config::default());
+        let bytes = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run("create table t (id int primary key, k int)").unwrap();
+        run("insert into t (id, k) values (1, 10)").unwrap();
+        let version = session.lock().unwrap().get_schema_version();
+        run("alter table t add column c int default 5").unwrap();
+        // The column passes through delete-only and write-only before it is public.
+        assert_eq!(session.lock().unwrap().get_schema_version(), version + 3);
+        assert!(matches!(
+            run("alter table t add c int"),
+            Err(MySQLError::DupColumn(_))
+        ));
+        run("insert into t (id, k, c) values (2, 20, 7)").unwrap();
+        assert_eq!(
+            run("select id, c from t").unwrap(),
+            vec![
+                vec![EncodeValue::Int(1), EncodeValue::Int(5)],
+                vec![EncodeValue::Int(2), EncodeValue::Int(7)],
+            ]
+        );
+
+        run("alter table t modify column k bigint").unwrap();
+        let table = session.lock().unwrap().get_table("test", "t").unwrap();
+        assert_eq!(
+            table.get_column(&"k".to_string()).unwrap().data_type,
+            DataType::BigInt
+        );
+        run("alter table t modify k char(10)").unwrap();
+        assert_eq!(
+            run("select k from t where id = 2").unwrap(),
+            vec![vec![bytes("20")]]
+        );
+
+        run("alter table t rename column c to d").unwrap();
+        assert!(run("select c from t").is_err());
+        run("alter table t drop column k").unwrap();
+        assert!(run("select k from t").is_err());
+        assert_eq!(
+            run("select * from t").unwrap(),
+            vec![
+                vec![EncodeValue::Int(1), EncodeValue::Int(5)],
+                vec![EncodeValue::Int(2), EncodeValue::Int(7)],
+            ]
+        );
+        assert!(matches!(
+            run("alter table t drop column id"),
+            Err(MySQLError::DropIndexedColumn(_))
+        ));
+
+        run("create database d1").unwrap();
+        run("alter table t rename to d1.t1").unwrap();
+        assert!(matches!(
+            run("select d from t"),
+            Err(MySQLError::NoTable(_))
+        ));
+        run("rename table d1.t1 to t").unwrap();
+        assert_eq!(
+            run("select d from t where id = 1").unwrap(),
+            vec![vec![EncodeValue::Int(5)]]
+        );
+    }
+
+    fn count_keys(storage: &Arc<dyn Storage>, range: &(Vec<u8>, Vec<u8>)) -> usize {
+        let r = runtime::Runtime::new().unwrap();
+        r.block_on(async {
+            let mut txn = storage
+                .new_transaction(&TransactionOptions::default())
+                .await
+                .unwrap();
+            txn.scan(&range.0, &range.1, usize::MAX)
+                .await
+                .unwrap()
+                .len()
+        })
+    }
+
+    #[test]
+    fn test_add_and_drop_index() {
+        let config = Config {
+            delete_range_delay: 0,
+            ..Config::default()
+        };
+        let (session, storage, run) = connect(config);
+        let table = || session.lock().unwrap().get_table("test", "t").unwrap();
+        let key = |name: &str| table().get_column(&name.to_string()).unwrap().key;
+
+        run("use test").unwrap();
+        run("create table t (id int primary key, k int, c int)").unwrap();
+        run("insert into t (id, k, c) values (1, 10, 100), (2, 20, 200)").unwrap();
+        let version = session.lock().unwrap().get_schema_version();
+        run("alter table t add index idx_k (k)").unwrap();
+        // The index passes through delete-only and write-only before it is public.
+        assert_eq!(session.lock().unwrap().get_schema_version(), version + 3);
+        let index = table().meta().indices.last().unwrap().clone();
+        let range = table().index_range(&index).unwrap();
+        // The entries of the rows before the index are filled in.
+        assert_eq!(count_keys(&storage, &range), 2);
+        assert_eq!(key("k"), IndexType::Index);
+        assert_eq!(key("c"), IndexType::None);
+        assert_eq!(
+            run("select id from t where k = 20").unwrap(),
+            vec![vec![EncodeValue::Int(2)]]
+        );
+
+        run("alter table t add unique (c)").unwrap();
+        assert_eq!(key("c"), IndexType::Unique);
+        assert!(matches!(
+            run("create index k2 on t (k)"),
+            Err(MySQLError::IndexExist)
+        ));
+        assert!(matches!(
+            run("alter table t add primary key (c)"),
+            Err(MySQLError::MultiplePrimaryKey)
+        ));
+        run("insert into t (id, k, c) values (3, 30, 300)").unwrap();
+        assert_eq!(count_keys(&storage, &range), 3);
+        assert!(matches!(
+            run("insert into t (id, k, c) values (4, 10, 300)"),
+            Err(MySQLError::DupEntry(..))
+        ));
+        // A unique index is not added if the rows before it have duplicate values.
+        run("insert into t (id, k, c) values (4, 10, 400)").unwrap();
+        match run("create unique index uk on t (k)") {
+            Err(MySQLError::DupEntry(value, name)) => {
+                assert_eq!(value, "10");
+                assert_eq!(name, "uk");
+            }
+            _ => assert!(false),
+        }
+        assert_eq!(key("k"), IndexType::Index);
+        assert!(matches!(
+            run("drop index uk on t"),
+            Err(MySQLError::NoIndex)
+        ));
+
+        run("drop index idx_k on t").unwrap();
+        assert_eq!(key("k"), IndexType::None);
+        assert_eq!(key("id"), IndexType::Primary);
+        run("alter table t drop key c").unwrap();
+        assert_eq!(key("c"), IndexType::None);
+        assert!(matches!(
+            run("drop index idx_k on t"),
+            Err(MySQLError::NoIndex)
+        ));
+
+        // The entries of the dropped index are deleted by the worker.
+        let worker = session.lock().unwrap().delete_ranges();
+        while worker.pending() > 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(count_keys(&storage, &range), 0);
+        assert_eq!(
+            run("select k from t where id = 3").unwrap(),
+            vec![vec![EncodeValue::Int(30)]]
+        );
+    }
+}
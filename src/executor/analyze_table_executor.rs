@@ -0,0 +1,52 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::planner::AnalyzeTablePlan;
+use crate::session::SessionRef;
+use crate::statistics::analyze_table;
+use crate::store::Storage;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+pub struct AnalyzeTableExecutor {
+    plan: AnalyzeTablePlan,
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+}
+
+impl AnalyzeTableExecutor {
+    pub fn new(
+        plan: AnalyzeTablePlan,
+        session: SessionRef,
+        storage: Arc<dyn Storage>,
+    ) -> AnalyzeTableExecutor {
+        AnalyzeTableExecutor {
+            plan,
+            session,
+            storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for AnalyzeTableExecutor {
+    fn name(&self) -> &str {
+        "AnalyzeTableExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let table = self.plan.table.clone();
+        let modify_count = table.meta().modify_count.load(Ordering::SeqCst);
+        let stats = analyze_table(self.storage.clone(), table.as_ref()).await?;
+        table
+            .meta()
+            .modify_count
+            .fetch_sub(modify_count, Ordering::SeqCst);
+        self.session.lock().unwrap().update_table_stats(stats);
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
@@ -1,6 +1,6 @@
 use super::Executor;
-use crate::common::SendableDataBlockStream;
-use crate::errors::MySQLResult;
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
 use crate::planner::CreateTablePlan;
 use crate::session::SessionRef;
 use crate::table::table::TableSource;
@@ -17,14 +17,35 @@ impl Executor for CreateTableExecutor {
         "CreateTableExecutor"
     }
 
-    async fn execute(&mut self) -> MySQLResult<SendableDataBlockStream> {
+    /// The tables referenced by the foreign keys must exist unless `foreign_key_checks` is off,
+    /// a table may reference itself.
+    async fn open(&mut self) -> MySQLResult<()> {
         let mut session = self.session.lock().unwrap();
+        if session.variables().foreign_key_checks {
+            let table_info = &self.plan.table_info;
+            for fk in table_info.foreign_keys.iter() {
+                let index = if fk.ref_db == self.plan.db && fk.ref_table == table_info.name {
+                    table_info.referenced_index(&fk.ref_columns)
+                } else {
+                    session
+                        .get_table(&fk.ref_db, &fk.ref_table)
+                        .and_then(|parent| parent.meta().referenced_index(&fk.ref_columns))
+                };
+                if index.is_none() {
+                    return Err(MySQLError::CannotAddForeignKey(fk.name.clone()));
+                }
+            }
+        }
         // TODO: run the realy DDL change.
         session.add_table(
+            &self.plan.db,
             self.plan.table_info.name.clone(),
             self.plan.table_info.clone(),
-        );
-        Ok(vec![])
+        )
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
     }
 }
 
@@ -0,0 +1,159 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::{CreateDatabasePlan, DropDatabasePlan, UseDatabasePlan};
+use crate::session::SessionRef;
+use crate::store::Storage;
+use std::sync::Arc;
+
+pub struct CreateDatabaseExecutor {
+    plan: CreateDatabasePlan,
+    session: SessionRef,
+}
+
+impl CreateDatabaseExecutor {
+    pub fn new(plan: CreateDatabasePlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for CreateDatabaseExecutor {
+    fn name(&self) -> &str {
+        "CreateDatabaseExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        match session.create_database(self.plan.name.clone()) {
+            Err(MySQLError::DBExists(_)) if self.plan.if_not_exists => Ok(()),
+            r => r,
+        }
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+pub struct DropDatabaseExecutor {
+    plan: DropDatabasePlan,
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+}
+
+impl DropDatabaseExecutor {
+    pub fn new(plan: DropDatabasePlan, session: SessionRef, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            plan,
+            session,
+            storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for DropDatabaseExecutor {
+    fn name(&self) -> &str {
+        "DropDatabaseExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        match session.drop_database(&self.plan.name, &self.storage) {
+            Err(MySQLError::NoDB(_)) if self.plan.if_exists => Ok(()),
+            r => r,
+        }
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+pub struct UseDatabaseExecutor {
+    plan: UseDatabasePlan,
+    session: SessionRef,
+}
+
+impl UseDatabaseExecutor {
+    pub fn new(plan: UseDatabasePlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for UseDatabaseExecutor {
+    fn name(&self) -> &str {
+        "UseDatabaseExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        session.set_db(self.plan.name.clone())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+
+    #[test]
+    fn test_databases() {
+        let (session, _, run) = connect(Config::default());
+
+        assert!(matches!(
+            run("create table t (id int primary key, k int)"),
+            Err(MySQLError::NoDBSelected)
+        ));
+        run("CREATE DATABASE d1").unwrap();
+        assert!(matches!(
+            run("create database d1"),
+            Err(MySQLError::DBExists(_))
+        ));
+        run("create schema if not exists d1").unwrap();
+        run("create table d1.t (id int primary key, k int)").unwrap();
+        run("insert into d1.t (id, k) values (1, 10)").unwrap();
+        run("use test").unwrap();
+        run("create table t (id int primary key, k int)").unwrap();
+        run("insert into t (id, k) values (2, 20)").unwrap();
+        let rows = run("select d1.t.k, test.t.k from d1.t, t where d1.t.id < test.t.id").unwrap();
+        assert_eq!(rows, vec![vec![EncodeValue::Int(10), EncodeValue::Int(20)]]);
+
+        assert!(matches!(run("use d2"), Err(MySQLError::NoDB(_))));
+        run("use d1").unwrap();
+        assert_eq!(
+            run("select k from t").unwrap(),
+            vec![vec![EncodeValue::Int(10)]]
+        );
+        // A database is not dropped while a table of another database references its tables.
+        run("create table d1.c (id int primary key, pid int, foreign key (pid) references t (id))")
+            .unwrap();
+        run("create table test.c (id int primary key, pid int, \
+             foreign key (pid) references d1.t (id))")
+        .unwrap();
+        assert!(matches!(
+            run("drop database d1"),
+            Err(MySQLError::RowIsReferenced(_))
+        ));
+        run("drop table test.c").unwrap();
+        run("drop database d1").unwrap();
+        assert_eq!(session.lock().unwrap().get_db(), "");
+        run("drop database if exists d1").unwrap();
+        assert!(matches!(
+            run("select k from d1.t"),
+            Err(MySQLError::NoTable(_))
+        ));
+        assert_eq!(
+            run("select k from test.t").unwrap(),
+            vec![vec![EncodeValue::Int(20)]]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::{DropTablePlan, TruncateTablePlan};
+use crate::session::SessionRef;
+use crate::store::Storage;
+use std::sync::Arc;
+
+pub struct DropTableExecutor {
+    plan: DropTablePlan,
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+}
+
+impl DropTableExecutor {
+    pub fn new(plan: DropTablePlan, session: SessionRef, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            plan,
+            session,
+            storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for DropTableExecutor {
+    fn name(&self) -> &str {
+        "DropTableExecutor"
+    }
+
+    /// The tables which exist are dropped even if some others do not, as MySQL does. Nothing is
+    /// dropped if another table references one of them.
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        for (db, name) in self.plan.tables.iter() {
+            session.check_referenced(db, name, &self.plan.tables)?;
+        }
+        let mut result = Ok(());
+        for (db, name) in self.plan.tables.iter() {
+            match session.drop_table(db, name, &self.storage) {
+                Err(MySQLError::NoTable(_)) if self.plan.if_exists => (),
+                Err(e) if result.is_ok() => result = Err(e),
+                _ => (),
+            }
+        }
+        result
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+pub struct TruncateTableExecutor {
+    plan: TruncateTablePlan,
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+}
+
+impl TruncateTableExecutor {
+    pub fn new(plan: TruncateTablePlan, session: SessionRef, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            plan,
+            session,
+            storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for TruncateTableExecutor {
+    fn name(&self) -> &str {
+        "TruncateTableExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        session.check_referenced(&self.plan.db, &self.plan.name, &[])?;
+        session.truncate_table(&self.plan.db, &self.plan.name, &self.storage)
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+    use crate::store::{Storage, TransactionOptions};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use tokio::runtime;
+
+    fn count_keys(storage: &Arc<dyn Storage>, range: &(Vec<u8>, Vec<u8>)) -> usize {
+        let r = runtime::Runtime::new().unwrap();
+        r.block_on(async {
+            let mut txn = storage
+                .new_transaction(&TransactionOptions::default())
+                .await
+                .unwrap();
+            txn.scan(&range.0, &range.1, usize::MAX)
+                .await
+                .unwrap()
+                .len()
+        })
+    }
+
+    #[test]
+    fn test_drop_and_truncate_table() {
+        let config = Config {
+            delete_range_delay: 0,
+            ..Config::default()
+        };
+        let (session, storage, run) = connect(config);
+
+        run("use test").unwrap();
+        run("create table t (id int primary key auto_increment, k int)").unwrap();
+        run("create index k on t (k)").unwrap();
+        run("insert into t (k) values (10), (20)").unwrap();
+        let old = session.lock().unwrap().get_table("test", "t").unwrap();
+        let old_range = old.table_range().unwrap();
+        // Two records, two index entries and the counter of `AUTO_INCREMENT`.
+        assert_eq!(count_keys(&storage, &old_range), 5);
+
+        run("truncate table t").unwrap();
+        assert!(!old.is_valid());
+        assert!(run("select k from t").unwrap().is_empty());
+        run("insert into t (k) values (30)").unwrap();
+        assert_eq!(
+            run("select id, k from t").unwrap(),
+            vec![vec![EncodeValue::Int(1), EncodeValue::Int(30)]]
+        );
+        let new = session.lock().unwrap().get_table("test", "t").unwrap();
+        assert_ne!(new.id(), old.id());
+
+        run("create table t2 (id int primary key)").unwrap();
+        assert!(matches!(
+            run("drop table t, t3"),
+            Err(MySQLError::NoTable(_))
+        ));
+        assert!(matches!(
+            run("select k from t"),
+            Err(MySQLError::NoTable(_))
+        ));
+        run("drop table if exists t2, t3").unwrap();
+        assert!(matches!(run("truncate t2"), Err(MySQLError::NoTable(_))));
+
+        // The data of the truncated and dropped tables is deleted by the worker.
+        let worker = session.lock().unwrap().delete_ranges();
+        while worker.pending() > 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(count_keys(&storage, &old_range), 0);
+        assert_eq!(count_keys(&storage, &new.table_range().unwrap()), 0);
+    }
+}
@@ -1,14 +1,42 @@
-use super::create_index_executor::CreateIndexExecutor;
+use super::aggregation_executor::AggregationExecutor;
+use super::alter_table_executor::AlterTableExecutor;
+use super::analyze_table_executor::AnalyzeTableExecutor;
 use super::create_table_executor::CreateTableExecutor;
+use super::database_executor::{CreateDatabaseExecutor, DropDatabaseExecutor, UseDatabaseExecutor};
+use super::drop_table_executor::{DropTableExecutor, TruncateTableExecutor};
+use super::explain_executor::{AnalyzedExecutor, ExplainExecutor, OperatorStats};
+use super::filter_executor::FilterExecutor;
+use super::index_scan_executor::IndexScanExecutor;
 use super::insert_executor::InsertExecutor;
+use super::join_executor::{HashJoinExecutor, NestedLoopJoinExecutor};
+use super::kill_executor::KillExecutor;
+use super::limit_executor::LimitExecutor;
 use super::point_get_executor::PointGetExecutor;
+use super::projection_executor::ProjectionExecutor;
+use super::set_variable_executor::SetVariableExecutor;
+use super::show_executor::ShowExecutor;
+use super::sort_executor::SortExecutor;
+use super::spill::QueryMemory;
+use super::system_table_scan_executor::SystemTableScanExecutor;
+use super::table_dual_executor::TableDualExecutor;
+use super::table_scan_executor::TableScanExecutor;
+use super::view_executor::{CreateViewExecutor, DropViewExecutor};
 use crate::executor::Executor;
 use crate::planner::PlanNode;
 use crate::session::SessionRef;
-use crate::store::Storage;
+use crate::store::{StatsStorage, Storage};
+use std::cell::RefCell;
 use std::sync::Arc;
 
-pub struct ExecutorBuilder {}
+/// Build the executor tree of a plan, the operators of one tree share the memory of the query.
+pub struct ExecutorBuilder {
+    session: SessionRef,
+    storage: Arc<dyn Storage>,
+    memory: Arc<QueryMemory>,
+    /// The statistics of the operators built for `EXPLAIN ANALYZE`, in the order of the rows of
+    /// `EXPLAIN`.
+    analyzed: Option<RefCell<Vec<Arc<OperatorStats>>>>,
+}
 
 impl ExecutorBuilder {
     pub fn build(
@@ -16,12 +44,142 @@ impl ExecutorBuilder {
         session: SessionRef,
         storage: Arc<dyn Storage>,
     ) -> Box<dyn Executor> {
+        Self::new(session, storage, false).build_plan(plan)
+    }
+
+    /// Build the executor tree of a plan for `EXPLAIN ANALYZE`, every operator records its rows,
+    /// time and storage requests. The statistics are returned in the order of the rows of
+    /// `EXPLAIN`.
+    fn build_analyzed(
+        plan: PlanNode,
+        session: SessionRef,
+        storage: Arc<dyn Storage>,
+    ) -> (Box<dyn Executor>, Vec<Arc<OperatorStats>>) {
+        let builder = Self::new(session, storage, true);
+        let executor = builder.build_plan(plan);
+        (executor, builder.analyzed.unwrap().into_inner())
+    }
+
+    fn new(session: SessionRef, storage: Arc<dyn Storage>, analyze: bool) -> ExecutorBuilder {
+        let memory = {
+            let session = session.lock().unwrap();
+            let variables = session.variables();
+            let tracker = session
+                .memory_tracker()
+                .child("query", variables.mem_quota_query);
+            QueryMemory::new(tracker, variables.oom_action, session.tmp_dir())
+        };
+        ExecutorBuilder {
+            session,
+            storage,
+            memory,
+            analyzed: if analyze {
+                Some(RefCell::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    fn build_plan(&self, plan: PlanNode) -> Box<dyn Executor> {
+        let operators = match &self.analyzed {
+            Some(operators) => operators,
+            None => return self.build_operator(plan, self.storage.clone()),
+        };
+        // The statistics are taken before the children are built, so they are in pre-order as
+        // the rows of `EXPLAIN`.
+        let stats = Arc::new(OperatorStats::default());
+        operators.borrow_mut().push(stats.clone());
+        let storage = Arc::new(StatsStorage::new(
+            self.storage.clone(),
+            stats.storage.clone(),
+        ));
+        let executor = self.build_operator(plan, storage);
+        Box::new(AnalyzedExecutor::new(executor, stats))
+    }
+
+    /// Build the executor of the root of `plan`, which sends its storage requests to `storage`.
+    fn build_operator(&self, plan: PlanNode, storage: Arc<dyn Storage>) -> Box<dyn Executor> {
+        let session = self.session.clone();
         match plan {
+            PlanNode::CreateDatabase(p) => Box::new(CreateDatabaseExecutor::new(p, session)),
+            PlanNode::DropDatabase(p) => Box::new(DropDatabaseExecutor::new(p, session, storage)),
+            PlanNode::UseDatabase(p) => Box::new(UseDatabaseExecutor::new(p, session)),
             PlanNode::CreateTable(p) => Box::new(CreateTableExecutor::new(p, session)),
+            PlanNode::DropTable(p) => Box::new(DropTableExecutor::new(p, session, storage)),
+            PlanNode::TruncateTable(p) => Box::new(TruncateTableExecutor::new(p, session, storage)),
+            PlanNode::AlterTable(p) => Box::new(AlterTableExecutor::new(p, session, storage)),
+            PlanNode::CreateView(p) => Box::new(CreateViewExecutor::new(p, session)),
+            PlanNode::DropView(p) => Box::new(DropViewExecutor::new(p, session)),
             PlanNode::PointGet(p) => Box::new(PointGetExecutor::new(p, storage)),
+            PlanNode::TableScan(p) => Box::new(TableScanExecutor::new(p, storage)),
+            PlanNode::SystemTableScan(p) => Box::new(SystemTableScanExecutor::new(p)),
+            PlanNode::IndexScan(p) => Box::new(IndexScanExecutor::new(p, storage)),
+            PlanNode::Filter(p) => Box::new(FilterExecutor::new(
+                self.build_plan(*p.input),
+                p.predicates,
+                p.schema,
+            )),
+            PlanNode::Projection(p) => Box::new(ProjectionExecutor::new(
+                self.build_plan(*p.input),
+                p.exprs,
+                p.schema,
+            )),
+            PlanNode::HashJoin(p) => Box::new(HashJoinExecutor::new(
+                self.build_plan(*p.left),
+                self.build_plan(*p.right),
+                p.join_type,
+                (p.left_keys, p.right_keys),
+                p.other_conditions,
+                p.schema,
+                self.memory.clone(),
+            )),
+            PlanNode::NestedLoopJoin(p) => Box::new(NestedLoopJoinExecutor::new(
+                self.build_plan(*p.left),
+                self.build_plan(*p.right),
+                p.join_type,
+                p.conditions,
+                p.schema,
+                self.memory.clone(),
+            )),
+            PlanNode::Aggregation(p) => {
+                let concurrency = session.lock().unwrap().variables().executor_concurrency;
+                Box::new(AggregationExecutor::new(
+                    self.build_plan(*p.input),
+                    p.group_by,
+                    p.aggs,
+                    p.schema,
+                    concurrency,
+                    self.memory.clone(),
+                ))
+            }
+            PlanNode::Sort(p) => Box::new(SortExecutor::new(
+                self.build_plan(*p.input),
+                p.items,
+                p.schema,
+                self.memory.clone(),
+            )),
+            PlanNode::Limit(p) => Box::new(LimitExecutor::new(
+                self.build_plan(*p.input),
+                p.limit,
+                p.offset,
+            )),
+            PlanNode::TableDual(p) => Box::new(TableDualExecutor::new(p.schema)),
             PlanNode::Insert(p) => Box::new(InsertExecutor::new(p, storage)),
-            PlanNode::CreateIndex(p) => Box::new(CreateIndexExecutor::new(p, session, storage)),
-            _ => unimplemented!(),
+            PlanNode::AnalyzeTable(p) => Box::new(AnalyzeTableExecutor::new(p, session, storage)),
+            PlanNode::Show(p) => Box::new(ShowExecutor::new(p)),
+            PlanNode::SetVariable(p) => Box::new(SetVariableExecutor::new(p, session)),
+            PlanNode::Kill(p) => Box::new(KillExecutor::new(p, session)),
+            PlanNode::Explain(p) => {
+                let mut rows = vec![];
+                p.plan.explain(0, &mut rows);
+                let analyze = if p.analyze {
+                    Some(Self::build_analyzed(*p.plan, session, storage))
+                } else {
+                    None
+                };
+                Box::new(ExplainExecutor::new(rows, analyze))
+            }
         }
     }
 }
@@ -0,0 +1,242 @@
+use crate::common::{DataBlock, EncodeValue};
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::ExplainRow;
+use crate::store::StorageStats;
+use crate::table::schema::DataSchema;
+use sqlparser::ast::DataType;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// What `EXPLAIN ANALYZE` measures of one operator. The time of an operator includes the time
+/// of its children which it drives, and the time of the parts of a split operator, which run
+/// concurrently, is added up. The storage requests are only the requests of the operator itself.
+#[derive(Default)]
+pub struct OperatorStats {
+    name: Mutex<String>,
+    rows: AtomicU64,
+    nanos: AtomicU64,
+    pub storage: Arc<StorageStats>,
+}
+
+impl OperatorStats {
+    fn add_time(&self, start: Instant) {
+        let nanos = start.elapsed().as_nanos() as u64;
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Wraps an operator of the plan analyzed by `EXPLAIN ANALYZE` to record its `OperatorStats`.
+pub struct AnalyzedExecutor {
+    inner: Box<dyn Executor>,
+    stats: Arc<OperatorStats>,
+}
+
+impl AnalyzedExecutor {
+    pub fn new(inner: Box<dyn Executor>, stats: Arc<OperatorStats>) -> AnalyzedExecutor {
+        *stats.name.lock().unwrap() = inner.name().to_string();
+        AnalyzedExecutor { inner, stats }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for AnalyzedExecutor {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let start = Instant::now();
+        let result = self.inner.open().await;
+        self.stats.add_time(start);
+        result
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let start = Instant::now();
+        let result = self.inner.next().await;
+        self.stats.add_time(start);
+        if let Ok(Some(block)) = &result {
+            self.stats
+                .rows
+                .fetch_add(block.len() as u64, Ordering::Relaxed);
+        }
+        result
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        let start = Instant::now();
+        let result = self.inner.close().await;
+        self.stats.add_time(start);
+        result
+    }
+
+    async fn split(&mut self, count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+        let parts = match self.inner.split(count).await? {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        let parts = parts
+            .into_iter()
+            .map(|part| {
+                let part = AnalyzedExecutor::new(part, self.stats.clone());
+                Box::new(part) as Box<dyn Executor>
+            })
+            .collect();
+        Ok(Some(parts))
+    }
+}
+
+pub struct ExplainExecutor {
+    rows: Vec<ExplainRow>,
+    /// The executor of the explained plan and the statistics of its operators in the order of
+    /// `rows`, only exists for `EXPLAIN ANALYZE`.
+    analyze: Option<(Box<dyn Executor>, Vec<Arc<OperatorStats>>)>,
+    output: Option<DataBlock>,
+}
+
+impl ExplainExecutor {
+    pub fn new(
+        rows: Vec<ExplainRow>,
+        analyze: Option<(Box<dyn Executor>, Vec<Arc<OperatorStats>>)>,
+    ) -> ExplainExecutor {
+        ExplainExecutor {
+            rows,
+            analyze,
+            output: None,
+        }
+    }
+
+    fn explain(&mut self) -> DataBlock {
+        let schema = DataSchema::result_schema(&[
+            ("id", DataType::String),
+            ("estRows", DataType::Double),
+            ("table", DataType::String),
+            ("index", DataType::String),
+            ("range", DataType::String),
+            ("filters", DataType::String),
+        ]);
+        let data = self
+            .rows
+            .drain(..)
+            .map(|row| {
+                vec![
+                    EncodeValue::Bytes(row.id.into_bytes()),
+                    EncodeValue::Double(row.est_rows),
+                    EncodeValue::Bytes(row.table.into_bytes()),
+                    EncodeValue::Bytes(row.index.into_bytes()),
+                    EncodeValue::Bytes(row.range.into_bytes()),
+                    EncodeValue::Bytes(row.filters.into_bytes()),
+                ]
+            })
+            .collect();
+        DataBlock::from_rows(Arc::new(schema), data)
+    }
+
+    async fn explain_analyze(
+        &mut self,
+        mut executor: Box<dyn Executor>,
+        operators: Vec<Arc<OperatorStats>>,
+    ) -> MySQLResult<DataBlock> {
+        executor.open().await?;
+        let drained = drain(&mut executor).await;
+        executor.close().await?;
+        drained?;
+        let schema = DataSchema::result_schema(&[
+            ("id", DataType::String),
+            ("estRows", DataType::Double),
+            ("actRows", DataType::BigInt),
+            ("executor", DataType::String),
+            ("time", DataType::String),
+            ("storage calls", DataType::String),
+            ("table", DataType::String),
+            ("index", DataType::String),
+            ("range", DataType::String),
+            ("filters", DataType::String),
+        ]);
+        let data = self
+            .rows
+            .drain(..)
+            .zip(operators)
+            .map(|(row, stats)| {
+                let act_rows = EncodeValue::Int(stats.rows.load(Ordering::Relaxed) as i64);
+                let name = stats.name.lock().unwrap().clone();
+                let time = Duration::from_nanos(stats.nanos.load(Ordering::Relaxed));
+                let time = format!("{:?}", time);
+                let calls = format!("{} ({})", stats.storage.total(), stats.storage);
+                vec![
+                    EncodeValue::Bytes(row.id.into_bytes()),
+                    EncodeValue::Double(row.est_rows),
+                    act_rows,
+                    EncodeValue::Bytes(name.into_bytes()),
+                    EncodeValue::Bytes(time.into_bytes()),
+                    EncodeValue::Bytes(calls.into_bytes()),
+                    EncodeValue::Bytes(row.table.into_bytes()),
+                    EncodeValue::Bytes(row.index.into_bytes()),
+                    EncodeValue::Bytes(row.range.into_bytes()),
+                    EncodeValue::Bytes(row.filters.into_bytes()),
+                ]
+            })
+            .collect();
+        Ok(DataBlock::from_rows(Arc::new(schema), data))
+    }
+}
+
+/// Read all rows of `executor`, which are counted by the operators.
+async fn drain(executor: &mut Box<dyn Executor>) -> MySQLResult<()> {
+    while executor.next().await?.is_some() {}
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl Executor for ExplainExecutor {
+    fn name(&self) -> &str {
+        "ExplainExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let block = match self.analyze.take() {
+            Some((executor, stats)) => self.explain_analyze(executor, stats).await?,
+            None => self.explain(),
+        };
+        self.output = Some(block);
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(self.output.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::executor::connect;
+
+    #[test]
+    fn test_explain_analyze() {
+        let (_, storage, run) = connect(Config::default());
+        let calls = |v: &EncodeValue| -> u64 {
+            let text = String::from(v.clone());
+            text.split_whitespace().next().unwrap().parse().unwrap()
+        };
+
+        run("use test").unwrap();
+        run("create table t (id int primary key, k int)").unwrap();
+        run("insert into t (id, k) values (1, 1), (2, 2), (3, 3)").unwrap();
+        let rows = run("explain analyze select k + 1 from t where k > 1").unwrap();
+        assert!(rows.len() > 1);
+        // Every operator reports its own rows and executor, only the scan reads storage.
+        for row in rows.iter() {
+            assert!(matches!(row[2], EncodeValue::Int(_)));
+            assert!(!String::from(row[3].clone()).is_empty());
+        }
+        assert_eq!(rows[0][2], EncodeValue::Int(2));
+        assert_eq!(calls(&rows[0][5]), 0);
+        let scan = rows.last().unwrap();
+        assert!(String::from(scan[3].clone()).contains("Scan"));
+        assert!(calls(&scan[5]) > 0);
+    }
+}
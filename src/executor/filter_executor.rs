@@ -0,0 +1,72 @@
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::Expression;
+use crate::table::schema::DataSchemaRef;
+
+pub struct FilterExecutor {
+    input: Box<dyn Executor>,
+    predicates: Vec<Expression>,
+    schema: DataSchemaRef,
+}
+
+impl FilterExecutor {
+    pub fn new(
+        input: Box<dyn Executor>,
+        predicates: Vec<Expression>,
+        schema: DataSchemaRef,
+    ) -> FilterExecutor {
+        FilterExecutor {
+            input,
+            predicates,
+            schema,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for FilterExecutor {
+    fn name(&self) -> &str {
+        "FilterExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.input.open().await
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let mut block = match self.input.next().await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        // Every predicate is only evaluated on the rows selected by the previous ones.
+        for p in self.predicates.iter() {
+            if block.is_empty() {
+                break;
+            }
+            let mask = p.eval_column(&block)?;
+            block.filter(&mask);
+        }
+        block.schema = self.schema.clone();
+        Ok(Some(block))
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.input.close().await
+    }
+
+    async fn split(&mut self, count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+        let parts = match self.input.split(count).await? {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        let parts = parts
+            .into_iter()
+            .map(|input| {
+                let part = FilterExecutor::new(input, self.predicates.clone(), self.schema.clone());
+                Box::new(part) as Box<dyn Executor>
+            })
+            .collect();
+        Ok(Some(parts))
+    }
+}
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
-use crate::common::SendableDataBlockStream;
-use crate::errors::MySQLResult;
+use crate::common::{DataBlock, EncodeValue};
+use crate::errors::{MySQLError, MySQLResult};
 use crate::executor::Executor;
-use crate::planner::InsertPlan;
+use crate::planner::{ForeignKeyCheck, InsertPlan};
+use crate::statistics::record_modify_count;
 use crate::store::{Storage, TransactionOptions};
+use crate::table::schema::TableState;
 use crate::table::EncoderRow;
 use crate::transaction::{OptimisticTransactionContext, TransactionContext};
 
@@ -19,20 +21,67 @@ impl Executor for InsertExecutor {
         "InsertIntoInterpreter"
     }
 
-    async fn execute(&mut self) -> MySQLResult<SendableDataBlockStream> {
-        let opts = TransactionOptions { pessimistic: false };
+    async fn open(&mut self) -> MySQLResult<()> {
+        let opts = TransactionOptions {
+            pessimistic: false,
+            memory: Some(self.plan.session.lock().unwrap().memory_tracker()),
+        };
+        let strict = self.plan.session.lock().unwrap().variables().strict_mode();
+        let first_id = self.alloc_auto_ids().await?;
         let txn = self.storage.new_transaction(&opts).await?;
         let mut ctx = OptimisticTransactionContext::new(txn);
         let mut row = EncoderRow::default();
+        let count = self.plan.values.len() as u64;
+        let table = self.plan.table.clone();
+        let mut warnings = vec![];
         for r in self.plan.values.drain(..) {
-            self.plan
-                .table
-                .add_record(&mut ctx, &mut row, &self.plan.schema.columns, r)
+            let columns = &self.plan.schema.columns;
+            let record = table.build_record(columns, r, strict, &mut warnings)?;
+            for check in self.plan.checks.iter() {
+                // A check is only violated if it is false, NULL satisfies it.
+                let v = check.expr.eval(&record)?;
+                if !v.is_null() && !v.is_true() {
+                    return Err(MySQLError::CheckViolated(check.check.name.clone()));
+                }
+            }
+            let references: Vec<Vec<EncodeValue>> = self
+                .plan
+                .foreign_keys
+                .iter()
+                .map(|check| {
+                    let columns = check.foreign_key.columns.iter();
+                    columns.map(|(_, offset)| record[*offset].clone()).collect()
+                })
+                .collect();
+            let row_id = table.alloc_row_id(&self.storage).await?;
+            table
+                .write_record(&mut ctx, &mut row, record, row_id)
                 .await?;
             row.clear();
+            // A row may reference itself, so it is checked after it is written.
+            for (check, values) in self.plan.foreign_keys.iter().zip(references) {
+                check_reference(&mut ctx, check, &values).await?;
+            }
         }
         ctx.commit().await?;
-        Ok(vec![])
+        let table_mgr = self.plan.session.lock().unwrap().get_table_manager();
+        // The failure of an earlier automatic analyze is reported to this statement.
+        warnings.extend(record_modify_count(
+            table_mgr,
+            self.storage.clone(),
+            self.plan.table.clone(),
+            count,
+        ));
+        let mut session = self.plan.session.lock().unwrap();
+        session.set_warnings(warnings);
+        if let Some(id) = first_id {
+            session.set_last_insert_id(id);
+        }
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
     }
 }
 
@@ -40,4 +89,381 @@ impl InsertExecutor {
     pub fn new(plan: InsertPlan, storage: Arc<dyn Storage>) -> InsertExecutor {
         InsertExecutor { storage, plan }
     }
+
+    /// Fill the `AUTO_INCREMENT` column of the rows which leave it out or insert NULL or 0 into
+    /// it, and make sure the values allocated later are above the ones inserted explicitly.
+    /// Return the first value allocated.
+    async fn alloc_auto_ids(&mut self) -> MySQLResult<Option<u64>> {
+        let table = self.plan.table.clone();
+        let col = match table
+            .meta()
+            .columns
+            .iter()
+            .find(|c| c.is_auto_increment() && c.state != TableState::DeleteOnly)
+        {
+            Some(col) => col.clone(),
+            None => return Ok(None),
+        };
+        let (increment, offset) = {
+            let session = self.plan.session.lock().unwrap();
+            let variables = session.variables();
+            (
+                variables.auto_increment_increment,
+                variables.auto_increment_offset,
+            )
+        };
+        let columns = &mut self.plan.schema.columns;
+        let pos = match columns.iter().position(|c| c.offset == col.offset) {
+            Some(pos) => pos,
+            None => {
+                columns.push(col);
+                for r in self.plan.values.iter_mut() {
+                    r.push(EncodeValue::NULL);
+                }
+                columns.len() - 1
+            }
+        };
+        let mut first_id = None;
+        for r in self.plan.values.iter_mut() {
+            match r[pos] {
+                EncodeValue::NULL | EncodeValue::Int(0) => {
+                    let id = table
+                        .alloc_auto_id(&self.storage, increment, offset)
+                        .await?;
+                    first_id.get_or_insert(id);
+                    r[pos] = EncodeValue::Int(id as i64);
+                }
+                EncodeValue::Int(v) if v > 0 => {
+                    table.rebase_auto_id(&self.storage, v as u64).await?
+                }
+                _ => (),
+            }
+        }
+        Ok(first_id)
+    }
+}
+
+/// Check that the row which `values` reference by a foreign key exists, `values` with NULL
+/// reference no row.
+async fn check_reference<W: TransactionContext>(
+    ctx: &mut W,
+    check: &ForeignKeyCheck,
+    values: &[EncodeValue],
+) -> MySQLResult<()> {
+    if values.iter().any(|v| v.is_null()) {
+        return Ok(());
+    }
+    if let Some((parent, index)) = check.parent.as_ref() {
+        if parent.contains_key(ctx, index, values).await? {
+            return Ok(());
+        }
+    }
+    Err(MySQLError::NoReferencedRow(check.foreign_key.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+    use crate::table::schema::ReferenceAction;
+
+    #[test]
+    fn test_auto_increment() {
+        let (session, _, run) = connect(Config::default());
+        let ids = |rows: Vec<Vec<EncodeValue>>| -> Vec<EncodeValue> {
+            rows.into_iter().map(|mut r| r.remove(0)).collect()
+        };
+
+        run("use test").unwrap();
+        run("create table t (id int primary key auto_increment, k int) \
+             engine=InnoDB auto_increment=100 default charset=utf8mb4")
+        .unwrap();
+        run("insert into t (k) values (1), (2)").unwrap();
+        assert_eq!(
+            ids(run("select id from t").unwrap()),
+            vec![EncodeValue::Int(100), EncodeValue::Int(101)]
+        );
+        assert_eq!(
+            run("select last_insert_id()").unwrap(),
+            vec![vec![EncodeValue::Int(100)]]
+        );
+        assert_eq!(session.lock().unwrap().take_insert_id(), 100);
+        assert_eq!(session.lock().unwrap().take_insert_id(), 0);
+
+        // An explicit value moves the counter above it, NULL and 0 are allocated.
+        run("insert into t (id, k) values (200, 3)").unwrap();
+        assert_eq!(session.lock().unwrap().last_insert_id(), 100);
+        run("insert into t (id, k) values (null, 4), (0, 5)").unwrap();
+        assert_eq!(
+            ids(run("select id from t where k > 3").unwrap()),
+            vec![EncodeValue::Int(201), EncodeValue::Int(202)]
+        );
+        assert_eq!(
+            run("select last_insert_id() + 1").unwrap(),
+            vec![vec![EncodeValue::Int(202)]]
+        );
+
+        run("set auto_increment_increment = 10").unwrap();
+        run("set auto_increment_offset = 5").unwrap();
+        run("insert into t (k) values (6), (7)").unwrap();
+        assert_eq!(
+            ids(run("select id from t where k > 5").unwrap()),
+            vec![EncodeValue::Int(205), EncodeValue::Int(215)]
+        );
+        assert!(matches!(
+            run("set auto_increment_increment = 0"),
+            Err(MySQLError::WrongValueForVariable(..))
+        ));
+        assert!(matches!(
+            run("set auto_increment_offset = 65536"),
+            Err(MySQLError::WrongValueForVariable(..))
+        ));
+    }
+
+    #[test]
+    fn test_unique_index() {
+        let (_, _, run) = connect(Config::default());
+
+        run("use test").unwrap();
+        run("create table t (id int, code int unique)").unwrap();
+        run("insert into t (id, code) values (1, 10), (2, 20)").unwrap();
+        match run("insert into t (id, code) values (3, 20)") {
+            Err(MySQLError::DupEntry(value, _)) => assert_eq!(value, "20"),
+            _ => assert!(false),
+        }
+        assert!(matches!(
+            run("insert into t (id, code) values (3, 30), (4, 30)"),
+            Err(MySQLError::DupEntry(..))
+        ));
+        // Any rows may have NULL in a unique index.
+        run("insert into t (id, code) values (3, null), (4, null)").unwrap();
+        assert_eq!(run("select id from t").unwrap().len(), 4);
+        assert_eq!(
+            run("select id from t where code = 20").unwrap(),
+            vec![vec![EncodeValue::Int(2)]]
+        );
+    }
+
+    #[test]
+    fn test_foreign_key() {
+        let (session, _, run) = connect(Config::default());
+
+        run("use test").unwrap();
+        run("create table parent (id int primary key, k int)").unwrap();
+        run("create table child (id int primary key, pid int, \
+             constraint fk_parent foreign key (pid) references parent (id) \
+             on delete cascade on update set null) engine=InnoDB")
+        .unwrap();
+        let child = session.lock().unwrap().get_table("test", "child").unwrap();
+        let fk = child.meta().foreign_keys[0].clone();
+        assert_eq!(fk.name, "fk_parent");
+        assert_eq!(fk.ref_db, "test");
+        assert_eq!(fk.on_delete, ReferenceAction::Cascade);
+        assert_eq!(fk.on_update, ReferenceAction::SetNull);
+
+        run("insert into parent (id, k) values (1, 10)").unwrap();
+        run("insert into child (id, pid) values (1, 1), (2, null)").unwrap();
+        assert!(matches!(
+            run("insert into child (id, pid) values (3, 1), (4, 2)"),
+            Err(MySQLError::NoReferencedRow(_))
+        ));
+        // The rows of the failed statement are not written.
+        assert_eq!(run("select id from child").unwrap().len(), 2);
+        assert!(matches!(
+            run("alter table child drop column pid"),
+            Err(MySQLError::DropForeignKeyColumn(..))
+        ));
+
+        // A table may reference itself, and a row may reference itself.
+        run(
+            "create table tree (id int primary key, up int, foreign key (up) references tree (id))",
+        )
+        .unwrap();
+        run("insert into tree (id, up) values (1, 1), (2, 1)").unwrap();
+        let tree = session.lock().unwrap().get_table("test", "tree").unwrap();
+        assert_eq!(tree.meta().foreign_keys[0].name, "tree_ibfk_1");
+
+        let orphan = "create table orphan (id int primary key, pid int, \
+                      foreign key (pid) references missing (id))";
+        assert!(matches!(
+            run(orphan),
+            Err(MySQLError::CannotAddForeignKey(_))
+        ));
+        assert!(matches!(
+            run("create table c (id int primary key, pid int not null, \
+                 foreign key (pid) references parent (id) on delete set null)"),
+            Err(MySQLError::CannotAddForeignKey(_))
+        ));
+        // A referenced table can not be dropped or emptied, whatever the actions are.
+        assert!(matches!(
+            run("drop table parent"),
+            Err(MySQLError::RowIsReferenced(_))
+        ));
+        assert!(matches!(
+            run("truncate table parent"),
+            Err(MySQLError::RowIsReferenced(_))
+        ));
+        assert_eq!(run("select id from parent").unwrap().len(), 1);
+        // A table which only references itself may be emptied.
+        run("truncate table tree").unwrap();
+        // The checks are skipped while `foreign_key_checks` is off.
+        run("set foreign_key_checks = 0").unwrap();
+        run(orphan).unwrap();
+        run("insert into orphan (id, pid) values (1, 5)").unwrap();
+        run("set foreign_key_checks = ON").unwrap();
+        assert!(matches!(
+            run("insert into orphan (id, pid) values (2, 5)"),
+            Err(MySQLError::NoReferencedRow(_))
+        ));
+        run("drop table child, parent").unwrap();
+    }
+
+    #[test]
+    fn test_check_values() {
+        let (_, _, run) = connect(Config::default());
+        let bytes = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run(
+            "create table t (id int primary key, name varchar(5) not null, \
+             age smallint check (age >= 0), tag char(2), \
+             constraint chk_name check (name <> 'bad'))",
+        )
+        .unwrap();
+        run("insert into t (id, name, age, tag) values (1, 'alice', 30, 'ab')").unwrap();
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, null, 1)"),
+            Err(MySQLError::BadNull(_))
+        ));
+        assert!(matches!(
+            run("insert into t (id, age) values (2, 1)"),
+            Err(MySQLError::NoDefault(_))
+        ));
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, 'bob', '-1')"),
+            Err(MySQLError::CheckViolated(_))
+        ));
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, 'bad', 1)"),
+            Err(MySQLError::CheckViolated(name)) if name == "chk_name"
+        ));
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, 'robert', 1)"),
+            Err(MySQLError::DataTooLong(_))
+        ));
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, 'bob', 40000)"),
+            Err(MySQLError::DataOutOfRange(_))
+        ));
+        assert!(matches!(
+            run("insert into t (id, name, age) values (2, 'bob', 'old')"),
+            Err(MySQLError::WrongValueForColumn(..))
+        ));
+        // NULL satisfies a check, and a nullable column left out is NULL.
+        run("insert into t (id, name, age) values (2, 'bob', null)").unwrap();
+        assert_eq!(
+            run("select age, tag from t where id = 2").unwrap(),
+            vec![vec![EncodeValue::NULL, EncodeValue::NULL]]
+        );
+
+        // The values are adjusted with warnings in non-strict mode.
+        run("set sql_mode = ''").unwrap();
+        run(
+            "insert into t (id, name, age, tag) values (3, 'robert', 40000, 'abc'), \
+             (4, null, 1, 'd')",
+        )
+        .unwrap();
+        assert_eq!(
+            run("select name, age, tag from t where id > 2").unwrap(),
+            vec![
+                vec![bytes("rober"), EncodeValue::Int(32767), bytes("ab")],
+                vec![bytes(""), EncodeValue::Int(1), bytes("d")],
+            ]
+        );
+        assert_eq!(run("show warnings").unwrap().len(), 4);
+        assert!(matches!(
+            run("insert into t (id, name, age) values (5, 'bob', '-1')"),
+            Err(MySQLError::CheckViolated(_))
+        ));
+        assert!(matches!(
+            run("set sql_mode = 'NO_SUCH_MODE'"),
+            Err(MySQLError::WrongValueForVariable(..))
+        ));
+
+        assert!(matches!(
+            run("alter table t drop column age"),
+            Err(MySQLError::DependentByCheck(..))
+        ));
+        assert!(matches!(
+            run("create table c (id int primary key, check (k > 0))"),
+            Err(MySQLError::MissColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_generated_columns() {
+        let (_, _, run) = connect(Config::default());
+        let int = EncodeValue::Int;
+
+        run("use test").unwrap();
+        run("create table t (id int primary key, c int, a int, b int, \
+             s int as (a + b) stored, v int generated always as (s * 2) virtual)")
+        .unwrap();
+        run("insert into t (id, a, b) values (1, 1, 2), (2, 3, null)").unwrap();
+        assert_eq!(
+            run("select s, v from t").unwrap(),
+            vec![
+                vec![int(3), int(6)],
+                vec![EncodeValue::NULL, EncodeValue::NULL]
+            ]
+        );
+        assert_eq!(
+            run("select v from t where id = 1").unwrap(),
+            vec![vec![int(6)]]
+        );
+        let create = String::from(run("show create table t").unwrap().remove(0).remove(1));
+        assert!(create.contains("`v` int GENERATED ALWAYS AS (s * 2) VIRTUAL"));
+
+        // The entries of an index on a virtual column are written, and the expressions still
+        // read the right columns after a column before them is dropped.
+        run("alter table t add index idx_v (v)").unwrap();
+        run("alter table t drop column c").unwrap();
+        run("insert into t (id, a, b) values (3, 5, 5)").unwrap();
+        assert_eq!(
+            run("select id, v from t where v > 5").unwrap(),
+            vec![vec![int(1), int(6)], vec![int(3), int(20)]]
+        );
+        // The entry of the row before the index is filled in, the one after it is written by
+        // the insert, and both are found through the index.
+        let plan = run("explain select id from t where v = 6").unwrap();
+        let index = EncodeValue::Bytes(b"idx_v".to_vec());
+        assert!(plan.iter().any(|row| row[3] == index));
+        assert_eq!(
+            run("select id from t where v = 6").unwrap(),
+            vec![vec![int(1)]]
+        );
+        assert_eq!(
+            run("select id from t where v = 20").unwrap(),
+            vec![vec![int(3)]]
+        );
+
+        assert!(matches!(
+            run("insert into t (id, s) values (4, 1)"),
+            Err(MySQLError::GeneratedColumnValue(..))
+        ));
+        assert!(matches!(
+            run("alter table t drop column a"),
+            Err(MySQLError::DependentByGenerated(_))
+        ));
+        assert!(matches!(
+            run("alter table t rename column s to total"),
+            Err(MySQLError::DependentByGenerated(_))
+        ));
+        assert!(matches!(
+            run("create table c (id int primary key, x int as (y + 1), y int as (id) stored)"),
+            Err(MySQLError::GeneratedColumnNonPrior)
+        ));
+    }
 }
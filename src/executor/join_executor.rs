@@ -0,0 +1,586 @@
+use super::spill::{
+    partition_of, row_size, MemoryReservation, QueryMemory, SpillFile, SpillReader,
+    SPILL_BATCH_SIZE, SPILL_PARTITIONS,
+};
+use super::{collect_rows, eval_conditions};
+use crate::common::{Column, DataBlock, EncodeValue};
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::{Expression, JoinType};
+use crate::table::schema::DataSchemaRef;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Compute the columns of join keys for a block.
+fn eval_join_keys(keys: &[Expression], block: &DataBlock) -> MySQLResult<Vec<Column>> {
+    let mut columns = Vec::with_capacity(keys.len());
+    for k in keys {
+        columns.push(k.eval_column(block)?);
+    }
+    Ok(columns)
+}
+
+/// Encode the values of join keys of the row `i` so that values which are equal in SQL have the
+/// same bytes. Return `None` if any key is NULL, since NULL never equals to any value.
+fn encode_join_key(keys: &[Column], i: usize) -> MySQLResult<Option<Vec<u8>>> {
+    let mut output = vec![];
+    for k in keys {
+        if k.is_null(i) {
+            return Ok(None);
+        }
+        k.encode_hash_key(i, &mut output)?;
+    }
+    Ok(Some(output))
+}
+
+/// Append a row of NULL to the row of the outer side which matches nothing.
+fn pad_null(row: Vec<EncodeValue>, width: usize) -> Vec<EncodeValue> {
+    let mut row = row;
+    row.resize(width, EncodeValue::NULL);
+    row
+}
+
+pub struct HashJoinExecutor {
+    left: Box<dyn Executor>,
+    right: Box<dyn Executor>,
+    join_type: JoinType,
+    left_keys: Vec<Expression>,
+    right_keys: Vec<Expression>,
+    other_conditions: Vec<Expression>,
+    schema: DataSchemaRef,
+    memory: Arc<QueryMemory>,
+    state: Option<HashJoinState>,
+}
+
+impl HashJoinExecutor {
+    pub fn new(
+        left: Box<dyn Executor>,
+        right: Box<dyn Executor>,
+        join_type: JoinType,
+        (left_keys, right_keys): (Vec<Expression>, Vec<Expression>),
+        other_conditions: Vec<Expression>,
+        schema: DataSchemaRef,
+        memory: Arc<QueryMemory>,
+    ) -> HashJoinExecutor {
+        HashJoinExecutor {
+            left,
+            right,
+            join_type,
+            left_keys,
+            right_keys,
+            other_conditions,
+            schema,
+            memory,
+            state: None,
+        }
+    }
+
+    fn prober(&self, build: HashTable) -> HashJoinProber {
+        HashJoinProber {
+            build,
+            join_type: self.join_type,
+            other_conditions: self.other_conditions.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+
+    /// Spill the rows of the probe side to the partitions of their join keys. The rows whose
+    /// keys are NULL match nothing, they are put into the first partition.
+    async fn partition_probe_side(&mut self) -> MySQLResult<Vec<SpillFile>> {
+        let mut partitions = create_partitions(&self.memory)?;
+        while let Some(block) = self.left.next().await? {
+            let keys = eval_join_keys(&self.left_keys, &block)?;
+            for i in 0..block.len() {
+                let key = encode_join_key(&keys, i)?;
+                let p = key.as_deref().map_or(0, partition_of);
+                write_keyed_row(&mut partitions[p], key, block.row(i))?;
+            }
+        }
+        Ok(partitions)
+    }
+}
+
+/// The rows of the build side indexed by their join keys, the rows whose keys are NULL are
+/// dropped since they match nothing, and the build side is never returned without a match.
+struct HashTable {
+    rows: Vec<Vec<EncodeValue>>,
+    index: HashMap<Vec<u8>, Vec<usize>>,
+    reservation: MemoryReservation,
+}
+
+impl HashTable {
+    fn new(memory: &Arc<QueryMemory>) -> HashTable {
+        HashTable {
+            rows: vec![],
+            index: HashMap::default(),
+            reservation: memory.reservation(),
+        }
+    }
+
+    fn insert(&mut self, key: Vec<u8>, row: Vec<EncodeValue>) -> MySQLResult<()> {
+        self.reservation.grow(key.len() + row_size(&row))?;
+        self.index
+            .entry(key)
+            .or_insert_with(Vec::new)
+            .push(self.rows.len());
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// Build the table from the rows of a spilled partition.
+    fn load(memory: &Arc<QueryMemory>, file: SpillFile) -> MySQLResult<HashTable> {
+        let mut table = HashTable::new(memory);
+        let mut reader = file.into_reader()?;
+        while let Some(row) = reader.read_row()? {
+            if let (Some(key), row) = split_keyed_row(row) {
+                table.insert(key, row)?;
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Every row of the probe side is joined with the rows of the build side as it arrives.
+struct HashJoinProber {
+    build: HashTable,
+    join_type: JoinType,
+    other_conditions: Vec<Expression>,
+    schema: DataSchemaRef,
+}
+
+impl HashJoinProber {
+    /// Join the rows of the probe side with their encoded join keys.
+    fn probe(&self, rows: Vec<(Option<Vec<u8>>, Vec<EncodeValue>)>) -> MySQLResult<DataBlock> {
+        let width = self.schema.columns.len();
+        let mut data = vec![];
+        for (key, probe) in rows {
+            let mut matched = false;
+            if let Some(indices) = key.and_then(|key| self.build.index.get(&key)) {
+                for i in indices {
+                    let mut row = probe.clone();
+                    row.extend(self.build.rows[*i].iter().cloned());
+                    if eval_conditions(&self.other_conditions, &row)? {
+                        data.push(row);
+                        matched = true;
+                    }
+                }
+            }
+            if !matched && self.join_type == JoinType::Left {
+                data.push(pad_null(probe, width));
+            }
+        }
+        Ok(DataBlock::from_rows(self.schema.clone(), data))
+    }
+
+    fn probe_block(&self, keys: &[Expression], block: DataBlock) -> MySQLResult<DataBlock> {
+        let keys = eval_join_keys(keys, &block)?;
+        let mut rows = Vec::with_capacity(block.len());
+        for i in 0..block.len() {
+            rows.push((encode_join_key(&keys, i)?, block.row(i)));
+        }
+        self.probe(rows)
+    }
+}
+
+enum HashJoinState {
+    /// The build side is in memory, and the probe side is joined block by block.
+    Memory(HashJoinProber),
+    /// Both sides are spilled to partitions by the hash of their join keys, and every pair of
+    /// partitions is joined in memory as grace hash join does.
+    Spilled {
+        partitions: Vec<(SpillFile, SpillFile)>,
+        current: Option<(HashJoinProber, SpillReader)>,
+        /// Whether a block has been returned, since a query returns at least one block.
+        returned: bool,
+    },
+}
+
+fn create_partitions(memory: &QueryMemory) -> MySQLResult<Vec<SpillFile>> {
+    (0..SPILL_PARTITIONS)
+        .map(|_| memory.create_file())
+        .collect()
+}
+
+/// The encoded join key is written before the row, NULL if any key is NULL.
+fn write_keyed_row(
+    file: &mut SpillFile,
+    key: Option<Vec<u8>>,
+    mut row: Vec<EncodeValue>,
+) -> MySQLResult<()> {
+    row.insert(0, key.map_or(EncodeValue::NULL, EncodeValue::Bytes));
+    file.write_row(&row)
+}
+
+fn split_keyed_row(mut row: Vec<EncodeValue>) -> (Option<Vec<u8>>, Vec<EncodeValue>) {
+    match row.remove(0) {
+        EncodeValue::Bytes(key) => (Some(key), row),
+        _ => (None, row),
+    }
+}
+
+enum BuildSide {
+    Memory(HashTable),
+    /// The partitions of the rows by the hash of their join keys.
+    Spilled(Vec<SpillFile>),
+}
+
+/// Read all rows of the build side and index them by their join keys. Once the query exceeds
+/// its memory, the rows are spilled to partitions instead.
+async fn build_hash_table(
+    build: &mut Box<dyn Executor>,
+    build_keys: &[Expression],
+    memory: &Arc<QueryMemory>,
+) -> MySQLResult<BuildSide> {
+    let mut table = HashTable::new(memory);
+    while let Some(block) = build.next().await? {
+        let keys = eval_join_keys(build_keys, &block)?;
+        for i in 0..block.len() {
+            if let Some(key) = encode_join_key(&keys, i)? {
+                table.insert(key, block.row(i))?;
+            }
+        }
+        if memory.exceeded() {
+            let partitions = spill_build_side(build, build_keys, memory, table).await?;
+            return Ok(BuildSide::Spilled(partitions));
+        }
+    }
+    Ok(BuildSide::Memory(table))
+}
+
+async fn spill_build_side(
+    build: &mut Box<dyn Executor>,
+    build_keys: &[Expression],
+    memory: &Arc<QueryMemory>,
+    table: HashTable,
+) -> MySQLResult<Vec<SpillFile>> {
+    let mut partitions = create_partitions(memory)?;
+    let mut keys = vec![vec![]; table.rows.len()];
+    for (key, indices) in table.index {
+        for i in indices {
+            keys[i] = key.clone();
+        }
+    }
+    for (key, row) in keys.into_iter().zip(table.rows) {
+        let p = partition_of(&key);
+        write_keyed_row(&mut partitions[p], Some(key), row)?;
+    }
+    while let Some(block) = build.next().await? {
+        let keys = eval_join_keys(build_keys, &block)?;
+        for i in 0..block.len() {
+            if let Some(key) = encode_join_key(&keys, i)? {
+                let p = partition_of(&key);
+                write_keyed_row(&mut partitions[p], Some(key), block.row(i))?;
+            }
+        }
+    }
+    Ok(partitions)
+}
+
+#[async_trait::async_trait]
+impl Executor for HashJoinExecutor {
+    fn name(&self) -> &str {
+        "HashJoinExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        // The build side is read to the end and closed before the probe side is opened, so
+        // that they never hold the transaction of the session at the same time.
+        self.right.open().await?;
+        let built = build_hash_table(&mut self.right, &self.right_keys, &self.memory).await;
+        self.right.close().await?;
+        match built? {
+            BuildSide::Memory(table) => {
+                self.state = Some(HashJoinState::Memory(self.prober(table)));
+                self.left.open().await
+            }
+            BuildSide::Spilled(build_partitions) => {
+                self.left.open().await?;
+                let probe_partitions = self.partition_probe_side().await?;
+                self.state = Some(HashJoinState::Spilled {
+                    partitions: build_partitions.into_iter().zip(probe_partitions).collect(),
+                    current: None,
+                    returned: false,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let (partitions, current, returned) = match self.state.as_mut() {
+            Some(HashJoinState::Memory(prober)) => {
+                return match self.left.next().await? {
+                    Some(block) => Ok(Some(prober.probe_block(&self.left_keys, block)?)),
+                    None => Ok(None),
+                };
+            }
+            Some(HashJoinState::Spilled {
+                partitions,
+                current,
+                returned,
+            }) => (partitions, current, returned),
+            None => return Ok(None),
+        };
+        loop {
+            if let Some((prober, reader)) = current.as_mut() {
+                let rows = reader.read_rows(SPILL_BATCH_SIZE)?;
+                if !rows.is_empty() {
+                    *returned = true;
+                    let rows = rows.into_iter().map(split_keyed_row).collect();
+                    return prober.probe(rows).map(Some);
+                }
+            }
+            *current = None;
+            let (build, probe) = match partitions.pop() {
+                Some(partition) => partition,
+                None if !*returned => {
+                    *returned = true;
+                    return Ok(Some(DataBlock::from_rows(self.schema.clone(), vec![])));
+                }
+                None => return Ok(None),
+            };
+            let build = HashTable::load(&self.memory, build)?;
+            let prober = HashJoinProber {
+                build,
+                join_type: self.join_type,
+                other_conditions: self.other_conditions.clone(),
+                schema: self.schema.clone(),
+            };
+            *current = Some((prober, probe.into_reader()?));
+        }
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.state = None;
+        self.left.close().await
+    }
+}
+
+pub struct NestedLoopJoinExecutor {
+    left: Box<dyn Executor>,
+    right: Box<dyn Executor>,
+    join_type: JoinType,
+    conditions: Vec<Expression>,
+    schema: DataSchemaRef,
+    memory: Arc<QueryMemory>,
+    prober: Option<NestedLoopJoinProber>,
+}
+
+impl NestedLoopJoinExecutor {
+    pub fn new(
+        left: Box<dyn Executor>,
+        right: Box<dyn Executor>,
+        join_type: JoinType,
+        conditions: Vec<Expression>,
+        schema: DataSchemaRef,
+        memory: Arc<QueryMemory>,
+    ) -> NestedLoopJoinExecutor {
+        NestedLoopJoinExecutor {
+            left,
+            right,
+            join_type,
+            conditions,
+            schema,
+            memory,
+            prober: None,
+        }
+    }
+}
+
+/// The rows of the inner side, every block of the outer side is joined with all of them.
+struct NestedLoopJoinProber {
+    inner_rows: Vec<Vec<EncodeValue>>,
+    _reservation: MemoryReservation,
+    join_type: JoinType,
+    conditions: Vec<Expression>,
+    schema: DataSchemaRef,
+}
+
+impl NestedLoopJoinProber {
+    fn probe(&self, block: DataBlock) -> MySQLResult<DataBlock> {
+        let width = self.schema.columns.len();
+        let mut data = vec![];
+        for outer in block.into_rows() {
+            let mut matched = false;
+            for inner in self.inner_rows.iter() {
+                let mut row = outer.clone();
+                row.extend(inner.iter().cloned());
+                if eval_conditions(&self.conditions, &row)? {
+                    data.push(row);
+                    matched = true;
+                }
+            }
+            if !matched && self.join_type == JoinType::Left {
+                data.push(pad_null(outer, width));
+            }
+        }
+        Ok(DataBlock::from_rows(self.schema.clone(), data))
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for NestedLoopJoinExecutor {
+    fn name(&self) -> &str {
+        "NestedLoopJoinExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.right.open().await?;
+        let mut reservation = self.memory.unspillable_reservation();
+        let inner_rows = collect_rows(&mut self.right, &mut reservation).await;
+        self.right.close().await?;
+        let inner_rows = inner_rows?;
+        self.prober = Some(NestedLoopJoinProber {
+            inner_rows,
+            _reservation: reservation,
+            join_type: self.join_type,
+            conditions: self.conditions.clone(),
+            schema: self.schema.clone(),
+        });
+        self.left.open().await
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let prober = match self.prober.as_ref() {
+            Some(prober) => prober,
+            None => return Ok(None),
+        };
+        match self.left.next().await? {
+            Some(block) => Ok(Some(prober.probe(block)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.prober = None;
+        self.left.close().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{MemTracker, OomAction};
+    use crate::planner::ColumnRef;
+    use crate::table::schema::DataSchema;
+    use sqlparser::ast::DataType;
+    use std::sync::Arc;
+    use tokio::runtime;
+
+    struct BlocksExecutor {
+        blocks: Vec<DataBlock>,
+    }
+
+    #[async_trait::async_trait]
+    impl Executor for BlocksExecutor {
+        fn name(&self) -> &str {
+            "BlocksExecutor"
+        }
+
+        async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+            if self.blocks.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(self.blocks.remove(0)))
+        }
+    }
+
+    #[test]
+    fn test_encode_join_key() {
+        let keys = vec![Expression::Column(ColumnRef {
+            id: 1,
+            index: 0,
+            name: "a".to_string(),
+            data_type: DataType::Double,
+        })];
+        let schema = Arc::new(DataSchema::result_schema(&[("a", DataType::Double)]));
+        let rows = vec![
+            vec![EncodeValue::Int(3)],
+            vec![EncodeValue::Double(3.0)],
+            vec![EncodeValue::Double(3.5)],
+            vec![EncodeValue::NULL],
+        ];
+        let block = DataBlock::from_rows(schema, rows);
+        let keys = eval_join_keys(&keys, &block).unwrap();
+        let a = encode_join_key(&keys, 0).unwrap();
+        let b = encode_join_key(&keys, 1).unwrap();
+        assert_eq!(a, b);
+        let c = encode_join_key(&keys, 2).unwrap();
+        assert_ne!(a, c);
+        assert!(encode_join_key(&keys, 3).unwrap().is_none());
+    }
+
+    /// Left join `(a, b)` with `(c)` on `a = c`, and return the sorted output rows.
+    fn hash_join(memory_limit: usize) -> Vec<String> {
+        let int = EncodeValue::Int;
+        let schema = Arc::new(DataSchema::result_schema(&[
+            ("a", DataType::Int),
+            ("b", DataType::Int),
+            ("c", DataType::Int),
+        ]));
+        let column = |index: usize| {
+            Expression::Column(ColumnRef {
+                id: index + 1,
+                index,
+                name: format!("c{}", index),
+                data_type: DataType::Int,
+            })
+        };
+        let left_schema = Arc::new(DataSchema::result_schema(&[
+            ("a", DataType::Int),
+            ("b", DataType::Int),
+        ]));
+        let right_schema = Arc::new(DataSchema::result_schema(&[("c", DataType::Int)]));
+        let left = BlocksExecutor {
+            blocks: vec![
+                DataBlock::from_rows(
+                    left_schema.clone(),
+                    vec![vec![int(1), int(10)], vec![EncodeValue::NULL, int(11)]],
+                ),
+                DataBlock::from_rows(
+                    left_schema,
+                    vec![vec![int(2), int(12)], vec![int(4), int(13)]],
+                ),
+            ],
+        };
+        let right = BlocksExecutor {
+            blocks: vec![
+                DataBlock::from_rows(right_schema.clone(), vec![vec![int(1)], vec![int(2)]]),
+                DataBlock::from_rows(
+                    right_schema,
+                    vec![vec![int(2)], vec![EncodeValue::NULL], vec![int(3)]],
+                ),
+            ],
+        };
+        let tracker = MemTracker::root("query", memory_limit);
+        let memory = QueryMemory::new(tracker, OomAction::Spill, std::env::temp_dir());
+        let mut executor = HashJoinExecutor::new(
+            Box::new(left),
+            Box::new(right),
+            JoinType::Left,
+            (vec![column(0)], vec![column(0)]),
+            vec![],
+            schema,
+            memory,
+        );
+        let r = runtime::Runtime::new().unwrap();
+        let mut rows: Vec<String> = r.block_on(async {
+            executor.open().await.unwrap();
+            let mut rows = vec![];
+            while let Some(block) = executor.next().await.unwrap() {
+                rows.extend(block.into_rows().iter().map(|row| format!("{:?}", row)));
+            }
+            executor.close().await.unwrap();
+            rows
+        });
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn test_grace_hash_join() {
+        let rows = hash_join(0);
+        assert_eq!(rows.len(), 5);
+        assert_eq!(hash_join(1), rows);
+    }
+}
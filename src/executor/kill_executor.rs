@@ -0,0 +1,36 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::KillPlan;
+use crate::session::SessionRef;
+
+pub struct KillExecutor {
+    plan: KillPlan,
+    session: SessionRef,
+}
+
+impl KillExecutor {
+    pub fn new(plan: KillPlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for KillExecutor {
+    fn name(&self) -> &str {
+        "KillExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let processes = self.session.lock().unwrap().processes();
+        let process = processes
+            .get(self.plan.connection_id)
+            .ok_or(MySQLError::UnknownThread(self.plan.connection_id))?;
+        process.kill(self.plan.query_only);
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+
+pub struct LimitExecutor {
+    input: Box<dyn Executor>,
+    /// The number of rows which can still be returned.
+    remaining: Option<usize>,
+    /// The number of rows which still have to be skipped.
+    skip: usize,
+    returned: bool,
+}
+
+impl LimitExecutor {
+    pub fn new(input: Box<dyn Executor>, limit: Option<usize>, offset: usize) -> LimitExecutor {
+        LimitExecutor {
+            input,
+            remaining: limit,
+            skip: offset,
+            returned: false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for LimitExecutor {
+    fn name(&self) -> &str {
+        "LimitExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.input.open().await
+    }
+
+    /// The input is not read any more once enough rows have been returned, but the first block
+    /// is always returned so that the client receives the columns.
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        loop {
+            if self.remaining == Some(0) && self.returned {
+                return Ok(None);
+            }
+            let mut block = match self.input.next().await? {
+                Some(block) => block,
+                None => return Ok(None),
+            };
+            let skip = self.skip.min(block.len());
+            let mut len = block.len() - skip;
+            self.skip -= skip;
+            if let Some(remaining) = self.remaining.as_mut() {
+                len = len.min(*remaining);
+                *remaining -= len;
+            }
+            block.slice(skip, len);
+            if block.is_empty() && self.returned {
+                continue;
+            }
+            self.returned = true;
+            return Ok(Some(block));
+        }
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.input.close().await
+    }
+}
@@ -1,15 +1,278 @@
-mod create_index_executor;
+mod aggregation_executor;
+mod alter_table_executor;
+mod analyze_table_executor;
 mod create_table_executor;
+mod database_executor;
+mod drop_table_executor;
 mod executor_builder;
+mod explain_executor;
+mod filter_executor;
+mod index_scan_executor;
 mod insert_executor;
+mod join_executor;
+mod kill_executor;
+mod limit_executor;
+mod parallel_executor;
 mod point_get_executor;
+mod projection_executor;
+mod range_scanner;
+mod set_variable_executor;
+mod show_executor;
+mod sort_executor;
+mod spill;
+mod system_table_scan_executor;
+mod table_dual_executor;
+mod table_scan_executor;
+mod view_executor;
 
-use crate::common::SendableDataBlockStream;
+use crate::common::{DataBlock, EncodeValue, SendableDataBlockStream};
 use crate::errors::MySQLResult;
+use crate::planner::Expression;
+use crate::session::SessionRef;
+use crate::store::{Storage, TransactionOptions};
+use crate::table::schema::DataSchemaRef;
+use crate::transaction::OptimisticTransactionContext;
 pub use executor_builder::ExecutorBuilder;
+use futures::stream;
+use spill::MemoryReservation;
+use std::sync::Arc;
 
+/// An operator of the executor tree built from a plan. The parent drives its children by
+/// `open`, `next` and `close`, and every call of `next` returns one block, so only the blocks in
+/// flight are held in memory. A query returns at least one block, which may be empty, so that
+/// the client always receives the columns of the result.
 #[async_trait::async_trait]
 pub trait Executor: Send + 'static {
     fn name(&self) -> &str;
-    async fn execute(&mut self) -> MySQLResult<SendableDataBlockStream>;
+
+    /// Prepare the executor and open its children, such as starting the transaction of a scan.
+    /// Statements without a result set, such as DDL, do all their work here.
+    async fn open(&mut self) -> MySQLResult<()> {
+        Ok(())
+    }
+
+    /// Return the next block of the result, or `None` after all rows have been returned.
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>>;
+
+    /// Release the resources held by the executor and close its children.
+    async fn close(&mut self) -> MySQLResult<()> {
+        Ok(())
+    }
+
+    /// Split an executor which is not opened into at most `count` executors, which return the
+    /// rows of this one together and can run concurrently. The parts are ordered, so their
+    /// results in order are the result of this executor. Return `None` if it can not be split.
+    async fn split(&mut self, _count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+        Ok(None)
+    }
+}
+
+enum ExecuteState {
+    Init(Box<dyn Executor>),
+    Opened(Box<dyn Executor>),
+    Done,
+}
+
+/// Run the root of an executor tree as a stream. The executor is opened when the stream is
+/// polled first, and closed after the last block or an error.
+pub fn execute(executor: Box<dyn Executor>) -> SendableDataBlockStream {
+    Box::pin(stream::unfold(
+        ExecuteState::Init(executor),
+        |state| async move {
+            let mut executor = match state {
+                ExecuteState::Init(mut executor) => {
+                    if let Err(e) = executor.open().await {
+                        let _ = executor.close().await;
+                        return Some((Err(e), ExecuteState::Done));
+                    }
+                    executor
+                }
+                ExecuteState::Opened(executor) => executor,
+                ExecuteState::Done => return None,
+            };
+            match executor.next().await {
+                Ok(Some(block)) => Some((Ok(block), ExecuteState::Opened(executor))),
+                Ok(None) => match executor.close().await {
+                    Ok(()) => None,
+                    Err(e) => Some((Err(e), ExecuteState::Done)),
+                },
+                Err(e) => {
+                    let _ = executor.close().await;
+                    Some((Err(e), ExecuteState::Done))
+                }
+            }
+        },
+    ))
+}
+
+/// Plan and run `sql` in `session`, return all rows of its result.
+#[cfg(test)]
+pub(crate) fn run(
+    session: &SessionRef,
+    storage: &Arc<dyn Storage>,
+    sql: &str,
+) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+    use crate::planner::PlanBuilder;
+    use futures::StreamExt;
+
+    let plan = PlanBuilder::create(session.clone()).build_from_sql(sql)?;
+    let mut blocks = execute(ExecutorBuilder::build(
+        plan,
+        session.clone(),
+        storage.clone(),
+    ));
+    let r = tokio::runtime::Runtime::new().unwrap();
+    r.block_on(async {
+        let mut rows = vec![];
+        while let Some(block) = blocks.next().await {
+            rows.extend(block?.into_rows());
+        }
+        Ok(rows)
+    })
+}
+
+/// Start a server core with `config` and connect to it, return the session of the connection,
+/// the storage of the core and a closure which runs SQL in them.
+#[cfg(test)]
+pub(crate) fn connect(
+    config: crate::config::Config,
+) -> (
+    SessionRef,
+    Arc<dyn Storage>,
+    impl Fn(&str) -> MySQLResult<Vec<Vec<EncodeValue>>>,
+) {
+    let core = tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(crate::mysql_driver::MysqlServerCore::new(config));
+    let conn = core.create_connection();
+    let (session, storage) = (conn.get_session(), conn.get_storage());
+    let (s, st) = (session.clone(), storage.clone());
+    (session, storage, move |sql: &str| run(&s, &st, sql))
+}
+
+/// The transaction to read data, it is taken from the session, or started for one statement if
+/// the session is not in a transaction. The transaction of the session is returned when this is
+/// dropped, so a scan which is not read to the end does not lose it.
+struct ReadContext {
+    session: SessionRef,
+    ctx: Option<OptimisticTransactionContext>,
+    owned: bool,
+}
+
+impl ReadContext {
+    async fn acquire(session: &SessionRef, storage: &Arc<dyn Storage>) -> MySQLResult<ReadContext> {
+        let (transaction, memory) = {
+            let mut session = session.lock().unwrap();
+            (session.take_transaction(), session.memory_tracker())
+        };
+        let (txn, owned) = match transaction {
+            Some(txn) => (txn, true),
+            None => {
+                let opts = TransactionOptions {
+                    pessimistic: false,
+                    memory: Some(memory),
+                };
+                (storage.new_transaction(&opts).await?, false)
+            }
+        };
+        Ok(ReadContext {
+            session: session.clone(),
+            ctx: Some(OptimisticTransactionContext::new(txn)),
+            owned,
+        })
+    }
+
+    /// Split a key range into at most `count` parts, each of which is read by its own snapshot
+    /// of the same timestamp. Return `None` if the range is too small to split, or the session
+    /// is in a transaction, whose own writes are not seen by the snapshots.
+    async fn split(
+        session: &SessionRef,
+        storage: &Arc<dyn Storage>,
+        (start, end): (Vec<u8>, Vec<u8>),
+        count: usize,
+    ) -> MySQLResult<Option<Vec<(ReadContext, (Vec<u8>, Vec<u8>))>>> {
+        if count <= 1 || session.lock().unwrap().has_transaction() {
+            return Ok(None);
+        }
+        let keys = storage.split_range(&start, &end, count).await?;
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let snapshots = storage.new_snapshots(keys.len() + 1).await?;
+        let mut bounds = Vec::with_capacity(keys.len() + 2);
+        bounds.push(start);
+        bounds.extend(keys);
+        bounds.push(end);
+        let parts = snapshots
+            .into_iter()
+            .zip(bounds.windows(2))
+            .map(|(txn, range)| {
+                let ctx = ReadContext {
+                    session: session.clone(),
+                    ctx: Some(OptimisticTransactionContext::new(txn)),
+                    owned: false,
+                };
+                (ctx, (range[0].clone(), range[1].clone()))
+            })
+            .collect();
+        Ok(Some(parts))
+    }
+
+    fn ctx(&mut self) -> &mut OptimisticTransactionContext {
+        self.ctx.as_mut().unwrap()
+    }
+}
+
+impl Drop for ReadContext {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+        if let (Some(ctx), Ok(mut session)) = (self.ctx.take(), self.session.lock()) {
+            session.set_transaction(ctx.take_transaction());
+        }
+    }
+}
+
+/// Keep the records which satisfy all `filters`, and return the values of `columns` of them.
+/// Both the block of records and filters are ordered as the columns of the table.
+fn select_records(
+    records: DataBlock,
+    filters: &[Expression],
+    columns: DataSchemaRef,
+) -> MySQLResult<DataBlock> {
+    let mut block = records;
+    for f in filters {
+        if block.is_empty() {
+            break;
+        }
+        let mask = f.eval_column(&block)?;
+        block.filter(&mask);
+    }
+    let offsets: Vec<usize> = columns.columns.iter().map(|c| c.offset).collect();
+    Ok(block.project(columns, &offsets))
+}
+
+fn eval_conditions(conditions: &[Expression], row: &[EncodeValue]) -> MySQLResult<bool> {
+    for c in conditions {
+        if !c.eval(row)?.is_true() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Read the remaining rows of an opened executor, and reserve the memory they hold.
+async fn collect_rows(
+    executor: &mut Box<dyn Executor>,
+    reservation: &mut MemoryReservation,
+) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+    let mut rows = vec![];
+    while let Some(block) = executor.next().await? {
+        for row in block.into_rows() {
+            reservation.grow(spill::row_size(&row))?;
+            rows.push(row);
+        }
+    }
+    Ok(rows)
 }
@@ -0,0 +1,128 @@
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::executor::Executor;
+use std::collections::VecDeque;
+use std::future::Future;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// The number of blocks which a part reads ahead before they are taken.
+const PART_BUFFER_SIZE: usize = 2;
+
+/// The tasks of the workers of an executor, which are awaited in the order they are spawned.
+/// The tasks still running when this is dropped are aborted, such as when the statement is
+/// interrupted in the middle of the executor.
+pub(super) struct WorkerTasks<T> {
+    handles: VecDeque<JoinHandle<T>>,
+}
+
+impl<T: Send + 'static> WorkerTasks<T> {
+    pub(super) fn new() -> WorkerTasks<T> {
+        WorkerTasks {
+            handles: VecDeque::new(),
+        }
+    }
+
+    pub(super) fn spawn<F>(&mut self, task: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.handles.push_back(tokio::spawn(task));
+    }
+
+    /// Wait for the earliest task which has not been awaited, `None` if all have been.
+    pub(super) async fn join_next(&mut self) -> Option<MySQLResult<T>> {
+        let handle = self.handles.pop_front()?;
+        Some(
+            handle
+                .await
+                .map_err(|e| MySQLError::WorkerFailed(e.to_string())),
+        )
+    }
+}
+
+impl<T> Drop for WorkerTasks<T> {
+    fn drop(&mut self) {
+        for handle in self.handles.iter() {
+            handle.abort();
+        }
+    }
+}
+
+/// Run the parts of a split executor concurrently, each on its own task. The blocks are returned
+/// in the order of the parts, and every part only reads a few blocks ahead of the consumer, so
+/// the later parts are read while the earlier ones are taken without holding the whole result.
+pub(super) struct ParallelExecutor {
+    parts: Vec<Box<dyn Executor>>,
+    receivers: VecDeque<mpsc::Receiver<MySQLResult<DataBlock>>>,
+    workers: WorkerTasks<()>,
+}
+
+impl ParallelExecutor {
+    pub(super) fn new(parts: Vec<Box<dyn Executor>>) -> ParallelExecutor {
+        ParallelExecutor {
+            parts,
+            receivers: VecDeque::new(),
+            workers: WorkerTasks::new(),
+        }
+    }
+}
+
+async fn read_part(
+    executor: &mut Box<dyn Executor>,
+    tx: &mpsc::Sender<MySQLResult<DataBlock>>,
+) -> MySQLResult<()> {
+    executor.open().await?;
+    while let Some(block) = executor.next().await? {
+        // The consumer has gone, such as after an error of another part.
+        if tx.send(Ok(block)).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+async fn run_part(mut executor: Box<dyn Executor>, tx: mpsc::Sender<MySQLResult<DataBlock>>) {
+    let result = read_part(&mut executor, &tx).await;
+    let closed = executor.close().await;
+    if let Err(e) = result.and(closed) {
+        let _ = tx.send(Err(e)).await;
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for ParallelExecutor {
+    fn name(&self) -> &str {
+        "ParallelExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        for part in self.parts.drain(..) {
+            let (tx, rx) = mpsc::channel(PART_BUFFER_SIZE);
+            self.workers.spawn(run_part(part, tx));
+            self.receivers.push_back(rx);
+        }
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        while let Some(rx) = self.receivers.front_mut() {
+            match rx.recv().await {
+                Some(result) => return result.map(Some),
+                None => {
+                    self.receivers.pop_front();
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        // The parts which are still running stop once they find the receiver dropped.
+        self.receivers.clear();
+        while let Some(result) = self.workers.join_next().await {
+            result?;
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
-use crate::common::{DataBlock, SendableDataBlockStream};
+use super::select_records;
+use crate::common::DataBlock;
 use crate::errors::MySQLResult;
 use crate::executor::Executor;
 use crate::planner::PointGetPlan;
 use crate::store::{Storage, TransactionOptions};
-use crate::transaction::{AutoCommitContext, OptimisticTransactionContext};
+use crate::table::schema::DataSchema;
+use crate::transaction::{AutoCommitContext, OptimisticTransactionContext, TransactionContext};
 use std::sync::Arc;
 
 pub struct PointGetExecutor {
     plan: PointGetPlan,
     storage: Arc<dyn Storage>,
+    done: bool,
 }
 
 #[async_trait::async_trait]
@@ -17,7 +20,11 @@ impl Executor for PointGetExecutor {
         "PointGetExecutor"
     }
 
-    async fn execute(&mut self) -> MySQLResult<SendableDataBlockStream> {
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
         let transaction = {
             let mut session = self.plan.session.lock().unwrap();
             session.take_transaction()
@@ -26,21 +33,10 @@ impl Executor for PointGetExecutor {
             txn
         } else {
             if self.plan.index_info.primary {
-                let table = self.plan.table.clone();
                 let mut ctx = AutoCommitContext::new(self.storage.clone());
-                let ret = table
-                    .read_record_by_index(
-                        &mut ctx,
-                        self.plan.index_info.as_ref(),
-                        self.plan.select_columns.as_ref(),
-                        &self.plan.index_value,
-                    )
-                    .await?;
-                let data = if ret.is_empty() { vec![] } else { vec![ret] };
-                let schema = self.plan.select_columns.clone();
-                return Ok(vec![DataBlock { schema, data }]);
+                return self.execute_transaction(&mut ctx).await.map(Some);
             } else {
-                let opts = TransactionOptions { pessimistic: false };
+                let opts = TransactionOptions::default();
                 self.storage.new_transaction(&opts).await?
             }
         };
@@ -51,34 +47,104 @@ impl Executor for PointGetExecutor {
             .lock()
             .unwrap()
             .set_transaction(ctx.take_transaction());
-        ret
+        ret.map(Some)
     }
 }
 
 impl PointGetExecutor {
     pub fn new(plan: PointGetPlan, storage: Arc<dyn Storage>) -> PointGetExecutor {
-        PointGetExecutor { plan, storage }
+        PointGetExecutor {
+            plan,
+            storage,
+            done: false,
+        }
     }
 
-    async fn execute_transaction(
+    async fn execute_transaction<W: TransactionContext>(
         &mut self,
-        ctx: &mut OptimisticTransactionContext,
-    ) -> MySQLResult<SendableDataBlockStream> {
+        ctx: &mut W,
+    ) -> MySQLResult<DataBlock> {
         let table = self.plan.table.clone();
-        if let Some(handle) = table
-            .read_handle_from_index(ctx, self.plan.index_info.as_ref(), &self.plan.index_value)
-            .await?
-        {
-            let ret = table
-                .read_record(ctx, self.plan.select_columns.as_ref(), &handle)
+        let index_info = self.plan.index_info.clone();
+        let schema = Arc::new(DataSchema {
+            columns: table.meta().columns.clone(),
+        });
+        let record = if index_info.primary {
+            let record = table
+                .read_record(ctx, &schema, &self.plan.index_values)
                 .await?;
-            let schema = self.plan.select_columns.clone();
-            Ok(vec![DataBlock {
-                schema,
-                data: vec![ret],
-            }])
+            if record.is_empty() {
+                None
+            } else {
+                Some(record)
+            }
         } else {
-            Ok(vec![])
-        }
+            match table
+                .read_handle_from_index(ctx, index_info.as_ref(), &self.plan.index_values)
+                .await?
+            {
+                Some(handle) => table.read_record_by_handle(ctx, &schema, &handle).await?,
+                None => None,
+            }
+        };
+        select_records(
+            DataBlock::from_rows(schema, record.into_iter().collect()),
+            &self.plan.filters,
+            self.plan.select_columns.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+
+    #[test]
+    fn test_composite_primary_key_and_rowid() {
+        let (_, _, run) = connect(Config::default());
+        let bytes = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run("create table t (a int, b int, c int, primary key (a, b))").unwrap();
+        run("insert into t (a, b, c) values (1, 1, 11), (1, 2, 12), (2, 1, 21)").unwrap();
+        let sql = "select c from t where b = 2 and a = 1";
+        let plan = run(&format!("explain {}", sql)).unwrap();
+        assert!(plan.iter().any(|row| row[0] == bytes("  PointGet")));
+        assert_eq!(run(sql).unwrap(), vec![vec![EncodeValue::Int(12)]]);
+        assert!(run("select c from t where a = 2 and b = 2")
+            .unwrap()
+            .is_empty());
+        assert_eq!(run("select c from t where a = 1").unwrap().len(), 2);
+
+        // The records of a table without primary key are stored by a hidden `_rowid`.
+        run("create table r (a int, b int)").unwrap();
+        run("insert into r (a, b) values (1, 10), (1, 11), (2, 20)").unwrap();
+        assert_eq!(run("select a from r where a = 1").unwrap().len(), 2);
+        run("create unique index b on r (b)").unwrap();
+        assert_eq!(
+            run("select a from r where b = 20").unwrap(),
+            vec![vec![EncodeValue::Int(2)]]
+        );
+        // Adding a primary key copies the records to be stored by it.
+        run("alter table r add primary key (a, b)").unwrap();
+        assert_eq!(run("select a from r").unwrap().len(), 3);
+        assert_eq!(
+            run("select b from r where a = 1 and b = 11").unwrap(),
+            vec![vec![EncodeValue::Int(11)]]
+        );
+        assert!(matches!(
+            run("alter table r add primary key (b)"),
+            Err(MySQLError::MultiplePrimaryKey)
+        ));
+
+        run("alter table t drop primary key").unwrap();
+        run("insert into t (a, b, c) values (1, 2, 0)").unwrap();
+        assert_eq!(
+            run("select c from t where a = 1 and b = 2").unwrap().len(),
+            2
+        );
     }
 }
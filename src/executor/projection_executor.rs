@@ -0,0 +1,71 @@
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::Expression;
+use crate::table::schema::DataSchemaRef;
+
+pub struct ProjectionExecutor {
+    input: Box<dyn Executor>,
+    exprs: Vec<Expression>,
+    schema: DataSchemaRef,
+}
+
+impl ProjectionExecutor {
+    pub fn new(
+        input: Box<dyn Executor>,
+        exprs: Vec<Expression>,
+        schema: DataSchemaRef,
+    ) -> ProjectionExecutor {
+        ProjectionExecutor {
+            input,
+            exprs,
+            schema,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for ProjectionExecutor {
+    fn name(&self) -> &str {
+        "ProjectionExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.input.open().await
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let block = match self.input.next().await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let mut columns = Vec::with_capacity(self.exprs.len());
+        for e in self.exprs.iter() {
+            columns.push(e.eval_column(&block)?);
+        }
+        Ok(Some(DataBlock::new(
+            self.schema.clone(),
+            columns,
+            block.len(),
+        )))
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.input.close().await
+    }
+
+    async fn split(&mut self, count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+        let parts = match self.input.split(count).await? {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        let parts = parts
+            .into_iter()
+            .map(|input| {
+                let part = ProjectionExecutor::new(input, self.exprs.clone(), self.schema.clone());
+                Box::new(part) as Box<dyn Executor>
+            })
+            .collect();
+        Ok(Some(parts))
+    }
+}
@@ -0,0 +1,101 @@
+use super::{select_records, ReadContext};
+use crate::common::{DataBlock, EncodeValue};
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::Expression;
+use crate::table::schema::{DataSchema, DataSchemaRef, IndexInfo};
+use crate::table::TableSource;
+use std::sync::Arc;
+
+/// The number of keys read from storage by one request of a scan.
+pub const SCAN_BATCH_SIZE: usize = 1024;
+
+/// Read the records of a table by batches, from a range of record keys, or from a range of the
+/// keys of a secondary index. Every batch becomes one block, and the first block is returned
+/// even if it is empty so that the consumer always learns the schema of the result.
+pub(super) struct RangeScanner {
+    ctx: ReadContext,
+    table: Arc<TableSource>,
+    /// The secondary index which the range belongs to.
+    index_info: Option<Arc<IndexInfo>>,
+    /// The key to continue the scan from, it is `None` once the range is exhausted.
+    next: Option<Vec<u8>>,
+    end: Vec<u8>,
+    schema: DataSchemaRef,
+    filters: Vec<Expression>,
+    columns: DataSchemaRef,
+    started: bool,
+}
+
+impl RangeScanner {
+    pub(super) fn new(
+        ctx: ReadContext,
+        table: Arc<TableSource>,
+        index_info: Option<Arc<IndexInfo>>,
+        (start, end): (Vec<u8>, Vec<u8>),
+        filters: Vec<Expression>,
+        columns: DataSchemaRef,
+    ) -> RangeScanner {
+        let schema = Arc::new(DataSchema {
+            columns: table.meta().columns.clone(),
+        });
+        RangeScanner {
+            ctx,
+            table,
+            index_info: index_info.filter(|index| !index.primary),
+            next: if start < end { Some(start) } else { None },
+            end,
+            schema,
+            filters,
+            columns,
+            started: false,
+        }
+    }
+
+    async fn read_batch(&mut self, start: &[u8]) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+        let ctx = self.ctx.ctx();
+        if self.index_info.is_none() {
+            let (records, next) = self
+                .table
+                .scan_record_batch(ctx, start, &self.end, SCAN_BATCH_SIZE, &self.schema)
+                .await?;
+            self.next = next;
+            return Ok(records);
+        }
+        let (handles, next) = self
+            .table
+            .scan_index_batch(ctx, start, &self.end, SCAN_BATCH_SIZE)
+            .await?;
+        self.next = next;
+        let mut records = Vec::with_capacity(handles.len());
+        for handle in handles {
+            if let Some(record) = self
+                .table
+                .read_record_by_handle(ctx, &self.schema, &handle)
+                .await?
+            {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for RangeScanner {
+    fn name(&self) -> &str {
+        "RangeScanner"
+    }
+
+    /// Return the block of the next batch, or `None` once the range is exhausted.
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        let records = match self.next.take() {
+            Some(start) => self.read_batch(&start).await?,
+            None if !self.started => vec![],
+            None => return Ok(None),
+        };
+        self.started = true;
+        let records = DataBlock::from_rows(self.schema.clone(), records);
+        select_records(records, &self.filters, self.columns.clone()).map(Some)
+    }
+}
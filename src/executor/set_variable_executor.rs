@@ -0,0 +1,34 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::planner::SetVariablePlan;
+use crate::session::SessionRef;
+
+pub struct SetVariableExecutor {
+    plan: SetVariablePlan,
+    session: SessionRef,
+}
+
+impl SetVariableExecutor {
+    pub fn new(plan: SetVariablePlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for SetVariableExecutor {
+    fn name(&self) -> &str {
+        "SetVariableExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.session
+            .lock()
+            .unwrap()
+            .set_variable(&self.plan.name, self.plan.value.as_ref())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
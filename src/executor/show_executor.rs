@@ -0,0 +1,146 @@
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::ShowPlan;
+
+pub struct ShowExecutor {
+    plan: ShowPlan,
+    done: bool,
+}
+
+impl ShowExecutor {
+    pub fn new(plan: ShowPlan) -> ShowExecutor {
+        ShowExecutor { plan, done: false }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for ShowExecutor {
+    fn name(&self) -> &str {
+        "ShowExecutor"
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        Ok(Some(DataBlock::from_rows(
+            self.plan.schema.clone(),
+            std::mem::take(&mut self.plan.rows),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+
+    #[test]
+    fn test_show_tables_and_columns() {
+        let (_, _, run) = connect(Config::default());
+        let text = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run("create table users (id int primary key auto_increment, \
+             name varchar(20) not null, age smallint default 18, \
+             check (age > 0))")
+        .unwrap();
+        run("create table orders (id bigint primary key, uid int, \
+             foreign key (uid) references users (id))")
+        .unwrap();
+        run("create unique index name_idx on users (name)").unwrap();
+
+        assert_eq!(
+            run("show tables").unwrap(),
+            vec![vec![text("orders")], vec![text("users")]]
+        );
+        assert_eq!(
+            run("show full tables from test like 'us%'").unwrap(),
+            vec![vec![text("users"), text("BASE TABLE")]]
+        );
+        assert!(matches!(
+            run("show tables in missing"),
+            Err(MySQLError::NoDB(_))
+        ));
+
+        assert_eq!(
+            run("desc users").unwrap(),
+            vec![
+                vec![
+                    text("id"),
+                    text("int"),
+                    text("NO"),
+                    text("PRI"),
+                    EncodeValue::NULL,
+                    text("auto_increment"),
+                ],
+                vec![
+                    text("name"),
+                    text("varchar(20)"),
+                    text("NO"),
+                    text("UNI"),
+                    EncodeValue::NULL,
+                    text(""),
+                ],
+                vec![
+                    text("age"),
+                    text("smallint"),
+                    text("YES"),
+                    text(""),
+                    text("18"),
+                    text(""),
+                ],
+            ]
+        );
+        assert_eq!(run("describe users age").unwrap().len(), 1);
+        assert_eq!(run("show full columns from users").unwrap()[1].len(), 9);
+        assert_eq!(
+            run("show columns from orders from test like 'u%'").unwrap(),
+            vec![vec![
+                text("uid"),
+                text("int"),
+                text("YES"),
+                text(""),
+                EncodeValue::NULL,
+                text(""),
+            ]]
+        );
+
+        assert_eq!(
+            run("show create table users").unwrap(),
+            vec![vec![
+                text("users"),
+                text(
+                    "CREATE TABLE `users` (\n  \
+                     `id` int NOT NULL AUTO_INCREMENT,\n  \
+                     `name` varchar(20) NOT NULL,\n  \
+                     `age` smallint DEFAULT '18',\n  \
+                     PRIMARY KEY (`id`),\n  \
+                     UNIQUE KEY `name_idx` (`name`),\n  \
+                     CONSTRAINT `users_chk_1` CHECK (age > 0)\n\
+                     ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4"
+                ),
+            ]]
+        );
+        let create = run("show create table test.orders").unwrap();
+        assert_eq!(
+            create[0][1],
+            text(
+                "CREATE TABLE `orders` (\n  \
+                 `id` bigint NOT NULL,\n  \
+                 `uid` int DEFAULT NULL,\n  \
+                 PRIMARY KEY (`id`),\n  \
+                 CONSTRAINT `orders_ibfk_1` FOREIGN KEY (`uid`) REFERENCES `users` (`id`)\n\
+                 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4"
+            )
+        );
+        assert!(matches!(
+            run("show create table missing"),
+            Err(MySQLError::NoTable(_))
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+use super::spill::{row_size, QueryMemory, SpillFile, SpillReader, SPILL_BATCH_SIZE};
+use crate::common::{DataBlock, EncodeValue};
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::SortItem;
+use crate::table::schema::DataSchemaRef;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+type KeyedRow = (Vec<EncodeValue>, Vec<EncodeValue>);
+
+pub struct SortExecutor {
+    input: Box<dyn Executor>,
+    items: Vec<SortItem>,
+    schema: DataSchemaRef,
+    memory: Arc<QueryMemory>,
+    output: Option<SortOutput>,
+}
+
+enum SortOutput {
+    /// All rows are sorted in memory and returned by one block.
+    Memory(Option<DataBlock>),
+    /// The rows are spilled to sorted runs, which are merged as the blocks are taken.
+    Merge(RunMerger),
+}
+
+impl SortExecutor {
+    pub fn new(
+        input: Box<dyn Executor>,
+        items: Vec<SortItem>,
+        schema: DataSchemaRef,
+        memory: Arc<QueryMemory>,
+    ) -> Self {
+        SortExecutor {
+            input,
+            items,
+            schema,
+            memory,
+            output: None,
+        }
+    }
+
+    /// Read all rows of the input with their sort keys, the keys are computed by blocks. Once
+    /// the query exceeds its memory, the rows read so far are sorted and spilled as a run.
+    async fn sort_input(&mut self) -> MySQLResult<SortOutput> {
+        let mut reservation = self.memory.reservation();
+        let mut keyed: Vec<KeyedRow> = vec![];
+        let mut runs = vec![];
+        while let Some(block) = self.input.next().await? {
+            let mut keys = Vec::with_capacity(self.items.len());
+            for item in self.items.iter() {
+                keys.push(item.expr.eval_column(&block)?);
+            }
+            for i in 0..block.len() {
+                let key: Vec<EncodeValue> = keys.iter().map(|c| c.value(i)).collect();
+                let row = block.row(i);
+                reservation.grow(row_size(&key) + row_size(&row))?;
+                keyed.push((key, row));
+            }
+            if self.memory.exceeded() && !keyed.is_empty() {
+                runs.push(self.write_run(&mut keyed)?);
+                reservation.free();
+            }
+        }
+        if runs.is_empty() {
+            let items = &self.items;
+            keyed.sort_by(|a, b| compare_keys(items, &a.0, &b.0));
+            let rows = keyed.into_iter().map(|(_, row)| row).collect();
+            let block = DataBlock::from_rows(self.schema.clone(), rows);
+            return Ok(SortOutput::Memory(Some(block)));
+        }
+        if !keyed.is_empty() {
+            runs.push(self.write_run(&mut keyed)?);
+        }
+        RunMerger::new(runs, self.items.len()).map(SortOutput::Merge)
+    }
+
+    /// Sort the rows and write them to a file, every row is written after its keys.
+    fn write_run(&self, keyed: &mut Vec<KeyedRow>) -> MySQLResult<SpillFile> {
+        let items = &self.items;
+        keyed.sort_by(|a, b| compare_keys(items, &a.0, &b.0));
+        let mut file = self.memory.create_file()?;
+        for (mut key, row) in keyed.drain(..) {
+            key.extend(row);
+            file.write_row(&key)?;
+        }
+        Ok(file)
+    }
+}
+
+/// Merge the sorted runs of a sort. The rows of equal keys are taken from the earlier run
+/// first, so the merge is stable as the sort in memory. The runs are few, so the smallest head
+/// is found by comparing all of them.
+struct RunMerger {
+    runs: Vec<SpillReader>,
+    heads: Vec<Option<KeyedRow>>,
+    key_len: usize,
+}
+
+impl RunMerger {
+    fn new(runs: Vec<SpillFile>, key_len: usize) -> MySQLResult<RunMerger> {
+        let mut merger = RunMerger {
+            runs: Vec::with_capacity(runs.len()),
+            heads: Vec::with_capacity(runs.len()),
+            key_len,
+        };
+        for run in runs {
+            let mut reader = run.into_reader()?;
+            let head = read_keyed_row(&mut reader, key_len)?;
+            merger.runs.push(reader);
+            merger.heads.push(head);
+        }
+        Ok(merger)
+    }
+
+    fn next_rows(
+        &mut self,
+        items: &[SortItem],
+        count: usize,
+    ) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+        let mut rows = vec![];
+        while rows.len() < count {
+            let mut min: Option<(usize, &Vec<EncodeValue>)> = None;
+            for (i, head) in self.heads.iter().enumerate() {
+                if let Some((key, _)) = head {
+                    match min {
+                        Some((_, m)) if compare_keys(items, key, m) != Ordering::Less => {}
+                        _ => min = Some((i, key)),
+                    }
+                }
+            }
+            let i = match min {
+                Some((i, _)) => i,
+                None => break,
+            };
+            let (_, row) = self.heads[i].take().unwrap();
+            self.heads[i] = read_keyed_row(&mut self.runs[i], self.key_len)?;
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+}
+
+fn read_keyed_row(reader: &mut SpillReader, key_len: usize) -> MySQLResult<Option<KeyedRow>> {
+    Ok(reader.read_row()?.map(|mut key| {
+        let row = key.split_off(key_len);
+        (key, row)
+    }))
+}
+
+/// NULL is smaller than any other value, as MySQL does.
+fn compare_keys(items: &[SortItem], a: &[EncodeValue], b: &[EncodeValue]) -> Ordering {
+    for ((item, x), y) in items.iter().zip(a.iter()).zip(b.iter()) {
+        let ord = match (x.is_null(), y.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => x.compare(y).unwrap_or(Ordering::Equal),
+        };
+        let ord = if item.asc { ord } else { ord.reverse() };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+#[async_trait::async_trait]
+impl Executor for SortExecutor {
+    fn name(&self) -> &str {
+        "SortExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        self.input.open().await?;
+        let output = self.sort_input().await;
+        self.input.close().await?;
+        self.output = Some(output?);
+        Ok(())
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        match self.output.as_mut() {
+            Some(SortOutput::Memory(block)) => Ok(block.take()),
+            Some(SortOutput::Merge(merger)) => {
+                let rows = merger.next_rows(&self.items, SPILL_BATCH_SIZE)?;
+                if rows.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(DataBlock::from_rows(self.schema.clone(), rows)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        self.output = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{MemTracker, OomAction};
+    use crate::errors::MySQLError;
+    use crate::planner::{ColumnRef, Expression};
+    use crate::table::schema::DataSchema;
+    use sqlparser::ast::DataType;
+    use tokio::runtime;
+
+    struct BlocksExecutor {
+        blocks: Vec<DataBlock>,
+    }
+
+    #[async_trait::async_trait]
+    impl Executor for BlocksExecutor {
+        fn name(&self) -> &str {
+            "BlocksExecutor"
+        }
+
+        async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+            if self.blocks.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(self.blocks.remove(0)))
+        }
+    }
+
+    #[test]
+    fn test_external_sort() {
+        let schema = Arc::new(DataSchema::result_schema(&[
+            ("a", DataType::Int),
+            ("b", DataType::Int),
+        ]));
+        let keys = [3, 1, 2, 1, 5, 4, 2, 0];
+        let blocks = || {
+            keys.chunks(3)
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let rows = chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(j, k)| {
+                            vec![EncodeValue::Int(*k), EncodeValue::Int((i * 3 + j) as i64)]
+                        })
+                        .collect();
+                    DataBlock::from_rows(schema.clone(), rows)
+                })
+                .collect()
+        };
+        let items = vec![SortItem {
+            expr: Expression::Column(ColumnRef {
+                id: 1,
+                index: 0,
+                name: "a".to_string(),
+                data_type: DataType::Int,
+            }),
+            asc: false,
+        }];
+        // Every block is spilled as a run, since the memory is exceeded by any row.
+        let tracker = MemTracker::root("query", 1);
+        let memory = QueryMemory::new(tracker.clone(), OomAction::Spill, std::env::temp_dir());
+        let input = BlocksExecutor { blocks: blocks() };
+        let mut executor =
+            SortExecutor::new(Box::new(input), items.clone(), schema.clone(), memory);
+        let r = runtime::Runtime::new().unwrap();
+        let rows = r.block_on(async {
+            executor.open().await.unwrap();
+            let mut rows = vec![];
+            while let Some(block) = executor.next().await.unwrap() {
+                rows.extend(block.into_rows());
+            }
+            rows
+        });
+        let rows: Vec<(i64, i64)> = rows
+            .iter()
+            .map(|row| (row[0].as_i64().unwrap(), row[1].as_i64().unwrap()))
+            .collect();
+        // The rows of equal keys keep their order in the input.
+        assert_eq!(
+            rows,
+            vec![
+                (5, 4),
+                (4, 5),
+                (3, 0),
+                (2, 2),
+                (2, 6),
+                (1, 1),
+                (1, 3),
+                (0, 7)
+            ]
+        );
+
+        // The query fails instead of spilling if the action is to cancel it.
+        let memory = QueryMemory::new(tracker.clone(), OomAction::Cancel, std::env::temp_dir());
+        let input = BlocksExecutor { blocks: blocks() };
+        let mut executor = SortExecutor::new(Box::new(input), items, schema, memory);
+        let result = r.block_on(executor.open());
+        assert!(matches!(result, Err(MySQLError::MemoryQuotaExceeded(_, 1))));
+        drop(executor);
+        assert_eq!(tracker.consumed(), 0);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::common::{EncodeValue, MemTrackerRef, OomAction};
+use crate::errors::{MySQLError, MySQLResult};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The number of partitions which a hash aggregation or a hash join spills its rows into.
+pub(super) const SPILL_PARTITIONS: usize = 16;
+
+/// The number of rows read back from a spill file for one block.
+pub(super) const SPILL_BATCH_SIZE: usize = 1024;
+
+static NEXT_SPILL_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// The memory of the rows buffered by the operators of one query, such as the input of a sort.
+/// An operator reserves the size of the rows it holds in the tracker of the query. Once a quota
+/// of the query, its session or the server is exceeded, the query fails if the action is
+/// `Cancel`, otherwise the operators spill their rows to files in `dir`.
+pub struct QueryMemory {
+    tracker: MemTrackerRef,
+    action: OomAction,
+    dir: PathBuf,
+}
+
+impl QueryMemory {
+    pub fn new(tracker: MemTrackerRef, action: OomAction, dir: PathBuf) -> Arc<QueryMemory> {
+        Arc::new(QueryMemory {
+            tracker,
+            action,
+            dir,
+        })
+    }
+
+    /// Whether the operators should spill the rows they hold.
+    pub fn exceeded(&self) -> bool {
+        self.action == OomAction::Spill && self.tracker.check().is_err()
+    }
+
+    /// The memory of an operator which spills its rows once a quota is exceeded.
+    pub fn reservation(self: &Arc<Self>) -> MemoryReservation {
+        MemoryReservation {
+            memory: self.clone(),
+            size: 0,
+            spillable: true,
+        }
+    }
+
+    /// The memory of an operator which can not spill, such as the inner side of a nested loop
+    /// join, it fails the query once a quota is exceeded whatever the action is.
+    pub fn unspillable_reservation(self: &Arc<Self>) -> MemoryReservation {
+        MemoryReservation {
+            spillable: false,
+            ..self.reservation()
+        }
+    }
+
+    pub fn create_file(&self) -> MySQLResult<SpillFile> {
+        SpillFile::create(&self.dir)
+    }
+}
+
+/// The memory held by one operator, it is returned to the query when this is dropped.
+pub struct MemoryReservation {
+    memory: Arc<QueryMemory>,
+    size: usize,
+    spillable: bool,
+}
+
+impl MemoryReservation {
+    /// Reserve `bytes` more, which fails if a quota is exceeded and the operator is not
+    /// expected to spill.
+    pub fn grow(&mut self, bytes: usize) -> MySQLResult<()> {
+        self.size += bytes;
+        self.memory.tracker.consume(bytes);
+        if self.spillable && self.memory.action == OomAction::Spill {
+            return Ok(());
+        }
+        self.memory.tracker.check()
+    }
+
+    pub fn free(&mut self) {
+        self.memory.tracker.release(self.size);
+        self.size = 0;
+    }
+
+    /// Take over the memory of another reservation of the same query.
+    pub fn merge(&mut self, mut other: MemoryReservation) {
+        self.size += other.size;
+        other.size = 0;
+    }
+
+    pub fn memory(&self) -> &Arc<QueryMemory> {
+        &self.memory
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+/// An estimate of the memory held by a row.
+pub fn row_size(row: &[EncodeValue]) -> usize {
+    let values: usize = row
+        .iter()
+        .map(|v| match v {
+            EncodeValue::Bytes(v) => v.len(),
+            _ => 0,
+        })
+        .sum();
+    size_of::<Vec<EncodeValue>>() + row.len() * size_of::<EncodeValue>() + values
+}
+
+/// The partition of a row by the encoded key, which is the same for equal keys.
+pub fn partition_of(key: &[u8]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % SPILL_PARTITIONS as u64) as usize
+}
+
+/// Rows written to a temporary file, which is removed when this is dropped. The rows are read
+/// back in the order they are written once all of them have been written.
+pub struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    rows: usize,
+}
+
+impl SpillFile {
+    fn create(dir: &Path) -> MySQLResult<SpillFile> {
+        fs::create_dir_all(dir)?;
+        let id = NEXT_SPILL_FILE.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("naivedb-spill-{}-{}", std::process::id(), id));
+        let file = File::create(&path)?;
+        Ok(SpillFile {
+            path,
+            writer: BufWriter::new(file),
+            rows: 0,
+        })
+    }
+
+    pub fn write_row(&mut self, row: &[EncodeValue]) -> MySQLResult<()> {
+        self.writer.write_u32::<LittleEndian>(row.len() as u32)?;
+        for v in row {
+            write_value(&mut self.writer, v)?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn into_reader(mut self) -> MySQLResult<SpillReader> {
+        self.writer.flush()?;
+        let reader = BufReader::new(File::open(&self.path)?);
+        Ok(SpillReader {
+            reader,
+            remaining: self.rows,
+            _file: self,
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub struct SpillReader {
+    reader: BufReader<File>,
+    remaining: usize,
+    _file: SpillFile,
+}
+
+impl SpillReader {
+    pub fn read_row(&mut self) -> MySQLResult<Option<Vec<EncodeValue>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let len = self.reader.read_u32::<LittleEndian>()? as usize;
+        let mut row = Vec::with_capacity(len);
+        for _ in 0..len {
+            row.push(read_value(&mut self.reader)?);
+        }
+        Ok(Some(row))
+    }
+
+    /// Read at most `count` rows, it returns no rows once all rows have been read.
+    pub fn read_rows(&mut self, count: usize) -> MySQLResult<Vec<Vec<EncodeValue>>> {
+        let mut rows = Vec::with_capacity(count.min(self.remaining));
+        while rows.len() < count {
+            match self.read_row()? {
+                Some(row) => rows.push(row),
+                None => break,
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// Values are written with their types, since the rows of an operator are not typed by a
+/// schema as the records of a table.
+fn write_value<W: Write>(w: &mut W, v: &EncodeValue) -> io::Result<()> {
+    match v {
+        EncodeValue::NULL => w.write_u8(0),
+        EncodeValue::Bytes(v) => {
+            w.write_u8(1)?;
+            w.write_u32::<LittleEndian>(v.len() as u32)?;
+            w.write_all(v)
+        }
+        EncodeValue::Int(v) => {
+            w.write_u8(2)?;
+            w.write_i64::<LittleEndian>(*v)
+        }
+        EncodeValue::Float(v) => {
+            w.write_u8(3)?;
+            w.write_f32::<LittleEndian>(*v)
+        }
+        EncodeValue::Double(v) => {
+            w.write_u8(4)?;
+            w.write_f64::<LittleEndian>(*v)
+        }
+        EncodeValue::Date(year, month, day, hour, minute, second, micro) => {
+            w.write_u8(5)?;
+            w.write_u16::<LittleEndian>(*year)?;
+            w.write_all(&[*month, *day, *hour, *minute, *second])?;
+            w.write_u32::<LittleEndian>(*micro)
+        }
+        EncodeValue::Time(negative, day, hour, minute, second, micro) => {
+            w.write_u8(6)?;
+            w.write_u8(*negative as u8)?;
+            w.write_u32::<LittleEndian>(*day)?;
+            w.write_all(&[*hour, *minute, *second])?;
+            w.write_u32::<LittleEndian>(*micro)
+        }
+    }
+}
+
+fn read_value<R: Read>(r: &mut R) -> MySQLResult<EncodeValue> {
+    let v = match r.read_u8()? {
+        0 => EncodeValue::NULL,
+        1 => {
+            let mut v = vec![0; r.read_u32::<LittleEndian>()? as usize];
+            r.read_exact(&mut v)?;
+            EncodeValue::Bytes(v)
+        }
+        2 => EncodeValue::Int(r.read_i64::<LittleEndian>()?),
+        3 => EncodeValue::Float(r.read_f32::<LittleEndian>()?),
+        4 => EncodeValue::Double(r.read_f64::<LittleEndian>()?),
+        5 => {
+            let year = r.read_u16::<LittleEndian>()?;
+            let mut v = [0u8; 5];
+            r.read_exact(&mut v)?;
+            let micro = r.read_u32::<LittleEndian>()?;
+            EncodeValue::Date(year, v[0], v[1], v[2], v[3], v[4], micro)
+        }
+        6 => {
+            let negative = r.read_u8()? != 0;
+            let day = r.read_u32::<LittleEndian>()?;
+            let mut v = [0u8; 3];
+            r.read_exact(&mut v)?;
+            let micro = r.read_u32::<LittleEndian>()?;
+            EncodeValue::Time(negative, day, v[0], v[1], v[2], micro)
+        }
+        tag => {
+            return Err(MySQLError::Io(format!(
+                "invalid value tag {} in spill file",
+                tag
+            )))
+        }
+    };
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::MemTracker;
+
+    #[test]
+    fn test_spill_file() {
+        let tracker = MemTracker::root("query", 100);
+        let memory = QueryMemory::new(tracker.clone(), OomAction::Spill, std::env::temp_dir());
+        let rows = vec![
+            vec![EncodeValue::Int(-1), EncodeValue::NULL],
+            vec![
+                EncodeValue::Bytes(b"abc".to_vec()),
+                EncodeValue::Double(1.5),
+                EncodeValue::Date(2021, 4, 15, 1, 2, 3, 4),
+                EncodeValue::Time(true, 1, 2, 3, 4, 5),
+            ],
+        ];
+        let mut file = memory.create_file().unwrap();
+        for row in rows.iter() {
+            file.write_row(row).unwrap();
+        }
+        let path = file.path.clone();
+        let mut reader = file.into_reader().unwrap();
+        assert_eq!(reader.read_rows(10).unwrap(), rows);
+        assert!(reader.read_row().unwrap().is_none());
+        drop(reader);
+        assert!(!path.exists());
+
+        let mut reservation = memory.reservation();
+        reservation.grow(80).unwrap();
+        assert!(!memory.exceeded());
+        let mut other = memory.reservation();
+        other.grow(40).unwrap();
+        assert!(memory.exceeded());
+        reservation.merge(other);
+        drop(reservation);
+        assert!(!memory.exceeded());
+        assert_eq!(tracker.consumed(), 0);
+
+        let memory = QueryMemory::new(tracker.clone(), OomAction::Cancel, std::env::temp_dir());
+        let mut reservation = memory.reservation();
+        assert!(reservation.grow(120).is_err());
+        assert!(!memory.exceeded());
+        drop(reservation);
+        assert_eq!(tracker.consumed(), 0);
+
+        let memory = QueryMemory::new(tracker.clone(), OomAction::Spill, std::env::temp_dir());
+        let mut reservation = memory.unspillable_reservation();
+        assert!(reservation.grow(120).is_err());
+        drop(reservation);
+        assert_eq!(tracker.consumed(), 0);
+    }
+}
@@ -0,0 +1,197 @@
+use super::select_records;
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::SystemTableScanPlan;
+use crate::table::schema::DataSchema;
+use std::sync::Arc;
+
+pub struct SystemTableScanExecutor {
+    plan: SystemTableScanPlan,
+    done: bool,
+}
+
+impl SystemTableScanExecutor {
+    pub fn new(plan: SystemTableScanPlan) -> SystemTableScanExecutor {
+        SystemTableScanExecutor { plan, done: false }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for SystemTableScanExecutor {
+    fn name(&self) -> &str {
+        "SystemTableScanExecutor"
+    }
+
+    /// All rows are returned by one block, they are built when it is read so that they show
+    /// the state of the server at that time.
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        let table = self.plan.table;
+        let rows = table.rows(&self.plan.session.lock().unwrap());
+        let schema = Arc::new(DataSchema {
+            columns: table.table_info().columns,
+        });
+        let records = DataBlock::from_rows(schema, rows);
+        select_records(records, &self.plan.filters, self.plan.columns.clone()).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::executor::{connect, ExecutorBuilder};
+    use crate::mysql_driver::MysqlServerCore;
+    use crate::planner::PlanBuilder;
+    use crate::store::MemStorage;
+    use std::sync::Arc;
+    use tokio::runtime;
+
+    #[test]
+    fn test_read_processlist() {
+        let core = MysqlServerCore::default();
+        let (a, b) = (core.create_connection(), core.create_connection());
+        let session = b.get_session();
+        let id = session.lock().unwrap().process().id;
+        session.lock().unwrap().set_db("test".to_string()).unwrap();
+        let sql = format!(
+            "select db, command from information_schema.processlist where id = {}",
+            id
+        );
+        let plan = PlanBuilder::create(a.get_session())
+            .build_from_sql(&sql)
+            .unwrap();
+        assert_eq!(plan.name(), "SystemTableScan");
+        let mut executor =
+            ExecutorBuilder::build(plan, a.get_session(), Arc::new(MemStorage::new()));
+        let r = runtime::Runtime::new().unwrap();
+        let rows = r.block_on(async {
+            executor.open().await.unwrap();
+            let mut rows = vec![];
+            while let Some(block) = executor.next().await.unwrap() {
+                rows.extend(block.into_rows());
+            }
+            rows
+        });
+        assert_eq!(
+            rows,
+            vec![vec![
+                EncodeValue::Bytes(b"test".to_vec()),
+                EncodeValue::Bytes(b"Sleep".to_vec()),
+            ]]
+        );
+        assert!(PlanBuilder::create(a.get_session())
+            .build_from_sql("select * from information_schema.triggers")
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_information_schema() {
+        let (_, _, run) = connect(Config::default());
+        let text = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run("create table users (id int primary key, name varchar(20) not null)").unwrap();
+        run("create table orders (id bigint primary key, uid int, \
+             foreign key (uid) references users (id))")
+        .unwrap();
+        run("create unique index name_idx on users (name)").unwrap();
+
+        assert_eq!(
+            run("select schema_name from information_schema.schemata \
+                 order by schema_name")
+            .unwrap(),
+            vec![
+                vec![text("information_schema")],
+                vec![text("performance_schema")],
+                vec![text("test")],
+            ]
+        );
+        assert_eq!(
+            run(
+                "select table_name, table_type from information_schema.tables \
+                 where table_schema = 'test' order by table_name desc"
+            )
+            .unwrap(),
+            vec![
+                vec![text("users"), text("BASE TABLE")],
+                vec![text("orders"), text("BASE TABLE")],
+            ]
+        );
+        assert_eq!(
+            run(
+                "select column_name, ordinal_position, data_type, is_nullable, column_key, \
+                 character_maximum_length from information_schema.columns \
+                 where table_schema = 'test' and table_name = 'users'"
+            )
+            .unwrap(),
+            vec![
+                vec![
+                    text("id"),
+                    EncodeValue::Int(1),
+                    text("int"),
+                    text("NO"),
+                    text("PRI"),
+                    EncodeValue::NULL,
+                ],
+                vec![
+                    text("name"),
+                    EncodeValue::Int(2),
+                    text("varchar"),
+                    text("NO"),
+                    text("UNI"),
+                    EncodeValue::Int(20),
+                ],
+            ]
+        );
+        assert_eq!(
+            run(
+                "select index_name, non_unique, column_name from information_schema.statistics \
+                 where table_schema = 'test' and table_name = 'users' order by index_name"
+            )
+            .unwrap(),
+            vec![
+                vec![text("PRIMARY"), EncodeValue::Int(0), text("id")],
+                vec![text("name_idx"), EncodeValue::Int(0), text("name")],
+            ]
+        );
+        assert_eq!(
+            run(
+                "select column_name, referenced_table_name, referenced_column_name \
+                 from information_schema.key_column_usage \
+                 where table_name = 'orders' and referenced_table_name = 'users'"
+            )
+            .unwrap(),
+            vec![vec![text("uid"), text("users"), text("id")]]
+        );
+        assert_eq!(
+            run(
+                "select t.table_name, c.column_name from information_schema.tables t \
+                 join information_schema.columns c on t.table_name = c.table_name \
+                 where t.table_schema = 'test' and c.table_schema = 'test' \
+                 and c.column_key = 'PRI' order by t.table_name"
+            )
+            .unwrap(),
+            vec![
+                vec![text("orders"), text("id")],
+                vec![text("users"), text("id")],
+            ]
+        );
+        assert_eq!(
+            run(
+                "select variable_value from performance_schema.session_variables \
+                 where variable_name = 'foreign_key_checks'"
+            )
+            .unwrap(),
+            vec![vec![text("ON")]]
+        );
+        assert_eq!(
+            run("show tables from performance_schema").unwrap(),
+            vec![vec![text("session_variables")]]
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::table::schema::DataSchemaRef;
+
+pub struct TableDualExecutor {
+    schema: DataSchemaRef,
+    done: bool,
+}
+
+impl TableDualExecutor {
+    pub fn new(schema: DataSchemaRef) -> TableDualExecutor {
+        TableDualExecutor {
+            schema,
+            done: false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for TableDualExecutor {
+    fn name(&self) -> &str {
+        "TableDualExecutor"
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        Ok(Some(DataBlock::new(self.schema.clone(), vec![], 1)))
+    }
+}
@@ -0,0 +1,84 @@
+use super::parallel_executor::ParallelExecutor;
+use super::range_scanner::RangeScanner;
+use super::ReadContext;
+use crate::common::DataBlock;
+use crate::errors::MySQLResult;
+use crate::executor::Executor;
+use crate::planner::TableScanPlan;
+use crate::store::Storage;
+use std::sync::Arc;
+
+pub struct TableScanExecutor {
+    plan: TableScanPlan,
+    storage: Arc<dyn Storage>,
+    scanner: Option<Box<dyn Executor>>,
+}
+
+impl TableScanExecutor {
+    pub fn new(plan: TableScanPlan, storage: Arc<dyn Storage>) -> TableScanExecutor {
+        TableScanExecutor {
+            plan,
+            storage,
+            scanner: None,
+        }
+    }
+
+    fn range_scanner(&self, ctx: ReadContext, range: (Vec<u8>, Vec<u8>)) -> Box<dyn Executor> {
+        Box::new(RangeScanner::new(
+            ctx,
+            self.plan.table.clone(),
+            None,
+            range,
+            self.plan.filters.clone(),
+            self.plan.columns.clone(),
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for TableScanExecutor {
+    fn name(&self) -> &str {
+        "TableScanExecutor"
+    }
+
+    /// The records are read by `executor_concurrency` workers if the table can be split.
+    async fn open(&mut self) -> MySQLResult<()> {
+        let session = self.plan.session.clone();
+        let concurrency = session.lock().unwrap().variables().executor_concurrency;
+        let scanner: Box<dyn Executor> = match self.split(concurrency).await? {
+            Some(parts) => Box::new(ParallelExecutor::new(parts)),
+            None => {
+                let range = self.plan.table.record_range()?;
+                let ctx = ReadContext::acquire(&session, &self.storage).await?;
+                self.range_scanner(ctx, range)
+            }
+        };
+        self.scanner = Some(scanner);
+        self.scanner.as_mut().unwrap().open().await
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        match self.scanner.as_mut() {
+            Some(scanner) => scanner.next().await,
+            None => Ok(None),
+        }
+    }
+
+    async fn close(&mut self) -> MySQLResult<()> {
+        match self.scanner.take() {
+            Some(mut scanner) => scanner.close().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn split(&mut self, count: usize) -> MySQLResult<Option<Vec<Box<dyn Executor>>>> {
+        let range = self.plan.table.record_range()?;
+        let parts = ReadContext::split(&self.plan.session, &self.storage, range, count).await?;
+        Ok(parts.map(|parts| {
+            parts
+                .into_iter()
+                .map(|(ctx, range)| self.range_scanner(ctx, range))
+                .collect()
+        }))
+    }
+}
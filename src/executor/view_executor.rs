@@ -0,0 +1,164 @@
+use super::Executor;
+use crate::common::DataBlock;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::{CreateViewPlan, DropViewPlan};
+use crate::session::SessionRef;
+
+pub struct CreateViewExecutor {
+    plan: CreateViewPlan,
+    session: SessionRef,
+}
+
+impl CreateViewExecutor {
+    pub fn new(plan: CreateViewPlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for CreateViewExecutor {
+    fn name(&self) -> &str {
+        "CreateViewExecutor"
+    }
+
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        session.create_view(&self.plan.db, self.plan.view.clone(), self.plan.or_replace)
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+pub struct DropViewExecutor {
+    plan: DropViewPlan,
+    session: SessionRef,
+}
+
+impl DropViewExecutor {
+    pub fn new(plan: DropViewPlan, session: SessionRef) -> Self {
+        Self { plan, session }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for DropViewExecutor {
+    fn name(&self) -> &str {
+        "DropViewExecutor"
+    }
+
+    /// The views which exist are dropped even if some others do not, as MySQL does.
+    async fn open(&mut self) -> MySQLResult<()> {
+        let mut session = self.session.lock().unwrap();
+        let mut result = Ok(());
+        for (db, name) in self.plan.views.iter() {
+            match session.drop_view(db, name) {
+                Err(MySQLError::NoTable(_)) if self.plan.if_exists => (),
+                Err(e) if result.is_ok() => result = Err(e),
+                _ => (),
+            }
+        }
+        result
+    }
+
+    async fn next(&mut self) -> MySQLResult<Option<DataBlock>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::EncodeValue;
+    use crate::config::Config;
+    use crate::errors::MySQLError;
+    use crate::executor::connect;
+
+    #[test]
+    fn test_create_and_read_view() {
+        let (_, _, run) = connect(Config::default());
+        let text = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+
+        run("use test").unwrap();
+        run("create table users (id int primary key, name varchar(20), age int)").unwrap();
+        run("insert into users (id, name, age) values (1, 'a', 10), (2, 'b', 20), (3, 'c', 30)")
+            .unwrap();
+        run("create view adults (uid, uname) as select id, name from users where age >= 18")
+            .unwrap();
+        assert!(matches!(
+            run("create view adults as select id from users"),
+            Err(MySQLError::TableExists(_))
+        ));
+        assert!(matches!(
+            run("create view v (a, b) as select id from users"),
+            Err(MySQLError::ViewWrongList)
+        ));
+
+        assert_eq!(
+            run("select uname from adults where uid > 2").unwrap(),
+            vec![vec![text("c")]]
+        );
+        assert_eq!(
+            run(
+                "select u.name, a.uname from users u join adults a on u.id = a.uid + 1 \
+                 order by u.id"
+            )
+            .unwrap(),
+            vec![vec![text("c"), text("b")]]
+        );
+
+        // A view on a view reads the tables of the database where it is created.
+        run("create or replace view names as select uname from test.adults").unwrap();
+        run("create database other").unwrap();
+        run("use other").unwrap();
+        assert_eq!(
+            run("select * from test.names order by uname").unwrap(),
+            vec![vec![text("b")], vec![text("c")]]
+        );
+        run("use test").unwrap();
+        assert!(matches!(
+            run("create or replace view adults as select * from names"),
+            Err(MySQLError::ViewRecursive(_))
+        ));
+        assert_eq!(
+            run("show create view names").unwrap(),
+            vec![vec![
+                text("names"),
+                text("CREATE VIEW `names` AS SELECT uname FROM test.adults"),
+                text("utf8mb4"),
+                text("utf8mb4_0900_ai_ci"),
+            ]]
+        );
+        assert_eq!(
+            run("show full tables").unwrap(),
+            vec![
+                vec![text("adults"), text("VIEW")],
+                vec![text("names"), text("VIEW")],
+                vec![text("users"), text("BASE TABLE")],
+            ]
+        );
+
+        // The columns of a view on one table are written to the table.
+        run("insert into adults (uid, uname) values (4, 'd')").unwrap();
+        assert_eq!(
+            run("select id, name, age from users where id = 4").unwrap(),
+            vec![vec![EncodeValue::Int(4), text("d"), EncodeValue::NULL]]
+        );
+        run("create view counts as select age, count(*) from users group by age").unwrap();
+        assert!(matches!(
+            run("insert into counts (age) values (1)"),
+            Err(MySQLError::NonInsertable(_))
+        ));
+
+        assert!(matches!(
+            run("drop view users"),
+            Err(MySQLError::NotView(_))
+        ));
+        run("drop view names, counts").unwrap();
+        run("drop view if exists names").unwrap();
+        assert!(matches!(
+            run("select * from names"),
+            Err(MySQLError::NoTable(_))
+        ));
+    }
+}
@@ -6,6 +6,7 @@ mod mysql_driver;
 mod planner;
 pub mod server;
 mod session;
+mod statistics;
 mod store;
 mod table;
 mod transaction;
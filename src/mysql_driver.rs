@@ -1,23 +1,34 @@
 use super::errors::{MySQLError, MySQLResult};
-use crate::common::SendableDataBlockStream;
+use crate::common::{EncodeValue, SendableDataBlockStream};
 use crate::config::{Config, StorageType};
-use crate::executor::ExecutorBuilder;
-use crate::planner::PlanBuilder;
-use crate::session::{Session, SessionRef};
+use crate::executor::{execute, ExecutorBuilder};
+use crate::planner::{
+    PlanBuilder, PlanCache, PlanNode, PreparedStatement, DEFAULT_PLAN_CACHE_CAPACITY,
+};
+use crate::session::{ProcessList, ProcessRef, Session, SessionOptions, SessionRef};
 use crate::store::Storage;
 use crate::store::{MemStorage, TiKVStorage};
 use crate::table::DBTableManager;
 use async_trait::async_trait;
+use byteorder::{LittleEndian, ReadBytesExt};
+use futures::{future, stream, FutureExt, StreamExt};
 use msql_srv::{
-    ErrorKind, InitWriter, MysqlShim, ParamParser, QueryResultWriter, StatementMetaWriter,
+    Column, ColumnFlags, ColumnType, ErrorKind, InitWriter, MysqlShim, ParamParser,
+    QueryResultWriter, StatementMetaWriter, ToMysqlValue, Value, ValueInner,
 };
-use sqlparser::dialect::MySqlDialect;
-use sqlparser::parser::Parser;
+use sqlparser::ast::DataType;
+use std::io::{self, Write};
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{sleep_until, Instant};
 
 pub struct MysqlServerCore {
     table_mgr: Arc<RwLock<DBTableManager>>,
+    plan_cache: Arc<PlanCache>,
     storage: Arc<dyn Storage>,
+    session_options: SessionOptions,
 }
 
 impl Default for MysqlServerCore {
@@ -25,7 +36,9 @@ impl Default for MysqlServerCore {
         let table_mgr = Arc::new(RwLock::new(DBTableManager::new()));
         MysqlServerCore {
             table_mgr,
+            plan_cache: Arc::new(PlanCache::new(DEFAULT_PLAN_CACHE_CAPACITY)),
             storage: Arc::new(MemStorage::new()),
+            session_options: Config::default().session_options(),
         }
     }
 }
@@ -38,29 +51,93 @@ impl MysqlServerCore {
             _ => panic!("unsupport storage type"),
         };
         let table_mgr = Arc::new(RwLock::new(DBTableManager::new()));
-        MysqlServerCore { table_mgr, storage }
+        let plan_cache = Arc::new(PlanCache::new(config.plan_cache_capacity));
+        MysqlServerCore {
+            table_mgr,
+            plan_cache,
+            storage,
+            session_options: config.session_options(),
+        }
     }
 
     pub fn create_connection(&self) -> ConnectionDriver {
-        ConnectionDriver::new(Session::new(self.table_mgr.clone()), self.storage.clone())
+        let session = Session::new(
+            self.table_mgr.clone(),
+            self.plan_cache.clone(),
+            self.session_options.clone(),
+        );
+        ConnectionDriver::new(session, self.storage.clone())
+    }
+
+    /// The live connections, every `ConnectionDriver` is registered until it is dropped.
+    pub fn processes(&self) -> Arc<ProcessList> {
+        self.session_options.processes.clone()
     }
 }
 
 pub struct ConnectionDriver {
     session: SessionRef,
     storage: Arc<dyn Storage>,
+    process: ProcessRef,
 }
 
 impl ConnectionDriver {
     pub fn new(session: Session, storage: Arc<dyn Storage>) -> ConnectionDriver {
+        let process = session.process();
         ConnectionDriver {
             session: SessionRef::new(Mutex::new(session)),
             storage,
+            process,
         }
     }
     pub fn get_session(&self) -> SessionRef {
         self.session.clone()
     }
+
+    pub fn get_storage(&self) -> Arc<dyn Storage> {
+        self.storage.clone()
+    }
+
+    /// Show the address of the client in the process list. `MysqlShim` is not told the user
+    /// authenticated by the handshake, so the process list can not show it.
+    pub fn set_client_address(&self, addr: SocketAddr) {
+        self.process.set_host(&addr.to_string());
+    }
+
+    /// Show the statement in the process list. A killed connection is closed by failing its
+    /// next command.
+    fn start_statement(&self, sql: &str) -> MySQLResult<oneshot::Receiver<()>> {
+        if self.process.is_killed() {
+            return Err(MySQLError::ConnectionKilled);
+        }
+        Ok(self.process.start(sql))
+    }
+
+    /// The transaction of the session is rolled back if the statement has been interrupted.
+    fn finish_statement(&self) {
+        if self.process.finish() {
+            drop(self.session.lock().unwrap().take_transaction());
+        }
+    }
+
+    fn run_plan(
+        &self,
+        plan: PlanNode,
+        interrupted: oneshot::Receiver<()>,
+    ) -> SendableDataBlockStream {
+        let max_execution_time = self.session.lock().unwrap().variables().max_execution_time;
+        let blocks = execute(ExecutorBuilder::build(
+            plan,
+            self.session.clone(),
+            self.storage.clone(),
+        ));
+        interruptible(
+            blocks,
+            interrupted,
+            self.process.clone(),
+            max_execution_time,
+        )
+    }
 }
 
 #[async_trait]
@@ -75,7 +152,7 @@ impl MysqlShim for ConnectionDriver {
                 Err(e) => e,
             }
         };
-        w.error(ErrorKind::ER_UNKNOWN_ERROR, format!("{:?}", e).as_bytes())
+        w.error(error_kind(&e), format!("{:?}", e).as_bytes())
             .await?;
         Ok(())
     }
@@ -83,70 +160,305 @@ impl MysqlShim for ConnectionDriver {
     async fn on_prepare(
         &mut self,
         query: &str,
-        _info: StatementMetaWriter<'_>,
+        info: StatementMetaWriter<'_>,
     ) -> Result<(), Self::Error> {
-        let dialect = MySqlDialect {};
-        let stmts = Parser::parse_sql(&dialect, query)?;
-        if stmts.len() != 1 {
-            return Err(MySQLError::PrepareMult(stmts.len() as u64));
-        }
+        let stmt = match PreparedStatement::prepare(query) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                info.error(error_kind(&e), format!("{:?}", e).as_bytes())
+                    .await?;
+                return Ok(());
+            }
+        };
+        let schema = PlanBuilder::create(self.session.clone())
+            .build_prepared_schema(&stmt)
+            .and_then(|schema| {
+                let columns = schema.columns.iter();
+                columns
+                    .map(|c| c.to_mysql_column())
+                    .collect::<MySQLResult<Vec<_>>>()
+            });
+        let columns = match schema {
+            Ok(columns) => columns,
+            Err(e) => {
+                info.error(error_kind(&e), format!("{:?}", e).as_bytes())
+                    .await?;
+                return Ok(());
+            }
+        };
+        // The types of parameters are decided by the client when executing.
+        let params: Vec<Column> = (0..stmt.param_count)
+            .map(|_| Column {
+                table: "".to_string(),
+                column: "?".to_string(),
+                coltype: ColumnType::MYSQL_TYPE_VAR_STRING,
+                colflags: ColumnFlags::empty(),
+            })
+            .collect();
+        let id = self.session.lock().unwrap().add_prepared_statement(stmt);
+        info.reply(id, &params, &columns).await?;
         Ok(())
     }
 
     async fn on_execute(
         &mut self,
-        _id: u32,
-        _params: ParamParser<'_>,
-        _results: QueryResultWriter<'_>,
+        id: u32,
+        params: ParamParser<'_>,
+        results: QueryResultWriter<'_>,
     ) -> Result<(), Self::Error> {
-        Ok(())
+        let params: Vec<EncodeValue> = params.into_iter().map(|p| param_value(p.value)).collect();
+        let plan_builder = PlanBuilder::create(self.session.clone()).with_params(params);
+        let stmt = self.session.lock().unwrap().get_prepared_statement(id);
+        let interrupted = self.start_statement(stmt.as_ref().map_or("", |s| s.sql.as_str()))?;
+        let output = stmt
+            .ok_or(MySQLError::UnknownStatement(id))
+            .and_then(|stmt| plan_builder.build_prepared_plan(&stmt))
+            .map(|plan| self.run_plan(plan, interrupted));
+
+        let result = match output {
+            Ok(data) => done(data, results, &self.session).await,
+            Err(e) => results
+                .error(error_kind(&e), format!("{:?}", e).as_bytes())
+                .await
+                .map_err(MySQLError::from),
+        };
+        self.finish_statement();
+        result
     }
 
-    async fn on_close(&mut self, _stmt: u32) {}
+    async fn on_close(&mut self, stmt: u32) {
+        self.session.lock().unwrap().remove_prepared_statement(stmt);
+    }
 
     async fn on_query(
         &mut self,
         query: &str,
         results: QueryResultWriter<'_>,
     ) -> Result<(), Self::Error> {
+        let interrupted = self.start_statement(query)?;
         let plan_builder = PlanBuilder::create(self.session.clone());
-        let output = async move {
-            let plan = plan_builder.build_from_sql(query)?;
-            let mut executor =
-                ExecutorBuilder::build(plan, self.session.clone(), self.storage.clone());
-            executor.execute().await
-        };
+        let output = plan_builder
+            .build_from_sql(query)
+            .map(|plan| self.run_plan(plan, interrupted));
 
-        match output.await {
-            Ok(data) => {
-                done(data, results).await?;
-            }
-            Err(e) => {
-                results
-                    .error(ErrorKind::ER_UNKNOWN_ERROR, format!("{:?}", e).as_bytes())
-                    .await?;
-            }
-        }
-        Ok(())
+        let result = match output {
+            Ok(data) => done(data, results, &self.session).await,
+            Err(e) => results
+                .error(error_kind(&e), format!("{:?}", e).as_bytes())
+                .await
+                .map_err(MySQLError::from),
+        };
+        self.finish_statement();
+        result
     }
 }
 
-async fn done<'a>(rows: SendableDataBlockStream, writer: QueryResultWriter<'a>) -> MySQLResult<()> {
-    if rows.is_empty() {
-        writer.completed(0, 0).await?;
-        return Ok(());
+/// The code of an error sent to the client.
+pub(crate) fn error_kind(e: &MySQLError) -> ErrorKind {
+    match e {
+        MySQLError::NoDB(_) => ErrorKind::ER_BAD_DB_ERROR,
+        MySQLError::NoDBSelected => ErrorKind::ER_NO_DB_ERROR,
+        MySQLError::DBExists(_) => ErrorKind::ER_DB_CREATE_EXISTS,
+        MySQLError::TableExists(_) => ErrorKind::ER_TABLE_EXISTS_ERROR,
+        MySQLError::DupColumn(_) => ErrorKind::ER_DUP_FIELDNAME,
+        MySQLError::DupEntry(..) => ErrorKind::ER_DUP_ENTRY,
+        MySQLError::DropAllColumns => ErrorKind::ER_CANT_REMOVE_ALL_FIELDS,
+        MySQLError::MultiplePrimaryKey => ErrorKind::ER_MULTIPLE_PRI_KEY,
+        MySQLError::CannotAddForeignKey(_) => ErrorKind::ER_CANNOT_ADD_FOREIGN,
+        MySQLError::NoReferencedRow(_) => ErrorKind::ER_NO_REFERENCED_ROW_2,
+        MySQLError::RowIsReferenced(_) => ErrorKind::ER_ROW_IS_REFERENCED_2,
+        MySQLError::BadNull(_) => ErrorKind::ER_BAD_NULL_ERROR,
+        MySQLError::NoDefault(_) => ErrorKind::ER_NO_DEFAULT_FOR_FIELD,
+        MySQLError::DataTooLong(_) => ErrorKind::ER_DATA_TOO_LONG,
+        MySQLError::DataOutOfRange(_) => ErrorKind::ER_WARN_DATA_OUT_OF_RANGE,
+        MySQLError::WrongValueForColumn(..) => ErrorKind::ER_TRUNCATED_WRONG_VALUE_FOR_FIELD,
+        MySQLError::NotView(_) => ErrorKind::ER_WRONG_OBJECT,
+        MySQLError::ViewWrongList => ErrorKind::ER_VIEW_WRONG_LIST,
+        MySQLError::ViewRecursive(_) => ErrorKind::ER_VIEW_RECURSIVE,
+        MySQLError::NonInsertable(_) => ErrorKind::ER_NON_INSERTABLE_TABLE,
+        _ => ErrorKind::ER_UNKNOWN_ERROR,
     }
+}
+
+/// Stop the blocks of a statement once it is killed, or has run for `max_execution_time`
+/// milliseconds if it is not 0. The executor is dropped in the middle of its work, so the
+/// transaction it has started is dropped without being committed.
+fn interruptible(
+    blocks: SendableDataBlockStream,
+    interrupted: oneshot::Receiver<()>,
+    process: ProcessRef,
+    max_execution_time: u64,
+) -> SendableDataBlockStream {
+    let deadline = match max_execution_time {
+        0 => None,
+        ms => Some(Instant::now() + Duration::from_millis(ms)),
+    };
+    Box::pin(stream::unfold(
+        Some((blocks, interrupted.fuse())),
+        move |state| {
+            let process = process.clone();
+            async move {
+                let (mut blocks, mut interrupted) = state?;
+                let timeout = async move {
+                    match deadline {
+                        Some(deadline) => sleep_until(deadline).await,
+                        None => future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    block = blocks.next() => block.map(|b| (b, Some((blocks, interrupted)))),
+                    // The sender is only dropped after the statement ends.
+                    Ok(()) = &mut interrupted => Some((Err(MySQLError::QueryInterrupted), None)),
+                    _ = timeout => {
+                        process.interrupt("timeout");
+                        Some((Err(MySQLError::ExecutionTimeout), None))
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// Write the result of an executor. A block is only pulled from the executor after the rows of
+/// the previous block have been written, so a large result is never held in memory at once.
+async fn done<'a>(
+    mut blocks: SendableDataBlockStream,
+    writer: QueryResultWriter<'a>,
+    session: &SessionRef,
+) -> MySQLResult<()> {
+    let first = match blocks.next().await {
+        Some(Ok(block)) => block,
+        Some(Err(e)) => {
+            writer
+                .error(error_kind(&e), format!("{:?}", e).as_bytes())
+                .await?;
+            return Ok(());
+        }
+        None => {
+            let insert_id = session.lock().unwrap().take_insert_id();
+            writer.completed(0, insert_id).await?;
+            return Ok(());
+        }
+    };
     let mut cols = vec![];
-    for c in rows[0].schema.columns.iter() {
+    for c in first.schema.columns.iter() {
         cols.push(c.to_mysql_column()?);
     }
+    let types: Vec<DataType> = first
+        .schema
+        .columns
+        .iter()
+        .map(|c| c.data_type.clone())
+        .collect();
     let mut row_writer = writer.start(&cols).await?;
-    for block in rows {
-        for row in block.data {
-            // let data = Vec::with_capacity(row.into_iter().map(||))
-            row_writer.write_row(row.into_iter().map(|v| String::from(v)))?;
+    let mut block = first;
+    loop {
+        for i in 0..block.len() {
+            row_writer.write_row(
+                types
+                    .iter()
+                    .enumerate()
+                    .map(|(c, tp)| ResultValue::new(block.value(i, c), tp)),
+            )?;
         }
+        // The header has been sent, so an error of a later block closes the connection.
+        block = match blocks.next().await {
+            Some(block) => block?,
+            None => break,
+        };
     }
     row_writer.finish().await?;
     Ok(())
 }
+
+/// A value of the result set converted to the type of its column, so that it can be written by
+/// both the text protocol and the binary protocol of prepared statements.
+enum ResultValue {
+    Null,
+    Int(i64),
+    Float(f32),
+    Double(f64),
+    Bytes(Vec<u8>),
+}
+
+impl ResultValue {
+    fn new(value: EncodeValue, data_type: &DataType) -> ResultValue {
+        if value.is_null() {
+            return ResultValue::Null;
+        }
+        match data_type {
+            DataType::SmallInt | DataType::Int | DataType::BigInt | DataType::Boolean => {
+                ResultValue::Int(value.as_i64().unwrap_or(0))
+            }
+            DataType::Float(_) => ResultValue::Float(value.as_f64().unwrap_or(0.0) as f32),
+            DataType::Double => ResultValue::Double(value.as_f64().unwrap_or(0.0)),
+            _ => ResultValue::Bytes(String::from(value).into_bytes()),
+        }
+    }
+}
+
+impl ToMysqlValue for ResultValue {
+    fn to_mysql_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            ResultValue::Null => None::<i64>.to_mysql_text(w),
+            ResultValue::Int(v) => v.to_mysql_text(w),
+            ResultValue::Float(v) => v.to_mysql_text(w),
+            ResultValue::Double(v) => v.to_mysql_text(w),
+            ResultValue::Bytes(v) => v.to_mysql_text(w),
+        }
+    }
+
+    fn to_mysql_bin<W: Write>(&self, w: &mut W, c: &Column) -> io::Result<()> {
+        match self {
+            // NULL is written in the null bitmap of the row.
+            ResultValue::Null => Ok(()),
+            ResultValue::Int(v) => v.to_mysql_bin(w, c),
+            ResultValue::Float(v) => v.to_mysql_bin(w, c),
+            ResultValue::Double(v) => v.to_mysql_bin(w, c),
+            ResultValue::Bytes(v) => v.to_mysql_bin(w, c),
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            ResultValue::Null => true,
+            _ => false,
+        }
+    }
+}
+
+/// Convert a parameter sent by `COM_STMT_EXECUTE`, temporal values are sent in the binary
+/// format of MySQL.
+fn param_value(value: Value<'_>) -> EncodeValue {
+    match value.into_inner() {
+        ValueInner::NULL => EncodeValue::NULL,
+        ValueInner::Bytes(v) => EncodeValue::Bytes(v.to_vec()),
+        ValueInner::Int(v) => EncodeValue::Int(v),
+        ValueInner::UInt(v) if v > i64::MAX as u64 => EncodeValue::Double(v as f64),
+        ValueInner::UInt(v) => EncodeValue::Int(v as i64),
+        ValueInner::Double(v) => EncodeValue::Double(v),
+        ValueInner::Date(v) | ValueInner::Datetime(v) => {
+            let mut r = v;
+            EncodeValue::Date(
+                r.read_u16::<LittleEndian>().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u32::<LittleEndian>().unwrap_or(0),
+            )
+        }
+        ValueInner::Time(v) => {
+            let mut r = v;
+            EncodeValue::Time(
+                r.read_u8().unwrap_or(0) != 0,
+                r.read_u32::<LittleEndian>().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u8().unwrap_or(0),
+                r.read_u32::<LittleEndian>().unwrap_or(0),
+            )
+        }
+    }
+}
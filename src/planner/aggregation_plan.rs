@@ -0,0 +1,124 @@
+use crate::errors::MySQLResult;
+use crate::planner::plan_expression::{is_integer_type, Expression};
+use crate::planner::{ExplainRow, PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+use sqlparser::ast::DataType;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AggFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggFunction {
+    /// Return `None` if `name` is not an aggregate function.
+    pub fn from_name(name: &str) -> Option<AggFunction> {
+        match name.to_lowercase().as_str() {
+            "count" => Some(AggFunction::Count),
+            "sum" => Some(AggFunction::Sum),
+            "avg" => Some(AggFunction::Avg),
+            "min" => Some(AggFunction::Min),
+            "max" => Some(AggFunction::Max),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            AggFunction::Count => "count",
+            AggFunction::Sum => "sum",
+            AggFunction::Avg => "avg",
+            AggFunction::Min => "min",
+            AggFunction::Max => "max",
+        }
+    }
+}
+
+/// A call of an aggregate function, `args` is empty for `COUNT(*)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregateExpr {
+    pub func: AggFunction,
+    pub args: Vec<Expression>,
+    pub distinct: bool,
+}
+
+impl AggregateExpr {
+    pub fn data_type(&self) -> DataType {
+        let arg_type = self.args.first().map(|e| e.data_type());
+        match (self.func, arg_type) {
+            (AggFunction::Count, _) => DataType::BigInt,
+            (AggFunction::Sum, Some(t)) if is_integer_type(&t) => DataType::BigInt,
+            (AggFunction::Sum, _) | (AggFunction::Avg, _) => DataType::Double,
+            (_, Some(t)) => t,
+            (_, None) => DataType::String,
+        }
+    }
+
+    pub fn collect_columns(&self, output: &mut HashSet<usize>) {
+        for e in self.args.iter() {
+            e.collect_columns(output);
+        }
+    }
+
+    pub fn bind(&mut self, input: &[usize]) -> MySQLResult<()> {
+        for e in self.args.iter_mut() {
+            e.bind(input)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AggregateExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|e| e.to_string()).collect();
+        let args = if args.is_empty() {
+            "*".to_string()
+        } else {
+            args.join(", ")
+        };
+        let distinct = if self.distinct { "distinct " } else { "" };
+        write!(f, "{}({}{})", self.func.name(), distinct, args)
+    }
+}
+
+/// Group the input rows by a hash table, the output row of a group is the values of
+/// `group_by` followed by the results of `aggs`.
+pub struct AggregationPlan {
+    pub input: Box<PlanNode>,
+    pub group_by: Vec<Expression>,
+    pub aggs: Vec<AggregateExpr>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for AggregationPlan {
+    fn name(&self) -> &str {
+        "HashAgg"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        let mut info = vec![];
+        if !self.group_by.is_empty() {
+            let exprs: Vec<String> = self.group_by.iter().map(|e| e.to_string()).collect();
+            info.push(format!("group by: {}", exprs.join(", ")));
+        }
+        if !self.aggs.is_empty() {
+            let aggs: Vec<String> = self.aggs.iter().map(|a| a.to_string()).collect();
+            info.push(format!("funcs: {}", aggs.join(", ")));
+        }
+        row.filters = info.join(", ");
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.input.as_ref()]
+    }
+}
@@ -0,0 +1,36 @@
+use crate::planner::{ExplainRow, PlanInfo};
+use crate::table::schema::IndexInfo;
+use crate::table::TableSource;
+use sqlparser::ast::ColumnDef;
+use std::sync::Arc;
+
+pub enum AlterTableOperation {
+    AddColumn(ColumnDef),
+    DropColumn(String),
+    ModifyColumn(ColumnDef),
+    /// The old and the new name of a column.
+    RenameColumn(String, String),
+    /// The database and the name the table moves to.
+    RenameTable(String, String),
+    AddIndex(IndexInfo),
+    DropIndex(String),
+}
+
+/// `ALTER TABLE name operation` with one operation, `RENAME TABLE name TO new_name`,
+/// `CREATE INDEX` or `DROP INDEX name ON table`.
+pub struct AlterTablePlan {
+    /// The database of the table.
+    pub db: String,
+    pub table: Arc<TableSource>,
+    pub operation: AlterTableOperation,
+}
+
+impl PlanInfo for AlterTablePlan {
+    fn name(&self) -> &str {
+        "AlterTable"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table.name().to_string();
+    }
+}
@@ -0,0 +1,17 @@
+use crate::planner::{ExplainRow, PlanInfo};
+use crate::table::TableSource;
+use std::sync::Arc;
+
+pub struct AnalyzeTablePlan {
+    pub table: Arc<TableSource>,
+}
+
+impl PlanInfo for AnalyzeTablePlan {
+    fn name(&self) -> &str {
+        "AnalyzeTable"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table.name().to_string();
+    }
+}
@@ -0,0 +1,319 @@
+use super::plan_expression::{eval_binary, eval_unary};
+use super::Expression;
+use crate::common::{Column, ColumnValues, DataBlock, NullBitmap};
+use crate::errors::{MySQLError, MySQLResult};
+use sqlparser::ast::BinaryOperator;
+use std::cmp::Ordering;
+
+/// The rows which an expression is evaluated on, `rows` are the offsets in `columns`.
+struct EvalInput<'a> {
+    columns: &'a [Column],
+    rows: Option<&'a [usize]>,
+    len: usize,
+}
+
+impl EvalInput<'_> {
+    fn offset(&self, i: usize) -> usize {
+        match self.rows {
+            Some(rows) => rows[i],
+            None => i,
+        }
+    }
+}
+
+impl Expression {
+    /// Evaluate the expression for all selected rows of a block, the result has one value for
+    /// every selected row. It returns the same values and errors as `eval` on every row.
+    pub fn eval_column(&self, block: &DataBlock) -> MySQLResult<Column> {
+        self.eval_input(&EvalInput {
+            columns: block.columns(),
+            rows: block.selection(),
+            len: block.len(),
+        })
+    }
+
+    fn eval_input(&self, input: &EvalInput) -> MySQLResult<Column> {
+        match self {
+            Expression::Column(c) => {
+                let column = input
+                    .columns
+                    .get(c.index)
+                    .ok_or_else(|| MySQLError::MissColumn(c.name.clone()))?;
+                Ok(match input.rows {
+                    Some(rows) => column.take(rows),
+                    None => column.clone(),
+                })
+            }
+            Expression::Value(v) => Ok(Column::repeat(v, input.len)),
+            Expression::Parameter(i) => Err(MySQLError::ParamMissMatch(i + 1, 0)),
+            Expression::LastInsertId => Err(MySQLError::UnsupportSQL),
+            Expression::BinaryOp { op, left, right } => match op {
+                BinaryOperator::And | BinaryOperator::Or => eval_logic(op, left, right, input),
+                op => eval_binary_column(op, &left.eval_input(input)?, &right.eval_input(input)?),
+            },
+            Expression::UnaryOp { op, expr } => {
+                let v = expr.eval_input(input)?;
+                let mut values = Vec::with_capacity(v.len());
+                for i in 0..v.len() {
+                    values.push(eval_unary(op, v.value(i))?);
+                }
+                Ok(Column::from_values(values))
+            }
+            Expression::IsNull(expr) | Expression::IsNotNull(expr) => {
+                let v = expr.eval_input(input)?;
+                let is_null = matches!(self, Expression::IsNull(_));
+                let values = (0..v.len())
+                    .map(|i| (v.is_null(i) == is_null) as i64)
+                    .collect();
+                Ok(Column::from_ints(values, not_null(v.len())))
+            }
+        }
+    }
+}
+
+fn not_null(len: usize) -> NullBitmap {
+    let mut nulls = NullBitmap::default();
+    for _ in 0..len {
+        nulls.push(false);
+    }
+    nulls
+}
+
+/// `AND` and `OR` only evaluate the right side on the rows whose result is not decided by the
+/// left side, so an error of the right side is raised on the same rows as `eval`.
+fn eval_logic(
+    op: &BinaryOperator,
+    left: &Expression,
+    right: &Expression,
+    input: &EvalInput,
+) -> MySQLResult<Column> {
+    let and = *op == BinaryOperator::And;
+    let l = left.eval_input(input)?;
+    let undecided: Vec<usize> = (0..l.len())
+        .filter(|i| {
+            if and {
+                l.is_null(*i) || l.is_true(*i)
+            } else {
+                !l.is_true(*i)
+            }
+        })
+        .collect();
+    let rows: Vec<usize> = undecided.iter().map(|i| input.offset(*i)).collect();
+    let r = right.eval_input(&EvalInput {
+        columns: input.columns,
+        rows: Some(&rows),
+        len: rows.len(),
+    })?;
+    let mut values = vec![if and { 0 } else { 1 }; l.len()];
+    let mut nulls = vec![false; l.len()];
+    for (j, i) in undecided.into_iter().enumerate() {
+        let r_false = !r.is_null(j) && !r.is_true(j);
+        if and && r_false {
+            values[i] = 0;
+        } else if !and && r.is_true(j) {
+            values[i] = 1;
+        } else if l.is_null(i) || r.is_null(j) {
+            nulls[i] = true;
+        } else {
+            values[i] = and as i64;
+        }
+    }
+    let mut bitmap = NullBitmap::default();
+    for null in nulls {
+        bitmap.push(null);
+    }
+    Ok(Column::from_ints(values, bitmap))
+}
+
+fn compare_result(op: &BinaryOperator, ord: Ordering) -> Option<bool> {
+    let v = match op {
+        BinaryOperator::Eq => ord == Ordering::Equal,
+        BinaryOperator::NotEq => ord != Ordering::Equal,
+        BinaryOperator::Lt => ord == Ordering::Less,
+        BinaryOperator::LtEq => ord != Ordering::Greater,
+        BinaryOperator::Gt => ord == Ordering::Greater,
+        BinaryOperator::GtEq => ord != Ordering::Less,
+        _ => return None,
+    };
+    Some(v)
+}
+
+fn is_comparison(op: &BinaryOperator) -> bool {
+    compare_result(op, Ordering::Equal).is_some()
+}
+
+fn is_arithmetic(op: &BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Plus
+            | BinaryOperator::Minus
+            | BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Modulo
+    )
+}
+
+fn is_numeric(values: &ColumnValues) -> bool {
+    matches!(values, ColumnValues::Int(_) | ColumnValues::Double(_))
+}
+
+fn f64_at(values: &ColumnValues, i: usize) -> f64 {
+    match values {
+        ColumnValues::Int(values) => values[i] as f64,
+        ColumnValues::Double(values) => values[i],
+        _ => 0.0,
+    }
+}
+
+/// Evaluate a binary operator other than `AND` and `OR`. Integers, doubles and strings are
+/// computed on their vectors, other values fall back to `eval_binary` value by value.
+fn eval_binary_column(op: &BinaryOperator, l: &Column, r: &Column) -> MySQLResult<Column> {
+    let len = l.len();
+    let nulls = l.nulls().union(r.nulls());
+    let comparison = is_comparison(op);
+    match (l.values(), r.values()) {
+        (ColumnValues::Int(a), ColumnValues::Int(b))
+            if comparison || (is_arithmetic(op) && *op != BinaryOperator::Divide) =>
+        {
+            let mut values = vec![0; len];
+            let mut result_nulls = NullBitmap::default();
+            for (i, v) in values.iter_mut().enumerate() {
+                let mut null = nulls.is_null(i);
+                if !null {
+                    let (x, y) = (a[i], b[i]);
+                    *v = match op {
+                        BinaryOperator::Plus => x.checked_add(y),
+                        BinaryOperator::Minus => x.checked_sub(y),
+                        BinaryOperator::Multiply => x.checked_mul(y),
+                        BinaryOperator::Modulo if y == 0 => {
+                            null = true;
+                            Some(0)
+                        }
+                        BinaryOperator::Modulo => Some(x.wrapping_rem(y)),
+                        op => compare_result(op, x.cmp(&y)).map(|v| v as i64),
+                    }
+                    .ok_or_else(|| MySQLError::OutOfRange(format!("{} {} {}", x, op, y)))?;
+                }
+                result_nulls.push(null);
+            }
+            Ok(Column::from_ints(values, result_nulls))
+        }
+        (ColumnValues::Bytes { .. }, ColumnValues::Bytes { .. }) if comparison => {
+            let values = (0..len)
+                .map(|i| {
+                    let ord = l.bytes(i).cmp(r.bytes(i));
+                    (!nulls.is_null(i) && compare_result(op, ord).unwrap_or(false)) as i64
+                })
+                .collect();
+            Ok(Column::from_ints(values, nulls))
+        }
+        (a, b) if is_numeric(a) && is_numeric(b) && comparison => {
+            let values = (0..len)
+                .map(|i| {
+                    let (x, y) = (f64_at(a, i), f64_at(b, i));
+                    let ord = x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+                    (!nulls.is_null(i) && compare_result(op, ord).unwrap_or(false)) as i64
+                })
+                .collect();
+            Ok(Column::from_ints(values, nulls))
+        }
+        (a, b) if is_numeric(a) && is_numeric(b) && is_arithmetic(op) => {
+            let mut values = vec![0.0; len];
+            let mut result_nulls = NullBitmap::default();
+            for (i, v) in values.iter_mut().enumerate() {
+                let mut null = nulls.is_null(i);
+                if !null {
+                    let (x, y) = (f64_at(a, i), f64_at(b, i));
+                    *v = match op {
+                        BinaryOperator::Plus => x + y,
+                        BinaryOperator::Minus => x - y,
+                        BinaryOperator::Multiply => x * y,
+                        _ if y == 0.0 => {
+                            null = true;
+                            0.0
+                        }
+                        BinaryOperator::Divide => x / y,
+                        _ => x % y,
+                    };
+                }
+                result_nulls.push(null);
+            }
+            Ok(Column::from_doubles(values, result_nulls))
+        }
+        _ => {
+            let mut values = Vec::with_capacity(len);
+            for i in 0..len {
+                values.push(eval_binary(op, &l.value(i), &r.value(i))?);
+            }
+            Ok(Column::from_values(values))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::EncodeValue;
+    use crate::planner::ColumnRef;
+    use crate::table::schema::DataSchema;
+    use sqlparser::ast::DataType;
+    use std::sync::Arc;
+
+    fn column(index: usize, name: &str, data_type: DataType) -> Expression {
+        Expression::Column(ColumnRef {
+            id: index,
+            index,
+            name: name.to_string(),
+            data_type,
+        })
+    }
+
+    #[test]
+    fn test_eval_column() {
+        let schema = DataSchema::result_schema(&[("a", DataType::Int), ("b", DataType::String)]);
+        let rows = vec![
+            vec![EncodeValue::Int(1), EncodeValue::Bytes(b"x".to_vec())],
+            vec![EncodeValue::NULL, EncodeValue::Bytes(b"y".to_vec())],
+            vec![EncodeValue::Int(i64::MAX), EncodeValue::NULL],
+            vec![EncodeValue::Int(4), EncodeValue::Bytes(b"y".to_vec())],
+        ];
+        let mut block = DataBlock::from_rows(Arc::new(schema), rows.clone());
+        let a = column(0, "a", DataType::Int);
+        let b = column(1, "b", DataType::String);
+        let one = Expression::Value(EncodeValue::Int(1));
+        let exprs = vec![
+            Expression::binary(
+                BinaryOperator::Eq,
+                b.clone(),
+                Expression::Value(EncodeValue::Bytes(b"y".to_vec())),
+            ),
+            Expression::binary(BinaryOperator::Divide, a.clone(), one.clone()),
+            Expression::IsNull(Box::new(a.clone())),
+            // `a + 1` overflows on the third row, where it is never evaluated.
+            Expression::binary(
+                BinaryOperator::Or,
+                Expression::IsNull(Box::new(b.clone())),
+                Expression::binary(
+                    BinaryOperator::Lt,
+                    Expression::binary(BinaryOperator::Plus, a.clone(), one.clone()),
+                    Expression::Value(EncodeValue::Int(3)),
+                ),
+            ),
+        ];
+        for e in exprs.iter() {
+            let column = e.eval_column(&block).unwrap();
+            for (i, row) in rows.iter().enumerate() {
+                assert_eq!(column.value(i), e.eval(row).unwrap());
+            }
+        }
+        let overflow = Expression::binary(BinaryOperator::Plus, a.clone(), one);
+        assert!(overflow.eval_column(&block).is_err());
+
+        block.filter(&exprs[3].eval_column(&block).unwrap());
+        assert_eq!(block.len(), 2);
+        assert_eq!(block.row(1), rows[2]);
+        let a = a.eval_column(&block).unwrap();
+        assert_eq!(a.value(0), EncodeValue::Int(1));
+        assert_eq!(a.value(1), EncodeValue::Int(i64::MAX));
+    }
+}
@@ -1,5 +1,17 @@
+use crate::planner::{ExplainRow, PlanInfo};
 use crate::table::schema::TableInfo;
 
 pub struct CreateTablePlan {
+    pub db: String,
     pub table_info: TableInfo,
 }
+
+impl PlanInfo for CreateTablePlan {
+    fn name(&self) -> &str {
+        "CreateTable"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table_info.name.clone();
+    }
+}
@@ -0,0 +1,36 @@
+use crate::planner::PlanInfo;
+
+/// `CREATE {DATABASE | SCHEMA} [IF NOT EXISTS] name`.
+pub struct CreateDatabasePlan {
+    pub name: String,
+    pub if_not_exists: bool,
+}
+
+impl PlanInfo for CreateDatabasePlan {
+    fn name(&self) -> &str {
+        "CreateDatabase"
+    }
+}
+
+/// `DROP {DATABASE | SCHEMA} [IF EXISTS] name`.
+pub struct DropDatabasePlan {
+    pub name: String,
+    pub if_exists: bool,
+}
+
+impl PlanInfo for DropDatabasePlan {
+    fn name(&self) -> &str {
+        "DropDatabase"
+    }
+}
+
+/// `USE name`.
+pub struct UseDatabasePlan {
+    pub name: String,
+}
+
+impl PlanInfo for UseDatabasePlan {
+    fn name(&self) -> &str {
+        "UseDatabase"
+    }
+}
@@ -0,0 +1,38 @@
+use crate::planner::{ExplainRow, PlanInfo};
+
+/// `DROP TABLE [IF EXISTS] name [, name] ...`, the tables are resolved to their databases.
+pub struct DropTablePlan {
+    pub tables: Vec<(String, String)>,
+    pub if_exists: bool,
+}
+
+impl PlanInfo for DropTablePlan {
+    fn name(&self) -> &str {
+        "DropTable"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        let names: Vec<String> = self
+            .tables
+            .iter()
+            .map(|(db, name)| format!("{}.{}", db, name))
+            .collect();
+        row.table = names.join(", ");
+    }
+}
+
+/// `TRUNCATE [TABLE] name`.
+pub struct TruncateTablePlan {
+    pub db: String,
+    pub name: String,
+}
+
+impl PlanInfo for TruncateTablePlan {
+    fn name(&self) -> &str {
+        "TruncateTable"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.name.clone();
+    }
+}
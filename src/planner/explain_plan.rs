@@ -0,0 +1,27 @@
+use crate::planner::{PlanInfo, PlanNode};
+
+pub struct ExplainPlan {
+    pub plan: Box<PlanNode>,
+    pub analyze: bool,
+}
+
+impl PlanInfo for ExplainPlan {
+    fn name(&self) -> &str {
+        "Explain"
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.plan.as_ref()]
+    }
+}
+
+/// One line of `EXPLAIN` output, describes a single operator of the plan tree.
+#[derive(Debug, Clone, Default)]
+pub struct ExplainRow {
+    pub id: String,
+    pub est_rows: f64,
+    pub table: String,
+    pub index: String,
+    pub range: String,
+    pub filters: String,
+}
@@ -0,0 +1,29 @@
+use crate::planner::plan_expression::Expression;
+use crate::planner::point_get_plan::join_expressions;
+use crate::planner::{ExplainRow, PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+
+pub struct FilterPlan {
+    pub input: Box<PlanNode>,
+    pub predicates: Vec<Expression>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for FilterPlan {
+    fn name(&self) -> &str {
+        "Filter"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.filters = join_expressions(&self.predicates);
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.input.as_ref()]
+    }
+}
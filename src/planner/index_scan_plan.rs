@@ -0,0 +1,37 @@
+use crate::planner::plan_expression::Expression;
+use crate::planner::point_get_plan::{index_name, join_expressions};
+use crate::planner::{ExplainRow, PlanInfo};
+use crate::session::SessionRef;
+use crate::table::schema::{DataSchemaRef, IndexInfo};
+use crate::table::table::TableSource;
+use crate::table::IndexRange;
+use std::sync::Arc;
+
+/// Read the records whose first column of the index is in `range`. For the primary index the
+/// records are scanned directly, otherwise every handle found in the index is looked up.
+pub struct IndexScanPlan {
+    pub table: Arc<TableSource>,
+    pub index_info: Arc<IndexInfo>,
+    pub range: IndexRange,
+    pub columns: DataSchemaRef,
+    pub filters: Vec<Expression>,
+    pub est_rows: f64,
+    pub session: SessionRef,
+}
+
+impl PlanInfo for IndexScanPlan {
+    fn name(&self) -> &str {
+        "IndexScan"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table.name().to_string();
+        row.index = index_name(self.index_info.as_ref());
+        row.range = format!("{}: {}", self.index_info.columns[0].0, self.range);
+        row.filters = join_expressions(&self.filters);
+    }
+}
@@ -1,12 +1,45 @@
 use crate::common::EncodeValue;
+use crate::planner::plan_expression::Expression;
+use crate::planner::{ExplainRow, PlanInfo};
 use crate::session::SessionRef;
-use crate::table::schema::DataSchema;
+use crate::table::schema::{CheckInfo, DataSchema, ForeignKeyInfo, IndexInfo};
 use crate::table::table::TableSource;
 use std::sync::Arc;
 
+/// A foreign key which the inserted rows are checked against. `parent` is the referenced table
+/// and its index on the referenced columns, it is `None` if the table does not exist.
+pub struct ForeignKeyCheck {
+    pub foreign_key: Arc<ForeignKeyInfo>,
+    pub parent: Option<(Arc<TableSource>, Arc<IndexInfo>)>,
+}
+
+/// A `CHECK` constraint which the inserted records are checked against, `expr` reads the
+/// columns from their offsets in the record.
+pub struct RecordCheck {
+    pub check: Arc<CheckInfo>,
+    pub expr: Expression,
+}
+
 pub struct InsertPlan {
     pub table: Arc<TableSource>,
     pub values: Vec<Vec<EncodeValue>>,
     pub schema: DataSchema,
+    /// Empty if `foreign_key_checks` is off.
+    pub foreign_keys: Vec<ForeignKeyCheck>,
+    pub checks: Vec<RecordCheck>,
     pub session: SessionRef,
 }
+
+impl PlanInfo for InsertPlan {
+    fn name(&self) -> &str {
+        "Insert"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.values.len() as f64
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table.name().to_string();
+    }
+}
@@ -0,0 +1,80 @@
+use crate::planner::logical_plan::JoinType;
+use crate::planner::plan_expression::Expression;
+use crate::planner::point_get_plan::join_expressions;
+use crate::planner::{ExplainRow, PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+
+/// Build a hash table from the rows of `right` and probe it with every row of `left`. The
+/// output row is the left row followed by the right row. `join_type` is never `Right`, the
+/// planner swaps the children of a right outer join.
+pub struct HashJoinPlan {
+    pub left: Box<PlanNode>,
+    pub right: Box<PlanNode>,
+    pub join_type: JoinType,
+    /// Bound to the columns of `left`.
+    pub left_keys: Vec<Expression>,
+    /// Bound to the columns of `right`.
+    pub right_keys: Vec<Expression>,
+    /// Bound to the output row.
+    pub other_conditions: Vec<Expression>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for HashJoinPlan {
+    fn name(&self) -> &str {
+        "HashJoin"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        let mut conditions: Vec<String> = self
+            .left_keys
+            .iter()
+            .zip(self.right_keys.iter())
+            .map(|(l, r)| format!("=({}, {})", l, r))
+            .collect();
+        conditions.extend(self.other_conditions.iter().map(|c| c.to_string()));
+        row.filters = format!("{}: {}", self.join_type.name(), conditions.join(" AND "));
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.left.as_ref(), self.right.as_ref()]
+    }
+}
+
+/// Evaluate `conditions` for every pair of rows from `left` and `right`.
+pub struct NestedLoopJoinPlan {
+    pub left: Box<PlanNode>,
+    pub right: Box<PlanNode>,
+    pub join_type: JoinType,
+    /// Bound to the output row.
+    pub conditions: Vec<Expression>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for NestedLoopJoinPlan {
+    fn name(&self) -> &str {
+        "NestedLoopJoin"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.filters = format!(
+            "{}: {}",
+            self.join_type.name(),
+            join_expressions(&self.conditions)
+        );
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.left.as_ref(), self.right.as_ref()]
+    }
+}
@@ -0,0 +1,13 @@
+use crate::planner::PlanInfo;
+
+/// `KILL [CONNECTION | QUERY] id`, `query_only` is set by `QUERY`.
+pub struct KillPlan {
+    pub connection_id: u64,
+    pub query_only: bool,
+}
+
+impl PlanInfo for KillPlan {
+    fn name(&self) -> &str {
+        "Kill"
+    }
+}
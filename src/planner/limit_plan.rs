@@ -0,0 +1,32 @@
+use crate::planner::{ExplainRow, PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+
+/// Skip the first `offset` rows of the input and return at most `limit` rows of the rest.
+pub struct LimitPlan {
+    pub input: Box<PlanNode>,
+    pub limit: Option<usize>,
+    pub offset: usize,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for LimitPlan {
+    fn name(&self) -> &str {
+        "Limit"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.filters = match self.limit {
+            Some(limit) => format!("offset: {}, count: {}", self.offset, limit),
+            None => format!("offset: {}", self.offset),
+        };
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.input.as_ref()]
+    }
+}
@@ -0,0 +1,203 @@
+use crate::common::EncodeValue;
+use crate::errors::MySQLResult;
+use crate::planner::aggregation_plan::AggregateExpr;
+use crate::planner::plan_expression::{ColumnRef, Expression};
+use crate::planner::sort_plan::SortItem;
+use crate::table::schema::ColumnInfo;
+use crate::table::TableSource;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A column produced by a logical operator. `id` is unique in the whole query, so that a column
+/// can be referenced without caring about where it is produced.
+#[derive(Clone, Debug)]
+pub struct LogicalColumn {
+    pub id: usize,
+    /// The database of the table which the column is read from, it is empty for a computed
+    /// column.
+    pub db: String,
+    pub qualifier: String,
+    pub name: String,
+    pub column: Arc<ColumnInfo>,
+}
+
+impl LogicalColumn {
+    pub fn to_expr(&self) -> Expression {
+        let name = if self.qualifier.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.qualifier, self.name)
+        };
+        Expression::Column(ColumnRef {
+            id: self.id,
+            index: 0,
+            name,
+            data_type: self.column.data_type.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+}
+
+impl JoinType {
+    pub fn name(&self) -> &str {
+        match self {
+            JoinType::Inner => "inner join",
+            JoinType::Left => "left outer join",
+            JoinType::Right => "right outer join",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LogicalScan {
+    pub table: Arc<TableSource>,
+    /// The id of every column of the table, ordered by the offset of the column.
+    pub column_ids: Vec<usize>,
+    /// The columns read by this scan.
+    pub columns: Vec<LogicalColumn>,
+    pub filters: Vec<Expression>,
+}
+
+#[derive(Clone)]
+pub enum LogicalPlan {
+    /// Produce one row without any column, it is the source of `SELECT` without `FROM`.
+    Dual,
+    Scan(LogicalScan),
+    Filter {
+        input: Box<LogicalPlan>,
+        predicates: Vec<Expression>,
+    },
+    Projection {
+        input: Box<LogicalPlan>,
+        exprs: Vec<Expression>,
+        columns: Vec<LogicalColumn>,
+    },
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        join_type: JoinType,
+        conditions: Vec<Expression>,
+    },
+    Aggregate {
+        input: Box<LogicalPlan>,
+        group_by: Vec<Expression>,
+        aggs: Vec<AggregateExpr>,
+        /// The columns of `group_by` followed by the columns of `aggs`.
+        columns: Vec<LogicalColumn>,
+    },
+    Sort {
+        input: Box<LogicalPlan>,
+        items: Vec<SortItem>,
+    },
+    /// `limit` and `offset` are constants or parameters, they are evaluated by the physical
+    /// planner.
+    Limit {
+        input: Box<LogicalPlan>,
+        limit: Option<Expression>,
+        offset: Option<Expression>,
+    },
+}
+
+impl LogicalPlan {
+    pub fn schema(&self) -> Vec<LogicalColumn> {
+        match self {
+            LogicalPlan::Dual => vec![],
+            LogicalPlan::Scan(scan) => scan.columns.clone(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.schema(),
+            LogicalPlan::Projection { columns, .. } | LogicalPlan::Aggregate { columns, .. } => {
+                columns.clone()
+            }
+            LogicalPlan::Join { left, right, .. } => {
+                let mut schema = left.schema();
+                schema.extend(right.schema());
+                schema
+            }
+        }
+    }
+
+    pub fn output_ids(&self) -> HashSet<usize> {
+        self.schema().iter().map(|c| c.id).collect()
+    }
+
+    /// Apply `f` to every expression of this operator, not including its children.
+    pub fn map_expressions<F>(&mut self, f: &mut F) -> MySQLResult<()>
+    where
+        F: FnMut(Expression) -> MySQLResult<Expression>,
+    {
+        let exprs: Vec<&mut Expression> = match self {
+            LogicalPlan::Dual => vec![],
+            LogicalPlan::Scan(scan) => scan.filters.iter_mut().collect(),
+            LogicalPlan::Filter { predicates, .. } => predicates.iter_mut().collect(),
+            LogicalPlan::Projection { exprs, .. } => exprs.iter_mut().collect(),
+            LogicalPlan::Join { conditions, .. } => conditions.iter_mut().collect(),
+            LogicalPlan::Aggregate { group_by, aggs, .. } => group_by
+                .iter_mut()
+                .chain(aggs.iter_mut().flat_map(|a| a.args.iter_mut()))
+                .collect(),
+            LogicalPlan::Sort { items, .. } => items.iter_mut().map(|i| &mut i.expr).collect(),
+            LogicalPlan::Limit { limit, offset, .. } => {
+                limit.iter_mut().chain(offset.iter_mut()).collect()
+            }
+        };
+        for e in exprs {
+            let old = std::mem::replace(e, Expression::Value(EncodeValue::NULL));
+            *e = f(old)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the parameters of a prepared statement and `LAST_INSERT_ID()` in the whole tree
+    /// by their values, the expressions which become constant are folded.
+    pub fn bind_parameters(
+        &mut self,
+        params: &[EncodeValue],
+        last_insert_id: u64,
+    ) -> MySQLResult<()> {
+        self.map_expressions(&mut |e| {
+            if e.has_parameter() {
+                e.bind_parameters(params, last_insert_id)?.fold_constant()
+            } else {
+                Ok(e)
+            }
+        })?;
+        for child in self.children_mut() {
+            child.bind_parameters(params, last_insert_id)?;
+        }
+        Ok(())
+    }
+
+    /// Whether all tables read by this plan are still the latest version, a plan kept across
+    /// statements must be rebuilt after any of them is changed.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            LogicalPlan::Dual => true,
+            LogicalPlan::Scan(scan) => scan.table.is_valid(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Projection { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.is_valid(),
+            LogicalPlan::Join { left, right, .. } => left.is_valid() && right.is_valid(),
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Vec<&mut LogicalPlan> {
+        match self {
+            LogicalPlan::Dual | LogicalPlan::Scan(_) => vec![],
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Projection { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. } => vec![input.as_mut()],
+            LogicalPlan::Join { left, right, .. } => vec![left.as_mut(), right.as_mut()],
+        }
+    }
+}
@@ -0,0 +1,808 @@
+use crate::common::EncodeValue;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::aggregation_plan::{AggFunction, AggregateExpr};
+use crate::planner::logical_plan::{JoinType, LogicalColumn, LogicalPlan, LogicalScan};
+use crate::planner::plan_expression::Expression;
+use crate::planner::prepared_statement::parameter_index;
+use crate::planner::sort_plan::SortItem;
+use crate::session::Session;
+use crate::table::schema::{ColumnInfo, TableInfo, TableState, ViewInfo};
+use crate::table::{is_system_database, SystemTable, TableSource};
+use sqlparser::ast::{
+    BinaryOperator, Expr, Function, FunctionArg, Ident, JoinConstraint, JoinOperator, ObjectName,
+    Query, Select, SelectItem, SetExpr, TableFactor, TableWithJoins, Value,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The groups and aggregates of a `SELECT` with aggregation, the aggregates are collected while
+/// building the expressions of the select list, `HAVING` and `ORDER BY`.
+#[derive(Default)]
+struct AggregateContext {
+    group_by: Vec<Expression>,
+    group_columns: Vec<LogicalColumn>,
+    aggs: Vec<AggregateExpr>,
+    agg_columns: Vec<LogicalColumn>,
+    /// Whether the arguments of an aggregate are being built, aggregates can not be nested.
+    in_aggregate: bool,
+}
+
+/// Translate a `SELECT` statement to a tree of logical operators, all names of tables and
+/// columns are resolved here.
+pub struct LogicalPlanBuilder<'a> {
+    session: &'a mut Session,
+    next_column_id: usize,
+    aggregate: Option<AggregateContext>,
+    /// The aliases of the select list, a name which is not a column may reference them.
+    aliases: Vec<(String, Expression)>,
+    /// The views being expanded with their databases, a view can not read itself.
+    views: Vec<(String, String)>,
+}
+
+impl<'a> LogicalPlanBuilder<'a> {
+    pub fn new(session: &'a mut Session) -> LogicalPlanBuilder<'a> {
+        LogicalPlanBuilder {
+            session,
+            next_column_id: 0,
+            aggregate: None,
+            aliases: vec![],
+            views: vec![],
+        }
+    }
+
+    fn alloc_column_id(&mut self) -> usize {
+        self.next_column_id += 1;
+        self.next_column_id
+    }
+
+    pub fn build_query(&mut self, query: &Query) -> MySQLResult<LogicalPlan> {
+        if query.with.is_some() || query.fetch.is_some() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        match &query.body {
+            SetExpr::Select(select) => self.build_select(select.as_ref(), query),
+            _ => Err(MySQLError::UnsupportSQL),
+        }
+    }
+
+    /// The operators are built in the order `FROM`, `WHERE`, `GROUP BY`, `HAVING`, `ORDER BY`,
+    /// `LIMIT` and the select list, so `ORDER BY` can use the columns which are not selected.
+    fn build_select(&mut self, select: &Select, query: &Query) -> MySQLResult<LogicalPlan> {
+        if select.top.is_some() || select.distinct {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        let mut plan = self.build_from(&select.from)?;
+        if let Some(selection) = select.selection.as_ref() {
+            let schema = plan.schema();
+            let mut predicates = vec![];
+            self.build_expr(selection, &schema)?
+                .split_conjunction(&mut predicates);
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                predicates,
+            };
+        }
+        let schema = plan.schema();
+        let has_aggregate = select.projection.iter().any(|item| match item {
+            SelectItem::UnnamedExpr(e) | SelectItem::ExprWithAlias { expr: e, .. } => {
+                has_aggregate(e)
+            }
+            _ => false,
+        }) || select.having.as_ref().map_or(false, has_aggregate)
+            || query.order_by.iter().any(|o| has_aggregate(&o.expr));
+        if has_aggregate || !select.group_by.is_empty() {
+            self.build_group_by(&select.group_by, &select.projection, &schema)?;
+        }
+        let (exprs, columns) = self.build_select_list(&schema, &select.projection)?;
+        let having = match select.having.as_ref() {
+            Some(having) => {
+                let e = self.build_expr(having, &schema)?;
+                Some(self.aggregate_output(e)?)
+            }
+            None => None,
+        };
+        let mut items = Vec::with_capacity(query.order_by.len());
+        for o in query.order_by.iter() {
+            items.push(SortItem {
+                expr: self.build_order_by_expr(&o.expr, &schema, &exprs)?,
+                asc: o.asc.unwrap_or(true),
+            });
+        }
+        self.aliases.clear();
+        if let Some(ctx) = self.aggregate.take() {
+            let mut columns = ctx.group_columns;
+            columns.extend(ctx.agg_columns);
+            plan = LogicalPlan::Aggregate {
+                input: Box::new(plan),
+                group_by: ctx.group_by,
+                aggs: ctx.aggs,
+                columns,
+            };
+        }
+        if let Some(having) = having {
+            let mut predicates = vec![];
+            having.split_conjunction(&mut predicates);
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                predicates,
+            };
+        }
+        if !items.is_empty() {
+            plan = LogicalPlan::Sort {
+                input: Box::new(plan),
+                items,
+            };
+        }
+        if query.limit.is_some() || query.offset.is_some() {
+            let limit = match query.limit.as_ref() {
+                Some(e) => Some(self.build_expr(e, &[])?),
+                None => None,
+            };
+            let offset = match query.offset.as_ref() {
+                Some(o) => Some(self.build_expr(&o.value, &[])?),
+                None => None,
+            };
+            plan = LogicalPlan::Limit {
+                input: Box::new(plan),
+                limit,
+                offset,
+            };
+        }
+        Ok(LogicalPlan::Projection {
+            input: Box::new(plan),
+            exprs,
+            columns,
+        })
+    }
+
+    /// Start the aggregation of the select. `GROUP BY` may reference a column of `FROM`, an
+    /// alias or a position of the select list.
+    fn build_group_by(
+        &mut self,
+        group_by: &[Expr],
+        projection: &[SelectItem],
+        schema: &[LogicalColumn],
+    ) -> MySQLResult<()> {
+        let mut ctx = AggregateContext::default();
+        for expr in group_by {
+            let e = match select_item_expr(expr, projection, false)? {
+                Some(item) => self.build_expr(item, schema)?,
+                None => match self.build_expr(expr, schema) {
+                    Err(MySQLError::MissColumn(name)) => {
+                        match select_item_expr(expr, projection, true)? {
+                            Some(item) => self.build_expr(item, schema)?,
+                            None => return Err(MySQLError::MissColumn(name)),
+                        }
+                    }
+                    e => e?,
+                },
+            };
+            if ctx.group_by.contains(&e) {
+                continue;
+            }
+            let column = match &e {
+                Expression::Column(c) => schema.iter().find(|col| col.id == c.id).cloned(),
+                _ => None,
+            };
+            let column = match column {
+                Some(col) => col,
+                None => {
+                    let name = e.to_string();
+                    let offset = ctx.group_columns.len();
+                    LogicalColumn {
+                        id: self.alloc_column_id(),
+                        db: "".to_string(),
+                        qualifier: "".to_string(),
+                        name: name.clone(),
+                        column: Arc::new(ColumnInfo::result_column(&name, offset, e.data_type())),
+                    }
+                }
+            };
+            ctx.group_by.push(e);
+            ctx.group_columns.push(column);
+        }
+        self.aggregate = Some(ctx);
+        Ok(())
+    }
+
+    /// `ORDER BY` references an alias or a position of the select list first, and then a
+    /// column of `FROM`.
+    fn build_order_by_expr(
+        &mut self,
+        expr: &Expr,
+        schema: &[LogicalColumn],
+        select_exprs: &[Expression],
+    ) -> MySQLResult<Expression> {
+        if let Expr::Value(Value::Number(n, _)) = expr {
+            return match n.parse::<usize>() {
+                Ok(i) if i >= 1 && i <= select_exprs.len() => Ok(select_exprs[i - 1].clone()),
+                _ => Err(MySQLError::MissColumn(n.clone())),
+            };
+        }
+        if let Expr::Identifier(ident) = expr {
+            let name = ident.value.to_lowercase();
+            if let Some((_, e)) = self.aliases.iter().find(|(alias, _)| *alias == name) {
+                return Ok(e.clone());
+            }
+        }
+        let e = self.build_expr(expr, schema)?;
+        self.aggregate_output(e)
+    }
+
+    fn build_from(&mut self, from: &[TableWithJoins]) -> MySQLResult<LogicalPlan> {
+        let mut plan: Option<LogicalPlan> = None;
+        for table in from {
+            let right = self.build_table_with_joins(table)?;
+            plan = Some(match plan {
+                None => right,
+                Some(left) => LogicalPlan::Join {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    join_type: JoinType::Inner,
+                    conditions: vec![],
+                },
+            });
+        }
+        Ok(plan.unwrap_or(LogicalPlan::Dual))
+    }
+
+    fn build_table_with_joins(&mut self, table: &TableWithJoins) -> MySQLResult<LogicalPlan> {
+        let mut plan = self.build_table_factor(&table.relation)?;
+        for join in table.joins.iter() {
+            let right = self.build_table_factor(&join.relation)?;
+            let (join_type, constraint) = match &join.join_operator {
+                JoinOperator::Inner(c) => (JoinType::Inner, Some(c)),
+                JoinOperator::LeftOuter(c) => (JoinType::Left, Some(c)),
+                JoinOperator::RightOuter(c) => (JoinType::Right, Some(c)),
+                JoinOperator::CrossJoin => (JoinType::Inner, None),
+                _ => return Err(MySQLError::UnsupportSQL),
+            };
+            let mut schema = plan.schema();
+            schema.extend(right.schema());
+            let mut conditions = vec![];
+            match constraint {
+                Some(JoinConstraint::On(expr)) => {
+                    self.build_expr(expr, &schema)?
+                        .split_conjunction(&mut conditions);
+                }
+                Some(JoinConstraint::Using(idents)) => {
+                    let left_schema = plan.schema();
+                    let right_schema = right.schema();
+                    for ident in idents {
+                        let name = ident.value.to_lowercase();
+                        let l = resolve_column(&left_schema, None, None, &name)?;
+                        let r = resolve_column(&right_schema, None, None, &name)?;
+                        conditions.push(Expression::binary(BinaryOperator::Eq, l, r));
+                    }
+                }
+                None => (),
+                _ => return Err(MySQLError::UnsupportSQL),
+            }
+            plan = LogicalPlan::Join {
+                left: Box::new(plan),
+                right: Box::new(right),
+                join_type,
+                conditions,
+            };
+        }
+        Ok(plan)
+    }
+
+    fn build_table_factor(&mut self, factor: &TableFactor) -> MySQLResult<LogicalPlan> {
+        match factor {
+            TableFactor::Table { name, alias, .. } => {
+                let (db, table_name) = resolve_table_name(self.session, name)?;
+                let qualifier = alias
+                    .as_ref()
+                    .map(|a| a.name.value.to_lowercase())
+                    .unwrap_or_else(|| table_name.clone());
+                if self.views.contains(&(db.clone(), table_name.clone())) {
+                    return Err(MySQLError::ViewRecursive(format!("{}.{}", db, table_name)));
+                }
+                if let Some(view) = self.session.get_view(&db, &table_name) {
+                    return self.build_view(&db, &view, qualifier);
+                }
+                let table = self.get_table(&db, &table_name)?;
+                let mut column_ids = vec![];
+                let mut columns = vec![];
+                for col in table.meta().columns.iter() {
+                    if col.state != TableState::Public {
+                        continue;
+                    }
+                    let id = self.alloc_column_id();
+                    column_ids.push(id);
+                    columns.push(LogicalColumn {
+                        id,
+                        db: db.clone(),
+                        qualifier: qualifier.clone(),
+                        name: col.name.clone(),
+                        column: col.clone(),
+                    });
+                }
+                Ok(LogicalPlan::Scan(LogicalScan {
+                    table,
+                    column_ids,
+                    columns,
+                    filters: vec![],
+                }))
+            }
+            TableFactor::NestedJoin(table) => self.build_table_with_joins(table.as_ref()),
+            _ => Err(MySQLError::UnsupportSQL),
+        }
+    }
+
+    fn get_table(&mut self, db: &str, table_name: &str) -> MySQLResult<Arc<TableSource>> {
+        let table = if is_system_database(db) {
+            SystemTable::from_name(db, table_name).map(|t| Arc::new(TableSource::system(t)))
+        } else {
+            self.session.get_table(db, table_name)
+        };
+        table.ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, table_name)))
+    }
+
+    /// Expand the view `view` of `db` to the plan of its query. The output columns are renamed
+    /// by the column list of the view and qualified by `qualifier`, the plain columns keep the
+    /// ids of the columns below so that the predicates on them are still pushed down.
+    pub fn build_view(
+        &mut self,
+        db: &str,
+        view: &ViewInfo,
+        qualifier: String,
+    ) -> MySQLResult<LogicalPlan> {
+        self.views.push((db.to_string(), view.name.clone()));
+        let plan = self.build_query(&view.query);
+        self.views.pop();
+        let mut plan = plan?;
+        if let LogicalPlan::Projection { columns, .. } = &mut plan {
+            if !view.columns.is_empty() && view.columns.len() != columns.len() {
+                return Err(MySQLError::ViewWrongList);
+            }
+            let mut names = HashSet::new();
+            for (i, col) in columns.iter_mut().enumerate() {
+                let name = view.columns.get(i).unwrap_or(&col.name).to_lowercase();
+                if !names.insert(name.clone()) {
+                    return Err(MySQLError::DupColumn(name));
+                }
+                if col.column.name != name {
+                    let data_type = col.column.data_type.clone();
+                    col.column = Arc::new(ColumnInfo::result_column(&name, i, data_type));
+                }
+                col.db = db.to_string();
+                col.qualifier = qualifier.clone();
+                col.name = name;
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Build the expressions of the select list and their output columns, the aliases are
+    /// recorded for the clauses built later.
+    fn build_select_list(
+        &mut self,
+        schema: &[LogicalColumn],
+        projection: &[SelectItem],
+    ) -> MySQLResult<(Vec<Expression>, Vec<LogicalColumn>)> {
+        let mut exprs = vec![];
+        let mut columns = vec![];
+        for item in projection {
+            match item {
+                SelectItem::Wildcard => {
+                    for col in schema.iter() {
+                        exprs.push(self.aggregate_output(col.to_expr())?);
+                        columns.push(col.clone());
+                    }
+                }
+                SelectItem::QualifiedWildcard(name) => {
+                    let qualifier = name.0.last().unwrap().value.to_lowercase();
+                    let db = match name.0.len() {
+                        1 => None,
+                        2 => Some(name.0[0].value.to_lowercase()),
+                        _ => return Err(MySQLError::UnsupportSQL),
+                    };
+                    let mut found = false;
+                    let matched = schema.iter().filter(|c| {
+                        c.qualifier == qualifier && db.as_ref().map_or(true, |db| c.db == *db)
+                    });
+                    for col in matched {
+                        exprs.push(self.aggregate_output(col.to_expr())?);
+                        columns.push(col.clone());
+                        found = true;
+                    }
+                    if !found {
+                        return Err(MySQLError::NoTable(qualifier));
+                    }
+                }
+                SelectItem::UnnamedExpr(expr) => {
+                    let e = self.build_expr(expr, schema)?;
+                    let e = self.aggregate_output(e)?;
+                    let name = match expr {
+                        Expr::Identifier(ident) if parameter_index(ident).is_some() => {
+                            "?".to_string()
+                        }
+                        Expr::Identifier(ident) => ident.value.clone(),
+                        Expr::CompoundIdentifier(idents) => idents.last().unwrap().value.clone(),
+                        e => e.to_string(),
+                    };
+                    columns.push(self.projection_column(&e, name, columns.len()));
+                    exprs.push(e);
+                }
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    let e = self.build_expr(expr, schema)?;
+                    let e = self.aggregate_output(e)?;
+                    self.aliases.push((alias.value.to_lowercase(), e.clone()));
+                    columns.push(self.projection_column(&e, alias.value.clone(), columns.len()));
+                    exprs.push(e);
+                }
+            }
+        }
+        Ok((exprs, columns))
+    }
+
+    /// Rewrite an expression built on the input of the aggregation to the output of it, the
+    /// expression can only use the groups and the aggregates.
+    fn aggregate_output(&self, expr: Expression) -> MySQLResult<Expression> {
+        let ctx = match self.aggregate.as_ref() {
+            Some(ctx) => ctx,
+            None => return Ok(expr),
+        };
+        let mut expr = expr;
+        for (g, c) in ctx.group_by.iter().zip(ctx.group_columns.iter()) {
+            if let Expression::Column(_) = g {
+                continue;
+            }
+            expr = expr.replace_expr(g, &c.to_expr());
+        }
+        let outputs: HashSet<usize> = ctx
+            .group_columns
+            .iter()
+            .chain(ctx.agg_columns.iter())
+            .map(|c| c.id)
+            .collect();
+        if !expr.columns().is_subset(&outputs) {
+            return Err(MySQLError::NotInGroupBy(expr.to_string()));
+        }
+        Ok(expr)
+    }
+
+    fn build_aggregate(
+        &mut self,
+        function: &Function,
+        schema: &[LogicalColumn],
+    ) -> MySQLResult<Expression> {
+        let name = function.name.to_string();
+        let func = AggFunction::from_name(&name).ok_or(MySQLError::UnsupportSQL)?;
+        if function.over.is_some() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        match self.aggregate.as_mut() {
+            Some(ctx) if !ctx.in_aggregate => ctx.in_aggregate = true,
+            _ => return Err(MySQLError::InvalidGroupFunc),
+        }
+        let args = self.build_aggregate_args(func, function, schema);
+        let ctx = self.aggregate.as_mut().unwrap();
+        ctx.in_aggregate = false;
+        let agg = AggregateExpr {
+            func,
+            args: args?,
+            distinct: function.distinct,
+        };
+        if let Some(i) = ctx.aggs.iter().position(|a| *a == agg) {
+            return Ok(ctx.agg_columns[i].to_expr());
+        }
+        let offset = ctx.group_columns.len() + ctx.aggs.len();
+        let name = agg.to_string();
+        let column = LogicalColumn {
+            id: self.alloc_column_id(),
+            db: "".to_string(),
+            qualifier: "".to_string(),
+            name: name.clone(),
+            column: Arc::new(ColumnInfo::result_column(&name, offset, agg.data_type())),
+        };
+        let expr = column.to_expr();
+        let ctx = self.aggregate.as_mut().unwrap();
+        ctx.aggs.push(agg);
+        ctx.agg_columns.push(column);
+        Ok(expr)
+    }
+
+    fn build_aggregate_args(
+        &mut self,
+        func: AggFunction,
+        function: &Function,
+        schema: &[LogicalColumn],
+    ) -> MySQLResult<Vec<Expression>> {
+        let count_star = func == AggFunction::Count
+            && !function.distinct
+            && function.args.len() == 1
+            && matches!(function.args[0], FunctionArg::Unnamed(Expr::Wildcard));
+        if count_star {
+            return Ok(vec![]);
+        }
+        let mut args = Vec::with_capacity(function.args.len());
+        for arg in function.args.iter() {
+            match arg {
+                FunctionArg::Unnamed(e) => args.push(self.build_expr(e, schema)?),
+                _ => return Err(MySQLError::UnsupportSQL),
+            }
+        }
+        // Only `COUNT(DISTINCT ...)` accepts many arguments.
+        let multiple = func == AggFunction::Count && function.distinct;
+        if args.is_empty() || (args.len() > 1 && !multiple) {
+            return Err(MySQLError::WrongArguments(function.name.to_string()));
+        }
+        Ok(args)
+    }
+
+    /// The output column of a projection expression, a plain column keeps its id so that the
+    /// operators above can still reference it.
+    fn projection_column(
+        &mut self,
+        expr: &Expression,
+        name: String,
+        offset: usize,
+    ) -> LogicalColumn {
+        let id = match expr {
+            Expression::Column(c) => c.id,
+            _ => self.alloc_column_id(),
+        };
+        LogicalColumn {
+            id,
+            db: "".to_string(),
+            qualifier: "".to_string(),
+            name: name.clone(),
+            column: Arc::new(ColumnInfo::result_column(&name, offset, expr.data_type())),
+        }
+    }
+
+    /// Build an expression on the records of `table`, such as a `CHECK` constraint, which reads
+    /// a column from its offset in the record. Return it with the columns it reads.
+    pub fn build_record_expr(
+        &mut self,
+        table: &TableInfo,
+        expr: &Expr,
+    ) -> MySQLResult<(Expression, Vec<Arc<ColumnInfo>>)> {
+        let (mut e, used) = self.build_column_expr(table, expr)?;
+        let ids: Vec<usize> = table.columns.iter().map(|c| c.id as usize).collect();
+        e.bind(&ids)?;
+        Ok((e, used))
+    }
+
+    /// Like `build_record_expr`, but the expression is not bound, its columns are identified by
+    /// the ids of the columns of `table`, which do not change when other columns are added or
+    /// dropped.
+    pub fn build_column_expr(
+        &mut self,
+        table: &TableInfo,
+        expr: &Expr,
+    ) -> MySQLResult<(Expression, Vec<Arc<ColumnInfo>>)> {
+        let columns: Vec<LogicalColumn> = table
+            .columns
+            .iter()
+            .map(|col| LogicalColumn {
+                id: col.id as usize,
+                db: "".to_string(),
+                qualifier: table.name.clone(),
+                name: col.name.clone(),
+                column: col.clone(),
+            })
+            .collect();
+        let e = self.build_expr(expr, &columns)?;
+        if e.has_parameter() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        let mut read = HashSet::new();
+        e.collect_columns(&mut read);
+        let used = columns
+            .into_iter()
+            .filter(|c| read.contains(&c.id))
+            .map(|c| c.column)
+            .collect();
+        Ok((e, used))
+    }
+
+    pub fn build_expr(&mut self, expr: &Expr, schema: &[LogicalColumn]) -> MySQLResult<Expression> {
+        match expr {
+            Expr::Identifier(ident) => match parameter_index(ident) {
+                Some(i) => Ok(Expression::Parameter(i)),
+                None => {
+                    let name = ident.value.to_lowercase();
+                    match resolve_column(schema, None, None, &name) {
+                        Err(MySQLError::MissColumn(_)) => self
+                            .aliases
+                            .iter()
+                            .find(|(alias, _)| *alias == name)
+                            .map(|(_, e)| e.clone())
+                            .ok_or(MySQLError::MissColumn(name)),
+                        r => r,
+                    }
+                }
+            },
+            Expr::CompoundIdentifier(idents) => self.resolve_compound(schema, idents),
+            Expr::Value(v) => Ok(Expression::Value(EncodeValue::from_parse_value(v.clone())?)),
+            Expr::Nested(e) => self.build_expr(e.as_ref(), schema),
+            Expr::IsNull(e) => Ok(Expression::IsNull(Box::new(
+                self.build_expr(e.as_ref(), schema)?,
+            ))),
+            Expr::IsNotNull(e) => Ok(Expression::IsNotNull(Box::new(
+                self.build_expr(e.as_ref(), schema)?,
+            ))),
+            Expr::UnaryOp { op, expr } => Ok(Expression::UnaryOp {
+                op: op.clone(),
+                expr: Box::new(self.build_expr(expr.as_ref(), schema)?),
+            }),
+            Expr::BinaryOp { left, op, right } => {
+                match op {
+                    BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Lt
+                    | BinaryOperator::LtEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::GtEq
+                    | BinaryOperator::And
+                    | BinaryOperator::Or
+                    | BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Divide
+                    | BinaryOperator::Modulo => (),
+                    _ => return Err(MySQLError::UnsupportSQL),
+                }
+                Ok(Expression::binary(
+                    op.clone(),
+                    self.build_expr(left.as_ref(), schema)?,
+                    self.build_expr(right.as_ref(), schema)?,
+                ))
+            }
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                let e = self.build_expr(expr.as_ref(), schema)?;
+                let low = self.build_expr(low.as_ref(), schema)?;
+                let high = self.build_expr(high.as_ref(), schema)?;
+                if *negated {
+                    Ok(Expression::binary(
+                        BinaryOperator::Or,
+                        Expression::binary(BinaryOperator::Lt, e.clone(), low),
+                        Expression::binary(BinaryOperator::Gt, e, high),
+                    ))
+                } else {
+                    Ok(Expression::binary(
+                        BinaryOperator::And,
+                        Expression::binary(BinaryOperator::GtEq, e.clone(), low),
+                        Expression::binary(BinaryOperator::LtEq, e, high),
+                    ))
+                }
+            }
+            Expr::Function(function)
+                if function
+                    .name
+                    .to_string()
+                    .eq_ignore_ascii_case("last_insert_id") =>
+            {
+                if !function.args.is_empty() {
+                    return Err(MySQLError::UnsupportSQL);
+                }
+                Ok(Expression::LastInsertId)
+            }
+            Expr::Function(function) => self.build_aggregate(function, schema),
+            _ => Err(MySQLError::UnsupportSQL),
+        }
+    }
+
+    fn resolve_compound(
+        &self,
+        schema: &[LogicalColumn],
+        idents: &[Ident],
+    ) -> MySQLResult<Expression> {
+        let names: Vec<String> = idents.iter().map(|i| i.value.to_lowercase()).collect();
+        match names.len() {
+            1 => resolve_column(schema, None, None, &names[0]),
+            2 => resolve_column(schema, None, Some(&names[0]), &names[1]),
+            3 => resolve_column(schema, Some(&names[0]), Some(&names[1]), &names[2]),
+            _ => Err(MySQLError::UnsupportSQL),
+        }
+    }
+}
+
+/// The database and the name of a table, the database is the current one unless `name` is
+/// qualified.
+pub fn resolve_table_name(session: &Session, name: &ObjectName) -> MySQLResult<(String, String)> {
+    let table_name = name.0.last().unwrap().value.to_lowercase();
+    let db = match name.0.len() {
+        1 => session.get_db().clone(),
+        2 => name.0[0].value.to_lowercase(),
+        _ => return Err(MySQLError::UnsupportSQL),
+    };
+    if db.is_empty() {
+        return Err(MySQLError::NoDBSelected);
+    }
+    Ok((db, table_name))
+}
+
+/// Whether the expression calls an aggregate function.
+fn has_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(f) => {
+            AggFunction::from_name(&f.name.to_string()).is_some()
+                || f.args.iter().any(|arg| match arg {
+                    FunctionArg::Unnamed(e) => has_aggregate(e),
+                    _ => false,
+                })
+        }
+        Expr::Nested(e) | Expr::IsNull(e) | Expr::IsNotNull(e) | Expr::UnaryOp { expr: e, .. } => {
+            has_aggregate(e.as_ref())
+        }
+        Expr::BinaryOp { left, right, .. } => has_aggregate(left) || has_aggregate(right),
+        Expr::Between {
+            expr, low, high, ..
+        } => has_aggregate(expr) || has_aggregate(low) || has_aggregate(high),
+        _ => false,
+    }
+}
+
+/// The expression of the select list referenced by a position, or by an alias if `alias` is
+/// true. A position must be in the range of the select list.
+fn select_item_expr<'b>(
+    expr: &Expr,
+    projection: &'b [SelectItem],
+    alias: bool,
+) -> MySQLResult<Option<&'b Expr>> {
+    match expr {
+        Expr::Value(Value::Number(n, _)) if !alias => {
+            let item = n
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| i.checked_sub(1))
+                .and_then(|i| projection.get(i));
+            match item {
+                Some(SelectItem::UnnamedExpr(e))
+                | Some(SelectItem::ExprWithAlias { expr: e, .. }) => Ok(Some(e)),
+                _ => Err(MySQLError::MissColumn(n.clone())),
+            }
+        }
+        Expr::Identifier(ident) if alias => {
+            let name = ident.value.to_lowercase();
+            Ok(projection.iter().find_map(|item| match item {
+                SelectItem::ExprWithAlias { expr, alias } if alias.value.to_lowercase() == name => {
+                    Some(expr)
+                }
+                _ => None,
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn resolve_column(
+    schema: &[LogicalColumn],
+    db: Option<&String>,
+    qualifier: Option<&String>,
+    name: &String,
+) -> MySQLResult<Expression> {
+    let mut found: Option<&LogicalColumn> = None;
+    for col in schema {
+        if col.name != *name {
+            continue;
+        }
+        if let Some(q) = qualifier {
+            if col.qualifier != *q {
+                continue;
+            }
+        }
+        if let Some(db) = db {
+            if col.db != *db {
+                continue;
+            }
+        }
+        if found.is_some() {
+            return Err(MySQLError::AmbiguousColumn(name.clone()));
+        }
+        found = Some(col);
+    }
+    found
+        .map(|col| col.to_expr())
+        .ok_or_else(|| MySQLError::MissColumn(name.clone()))
+}
@@ -1,22 +1,159 @@
-mod create_index_plan;
+mod aggregation_plan;
+mod alter_table_plan;
+mod analyze_table_plan;
+mod column_eval;
 mod create_table_plan;
+mod database_plan;
+mod drop_table_plan;
+mod explain_plan;
+mod filter_plan;
+mod index_scan_plan;
 mod insert_plan;
+mod join_plan;
+mod kill_plan;
+mod limit_plan;
+mod logical_plan;
+mod logical_plan_builder;
+mod optimizer;
 mod plan_builder;
+mod plan_cache;
 mod plan_expression;
 mod point_get_plan;
-mod select_plan;
+mod prepared_statement;
+mod projection_plan;
+mod set_variable_plan;
+mod show_plan;
+mod sort_plan;
+mod system_table_scan_plan;
+mod table_dual_plan;
+mod table_scan_plan;
+mod view_plan;
 
-pub use create_index_plan::CreateIndexPlan;
+pub use aggregation_plan::{AggFunction, AggregateExpr, AggregationPlan};
+pub use alter_table_plan::{AlterTableOperation, AlterTablePlan};
+pub use analyze_table_plan::AnalyzeTablePlan;
 pub use create_table_plan::CreateTablePlan;
-pub use insert_plan::InsertPlan;
+pub use database_plan::{CreateDatabasePlan, DropDatabasePlan, UseDatabasePlan};
+pub use drop_table_plan::{DropTablePlan, TruncateTablePlan};
+pub use explain_plan::{ExplainPlan, ExplainRow};
+pub use filter_plan::FilterPlan;
+pub use index_scan_plan::IndexScanPlan;
+pub use insert_plan::{ForeignKeyCheck, InsertPlan, RecordCheck};
+pub use join_plan::{HashJoinPlan, NestedLoopJoinPlan};
+pub use kill_plan::KillPlan;
+pub use limit_plan::LimitPlan;
+pub use logical_plan::JoinType;
 pub use plan_builder::PlanBuilder;
+pub use plan_cache::{PlanCache, PlanCacheKey, DEFAULT_PLAN_CACHE_CAPACITY};
+pub use plan_expression::{ColumnEvaluator, ColumnRef, Expression};
 pub use point_get_plan::PointGetPlan;
-pub use select_plan::SelectPlan;
+pub use prepared_statement::PreparedStatement;
+pub use projection_plan::ProjectionPlan;
+pub use set_variable_plan::SetVariablePlan;
+pub use show_plan::ShowPlan;
+pub use sort_plan::{SortItem, SortPlan};
+pub use system_table_scan_plan::SystemTableScanPlan;
+pub use table_dual_plan::TableDualPlan;
+pub use table_scan_plan::TableScanPlan;
+pub use view_plan::{CreateViewPlan, DropViewPlan};
 
 pub enum PlanNode {
+    CreateDatabase(CreateDatabasePlan),
+    DropDatabase(DropDatabasePlan),
+    UseDatabase(UseDatabasePlan),
     CreateTable(CreateTablePlan),
-    CreateIndex(CreateIndexPlan),
+    DropTable(DropTablePlan),
+    TruncateTable(TruncateTablePlan),
+    AlterTable(AlterTablePlan),
+    CreateView(CreateViewPlan),
+    DropView(DropViewPlan),
     PointGet(PointGetPlan),
+    TableScan(TableScanPlan),
+    SystemTableScan(SystemTableScanPlan),
+    IndexScan(IndexScanPlan),
+    Filter(FilterPlan),
+    Projection(ProjectionPlan),
+    HashJoin(HashJoinPlan),
+    NestedLoopJoin(NestedLoopJoinPlan),
+    Aggregation(AggregationPlan),
+    Sort(SortPlan),
+    Limit(LimitPlan),
+    TableDual(TableDualPlan),
     Insert(InsertPlan),
-    Select(SelectPlan),
+    Explain(ExplainPlan),
+    AnalyzeTable(AnalyzeTablePlan),
+    Show(ShowPlan),
+    SetVariable(SetVariablePlan),
+    Kill(KillPlan),
+}
+
+/// The description of a plan which is shared by every kind of `PlanNode`. `EXPLAIN` walks the
+/// plan tree only through this trait.
+pub trait PlanInfo {
+    fn name(&self) -> &str;
+
+    fn est_rows(&self) -> f64 {
+        0.0
+    }
+
+    /// Fill the operator specific columns of the `EXPLAIN` output.
+    fn explain_info(&self, _row: &mut ExplainRow) {}
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![]
+    }
+}
+
+impl PlanNode {
+    pub fn info(&self) -> &dyn PlanInfo {
+        match self {
+            PlanNode::CreateDatabase(p) => p,
+            PlanNode::DropDatabase(p) => p,
+            PlanNode::UseDatabase(p) => p,
+            PlanNode::CreateTable(p) => p,
+            PlanNode::DropTable(p) => p,
+            PlanNode::TruncateTable(p) => p,
+            PlanNode::AlterTable(p) => p,
+            PlanNode::CreateView(p) => p,
+            PlanNode::DropView(p) => p,
+            PlanNode::PointGet(p) => p,
+            PlanNode::TableScan(p) => p,
+            PlanNode::SystemTableScan(p) => p,
+            PlanNode::IndexScan(p) => p,
+            PlanNode::Filter(p) => p,
+            PlanNode::Projection(p) => p,
+            PlanNode::HashJoin(p) => p,
+            PlanNode::NestedLoopJoin(p) => p,
+            PlanNode::Aggregation(p) => p,
+            PlanNode::Sort(p) => p,
+            PlanNode::Limit(p) => p,
+            PlanNode::TableDual(p) => p,
+            PlanNode::Insert(p) => p,
+            PlanNode::Explain(p) => p,
+            PlanNode::AnalyzeTable(p) => p,
+            PlanNode::Show(p) => p,
+            PlanNode::SetVariable(p) => p,
+            PlanNode::Kill(p) => p,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.info().name()
+    }
+
+    /// Append the description of this node and its children to `rows`, children are indented
+    /// by `depth` so that the tree shape is visible in the result set.
+    pub fn explain(&self, depth: usize, rows: &mut Vec<ExplainRow>) {
+        let info = self.info();
+        let mut row = ExplainRow {
+            id: format!("{}{}", "  ".repeat(depth), info.name()),
+            est_rows: info.est_rows(),
+            ..Default::default()
+        };
+        info.explain_info(&mut row);
+        rows.push(row);
+        for child in info.children() {
+            child.explain(depth + 1, rows);
+        }
+    }
 }
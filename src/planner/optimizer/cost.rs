@@ -0,0 +1,185 @@
+use crate::common::EncodeValue;
+use crate::planner::plan_expression::{is_integer_type, Expression};
+use crate::statistics::TableStatistics;
+use crate::table::schema::ColumnInfo;
+use crate::table::{IndexRange, TableSource};
+use sqlparser::ast::{BinaryOperator, DataType};
+use std::sync::Arc;
+
+/// The row count of a table which has never been analyzed.
+pub const PSEUDO_ROW_COUNT: f64 = 10000.0;
+pub const PSEUDO_EQUAL_SELECTIVITY: f64 = 0.001;
+pub const PSEUDO_RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+/// The selectivity of a predicate which can not be estimated by statistics.
+pub const DEFAULT_SELECTIVITY: f64 = 0.8;
+
+/// The cost to locate the first key of a scan.
+pub const SEEK_COST: f64 = 1.0;
+/// The cost to read a key by `get`.
+pub const GET_COST: f64 = 1.0;
+pub const SCAN_ROW_COST: f64 = 1.0;
+/// The extra cost to read a record by the handle found in a secondary index.
+pub const LOOKUP_ROW_COST: f64 = 3.0;
+/// The cost to evaluate one expression for a row.
+pub const CPU_ROW_COST: f64 = 0.1;
+pub const HASH_BUILD_ROW_COST: f64 = 0.5;
+pub const HASH_PROBE_ROW_COST: f64 = 0.2;
+
+/// Match a predicate in the form of `column op constant`, the operator is reversed if the
+/// constant is on the left side.
+pub fn match_column_const(expr: &Expression) -> Option<(usize, BinaryOperator, &EncodeValue)> {
+    if let Expression::BinaryOp { op, left, right } = expr {
+        let (id, op, value) = match (left.as_ref(), right.as_ref()) {
+            (Expression::Column(c), Expression::Value(v)) => (c.id, op.clone(), v),
+            (Expression::Value(v), Expression::Column(c)) => {
+                let op = match op {
+                    BinaryOperator::Lt => BinaryOperator::Gt,
+                    BinaryOperator::LtEq => BinaryOperator::GtEq,
+                    BinaryOperator::Gt => BinaryOperator::Lt,
+                    BinaryOperator::GtEq => BinaryOperator::LtEq,
+                    op => op.clone(),
+                };
+                (c.id, op, v)
+            }
+            _ => return None,
+        };
+        match op {
+            BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq => return Some((id, op, value)),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Convert a constant compared with a column of `data_type` to the value used in an index
+/// range. Return `None` if the conversion may change the result of the comparison.
+pub fn range_value(data_type: &DataType, value: &EncodeValue) -> Option<EncodeValue> {
+    match (value, data_type) {
+        (EncodeValue::Int(_), data_type) if is_integer_type(data_type) => Some(value.clone()),
+        (EncodeValue::Int(_), DataType::Double) | (EncodeValue::Double(_), DataType::Double) => {
+            Some(value.convert_to(data_type))
+        }
+        (EncodeValue::Bytes(_), DataType::Char(_))
+        | (EncodeValue::Bytes(_), DataType::Varchar(_))
+        | (EncodeValue::Bytes(_), DataType::String)
+        | (EncodeValue::Bytes(_), DataType::Text) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Estimate the number of rows of a table which satisfy some predicates, the statistics are
+/// used if the table has been analyzed.
+pub struct TableEstimator<'a> {
+    table: &'a TableSource,
+    /// The id of every column of the table in the query, ordered by the offset of the column.
+    column_ids: &'a [usize],
+    stats: Option<Arc<TableStatistics>>,
+}
+
+impl<'a> TableEstimator<'a> {
+    pub fn new(
+        table: &'a TableSource,
+        column_ids: &'a [usize],
+        stats: Option<Arc<TableStatistics>>,
+    ) -> TableEstimator<'a> {
+        TableEstimator {
+            table,
+            column_ids,
+            stats,
+        }
+    }
+
+    pub fn column(&self, id: usize) -> Option<&'a ColumnInfo> {
+        let table: &'a TableSource = self.table;
+        self.column_ids
+            .iter()
+            .position(|i| *i == id)
+            .map(|offset| table.meta().columns[offset].as_ref())
+    }
+
+    pub fn row_count(&self) -> f64 {
+        match self.stats.as_ref() {
+            Some(stats) => stats.row_count as f64,
+            None => PSEUDO_ROW_COUNT,
+        }
+    }
+
+    pub fn ndv(&self, col: &ColumnInfo) -> Option<f64> {
+        let stats = self.stats.as_ref()?;
+        stats.column(col).map(|c| c.ndv as f64)
+    }
+
+    pub fn equal_rows(&self, col: &ColumnInfo, value: &EncodeValue) -> f64 {
+        if let Some(stats) = self.stats.as_ref() {
+            if let Ok(rows) = stats.equal_row_count(col, &value.convert_to(&col.data_type)) {
+                return rows;
+            }
+        }
+        self.row_count() * PSEUDO_EQUAL_SELECTIVITY
+    }
+
+    pub fn range_rows(&self, col: &ColumnInfo, range: &IndexRange) -> f64 {
+        if range.is_empty() {
+            return 0.0;
+        }
+        if range.is_point() {
+            return self.equal_rows(col, &range.low.as_ref().unwrap().0);
+        }
+        if range.is_full() {
+            return self.row_count();
+        }
+        if let Some(stats) = self.stats.as_ref() {
+            let low = range
+                .low
+                .as_ref()
+                .map(|(v, _)| v.convert_to(&col.data_type));
+            let high = range
+                .high
+                .as_ref()
+                .map(|(v, _)| v.convert_to(&col.data_type));
+            if let Ok(rows) = stats.range_row_count(col, low.as_ref(), high.as_ref()) {
+                return rows;
+            }
+        }
+        self.row_count() * PSEUDO_RANGE_SELECTIVITY
+    }
+
+    /// The ratio of rows which satisfy `predicate`.
+    pub fn selectivity(&self, predicate: &Expression) -> f64 {
+        let row_count = self.row_count();
+        if row_count <= 0.0 {
+            return 1.0;
+        }
+        let (id, op, value) = match match_column_const(predicate) {
+            Some(m) => m,
+            None => return DEFAULT_SELECTIVITY,
+        };
+        let col = match self.column(id) {
+            Some(col) => col,
+            None => return DEFAULT_SELECTIVITY,
+        };
+        let mut range = IndexRange::default();
+        match op {
+            BinaryOperator::Eq => return (self.equal_rows(col, value) / row_count).min(1.0),
+            BinaryOperator::NotEq => {
+                return (1.0 - self.equal_rows(col, value) / row_count).max(0.0);
+            }
+            BinaryOperator::Lt => range.restrict_high(value.clone(), false),
+            BinaryOperator::LtEq => range.restrict_high(value.clone(), true),
+            BinaryOperator::Gt => range.restrict_low(value.clone(), false),
+            _ => range.restrict_low(value.clone(), true),
+        }
+        (self.range_rows(col, &range) / row_count).min(1.0)
+    }
+
+    pub fn filtered_rows(&self, rows: f64, predicates: &[Expression]) -> f64 {
+        predicates
+            .iter()
+            .fold(rows, |rows, p| rows * self.selectivity(p))
+    }
+}
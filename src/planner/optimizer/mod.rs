@@ -0,0 +1,40 @@
+mod cost;
+mod physical_planner;
+mod rules;
+
+use crate::errors::MySQLResult;
+use crate::planner::logical_plan::LogicalPlan;
+
+pub use physical_planner::{
+    AccessPath, IndexScanPath, PhysicalPlanner, PointGetPath, TableScanPath,
+};
+pub use rules::{
+    ColumnPruning, ConstantFolding, OptimizerRule, OuterJoinToInner, PredicatePushDown,
+};
+
+/// Rewrite a logical plan by a list of rules, each rule is applied once in order.
+pub struct Optimizer {
+    rules: Vec<Box<dyn OptimizerRule>>,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Optimizer {
+            rules: vec![
+                Box::new(ConstantFolding {}),
+                Box::new(OuterJoinToInner {}),
+                Box::new(PredicatePushDown {}),
+                Box::new(ColumnPruning {}),
+            ],
+        }
+    }
+}
+
+impl Optimizer {
+    pub fn optimize(&self, mut plan: LogicalPlan) -> MySQLResult<LogicalPlan> {
+        for rule in self.rules.iter() {
+            plan = rule.optimize(plan)?;
+        }
+        Ok(plan)
+    }
+}
@@ -0,0 +1,856 @@
+use super::cost::*;
+use crate::common::EncodeValue;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::logical_plan::{JoinType, LogicalColumn, LogicalPlan, LogicalScan};
+use crate::planner::plan_expression::Expression;
+use crate::planner::{
+    AggregateExpr, AggregationPlan, FilterPlan, HashJoinPlan, IndexScanPlan, LimitPlan,
+    NestedLoopJoinPlan, PlanNode, PointGetPlan, ProjectionPlan, SortItem, SortPlan,
+    SystemTableScanPlan, TableDualPlan, TableScanPlan,
+};
+use crate::session::SessionRef;
+use crate::table::schema::{ColumnInfo, DataSchema, DataSchemaRef, IndexInfo, TableState};
+use crate::table::IndexRange;
+use sqlparser::ast::BinaryOperator;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The information of a table scan shared by all access paths.
+pub struct ScanContext<'a> {
+    pub scan: &'a LogicalScan,
+    pub estimator: TableEstimator<'a>,
+    /// The columns returned by the scan.
+    pub columns: DataSchemaRef,
+    pub session: SessionRef,
+}
+
+impl<'a> ScanContext<'a> {
+    /// Bind the filters except those in `skip` to the columns of the table.
+    fn bind_filters(&self, skip: &[usize]) -> MySQLResult<Vec<Expression>> {
+        let mut filters = vec![];
+        for (i, f) in self.scan.filters.iter().enumerate() {
+            if skip.contains(&i) {
+                continue;
+            }
+            let mut f = f.clone();
+            f.bind(&self.scan.column_ids)?;
+            filters.push(f);
+        }
+        Ok(filters)
+    }
+
+    fn filter_cost(&self, rows: f64, filters: &[Expression]) -> f64 {
+        rows * filters.len() as f64 * CPU_ROW_COST
+    }
+}
+
+pub struct ScanCandidate {
+    pub plan: PlanNode,
+    pub rows: f64,
+    pub cost: f64,
+}
+
+/// A way to read the rows of a table. The planner asks every registered access path for a
+/// plan and takes the cheapest one.
+pub trait AccessPath {
+    fn name(&self) -> &str;
+    /// Return `None` if this path can not be used for the scan.
+    fn build(&self, ctx: &ScanContext) -> MySQLResult<Option<ScanCandidate>>;
+}
+
+/// Read a single row by the equal condition on an unique index.
+pub struct PointGetPath {}
+
+impl AccessPath for PointGetPath {
+    fn name(&self) -> &str {
+        "point_get"
+    }
+
+    fn build(&self, ctx: &ScanContext) -> MySQLResult<Option<ScanCandidate>> {
+        for (i, f) in ctx.scan.filters.iter().enumerate() {
+            let (id, value) = match match_column_const(f) {
+                Some((id, BinaryOperator::Eq, value)) => (id, value),
+                _ => continue,
+            };
+            let col = match ctx.estimator.column(id) {
+                Some(col) => col,
+                None => continue,
+            };
+            let index_info = match ctx.scan.table.get_index(&col.name) {
+                Some(index) if index.state == TableState::Public => index,
+                _ => continue,
+            };
+            let index_value = match range_value(&col.data_type, value) {
+                Some(v) => v,
+                None => continue,
+            };
+            return point_get(ctx, index_info, vec![index_value], &[i]).map(Some);
+        }
+        // A primary key of many columns is used if all of them are matched.
+        let index_info = match ctx.scan.table.meta().get_primary_index() {
+            Some(index) if index.columns.len() > 1 && index.state == TableState::Public => index,
+            _ => return Ok(None),
+        };
+        let mut index_values = vec![];
+        let mut used = vec![];
+        for (_, offset) in index_info.columns.iter() {
+            let col = ctx.scan.table.meta().columns[*offset].as_ref();
+            let col_id = ctx.scan.column_ids[*offset];
+            let matched = ctx.scan.filters.iter().enumerate().find_map(|(i, f)| {
+                match match_column_const(f) {
+                    Some((id, BinaryOperator::Eq, value)) if id == col_id => {
+                        range_value(&col.data_type, value).map(|v| (i, v))
+                    }
+                    _ => None,
+                }
+            });
+            match matched {
+                Some((i, value)) => {
+                    used.push(i);
+                    index_values.push(value);
+                }
+                None => return Ok(None),
+            }
+        }
+        point_get(ctx, index_info, index_values, &used).map(Some)
+    }
+}
+
+fn point_get(
+    ctx: &ScanContext,
+    index_info: Arc<IndexInfo>,
+    index_values: Vec<EncodeValue>,
+    used: &[usize],
+) -> MySQLResult<ScanCandidate> {
+    let filters = ctx.bind_filters(used)?;
+    let rows = ctx.estimator.filtered_rows(1.0, &filters);
+    let mut cost = ctx.filter_cost(1.0, &filters) + GET_COST;
+    if !index_info.primary {
+        cost += GET_COST;
+    }
+    let plan = PointGetPlan {
+        table: ctx.scan.table.clone(),
+        index_info,
+        index_values,
+        select_columns: ctx.columns.clone(),
+        filters,
+        est_rows: rows,
+        session: ctx.session.clone(),
+    };
+    Ok(ScanCandidate {
+        plan: PlanNode::PointGet(plan),
+        rows,
+        cost,
+    })
+}
+
+/// Read a range of an index, the range is built from the conditions on the first column of
+/// the index. The cheapest index is chosen if many of them can be used.
+pub struct IndexScanPath {}
+
+impl AccessPath for IndexScanPath {
+    fn name(&self) -> &str {
+        "index_scan"
+    }
+
+    fn build(&self, ctx: &ScanContext) -> MySQLResult<Option<ScanCandidate>> {
+        let table = ctx.scan.table.as_ref();
+        let mut best: Option<ScanCandidate> = None;
+        for index_info in table.meta().indices.iter() {
+            if index_info.state != TableState::Public {
+                continue;
+            }
+            let offset = index_info.columns[0].1;
+            let col = table.meta().columns[offset].as_ref();
+            let col_id = ctx.scan.column_ids[offset];
+            let mut range = IndexRange::default();
+            let mut used = vec![];
+            for (i, f) in ctx.scan.filters.iter().enumerate() {
+                let (op, value) = match match_column_const(f) {
+                    Some((id, op, value)) if id == col_id => (op, value),
+                    _ => continue,
+                };
+                let value = match range_value(&col.data_type, value) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                match op {
+                    BinaryOperator::Eq => {
+                        range.restrict_low(value.clone(), true);
+                        range.restrict_high(value, true);
+                    }
+                    BinaryOperator::Lt => range.restrict_high(value, false),
+                    BinaryOperator::LtEq => range.restrict_high(value, true),
+                    BinaryOperator::Gt => range.restrict_low(value, false),
+                    BinaryOperator::GtEq => range.restrict_low(value, true),
+                    _ => continue,
+                }
+                used.push(i);
+            }
+            if used.is_empty() {
+                continue;
+            }
+            let filters = ctx.bind_filters(&used)?;
+            let scan_rows = ctx.estimator.range_rows(col, &range);
+            let row_cost = if index_info.primary {
+                SCAN_ROW_COST
+            } else {
+                SCAN_ROW_COST + LOOKUP_ROW_COST
+            };
+            let cost = SEEK_COST + scan_rows * row_cost + ctx.filter_cost(scan_rows, &filters);
+            if best.as_ref().map_or(false, |b| b.cost <= cost) {
+                continue;
+            }
+            let rows = ctx.estimator.filtered_rows(scan_rows, &filters);
+            let plan = IndexScanPlan {
+                table: ctx.scan.table.clone(),
+                index_info: index_info.clone(),
+                range,
+                columns: ctx.columns.clone(),
+                filters,
+                est_rows: rows,
+                session: ctx.session.clone(),
+            };
+            best = Some(ScanCandidate {
+                plan: PlanNode::IndexScan(plan),
+                rows,
+                cost,
+            });
+        }
+        Ok(best)
+    }
+}
+
+/// Read the whole table, it can be used by every table which is stored.
+pub struct TableScanPath {}
+
+impl AccessPath for TableScanPath {
+    fn name(&self) -> &str {
+        "table_scan"
+    }
+
+    fn build(&self, ctx: &ScanContext) -> MySQLResult<Option<ScanCandidate>> {
+        if ctx.scan.table.system_table().is_some() {
+            return Ok(None);
+        }
+        let filters = ctx.bind_filters(&[])?;
+        let scan_rows = ctx.estimator.row_count();
+        let cost = SEEK_COST + scan_rows * SCAN_ROW_COST + ctx.filter_cost(scan_rows, &filters);
+        let rows = ctx.estimator.filtered_rows(scan_rows, &filters);
+        let plan = TableScanPlan {
+            table: ctx.scan.table.clone(),
+            columns: ctx.columns.clone(),
+            filters,
+            est_rows: rows,
+            session: ctx.session.clone(),
+        };
+        Ok(Some(ScanCandidate {
+            plan: PlanNode::TableScan(plan),
+            rows,
+            cost,
+        }))
+    }
+}
+
+/// Build the rows of a table of `information_schema`, which is the only way to read them.
+pub struct SystemTablePath {}
+
+impl AccessPath for SystemTablePath {
+    fn name(&self) -> &str {
+        "system_table"
+    }
+
+    fn build(&self, ctx: &ScanContext) -> MySQLResult<Option<ScanCandidate>> {
+        let table = match ctx.scan.table.system_table() {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+        let filters = ctx.bind_filters(&[])?;
+        let scan_rows = ctx.estimator.row_count();
+        let rows = ctx.estimator.filtered_rows(scan_rows, &filters);
+        let plan = SystemTableScanPlan {
+            table,
+            columns: ctx.columns.clone(),
+            filters,
+            est_rows: rows,
+            session: ctx.session.clone(),
+        };
+        Ok(Some(ScanCandidate {
+            plan: PlanNode::SystemTableScan(plan),
+            rows,
+            cost: scan_rows * CPU_ROW_COST,
+        }))
+    }
+}
+
+/// A physical plan with the estimation used to compare it with other plans.
+struct PhysicalOutput {
+    plan: PlanNode,
+    /// The id of every output column, ordered as the output row.
+    ids: Vec<usize>,
+    columns: Vec<Arc<ColumnInfo>>,
+    rows: f64,
+    cost: f64,
+    /// The number of distinct values of output columns, only known for columns of analyzed
+    /// tables.
+    ndv: HashMap<usize, f64>,
+}
+
+impl PhysicalOutput {
+    fn id_set(&self) -> HashSet<usize> {
+        self.ids.iter().cloned().collect()
+    }
+
+    fn schema(&self) -> DataSchemaRef {
+        Arc::new(DataSchema {
+            columns: self.columns.clone(),
+        })
+    }
+
+    fn ndv_of(&self, expr: &Expression) -> f64 {
+        if let Expression::Column(c) = expr {
+            if let Some(ndv) = self.ndv.get(&c.id) {
+                return ndv.min(self.rows);
+            }
+        }
+        self.rows
+    }
+}
+
+/// Split join conditions to the pairs of keys compared by `=`, and other conditions.
+fn split_join_conditions(
+    left_ids: &HashSet<usize>,
+    right_ids: &HashSet<usize>,
+    conditions: Vec<Expression>,
+) -> (Vec<(Expression, Expression)>, Vec<Expression>) {
+    let mut keys = vec![];
+    let mut others = vec![];
+    for c in conditions {
+        if let Expression::BinaryOp {
+            op: BinaryOperator::Eq,
+            left,
+            right,
+        } = &c
+        {
+            let l = left.columns();
+            let r = right.columns();
+            if !l.is_empty() && !r.is_empty() {
+                if l.is_subset(left_ids) && r.is_subset(right_ids) {
+                    keys.push((left.as_ref().clone(), right.as_ref().clone()));
+                    continue;
+                }
+                if l.is_subset(right_ids) && r.is_subset(left_ids) {
+                    keys.push((right.as_ref().clone(), left.as_ref().clone()));
+                    continue;
+                }
+            }
+        }
+        others.push(c);
+    }
+    (keys, others)
+}
+
+/// The value of `LIMIT` or `OFFSET`, the parameters have been bound before.
+fn limit_value(expr: &Expression) -> MySQLResult<usize> {
+    match expr {
+        Expression::Value(EncodeValue::Int(v)) if *v >= 0 => Ok(*v as usize),
+        _ => Err(MySQLError::WrongArguments("LIMIT".to_string())),
+    }
+}
+
+fn bind_all(exprs: &mut [Expression], ids: &[usize]) -> MySQLResult<()> {
+    for e in exprs.iter_mut() {
+        e.bind(ids)?;
+    }
+    Ok(())
+}
+
+/// Build the physical plan of a logical plan. Access paths of tables are chosen by cost, and
+/// inner joins are reordered greedily so that the smallest intermediate result comes first.
+pub struct PhysicalPlanner {
+    session: SessionRef,
+    access_paths: Vec<Box<dyn AccessPath>>,
+}
+
+impl PhysicalPlanner {
+    pub fn new(session: SessionRef) -> PhysicalPlanner {
+        PhysicalPlanner {
+            session,
+            access_paths: vec![
+                Box::new(PointGetPath {}),
+                Box::new(IndexScanPath {}),
+                Box::new(TableScanPath {}),
+                Box::new(SystemTablePath {}),
+            ],
+        }
+    }
+
+    pub fn build(&self, plan: LogicalPlan) -> MySQLResult<PlanNode> {
+        Ok(self.build_plan(plan)?.plan)
+    }
+
+    fn build_plan(&self, plan: LogicalPlan) -> MySQLResult<PhysicalOutput> {
+        match plan {
+            LogicalPlan::Dual => Ok(PhysicalOutput {
+                plan: PlanNode::TableDual(TableDualPlan {
+                    schema: Arc::new(DataSchema { columns: vec![] }),
+                }),
+                ids: vec![],
+                columns: vec![],
+                rows: 1.0,
+                cost: 0.0,
+                ndv: HashMap::default(),
+            }),
+            LogicalPlan::Scan(scan) => {
+                let output = scan.columns.clone();
+                self.build_scan(&scan, output)
+            }
+            LogicalPlan::Filter { input, predicates } => {
+                let input = self.build_plan(*input)?;
+                self.build_filter(input, predicates)
+            }
+            LogicalPlan::Projection {
+                input,
+                exprs,
+                columns,
+            } => self.build_projection(*input, exprs, columns),
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type: JoinType::Inner,
+                conditions,
+            } => {
+                let mut leaves = vec![];
+                let mut all_conditions = conditions;
+                Self::flatten_inner_join(*left, &mut leaves, &mut all_conditions);
+                Self::flatten_inner_join(*right, &mut leaves, &mut all_conditions);
+                let mut inputs = Vec::with_capacity(leaves.len());
+                for leaf in leaves {
+                    inputs.push(self.build_plan(leaf)?);
+                }
+                self.reorder_join(inputs, all_conditions)
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                conditions,
+            } => {
+                let left = self.build_plan(*left)?;
+                let right = self.build_plan(*right)?;
+                // A right outer join is executed as a left outer join with swapped inputs.
+                if join_type == JoinType::Right {
+                    self.build_join(right, left, JoinType::Left, conditions)
+                } else {
+                    self.build_join(left, right, join_type, conditions)
+                }
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggs,
+                columns,
+            } => {
+                let input = self.build_plan(*input)?;
+                self.build_aggregation(input, group_by, aggs, columns)
+            }
+            LogicalPlan::Sort { input, items } => {
+                let input = self.build_plan(*input)?;
+                self.build_sort(input, items)
+            }
+            LogicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => {
+                let input = self.build_plan(*input)?;
+                let limit = match limit {
+                    Some(e) => Some(limit_value(&e)?),
+                    None => None,
+                };
+                let offset = match offset {
+                    Some(e) => limit_value(&e)?,
+                    None => 0,
+                };
+                self.build_limit(input, limit, offset)
+            }
+        }
+    }
+
+    fn build_scan(
+        &self,
+        scan: &LogicalScan,
+        output: Vec<LogicalColumn>,
+    ) -> MySQLResult<PhysicalOutput> {
+        let stats = self
+            .session
+            .lock()
+            .unwrap()
+            .get_table_stats(scan.table.id());
+        let ctx = ScanContext {
+            scan,
+            estimator: TableEstimator::new(scan.table.as_ref(), &scan.column_ids, stats),
+            columns: Arc::new(DataSchema {
+                columns: output.iter().map(|c| c.column.clone()).collect(),
+            }),
+            session: self.session.clone(),
+        };
+        let mut best: Option<ScanCandidate> = None;
+        for path in self.access_paths.iter() {
+            if let Some(candidate) = path.build(&ctx)? {
+                if best.as_ref().map_or(true, |b| candidate.cost < b.cost) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        let best = best.ok_or(MySQLError::UnsupportSQL)?;
+        let mut ndv = HashMap::default();
+        for col in output.iter() {
+            if let Some(v) = ctx.estimator.ndv(col.column.as_ref()) {
+                ndv.insert(col.id, v);
+            }
+        }
+        Ok(PhysicalOutput {
+            plan: best.plan,
+            ids: output.iter().map(|c| c.id).collect(),
+            columns: output.into_iter().map(|c| c.column).collect(),
+            rows: best.rows,
+            cost: best.cost,
+            ndv,
+        })
+    }
+
+    fn build_filter(
+        &self,
+        input: PhysicalOutput,
+        mut predicates: Vec<Expression>,
+    ) -> MySQLResult<PhysicalOutput> {
+        bind_all(&mut predicates, &input.ids)?;
+        let rows = input.rows * DEFAULT_SELECTIVITY.powi(predicates.len() as i32);
+        let cost = input.cost + input.rows * predicates.len() as f64 * CPU_ROW_COST;
+        let plan = FilterPlan {
+            schema: input.schema(),
+            input: Box::new(input.plan),
+            predicates,
+            est_rows: rows,
+        };
+        Ok(PhysicalOutput {
+            plan: PlanNode::Filter(plan),
+            ids: input.ids,
+            columns: input.columns,
+            rows,
+            cost,
+            ndv: input.ndv,
+        })
+    }
+
+    fn build_projection(
+        &self,
+        input: LogicalPlan,
+        mut exprs: Vec<Expression>,
+        columns: Vec<LogicalColumn>,
+    ) -> MySQLResult<PhysicalOutput> {
+        // A projection which only selects columns of a table is done by the scan.
+        if let LogicalPlan::Scan(scan) = &input {
+            let mut output = vec![];
+            for (e, c) in exprs.iter().zip(columns.iter()) {
+                if let Expression::Column(r) = e {
+                    if let Some(col) = scan.columns.iter().find(|col| col.id == r.id) {
+                        if col.name.eq_ignore_ascii_case(&c.name) {
+                            output.push(col.clone());
+                        }
+                    }
+                }
+            }
+            if output.len() == exprs.len() {
+                return self.build_scan(scan, output);
+            }
+        }
+        let input = self.build_plan(input)?;
+        bind_all(&mut exprs, &input.ids)?;
+        let cost = input.cost + input.rows * exprs.len() as f64 * CPU_ROW_COST;
+        let plan = ProjectionPlan {
+            input: Box::new(input.plan),
+            exprs,
+            schema: Arc::new(DataSchema {
+                columns: columns.iter().map(|c| c.column.clone()).collect(),
+            }),
+            est_rows: input.rows,
+        };
+        Ok(PhysicalOutput {
+            plan: PlanNode::Projection(plan),
+            ids: columns.iter().map(|c| c.id).collect(),
+            columns: columns.into_iter().map(|c| c.column).collect(),
+            rows: input.rows,
+            cost,
+            ndv: input.ndv,
+        })
+    }
+
+    fn build_aggregation(
+        &self,
+        input: PhysicalOutput,
+        mut group_by: Vec<Expression>,
+        mut aggs: Vec<AggregateExpr>,
+        columns: Vec<LogicalColumn>,
+    ) -> MySQLResult<PhysicalOutput> {
+        let mut ndv = HashMap::default();
+        let mut groups = 1.0;
+        for (e, c) in group_by.iter().zip(columns.iter()) {
+            let v = input.ndv_of(e);
+            groups *= v.max(1.0);
+            if let Some(v) = input.ndv.get(&c.id) {
+                ndv.insert(c.id, *v);
+            }
+        }
+        let rows = if group_by.is_empty() {
+            1.0
+        } else {
+            groups.min(input.rows)
+        };
+        bind_all(&mut group_by, &input.ids)?;
+        for a in aggs.iter_mut() {
+            a.bind(&input.ids)?;
+        }
+        let cost = input.cost
+            + input.rows * HASH_BUILD_ROW_COST
+            + input.rows * (group_by.len() + aggs.len()) as f64 * CPU_ROW_COST;
+        let plan = AggregationPlan {
+            input: Box::new(input.plan),
+            group_by,
+            aggs,
+            schema: Arc::new(DataSchema {
+                columns: columns.iter().map(|c| c.column.clone()).collect(),
+            }),
+            est_rows: rows,
+        };
+        Ok(PhysicalOutput {
+            plan: PlanNode::Aggregation(plan),
+            ids: columns.iter().map(|c| c.id).collect(),
+            columns: columns.into_iter().map(|c| c.column).collect(),
+            rows,
+            cost,
+            ndv,
+        })
+    }
+
+    fn build_sort(
+        &self,
+        input: PhysicalOutput,
+        mut items: Vec<SortItem>,
+    ) -> MySQLResult<PhysicalOutput> {
+        for i in items.iter_mut() {
+            i.expr.bind(&input.ids)?;
+        }
+        let cost = input.cost + input.rows * input.rows.max(2.0).log2() * CPU_ROW_COST;
+        let plan = SortPlan {
+            schema: input.schema(),
+            input: Box::new(input.plan),
+            items,
+            est_rows: input.rows,
+        };
+        Ok(PhysicalOutput {
+            plan: PlanNode::Sort(plan),
+            ids: input.ids,
+            columns: input.columns,
+            rows: input.rows,
+            cost,
+            ndv: input.ndv,
+        })
+    }
+
+    fn build_limit(
+        &self,
+        input: PhysicalOutput,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> MySQLResult<PhysicalOutput> {
+        let mut rows = (input.rows - offset as f64).max(0.0);
+        if let Some(limit) = limit {
+            rows = rows.min(limit as f64);
+        }
+        let plan = LimitPlan {
+            schema: input.schema(),
+            input: Box::new(input.plan),
+            limit,
+            offset,
+            est_rows: rows,
+        };
+        Ok(PhysicalOutput {
+            plan: PlanNode::Limit(plan),
+            ids: input.ids,
+            columns: input.columns,
+            rows,
+            cost: input.cost,
+            ndv: input.ndv,
+        })
+    }
+
+    fn flatten_inner_join(
+        plan: LogicalPlan,
+        leaves: &mut Vec<LogicalPlan>,
+        conditions: &mut Vec<Expression>,
+    ) {
+        match plan {
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type: JoinType::Inner,
+                conditions: c,
+            } => {
+                conditions.extend(c);
+                Self::flatten_inner_join(*left, leaves, conditions);
+                Self::flatten_inner_join(*right, leaves, conditions);
+            }
+            plan => leaves.push(plan),
+        }
+    }
+
+    /// Join the inputs greedily: start from the smallest input, and then join the input which
+    /// is connected by an equal condition and produces the fewest rows.
+    fn reorder_join(
+        &self,
+        mut inputs: Vec<PhysicalOutput>,
+        mut conditions: Vec<Expression>,
+    ) -> MySQLResult<PhysicalOutput> {
+        let first = inputs
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.rows.partial_cmp(&b.1.rows).unwrap())
+            .map(|(i, _)| i)
+            .unwrap();
+        let mut current = inputs.remove(first);
+        while !inputs.is_empty() {
+            let current_ids = current.id_set();
+            let mut best: Option<(usize, bool, f64)> = None;
+            for (i, input) in inputs.iter().enumerate() {
+                let ids = input.id_set();
+                let applicable: Vec<Expression> = conditions
+                    .iter()
+                    .filter(|c| {
+                        c.columns()
+                            .iter()
+                            .all(|id| current_ids.contains(id) || ids.contains(id))
+                    })
+                    .cloned()
+                    .collect();
+                let (keys, others) = split_join_conditions(&current_ids, &ids, applicable);
+                let connected = !keys.is_empty();
+                let rows =
+                    self.estimate_join_rows(&current, input, &keys, &others, JoinType::Inner);
+                let better = match best {
+                    None => true,
+                    Some((_, c, r)) => (connected && !c) || (connected == c && rows < r),
+                };
+                if better {
+                    best = Some((i, connected, rows));
+                }
+            }
+            let input = inputs.remove(best.unwrap().0);
+            let ids = input.id_set();
+            let (applicable, rest): (Vec<_>, Vec<_>) = conditions.into_iter().partition(|c| {
+                c.columns()
+                    .iter()
+                    .all(|id| current_ids.contains(id) || ids.contains(id))
+            });
+            conditions = rest;
+            // The right input of a hash join is used to build the hash table, so put the smaller
+            // one on the right.
+            current = if input.rows > current.rows {
+                self.build_join(input, current, JoinType::Inner, applicable)?
+            } else {
+                self.build_join(current, input, JoinType::Inner, applicable)?
+            };
+        }
+        if conditions.is_empty() {
+            Ok(current)
+        } else {
+            self.build_filter(current, conditions)
+        }
+    }
+
+    fn estimate_join_rows(
+        &self,
+        left: &PhysicalOutput,
+        right: &PhysicalOutput,
+        keys: &[(Expression, Expression)],
+        others: &[Expression],
+        join_type: JoinType,
+    ) -> f64 {
+        let mut rows = left.rows * right.rows;
+        if let Some((l, r)) = keys.first() {
+            rows /= left.ndv_of(l).max(right.ndv_of(r)).max(1.0);
+        }
+        rows *= DEFAULT_SELECTIVITY.powi(others.len() as i32);
+        if join_type == JoinType::Left {
+            rows = rows.max(left.rows);
+        }
+        rows
+    }
+
+    fn build_join(
+        &self,
+        left: PhysicalOutput,
+        right: PhysicalOutput,
+        join_type: JoinType,
+        conditions: Vec<Expression>,
+    ) -> MySQLResult<PhysicalOutput> {
+        let (keys, mut others) = split_join_conditions(&left.id_set(), &right.id_set(), conditions);
+        let rows = self.estimate_join_rows(&left, &right, &keys, &others, join_type);
+        let mut ids = left.ids.clone();
+        ids.extend(right.ids.iter().cloned());
+        let mut columns = left.columns.clone();
+        columns.extend(right.columns.iter().cloned());
+        let schema = Arc::new(DataSchema {
+            columns: columns.clone(),
+        });
+        let mut ndv = left.ndv;
+        ndv.extend(right.ndv);
+        bind_all(&mut others, &ids)?;
+        let (plan, cost) = if keys.is_empty() {
+            let cost = left.cost + right.cost + left.rows * right.rows * CPU_ROW_COST;
+            let plan = PlanNode::NestedLoopJoin(NestedLoopJoinPlan {
+                left: Box::new(left.plan),
+                right: Box::new(right.plan),
+                join_type,
+                conditions: others,
+                schema,
+                est_rows: rows,
+            });
+            (plan, cost)
+        } else {
+            let cost = left.cost
+                + right.cost
+                + right.rows * HASH_BUILD_ROW_COST
+                + left.rows * HASH_PROBE_ROW_COST
+                + rows * others.len() as f64 * CPU_ROW_COST;
+            let mut left_keys = vec![];
+            let mut right_keys = vec![];
+            for (mut l, mut r) in keys {
+                l.bind(&left.ids)?;
+                r.bind(&right.ids)?;
+                left_keys.push(l);
+                right_keys.push(r);
+            }
+            let plan = PlanNode::HashJoin(HashJoinPlan {
+                left: Box::new(left.plan),
+                right: Box::new(right.plan),
+                join_type,
+                left_keys,
+                right_keys,
+                other_conditions: others,
+                schema,
+                est_rows: rows,
+            });
+            (plan, cost)
+        };
+        Ok(PhysicalOutput {
+            plan,
+            ids,
+            columns,
+            rows,
+            cost,
+            ndv,
+        })
+    }
+}
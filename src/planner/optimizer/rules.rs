@@ -0,0 +1,402 @@
+use crate::errors::MySQLResult;
+use crate::planner::logical_plan::{JoinType, LogicalPlan};
+use crate::planner::plan_expression::Expression;
+use std::collections::HashSet;
+
+/// A rewrite of the logical plan which never changes the result of the query.
+pub trait OptimizerRule {
+    fn name(&self) -> &str;
+    fn optimize(&self, plan: LogicalPlan) -> MySQLResult<LogicalPlan>;
+}
+
+fn predicates_mut(plan: &mut LogicalPlan) -> Option<&mut Vec<Expression>> {
+    match plan {
+        LogicalPlan::Scan(scan) => Some(&mut scan.filters),
+        LogicalPlan::Filter { predicates, .. } => Some(predicates),
+        LogicalPlan::Join { conditions, .. } => Some(conditions),
+        LogicalPlan::Dual
+        | LogicalPlan::Projection { .. }
+        | LogicalPlan::Aggregate { .. }
+        | LogicalPlan::Sort { .. }
+        | LogicalPlan::Limit { .. } => None,
+    }
+}
+
+fn with_filter(plan: LogicalPlan, predicates: Vec<Expression>) -> LogicalPlan {
+    if predicates.is_empty() {
+        plan
+    } else {
+        LogicalPlan::Filter {
+            input: Box::new(plan),
+            predicates,
+        }
+    }
+}
+
+/// Evaluate constant expressions once while planning, and remove the predicates which are
+/// always true.
+pub struct ConstantFolding {}
+
+impl ConstantFolding {
+    fn fold(plan: &mut LogicalPlan) -> MySQLResult<()> {
+        plan.map_expressions(&mut |e| e.fold_constant())?;
+        if let Some(predicates) = predicates_mut(plan) {
+            predicates.retain(|e| match e {
+                Expression::Value(v) => !v.is_true(),
+                _ => true,
+            });
+        }
+        for child in plan.children_mut() {
+            Self::fold(child)?;
+        }
+        Ok(())
+    }
+}
+
+impl OptimizerRule for ConstantFolding {
+    fn name(&self) -> &str {
+        "constant_folding"
+    }
+
+    fn optimize(&self, mut plan: LogicalPlan) -> MySQLResult<LogicalPlan> {
+        Self::fold(&mut plan)?;
+        Ok(plan)
+    }
+}
+
+/// Convert an outer join to an inner join if a predicate above it rejects the rows padded with
+/// NULL, such as `t1 LEFT JOIN t2 ON ... WHERE t2.a > 1`.
+pub struct OuterJoinToInner {}
+
+impl OuterJoinToInner {
+    /// Whether any of `predicates` is false or NULL when all the columns in `ids` are NULL.
+    fn reject_null(predicates: &[Expression], ids: &HashSet<usize>) -> MySQLResult<bool> {
+        for p in predicates {
+            if p.columns().is_disjoint(ids) {
+                continue;
+            }
+            if let Expression::Value(v) = p.replace_with_null(ids).fold_constant()? {
+                if !v.is_true() {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn convert(plan: &mut LogicalPlan, predicates: &[Expression]) -> MySQLResult<()> {
+        match plan {
+            LogicalPlan::Dual | LogicalPlan::Scan(_) => Ok(()),
+            LogicalPlan::Filter {
+                input,
+                predicates: own,
+            } => {
+                let mut all = predicates.to_vec();
+                all.extend(own.iter().cloned());
+                Self::convert(input, &all)
+            }
+            LogicalPlan::Projection { input, .. } | LogicalPlan::Sort { input, .. } => {
+                Self::convert(input, predicates)
+            }
+            // The rows rejected by a predicate above may still change the result of these
+            // operators.
+            LogicalPlan::Aggregate { input, .. } | LogicalPlan::Limit { input, .. } => {
+                Self::convert(input, &[])
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                conditions,
+            } => {
+                if *join_type == JoinType::Left
+                    && Self::reject_null(predicates, &right.output_ids())?
+                {
+                    *join_type = JoinType::Inner;
+                } else if *join_type == JoinType::Right
+                    && Self::reject_null(predicates, &left.output_ids())?
+                {
+                    *join_type = JoinType::Inner;
+                }
+                // The join conditions of an outer join only filter the rows of its inner side.
+                match join_type {
+                    JoinType::Inner => {
+                        let mut all = predicates.to_vec();
+                        all.extend(conditions.iter().cloned());
+                        Self::convert(left, &all)?;
+                        Self::convert(right, &all)
+                    }
+                    JoinType::Left => {
+                        Self::convert(left, predicates)?;
+                        Self::convert(right, conditions)
+                    }
+                    JoinType::Right => {
+                        Self::convert(right, predicates)?;
+                        Self::convert(left, conditions)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl OptimizerRule for OuterJoinToInner {
+    fn name(&self) -> &str {
+        "outer_join_to_inner"
+    }
+
+    fn optimize(&self, mut plan: LogicalPlan) -> MySQLResult<LogicalPlan> {
+        Self::convert(&mut plan, &[])?;
+        Ok(plan)
+    }
+}
+
+/// Move every predicate as close to the table as possible, predicates reaching a scan become
+/// the filters of the scan so that the physical planner can match them with indices.
+pub struct PredicatePushDown {}
+
+impl PredicatePushDown {
+    fn push_down(plan: LogicalPlan, predicates: Vec<Expression>) -> LogicalPlan {
+        match plan {
+            LogicalPlan::Dual => with_filter(LogicalPlan::Dual, predicates),
+            LogicalPlan::Scan(mut scan) => {
+                scan.filters.extend(predicates);
+                LogicalPlan::Scan(scan)
+            }
+            LogicalPlan::Filter {
+                input,
+                predicates: own,
+            } => {
+                let mut all = predicates;
+                all.extend(own);
+                Self::push_down(*input, all)
+            }
+            LogicalPlan::Projection {
+                input,
+                exprs,
+                columns,
+            } => {
+                let ids = input.output_ids();
+                let (pushed, kept): (Vec<_>, Vec<_>) = predicates
+                    .into_iter()
+                    .partition(|p| p.columns().is_subset(&ids));
+                let plan = LogicalPlan::Projection {
+                    input: Box::new(Self::push_down(*input, pushed)),
+                    exprs,
+                    columns,
+                };
+                with_filter(plan, kept)
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                conditions,
+            } => {
+                let left_ids = left.output_ids();
+                let right_ids = right.output_ids();
+                let mut left_predicates = vec![];
+                let mut right_predicates = vec![];
+                let mut join_conditions = vec![];
+                let mut above = vec![];
+                match join_type {
+                    JoinType::Inner => {
+                        for p in predicates.into_iter().chain(conditions) {
+                            let cols = p.columns();
+                            if cols.is_subset(&left_ids) {
+                                left_predicates.push(p);
+                            } else if cols.is_subset(&right_ids) {
+                                right_predicates.push(p);
+                            } else {
+                                join_conditions.push(p);
+                            }
+                        }
+                    }
+                    JoinType::Left | JoinType::Right => {
+                        let (outer_ids, outer, inner) = if join_type == JoinType::Left {
+                            (&left_ids, &mut left_predicates, &mut right_predicates)
+                        } else {
+                            (&right_ids, &mut right_predicates, &mut left_predicates)
+                        };
+                        for p in predicates {
+                            if p.columns().is_subset(outer_ids) {
+                                outer.push(p);
+                            } else {
+                                above.push(p);
+                            }
+                        }
+                        for c in conditions {
+                            let cols = c.columns();
+                            if !cols.is_empty() && cols.is_disjoint(outer_ids) {
+                                inner.push(c);
+                            } else {
+                                join_conditions.push(c);
+                            }
+                        }
+                    }
+                }
+                let plan = LogicalPlan::Join {
+                    left: Box::new(Self::push_down(*left, left_predicates)),
+                    right: Box::new(Self::push_down(*right, right_predicates)),
+                    join_type,
+                    conditions: join_conditions,
+                };
+                with_filter(plan, above)
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggs,
+                columns,
+            } => {
+                // Only the predicates on the grouping columns can be evaluated before grouping,
+                // a grouping column has the same id as the input column. A predicate without
+                // columns stays above, an aggregate without groups returns a row for no input.
+                let ids: HashSet<usize> = group_by
+                    .iter()
+                    .filter_map(|e| match e {
+                        Expression::Column(c) => Some(c.id),
+                        _ => None,
+                    })
+                    .collect();
+                let (pushed, kept): (Vec<_>, Vec<_>) = predicates.into_iter().partition(|p| {
+                    let columns = p.columns();
+                    !columns.is_empty() && columns.is_subset(&ids)
+                });
+                let plan = LogicalPlan::Aggregate {
+                    input: Box::new(Self::push_down(*input, pushed)),
+                    group_by,
+                    aggs,
+                    columns,
+                };
+                with_filter(plan, kept)
+            }
+            LogicalPlan::Sort { input, items } => LogicalPlan::Sort {
+                input: Box::new(Self::push_down(*input, predicates)),
+                items,
+            },
+            LogicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => {
+                let plan = LogicalPlan::Limit {
+                    input: Box::new(Self::push_down(*input, vec![])),
+                    limit,
+                    offset,
+                };
+                with_filter(plan, predicates)
+            }
+        }
+    }
+}
+
+impl OptimizerRule for PredicatePushDown {
+    fn name(&self) -> &str {
+        "predicate_push_down"
+    }
+
+    fn optimize(&self, plan: LogicalPlan) -> MySQLResult<LogicalPlan> {
+        Ok(Self::push_down(plan, vec![]))
+    }
+}
+
+/// Remove the columns which are never used by the operators above.
+pub struct ColumnPruning {}
+
+impl ColumnPruning {
+    fn prune(plan: &mut LogicalPlan, required: &HashSet<usize>) {
+        match plan {
+            LogicalPlan::Dual => (),
+            LogicalPlan::Scan(scan) => {
+                let mut used = required.clone();
+                for f in scan.filters.iter() {
+                    f.collect_columns(&mut used);
+                }
+                scan.columns.retain(|c| used.contains(&c.id));
+            }
+            LogicalPlan::Filter { input, predicates } => {
+                let mut used = required.clone();
+                for p in predicates.iter() {
+                    p.collect_columns(&mut used);
+                }
+                Self::prune(input, &used);
+            }
+            LogicalPlan::Projection {
+                input,
+                exprs,
+                columns,
+            } => {
+                let old_exprs = std::mem::take(exprs);
+                let old_columns = std::mem::take(columns);
+                for (e, c) in old_exprs.into_iter().zip(old_columns) {
+                    if required.contains(&c.id) {
+                        exprs.push(e);
+                        columns.push(c);
+                    }
+                }
+                let mut used = HashSet::default();
+                for e in exprs.iter() {
+                    e.collect_columns(&mut used);
+                }
+                Self::prune(input, &used);
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                conditions,
+                ..
+            } => {
+                let mut used = required.clone();
+                for c in conditions.iter() {
+                    c.collect_columns(&mut used);
+                }
+                Self::prune(left, &used);
+                Self::prune(right, &used);
+            }
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggs,
+                columns,
+            } => {
+                let agg_columns = columns.split_off(group_by.len());
+                let old_aggs = std::mem::take(aggs);
+                for (a, c) in old_aggs.into_iter().zip(agg_columns) {
+                    if required.contains(&c.id) {
+                        aggs.push(a);
+                        columns.push(c);
+                    }
+                }
+                let mut used = HashSet::default();
+                for e in group_by.iter() {
+                    e.collect_columns(&mut used);
+                }
+                for a in aggs.iter() {
+                    a.collect_columns(&mut used);
+                }
+                Self::prune(input, &used);
+            }
+            LogicalPlan::Sort { input, items } => {
+                let mut used = required.clone();
+                for i in items.iter() {
+                    i.expr.collect_columns(&mut used);
+                }
+                Self::prune(input, &used);
+            }
+            LogicalPlan::Limit { input, .. } => Self::prune(input, required),
+        }
+    }
+}
+
+impl OptimizerRule for ColumnPruning {
+    fn name(&self) -> &str {
+        "column_pruning"
+    }
+
+    fn optimize(&self, mut plan: LogicalPlan) -> MySQLResult<LogicalPlan> {
+        let required = plan.output_ids();
+        Self::prune(&mut plan, &required);
+        Ok(plan)
+    }
+}
@@ -1,25 +1,80 @@
 use crate::common::EncodeValue;
 use crate::errors::{MySQLError, MySQLResult};
-use crate::planner::point_get_plan::QueryPlanBuilder;
-use crate::planner::{CreateIndexPlan, CreateTablePlan, InsertPlan, PlanNode};
+use crate::mysql_driver::error_kind;
+use crate::planner::logical_plan::{LogicalColumn, LogicalPlan};
+use crate::planner::logical_plan_builder::{resolve_table_name, LogicalPlanBuilder};
+use crate::planner::optimizer::{Optimizer, PhysicalPlanner};
+use crate::planner::prepared_statement::{parameter_index, parameterize, PreparedStatement};
+use crate::planner::show_plan::like_match;
+use crate::planner::{
+    AlterTableOperation, AlterTablePlan, AnalyzeTablePlan, ColumnEvaluator, CreateDatabasePlan,
+    CreateTablePlan, CreateViewPlan, DropDatabasePlan, DropTablePlan, DropViewPlan, ExplainPlan,
+    ForeignKeyCheck, InsertPlan, KillPlan, PlanCache, PlanCacheKey, PlanNode, RecordCheck,
+    SetVariablePlan, ShowPlan, TruncateTablePlan, UseDatabasePlan,
+};
 use crate::session::SessionRef;
-use crate::table::schema::{DataSchema, IndexInfo, TableInfo, TableState};
+use crate::table::auto_id::AutoIdAllocator;
+use crate::table::schema::{
+    ColumnInfo, DataSchema, GeneratedColumn, IndexInfo, ReferenceAction, TableInfo, TableState,
+    ViewInfo, DEFAULT_CHARSET, DEFAULT_COLLATION, PRIMARY_INDEX_NAME,
+};
+use crate::table::{is_system_database, SystemTable, TableSource};
 use sqlparser::ast::{
-    ColumnDef, Expr, Ident, ObjectName, OrderByExpr, Query, SqlOption, Statement, TableConstraint,
+    ColumnDef, DataType, Expr, Ident, ObjectName, ObjectType, OrderByExpr, Query, SelectItem,
+    SetExpr, SqlOption, Statement, TableConstraint, TableFactor, TableWithJoins,
 };
 use sqlparser::dialect::MySqlDialect;
 use sqlparser::parser::Parser;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 pub struct PlanBuilder {
     session: SessionRef,
+    /// The values of the placeholders when building the plan of a prepared statement.
+    params: Vec<EncodeValue>,
 }
 
 impl PlanBuilder {
     pub fn create(session: SessionRef) -> Self {
-        Self { session }
+        Self {
+            session,
+            params: vec![],
+        }
+    }
+
+    pub fn with_params(mut self, params: Vec<EncodeValue>) -> Self {
+        self.params = params;
+        self
     }
 
     pub fn build_from_sql(&self, query: &str) -> MySQLResult<PlanNode> {
+        if let Some(plan) = self.sql_show_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_describe_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_set_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_kill_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_database_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_truncate_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_alter_table_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.sql_create_table_options_to_plan(query)? {
+            return Ok(plan);
+        }
+        if let Some(plan) = self.build_cached_query(query)? {
+            return Ok(plan);
+        }
         let dialect = MySqlDialect {};
         let mut statement = Parser::parse_sql(&dialect, query)?;
         if statement.len() != 1 {
@@ -28,6 +83,644 @@ impl PlanBuilder {
         statement.pop().map(|s| self.statement_to_plan(s)).unwrap()
     }
 
+    /// The parser does not know `SHOW [GLOBAL | SESSION] {STATUS | VARIABLES} [LIKE 'pattern']`,
+    /// `SHOW [FULL] PROCESSLIST`, `SHOW WARNINGS`, `SHOW [FULL] TABLES`, `SHOW [FULL] COLUMNS`
+    /// and `SHOW CREATE TABLE`, so they are recognized by words. Return `None` for other
+    /// statements.
+    fn sql_show_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let words: Vec<&str> = sql
+            .trim()
+            .trim_end_matches(';')
+            .split_whitespace()
+            .collect();
+        let mut words = words.as_slice();
+        match words.first() {
+            Some(w) if w.eq_ignore_ascii_case("show") => words = &words[1..],
+            _ => return Ok(None),
+        }
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        let is_from = |w: &str| is(w, "from") || is(w, "in");
+        match words {
+            [w] if is(w, "processlist") => {
+                return Ok(Some(self.show_processlist(false)));
+            }
+            [full, w] if is(full, "full") && is(w, "processlist") => {
+                return Ok(Some(self.show_processlist(true)));
+            }
+            [w] if is(w, "warnings") => return Ok(Some(self.show_warnings())),
+            [c, t, name] if is(c, "create") && is(t, "table") => {
+                return self.show_create_table(name).map(Some);
+            }
+            [c, v, name] if is(c, "create") && is(v, "view") => {
+                let name = object_name(name);
+                return match self.get_view(&name)? {
+                    Some(view) => Ok(Some(Self::show_create_view(&view))),
+                    None => {
+                        let (db, table) = self.get_table(&name)?;
+                        Err(MySQLError::NotView(format!("{}.{}", db, table.name())))
+                    }
+                };
+            }
+            _ => (),
+        }
+        let (full, rest) = match words {
+            [full, rest @ ..] if is(full, "full") => (true, rest),
+            _ => (false, words),
+        };
+        match rest {
+            [t, rest @ ..] if is(t, "tables") => {
+                let (db, rest) = match rest {
+                    [from, db, rest @ ..] if is_from(from) => {
+                        (db.trim_matches('`').to_lowercase(), rest)
+                    }
+                    _ => (self.session.lock().unwrap().get_db().clone(), rest),
+                };
+                return self.show_tables(&db, full, like_pattern(rest)?).map(Some);
+            }
+            [c, from, table, rest @ ..]
+                if (is(c, "columns") || is(c, "fields")) && is_from(from) =>
+            {
+                let (name, rest) = match rest {
+                    [from, db, rest @ ..] if is_from(from) => (format!("{}.{}", db, table), rest),
+                    _ => (table.to_string(), rest),
+                };
+                return self
+                    .show_columns(&name, full, like_pattern(rest)?)
+                    .map(Some);
+            }
+            _ => (),
+        }
+        if let Some(w) = words.first() {
+            if w.eq_ignore_ascii_case("global") || w.eq_ignore_ascii_case("session") {
+                words = &words[1..];
+            }
+        }
+        let variables = match words.first() {
+            Some(w) if w.eq_ignore_ascii_case("status") => false,
+            Some(w) if w.eq_ignore_ascii_case("variables") => true,
+            _ => return Ok(None),
+        };
+        let pattern = like_pattern(&words[1..])?;
+        let status: Vec<(&str, String)> = if variables {
+            self.session.lock().unwrap().variables().values()
+        } else {
+            let cache = self.session.lock().unwrap().get_plan_cache();
+            vec![
+                ("plan_cache_hits", cache.hits().to_string()),
+                ("plan_cache_misses", cache.misses().to_string()),
+                ("plan_cache_size", cache.len().to_string()),
+            ]
+        };
+        let rows = status
+            .into_iter()
+            .filter(|(name, _)| pattern.as_ref().map_or(true, |p| like_match(p, name)))
+            .map(|(name, value)| {
+                vec![
+                    EncodeValue::Bytes(name.as_bytes().to_vec()),
+                    EncodeValue::Bytes(value.into_bytes()),
+                ]
+            })
+            .collect();
+        let schema = DataSchema::result_schema(&[
+            ("Variable_name", DataType::String),
+            ("Value", DataType::String),
+        ]);
+        Ok(Some(PlanNode::Show(ShowPlan {
+            schema: Arc::new(schema),
+            rows,
+        })))
+    }
+
+    /// The statements are truncated unless `full`.
+    fn show_processlist(&self, full: bool) -> PlanNode {
+        let processes = self.session.lock().unwrap().processes();
+        let rows = processes
+            .list()
+            .into_iter()
+            .map(|p| p.info().to_row(full))
+            .collect();
+        let schema = DataSchema::result_schema(&[
+            ("Id", DataType::BigInt),
+            ("User", DataType::String),
+            ("Host", DataType::String),
+            ("db", DataType::String),
+            ("Command", DataType::String),
+            ("Time", DataType::BigInt),
+            ("State", DataType::String),
+            ("Info", DataType::String),
+        ]);
+        PlanNode::Show(ShowPlan {
+            schema: Arc::new(schema),
+            rows,
+        })
+    }
+
+    fn show_warnings(&self) -> PlanNode {
+        let session = self.session.lock().unwrap();
+        let rows = session
+            .warnings()
+            .iter()
+            .map(|w| {
+                vec![
+                    EncodeValue::Bytes(b"Warning".to_vec()),
+                    EncodeValue::Int(error_kind(w) as i64),
+                    EncodeValue::Bytes(w.to_string().into_bytes()),
+                ]
+            })
+            .collect();
+        let schema = DataSchema::result_schema(&[
+            ("Level", DataType::String),
+            ("Code", DataType::BigInt),
+            ("Message", DataType::String),
+        ]);
+        PlanNode::Show(ShowPlan {
+            schema: Arc::new(schema),
+            rows,
+        })
+    }
+
+    /// The tables of `db` whose names match `pattern`, with their types if `full`.
+    fn show_tables(&self, db: &str, full: bool, pattern: Option<String>) -> MySQLResult<PlanNode> {
+        if db.is_empty() {
+            return Err(MySQLError::NoDBSelected);
+        }
+        let tables: Vec<(String, &str)> = if is_system_database(db) {
+            let tables = SystemTable::tables_of(db).into_iter();
+            tables
+                .map(|t| (t.name().to_string(), "SYSTEM VIEW"))
+                .collect()
+        } else {
+            let table_mgr = self.session.lock().unwrap().get_table_manager();
+            let table_mgr = table_mgr.read().unwrap();
+            let tables = table_mgr.table_names(db)?.into_iter();
+            let views = table_mgr.view_names(db)?.into_iter();
+            let mut tables: Vec<_> = tables
+                .map(|name| (name, "BASE TABLE"))
+                .chain(views.map(|name| (name, "VIEW")))
+                .collect();
+            tables.sort();
+            tables
+        };
+        let rows = tables
+            .into_iter()
+            .filter(|(name, _)| pattern.as_ref().map_or(true, |p| like_match(p, name)))
+            .map(|(name, table_type)| {
+                let mut row = vec![EncodeValue::Bytes(name.into_bytes())];
+                if full {
+                    row.push(EncodeValue::Bytes(table_type.as_bytes().to_vec()));
+                }
+                row
+            })
+            .collect();
+        let column = format!("Tables_in_{}", db);
+        let mut fields = vec![(column.as_str(), DataType::String)];
+        if full {
+            fields.push(("Table_type", DataType::String));
+        }
+        Ok(PlanNode::Show(ShowPlan {
+            schema: Arc::new(DataSchema::result_schema(&fields)),
+            rows,
+        }))
+    }
+
+    /// The columns of the table `name` whose names match `pattern`, as `DESCRIBE` shows them.
+    /// `full` adds their collations, privileges and comments.
+    fn show_columns(
+        &self,
+        name: &str,
+        full: bool,
+        pattern: Option<String>,
+    ) -> MySQLResult<PlanNode> {
+        let table = self.get_readable_table(&object_name(name))?;
+        let text = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+        let rows = table
+            .meta()
+            .columns
+            .iter()
+            .filter(|c| c.state == TableState::Public)
+            .filter(|c| pattern.as_ref().map_or(true, |p| like_match(p, &c.name)))
+            .map(|c| {
+                let mut row = vec![text(&c.name), text(&c.type_name())];
+                if full {
+                    row.push(c.collation().map_or(EncodeValue::NULL, text));
+                }
+                row.push(text(if c.not_null { "NO" } else { "YES" }));
+                row.push(text(c.key.name()));
+                row.push(c.default_text().map_or(EncodeValue::NULL, |v| text(&v)));
+                row.push(text(c.extra()));
+                if full {
+                    row.push(text("select,insert,update,references"));
+                    row.push(text(&c.comment));
+                }
+                row
+            })
+            .collect();
+        let mut fields = vec![("Field", DataType::String), ("Type", DataType::String)];
+        if full {
+            fields.push(("Collation", DataType::String));
+        }
+        fields.extend(vec![
+            ("Null", DataType::String),
+            ("Key", DataType::String),
+            ("Default", DataType::String),
+            ("Extra", DataType::String),
+        ]);
+        if full {
+            fields.push(("Privileges", DataType::String));
+            fields.push(("Comment", DataType::String));
+        }
+        Ok(PlanNode::Show(ShowPlan {
+            schema: Arc::new(DataSchema::result_schema(&fields)),
+            rows,
+        }))
+    }
+
+    /// `SHOW CREATE TABLE` of a view is `SHOW CREATE VIEW`, as MySQL.
+    fn show_create_table(&self, name: &str) -> MySQLResult<PlanNode> {
+        let name = object_name(name);
+        if let Some(view) = self.get_view(&name)? {
+            return Ok(Self::show_create_view(&view));
+        }
+        let (_, table) = self.get_table(&name)?;
+        let rows = vec![vec![
+            EncodeValue::Bytes(table.name().as_bytes().to_vec()),
+            EncodeValue::Bytes(table.meta().create_statement().into_bytes()),
+        ]];
+        let schema = DataSchema::result_schema(&[
+            ("Table", DataType::String),
+            ("Create Table", DataType::String),
+        ]);
+        Ok(PlanNode::Show(ShowPlan {
+            schema: Arc::new(schema),
+            rows,
+        }))
+    }
+
+    fn show_create_view(view: &ViewInfo) -> PlanNode {
+        let text = |s: &str| EncodeValue::Bytes(s.as_bytes().to_vec());
+        let rows = vec![vec![
+            text(&view.name),
+            text(&view.create_statement()),
+            text(DEFAULT_CHARSET),
+            text(DEFAULT_COLLATION),
+        ]];
+        let schema = DataSchema::result_schema(&[
+            ("View", DataType::String),
+            ("Create View", DataType::String),
+            ("character_set_client", DataType::String),
+            ("collation_connection", DataType::String),
+        ]);
+        PlanNode::Show(ShowPlan {
+            schema: Arc::new(schema),
+            rows,
+        })
+    }
+
+    /// `{DESCRIBE | DESC} name [column]` is recognized by words as well, it is `SHOW COLUMNS`
+    /// whose pattern is the column. Return `None` for other statements, including `DESCRIBE`
+    /// of a query which is `EXPLAIN`.
+    fn sql_describe_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let words: Vec<&str> = sql
+            .trim()
+            .trim_end_matches(';')
+            .split_whitespace()
+            .collect();
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        match words.as_slice() {
+            [d, table, rest @ ..] if (is(d, "describe") || is(d, "desc")) && rest.len() <= 1 => {
+                let statements = ["select", "insert", "with", "analyze", "table"];
+                if statements.iter().any(|s| is(table, s)) {
+                    return Ok(None);
+                }
+                let pattern = rest.first().map(|c| c.trim_matches('`').to_string());
+                self.show_columns(table, false, pattern).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `KILL [CONNECTION | QUERY] id` is recognized by words as well. Return `None` for other
+    /// statements.
+    fn sql_kill_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let words: Vec<&str> = sql
+            .trim()
+            .trim_end_matches(';')
+            .split_whitespace()
+            .collect();
+        let (query_only, id) = match words.as_slice() {
+            [kill, id] if kill.eq_ignore_ascii_case("kill") => (false, id),
+            [kill, w, id] if kill.eq_ignore_ascii_case("kill") => {
+                if w.eq_ignore_ascii_case("query") {
+                    (true, id)
+                } else if w.eq_ignore_ascii_case("connection") {
+                    (false, id)
+                } else {
+                    return Err(MySQLError::UnsupportSQL);
+                }
+            }
+            _ => return Ok(None),
+        };
+        let connection_id = id.parse::<u64>()?;
+        Ok(Some(PlanNode::Kill(KillPlan {
+            connection_id,
+            query_only,
+        })))
+    }
+
+    /// `CREATE {DATABASE | SCHEMA} [IF NOT EXISTS] name`, `DROP {DATABASE | SCHEMA} [IF EXISTS]
+    /// name` and `USE name` are not known by the parser either, the options after the name of
+    /// `CREATE DATABASE` are ignored. Return `None` for other statements.
+    fn sql_database_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let words: Vec<&str> = sql
+            .trim()
+            .trim_end_matches(';')
+            .split_whitespace()
+            .collect();
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        let is_database = |w: &str| is(w, "database") || is(w, "schema");
+        let name = |w: &str| w.trim_matches('`').to_lowercase();
+        let plan = match words.as_slice() {
+            [u, db] if is(u, "use") => PlanNode::UseDatabase(UseDatabasePlan { name: name(db) }),
+            [c, d, rest @ ..] if is(c, "create") && is_database(d) => {
+                let (if_not_exists, rest) = match rest {
+                    [i, n, e, rest @ ..] if is(i, "if") && is(n, "not") && is(e, "exists") => {
+                        (true, rest)
+                    }
+                    rest => (false, rest),
+                };
+                let db = rest.first().ok_or(MySQLError::UnsupportSQL)?;
+                PlanNode::CreateDatabase(CreateDatabasePlan {
+                    name: name(db),
+                    if_not_exists,
+                })
+            }
+            [d, s, rest @ ..] if is(d, "drop") && is_database(s) => {
+                let (if_exists, db) = match rest {
+                    [db] => (false, db),
+                    [i, e, db] if is(i, "if") && is(e, "exists") => (true, db),
+                    _ => return Err(MySQLError::UnsupportSQL),
+                };
+                PlanNode::DropDatabase(DropDatabasePlan {
+                    name: name(db),
+                    if_exists,
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(plan))
+    }
+
+    /// `TRUNCATE [TABLE] name` is not known by the parser either. Return `None` for other
+    /// statements.
+    fn sql_truncate_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let words: Vec<&str> = sql
+            .trim()
+            .trim_end_matches(';')
+            .split_whitespace()
+            .collect();
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        let name = match words.as_slice() {
+            [t, w, name] if is(t, "truncate") && is(w, "table") => name,
+            [t, name] if is(t, "truncate") && !is(name, "table") => name,
+            [t, ..] if is(t, "truncate") => return Err(MySQLError::UnsupportSQL),
+            _ => return Ok(None),
+        };
+        let (db, name) = resolve_table_name(&self.session.lock().unwrap(), &object_name(name))?;
+        Ok(Some(PlanNode::TruncateTable(TruncateTablePlan {
+            db,
+            name,
+        })))
+    }
+
+    /// The parser does not know the table options after the columns of `CREATE TABLE`, such as
+    /// `ENGINE=InnoDB AUTO_INCREMENT=100 DEFAULT CHARSET=utf8mb4`, the `ON DELETE` and
+    /// `ON UPDATE` actions of foreign keys, nor the expressions of generated columns, so they
+    /// are cut off before the statement is parsed.
+    /// `AUTO_INCREMENT=N` makes the `AUTO_INCREMENT` column start from N, the other options are
+    /// ignored. Return `None` for other statements.
+    fn sql_create_table_options_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        let sql = sql.trim().trim_end_matches(';');
+        let (first, rest) = next_word(sql);
+        let (second, _) = next_word(rest);
+        if !is(first, "create") || !is(second, "table") {
+            return Ok(None);
+        }
+        let (sql, actions) = strip_reference_actions(sql)?;
+        let (sql, generated) = strip_generated_columns(&sql)?;
+        let mut end = sql.len();
+        let mut auto_increment = None;
+        if let Some(close) = sql.rfind(')') {
+            if let Some(options) = table_options(&sql[close + 1..])? {
+                end = close + 1;
+                auto_increment = options;
+            }
+        }
+        let dialect = MySqlDialect {};
+        let mut statement = Parser::parse_sql(&dialect, &sql[..end])?;
+        if statement.len() != 1 {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        let mut plan = self.statement_to_plan(statement.pop().unwrap())?;
+        if let PlanNode::CreateTable(plan) = &mut plan {
+            if let Some(n) = auto_increment {
+                plan.table_info.auto_id = Arc::new(AutoIdAllocator::new(n.saturating_sub(1)));
+            }
+            plan.table_info.set_reference_actions(&actions)?;
+            self.set_generated_columns(&mut plan.table_info, &generated)?;
+        }
+        Ok(Some(plan))
+    }
+
+    /// `ALTER TABLE name {ADD [COLUMN] column_definition | DROP [COLUMN] column | MODIFY [COLUMN]
+    /// column_definition | RENAME COLUMN old TO new | RENAME [TO | AS] new_name}` with one
+    /// operation, and `RENAME TABLE name TO new_name`, are recognized by words as well. A column
+    /// definition is parsed as in `CREATE TABLE`. Return `None` for other statements.
+    fn sql_alter_table_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+        let column = |w: &str| w.trim_matches('`').to_lowercase();
+        let (first, rest) = next_word(sql.trim().trim_end_matches(';'));
+        let (second, rest) = next_word(rest);
+        if is(first, "drop") && is(second, "index") {
+            return self.sql_drop_index_to_plan(rest).map(Some);
+        }
+        if !(is(first, "alter") || is(first, "rename")) || !is(second, "table") {
+            return Ok(None);
+        }
+        let (name, rest) = next_word(rest);
+        let (db, table) = self.get_table(&object_name(name))?;
+        let (op, args) = if is(first, "rename") {
+            (first, rest)
+        } else {
+            next_word(rest)
+        };
+        let (w, after) = next_word(args);
+        let def = if is(w, "column") { after } else { args };
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let keyword = w.split('(').next().unwrap_or("");
+        let is_index = is(keyword, "index") || is(keyword, "key");
+        let operation = match words.as_slice() {
+            _ if is(op, "add") && (is_index || is(keyword, "unique") || is(keyword, "primary")) => {
+                AlterTableOperation::AddIndex(parse_index_def(&table, args)?)
+            }
+            _ if is(op, "add") => AlterTableOperation::AddColumn(parse_column_def(def)?),
+            [k, name] if is(op, "drop") && (is(k, "index") || is(k, "key")) => {
+                AlterTableOperation::DropIndex(column(name))
+            }
+            [p, k] if is(op, "drop") && is(p, "primary") && is(k, "key") => {
+                AlterTableOperation::DropIndex(PRIMARY_INDEX_NAME.to_string())
+            }
+            _ if is(op, "modify") => AlterTableOperation::ModifyColumn(parse_column_def(def)?),
+            [c, name] if is(op, "drop") && is(c, "column") => {
+                AlterTableOperation::DropColumn(column(name))
+            }
+            [name] if is(op, "drop") => AlterTableOperation::DropColumn(column(name)),
+            [c, old, to, new] if is(op, "rename") && is(c, "column") && is(to, "to") => {
+                AlterTableOperation::RenameColumn(column(old), column(new))
+            }
+            [to, new] if is(op, "rename") && (is(to, "to") || is(to, "as")) => {
+                self.rename_table_operation(new)?
+            }
+            [new] if is(op, "rename") && !is(first, "rename") => {
+                self.rename_table_operation(new)?
+            }
+            _ => return Err(MySQLError::UnsupportSQL),
+        };
+        Ok(Some(PlanNode::AlterTable(AlterTablePlan {
+            db,
+            table,
+            operation,
+        })))
+    }
+
+    /// `DROP INDEX name ON table`.
+    fn sql_drop_index_to_plan(&self, args: &str) -> MySQLResult<PlanNode> {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        match words.as_slice() {
+            [name, on, table] if on.eq_ignore_ascii_case("on") => {
+                let (db, table) = self.get_table(&object_name(table))?;
+                Ok(PlanNode::AlterTable(AlterTablePlan {
+                    db,
+                    table,
+                    operation: AlterTableOperation::DropIndex(
+                        name.trim_matches('`').to_lowercase(),
+                    ),
+                }))
+            }
+            _ => Err(MySQLError::UnsupportSQL),
+        }
+    }
+
+    fn rename_table_operation(&self, new_name: &str) -> MySQLResult<AlterTableOperation> {
+        let session = self.session.lock().unwrap();
+        let (db, name) = resolve_table_name(&session, &object_name(new_name))?;
+        Ok(AlterTableOperation::RenameTable(db, name))
+    }
+
+    /// Find the table of `name`, in the current database unless it is qualified.
+    fn get_table(&self, name: &ObjectName) -> MySQLResult<(String, Arc<TableSource>)> {
+        let mut session = self.session.lock().unwrap();
+        let (db, table_name) = resolve_table_name(&session, name)?;
+        match session.get_table(&db, &table_name) {
+            Some(table) => Ok((db, table)),
+            None => Err(MySQLError::NoTable(format!("{}.{}", db, table_name))),
+        }
+    }
+
+    /// Find the view `name`, `None` if there is no view of the name.
+    fn get_view(&self, name: &ObjectName) -> MySQLResult<Option<Arc<ViewInfo>>> {
+        let session = self.session.lock().unwrap();
+        let (db, view_name) = resolve_table_name(&session, name)?;
+        Ok(session.get_view(&db, &view_name))
+    }
+
+    /// Find the table `name` which may also be a system table, those can be read but not
+    /// changed.
+    fn get_readable_table(&self, name: &ObjectName) -> MySQLResult<Arc<TableSource>> {
+        let mut session = self.session.lock().unwrap();
+        let (db, table_name) = resolve_table_name(&session, name)?;
+        let table = if is_system_database(&db) {
+            SystemTable::from_name(&db, &table_name).map(|t| Arc::new(TableSource::system(t)))
+        } else {
+            session.get_table(&db, &table_name)
+        };
+        table.ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, table_name)))
+    }
+
+    /// `SET [SESSION | LOCAL] name = value` is also recognized by words, the name may be written
+    /// as `@@name` or `@@session.name`, and `DEFAULT` resets the variable. Global variables are
+    /// not supported. Return `None` for other statements.
+    fn sql_set_to_plan(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let sql = sql.trim().trim_end_matches(';');
+        let mut parts = sql.splitn(2, char::is_whitespace);
+        match (parts.next(), parts.next()) {
+            (Some(w), Some(_)) if w.eq_ignore_ascii_case("set") => {}
+            _ => return Ok(None),
+        }
+        let rest = sql[3..].trim_start();
+        let (name, value) = match rest.find('=') {
+            Some(i) => (rest[..i].trim(), rest[i + 1..].trim()),
+            None => return Ok(None),
+        };
+        let mut name = name.to_lowercase();
+        if name.starts_with("global ") || name.starts_with("@@global.") {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        for prefix in &["session ", "local ", "@@session.", "@@local.", "@@"] {
+            if name.starts_with(prefix) {
+                name = name[prefix.len()..].trim_start().to_string();
+                break;
+            }
+        }
+        let value = if value.eq_ignore_ascii_case("default") {
+            None
+        } else if let Ok(v) = value.parse::<i64>() {
+            Some(EncodeValue::Int(v))
+        } else if let Ok(v) = value.parse::<f64>() {
+            Some(EncodeValue::Double(v))
+        } else {
+            let v = value.trim_matches(|c| c == '\'' || c == '"');
+            Some(EncodeValue::Bytes(v.as_bytes().to_vec()))
+        };
+        Ok(Some(PlanNode::SetVariable(SetVariablePlan { name, value })))
+    }
+
+    /// Build the plan of a `SELECT` through the plan cache, the literals after `FROM` are bound
+    /// to the cached plan as parameters. Return `None` if the query can not be cached.
+    fn build_cached_query(&self, sql: &str) -> MySQLResult<Option<PlanNode>> {
+        let is_select = sql
+            .trim_start()
+            .get(..6)
+            .map_or(false, |w| w.eq_ignore_ascii_case("select"));
+        if !is_select {
+            return Ok(None);
+        }
+        let parameterized = match parameterize(sql, true) {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        let (key, cache) = self.plan_cache_key(parameterized.sql.clone());
+        let plan = match cache.get(&key) {
+            Some(plan) => plan,
+            None => {
+                let dialect = MySqlDialect {};
+                let mut stmts = match Parser::parse_sql(&dialect, &parameterized.sql) {
+                    Ok(stmts) => stmts,
+                    Err(_) => return Ok(None),
+                };
+                let query = match (stmts.len(), stmts.pop()) {
+                    (1, Some(Statement::Query(q))) => q,
+                    _ => return Ok(None),
+                };
+                let plan = self.build_logical_plan(&query)?;
+                cache.put(key, plan.clone());
+                plan
+            }
+        };
+        self.build_physical_plan(plan, &parameterized.literals)
+            .map(Some)
+    }
+
     pub fn statement_to_plan(&self, statement: Statement) -> MySQLResult<PlanNode> {
         match statement {
             Statement::Query(q) => self.sql_query_to_plan(&q),
@@ -60,16 +753,213 @@ impl PlanBuilder {
                 unique,
                 if_not_exists,
             } => self.sql_create_index_to_plan(name, table_name, columns, unique, if_not_exists),
+            Statement::Explain {
+                analyze, statement, ..
+            } => self.sql_explain_to_plan(*statement, analyze),
+            Statement::Analyze { table_name, .. } => self.sql_analyze_table_to_plan(table_name),
+            Statement::Drop {
+                object_type: ObjectType::Table,
+                if_exists,
+                names,
+                ..
+            } => self.sql_drop_table_to_plan(names, if_exists),
+            Statement::CreateView {
+                or_replace,
+                materialized: false,
+                name,
+                columns,
+                query,
+                ..
+            } => self.sql_create_view_to_plan(name, columns, query, or_replace),
+            Statement::Drop {
+                object_type: ObjectType::View,
+                if_exists,
+                names,
+                ..
+            } => self.sql_drop_view_to_plan(names, if_exists),
             _ => return Err(MySQLError::UnsupportSQL),
         }
     }
 
+    /// Build the plan of a prepared statement with the parameters of this builder, the logical
+    /// plan of a query is shared with other statements of the same text by the plan cache.
+    pub fn build_prepared_plan(&self, stmt: &PreparedStatement) -> MySQLResult<PlanNode> {
+        if stmt.param_count != self.params.len() {
+            return Err(MySQLError::ParamMissMatch(
+                stmt.param_count,
+                self.params.len(),
+            ));
+        }
+        let query = match &stmt.statement {
+            Statement::Query(q) => q,
+            s => return self.statement_to_plan(s.clone()),
+        };
+        let logical_plan = self.cached_logical_plan(&stmt.sql, query)?;
+        self.build_physical_plan(logical_plan, &self.params)
+    }
+
+    /// The columns of the result of a prepared statement, which the client is told when it
+    /// prepares the statement. The logical plan of a query is built and cached for the executions
+    /// to come, the other statements return no columns.
+    pub fn build_prepared_schema(&self, stmt: &PreparedStatement) -> MySQLResult<DataSchema> {
+        let columns = match &stmt.statement {
+            Statement::Query(query) => self
+                .cached_logical_plan(&stmt.sql, query)?
+                .schema()
+                .into_iter()
+                .map(|c| c.column)
+                .collect(),
+            _ => vec![],
+        };
+        Ok(DataSchema { columns })
+    }
+
+    fn cached_logical_plan(&self, sql: &str, query: &Query) -> MySQLResult<LogicalPlan> {
+        let (key, cache) = self.plan_cache_key(sql.to_string());
+        match cache.get(&key) {
+            Some(plan) => Ok(plan),
+            None => {
+                let plan = self.build_logical_plan(query)?;
+                cache.put(key, plan.clone());
+                Ok(plan)
+            }
+        }
+    }
+
+    fn plan_cache_key(&self, sql: String) -> (PlanCacheKey, Arc<PlanCache>) {
+        let session = self.session.lock().unwrap();
+        let key = PlanCacheKey {
+            db: session.get_db().clone(),
+            sql,
+            schema_version: session.get_schema_version(),
+        };
+        (key, session.get_plan_cache())
+    }
+
+    fn build_physical_plan(
+        &self,
+        mut logical_plan: LogicalPlan,
+        params: &[EncodeValue],
+    ) -> MySQLResult<PlanNode> {
+        let last_insert_id = self.session.lock().unwrap().last_insert_id();
+        logical_plan.bind_parameters(params, last_insert_id)?;
+        PhysicalPlanner::new(self.session.clone()).build(logical_plan)
+    }
+
+    fn build_logical_plan(&self, query: &Query) -> MySQLResult<LogicalPlan> {
+        let logical_plan = {
+            let mut session = self.session.lock().unwrap();
+            LogicalPlanBuilder::new(&mut *session).build_query(query)?
+        };
+        Optimizer::default().optimize(logical_plan)
+    }
+
     fn sql_query_to_plan(&self, query: &Box<Query>) -> MySQLResult<PlanNode> {
-        let point_get_builder = QueryPlanBuilder::new(self.session.clone());
-        if let Some(plan) = point_get_builder.try_point_get(query)? {
-            return Ok(PlanNode::PointGet(plan));
+        let logical_plan = self.build_logical_plan(query)?;
+        self.build_physical_plan(logical_plan, &self.params)
+    }
+
+    fn sql_explain_to_plan(&self, statement: Statement, analyze: bool) -> MySQLResult<PlanNode> {
+        if let Statement::Explain { .. } = &statement {
+            return Err(MySQLError::UnsupportSQL);
         }
-        Err(MySQLError::UnsupportSQL)
+        let plan = self.statement_to_plan(statement)?;
+        Ok(PlanNode::Explain(ExplainPlan {
+            plan: Box::new(plan),
+            analyze,
+        }))
+    }
+
+    fn sql_analyze_table_to_plan(&self, table_name: ObjectName) -> MySQLResult<PlanNode> {
+        let (_, table) = self.get_table(&table_name)?;
+        Ok(PlanNode::AnalyzeTable(AnalyzeTablePlan { table }))
+    }
+
+    fn sql_drop_table_to_plan(
+        &self,
+        names: Vec<ObjectName>,
+        if_exists: bool,
+    ) -> MySQLResult<PlanNode> {
+        let session = self.session.lock().unwrap();
+        let tables = names
+            .iter()
+            .map(|name| resolve_table_name(&session, name))
+            .collect::<MySQLResult<Vec<_>>>()?;
+        Ok(PlanNode::DropTable(DropTablePlan { tables, if_exists }))
+    }
+
+    /// The tables read by the query are qualified by the current database, and the query is
+    /// planned once so that a view reading a missing table or column is never created.
+    fn sql_create_view_to_plan(
+        &self,
+        name: ObjectName,
+        columns: Vec<Ident>,
+        mut query: Box<Query>,
+        or_replace: bool,
+    ) -> MySQLResult<PlanNode> {
+        let mut session = self.session.lock().unwrap();
+        let (db, view_name) = resolve_table_name(&session, &name)?;
+        if !session.get_db().is_empty() {
+            qualify_tables(&mut query, session.get_db());
+        }
+        let view = ViewInfo {
+            name: view_name,
+            columns: columns.iter().map(|c| c.value.to_lowercase()).collect(),
+            query: *query,
+        };
+        LogicalPlanBuilder::new(&mut *session).build_view(&db, &view, view.name.clone())?;
+        Ok(PlanNode::CreateView(CreateViewPlan {
+            db,
+            view,
+            or_replace,
+        }))
+    }
+
+    fn sql_drop_view_to_plan(
+        &self,
+        names: Vec<ObjectName>,
+        if_exists: bool,
+    ) -> MySQLResult<PlanNode> {
+        let session = self.session.lock().unwrap();
+        let views = names
+            .iter()
+            .map(|name| resolve_table_name(&session, name))
+            .collect::<MySQLResult<Vec<_>>>()?;
+        Ok(PlanNode::DropView(DropViewPlan { views, if_exists }))
+    }
+
+    /// Make the columns named by `generated` computed from their expressions. As MySQL, a
+    /// generated column can not read itself nor the generated columns after it.
+    fn set_generated_columns(
+        &self,
+        table_info: &mut TableInfo,
+        generated: &[(String, String, bool)],
+    ) -> MySQLResult<()> {
+        let names: Vec<&String> = generated.iter().map(|(name, _, _)| name).collect();
+        let mut session = self.session.lock().unwrap();
+        let mut builder = LogicalPlanBuilder::new(&mut *session);
+        for (name, text, stored) in generated {
+            let offset = table_info
+                .columns
+                .iter()
+                .position(|c| c.name == *name)
+                .ok_or(MySQLError::NoColumn)?;
+            let expr = parse_expr(text)?;
+            let (e, columns) = builder.build_column_expr(table_info, &expr)?;
+            if columns
+                .iter()
+                .any(|c| c.offset >= offset && names.contains(&&c.name))
+            {
+                return Err(MySQLError::GeneratedColumnNonPrior);
+            }
+            Arc::make_mut(&mut table_info.columns[offset]).generated = Some(GeneratedColumn {
+                expr,
+                stored: *stored,
+                columns: columns.iter().map(|c| c.name.clone()).collect(),
+                evaluator: Arc::new(ColumnEvaluator::new(e)),
+            });
+        }
+        Ok(())
     }
 
     fn sql_create_table_to_plan(
@@ -81,8 +971,22 @@ impl PlanBuilder {
         _or_replace: bool,
         _table_properties: Vec<SqlOption>,
     ) -> MySQLResult<PlanNode> {
-        let table_info = TableInfo::create(&name, &column_defs, &constrains)?;
-        Ok(PlanNode::CreateTable(CreateTablePlan { table_info }))
+        let (db, _) = resolve_table_name(&self.session.lock().unwrap(), &name)?;
+        let mut table_info = TableInfo::create(&name, &column_defs, &constrains)?;
+        for fk in table_info.foreign_keys.iter_mut() {
+            if fk.ref_db.is_empty() {
+                Arc::make_mut(fk).ref_db = db.clone();
+            }
+        }
+        let mut session = self.session.lock().unwrap();
+        let mut builder = LogicalPlanBuilder::new(&mut *session);
+        let mut checks = std::mem::take(&mut table_info.checks);
+        for check in checks.iter_mut() {
+            let (_, columns) = builder.build_record_expr(&table_info, &check.expr)?;
+            Arc::make_mut(check).columns = columns.iter().map(|c| c.name.clone()).collect();
+        }
+        table_info.checks = checks;
+        Ok(PlanNode::CreateTable(CreateTablePlan { db, table_info }))
     }
 
     fn sql_insert_to_plan(
@@ -91,25 +995,20 @@ impl PlanBuilder {
         cols: Vec<Ident>,
         source: Box<Query>,
     ) -> MySQLResult<PlanNode> {
-        let table_name = table_name.0.last().unwrap().value.to_lowercase();
-        let table = match self.session.lock().unwrap().get_table(&table_name) {
-            Some(t) => t,
-            None => return Err(MySQLError::NoTable(table_name)),
-        };
-        let mut columns = vec![];
-        for col_name in cols.iter() {
-            let col_name = col_name.value.to_lowercase();
-            let col = match table.get_column(&col_name) {
-                Some(col) => col,
-                None => return Err(MySQLError::NoColumn),
-            };
-            columns.push(col);
+        let (table, columns) = self.insert_target(&table_name, &cols)?;
+        if let Some(col) = columns.iter().find(|c| c.generated.is_some()) {
+            return Err(MySQLError::GeneratedColumnValue(
+                col.name.clone(),
+                table.name().to_string(),
+            ));
         }
+        let foreign_keys = self.foreign_key_checks(&table);
+        let checks = self.record_checks(&table)?;
         match source.body {
             sqlparser::ast::SetExpr::Values(values) => {
                 let mut ec_values = vec![];
                 for value in values.0 {
-                    if value.len() != cols.len() {
+                    if value.len() != columns.len() {
                         return Err(MySQLError::ColumnMissMatch);
                     }
                     let mut row = vec![];
@@ -118,6 +1017,12 @@ impl PlanBuilder {
                             Expr::Value(v) => {
                                 row.push(EncodeValue::from_parse_value(v)?);
                             }
+                            Expr::Identifier(ident) => {
+                                match parameter_index(&ident).and_then(|i| self.params.get(i)) {
+                                    Some(v) => row.push(v.clone()),
+                                    None => return Err(MySQLError::UnsupportSQL),
+                                }
+                            }
                             _ => return Err(MySQLError::UnsupportSQL),
                         }
                     }
@@ -127,6 +1032,8 @@ impl PlanBuilder {
                     table,
                     values: ec_values,
                     schema: DataSchema { columns },
+                    foreign_keys,
+                    checks,
                     session: self.session.clone(),
                 }))
             }
@@ -134,6 +1041,90 @@ impl PlanBuilder {
         }
     }
 
+    /// The table which `INSERT` writes and its columns named by `cols`. The target may be a view
+    /// which only filters and renames the columns of one table, its columns are mapped to the
+    /// columns of the table and all of them are inserted if `cols` is empty.
+    fn insert_target(
+        &self,
+        name: &ObjectName,
+        cols: &[Ident],
+    ) -> MySQLResult<(Arc<TableSource>, Vec<Arc<ColumnInfo>>)> {
+        let names: Vec<String> = cols.iter().map(|c| c.value.to_lowercase()).collect();
+        let view = match self.get_view(name)? {
+            Some(view) => view,
+            None => {
+                let (_, table) = self.get_table(name)?;
+                let columns = names
+                    .iter()
+                    .map(|name| table.get_column(name).ok_or(MySQLError::NoColumn))
+                    .collect::<MySQLResult<Vec<_>>>()?;
+                return Ok((table, columns));
+            }
+        };
+        let mut session = self.session.lock().unwrap();
+        let (db, _) = resolve_table_name(&session, name)?;
+        let mut builder = LogicalPlanBuilder::new(&mut *session);
+        let plan = builder.build_view(&db, &view, view.name.clone())?;
+        let non_insertable = || MySQLError::NonInsertable(view.name.clone());
+        let (table, sources) = view_source(&plan).ok_or_else(non_insertable)?;
+        let schema = plan.schema();
+        let view_columns: Vec<&LogicalColumn> = if names.is_empty() {
+            schema.iter().collect()
+        } else {
+            names
+                .iter()
+                .map(|name| schema.iter().find(|c| c.name == *name))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(MySQLError::NoColumn)?
+        };
+        let columns = view_columns
+            .iter()
+            .map(|c| sources.get(&c.id).cloned().ok_or_else(non_insertable))
+            .collect::<MySQLResult<Vec<_>>>()?;
+        Ok((table, columns))
+    }
+
+    /// The foreign keys which the rows inserted into `table` are checked against, there is none
+    /// if `foreign_key_checks` is off.
+    fn foreign_key_checks(&self, table: &TableSource) -> Vec<ForeignKeyCheck> {
+        let mut session = self.session.lock().unwrap();
+        if !session.variables().foreign_key_checks {
+            return vec![];
+        }
+        table
+            .meta()
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                let parent = session
+                    .get_table(&fk.ref_db, &fk.ref_table)
+                    .and_then(|parent| {
+                        let index = parent.meta().referenced_index(&fk.ref_columns)?;
+                        Some((parent, index))
+                    });
+                ForeignKeyCheck {
+                    foreign_key: fk.clone(),
+                    parent,
+                }
+            })
+            .collect()
+    }
+
+    /// The `CHECK` constraints of `table`, built to be evaluated on its records.
+    fn record_checks(&self, table: &TableSource) -> MySQLResult<Vec<RecordCheck>> {
+        let mut session = self.session.lock().unwrap();
+        let mut builder = LogicalPlanBuilder::new(&mut *session);
+        let mut checks = vec![];
+        for check in table.meta().checks.iter() {
+            let (expr, _) = builder.build_record_expr(table.meta(), &check.expr)?;
+            checks.push(RecordCheck {
+                check: check.clone(),
+                expr,
+            });
+        }
+        Ok(checks)
+    }
+
     fn sql_create_index_to_plan(
         &self,
         name: ObjectName,
@@ -142,40 +1133,459 @@ impl PlanBuilder {
         unique: bool,
         _if_not_exists: bool,
     ) -> MySQLResult<PlanNode> {
-        let table_name = table_name.0.last().unwrap().value.to_lowercase();
+        let (db, table) = self.get_table(&table_name)?;
         let index_name = name
             .0
             .last()
             .map(|ident| ident.value.to_lowercase())
             .unwrap_or("".to_string());
-        match self.session.lock().unwrap().get_table(&table_name) {
-            Some(table) => {
-                let mut column_infos = vec![];
-                for expr in columns {
-                    if let Expr::Identifier(ident) = expr.expr {
-                        if let Some(col) = table.get_column(&ident.value.to_lowercase()) {
-                            column_infos.push((col.name.clone(), col.offset));
-                        } else {
-                            return Err(MySQLError::NoColumn);
-                        }
+        let mut column_names = vec![];
+        for expr in columns {
+            if let Expr::Identifier(ident) = expr.expr {
+                column_names.push(ident.value.to_lowercase());
+            } else {
+                return Err(MySQLError::UnsupportSQL);
+            }
+        }
+        let index_info = index_info(&table, index_name, &column_names, unique, false)?;
+        Ok(PlanNode::AlterTable(AlterTablePlan {
+            db,
+            table,
+            operation: AlterTableOperation::AddIndex(index_info),
+        }))
+    }
+}
+
+/// Parse `[LIKE 'pattern']` at the end of a `SHOW` statement.
+fn like_pattern(words: &[&str]) -> MySQLResult<Option<String>> {
+    match words {
+        [] => Ok(None),
+        [like, pattern] if like.eq_ignore_ascii_case("like") => {
+            let pattern = pattern.trim_matches(|c| c == '\'' || c == '"');
+            Ok(Some(pattern.to_string()))
+        }
+        _ => Err(MySQLError::UnsupportSQL),
+    }
+}
+
+/// Build the index of `table` on the columns of `column_names`, it is given an id and a state
+/// when it is added.
+fn index_info(
+    table: &TableSource,
+    name: String,
+    column_names: &[String],
+    unique: bool,
+    primary: bool,
+) -> MySQLResult<IndexInfo> {
+    let mut columns = vec![];
+    for column in column_names {
+        match table.get_column(column) {
+            Some(col) => columns.push((col.name.clone(), col.offset)),
+            None => return Err(MySQLError::NoColumn),
+        }
+    }
+    columns.sort_by_key(|col| col.1);
+    Ok(IndexInfo {
+        id: 0,
+        name,
+        table_name: table.name().to_string(),
+        columns,
+        state: TableState::Public,
+        primary,
+        unique,
+    })
+}
+
+/// Parse `{INDEX | KEY} [name] (columns)`, `UNIQUE [INDEX | KEY] [name] (columns)` or
+/// `PRIMARY KEY (columns)` after `ALTER TABLE ... ADD`.
+fn parse_index_def(table: &TableSource, sql: &str) -> MySQLResult<IndexInfo> {
+    let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+    let is_index = |w: &str| is(w, "index") || is(w, "key");
+    let (open, close) = match (sql.find('('), sql.rfind(')')) {
+        (Some(open), Some(close)) if open < close => (open, close),
+        _ => return Err(MySQLError::UnsupportSQL),
+    };
+    let words: Vec<&str> = sql[..open].split_whitespace().collect();
+    let (unique, primary, rest) = match words.as_slice() {
+        [p, k] if is(p, "primary") && is(k, "key") => (true, true, &[][..]),
+        [u, k, rest @ ..] if is(u, "unique") && is_index(k) => (true, false, rest),
+        [u, rest @ ..] if is(u, "unique") => (true, false, rest),
+        [k, rest @ ..] if is_index(k) => (false, false, rest),
+        _ => return Err(MySQLError::UnsupportSQL),
+    };
+    let name = match rest {
+        [] => String::new(),
+        [name] => name.trim_matches('`').to_lowercase(),
+        _ => return Err(MySQLError::UnsupportSQL),
+    };
+    let columns: Vec<String> = sql[open + 1..close]
+        .split(',')
+        .map(|c| c.trim().trim_matches('`').to_lowercase())
+        .collect();
+    index_info(table, name, &columns, unique, primary)
+}
+
+/// The table options of MySQL which `CREATE TABLE` accepts.
+const TABLE_OPTIONS: [&str; 8] = [
+    "auto_increment",
+    "character",
+    "charset",
+    "collate",
+    "comment",
+    "default",
+    "engine",
+    "row_format",
+];
+
+/// Parse the table options which follow the columns of `CREATE TABLE` into the value of
+/// `AUTO_INCREMENT`. Return `None` if `sql` is not made of table options.
+fn table_options(sql: &str) -> MySQLResult<Option<Option<u64>>> {
+    let sql = sql.replace('=', " = ");
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    match words.first() {
+        Some(w) if TABLE_OPTIONS.iter().any(|o| w.eq_ignore_ascii_case(o)) => (),
+        _ => return Ok(None),
+    }
+    let mut auto_increment = None;
+    let mut words = words.as_slice();
+    while let [w, rest @ ..] = words {
+        words = rest;
+        if !w.eq_ignore_ascii_case("auto_increment") {
+            continue;
+        }
+        if let ["=", rest @ ..] = words {
+            words = rest;
+        }
+        match words {
+            [n, rest @ ..] => {
+                auto_increment = Some(n.parse().map_err(|_| MySQLError::UnsupportSQL)?);
+                words = rest;
+            }
+            [] => return Err(MySQLError::UnsupportSQL),
+        }
+    }
+    Ok(Some(auto_increment))
+}
+
+/// Cut the `ON DELETE action` and `ON UPDATE action` clauses after `REFERENCES` off `sql`, and
+/// return the statement left with the actions of every `FOREIGN KEY` in order. The actions of
+/// a `REFERENCES` in a column definition are dropped, as MySQL ignores such a reference.
+fn strip_reference_actions(
+    sql: &str,
+) -> MySQLResult<(String, Vec<(ReferenceAction, ReferenceAction)>)> {
+    let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+    let bytes = sql.as_bytes();
+    let mut stripped = String::with_capacity(sql.len());
+    let mut actions = vec![];
+    let mut copied = 0;
+    let mut depth: usize = 0;
+    let mut prev_word = "";
+    let mut foreign_key = false;
+    // The depth of the last `REFERENCES` and the foreign key it belongs to.
+    let mut references: Option<(usize, Option<usize>)> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => i = quote_end(bytes, i),
+            b'(' => depth += 1,
+            b')' => {
+                depth = depth.saturating_sub(1);
+                if references.map_or(false, |(d, _)| depth < d) {
+                    references = None;
+                }
+            }
+            b',' if references.map_or(false, |(d, _)| depth == d) => references = None,
+            c if c.is_ascii_alphanumeric() || c == b'_' => {
+                let (start, end) = word_at(sql, i);
+                let word = &sql[start..end];
+                i = end;
+                if is(word, "key") && is(prev_word, "foreign") {
+                    foreign_key = true;
+                } else if is(word, "references") {
+                    let fk = if foreign_key {
+                        actions.push((ReferenceAction::NoAction, ReferenceAction::NoAction));
+                        Some(actions.len() - 1)
                     } else {
-                        return Err(MySQLError::UnsupportSQL);
+                        None
+                    };
+                    references = Some((depth, fk));
+                    foreign_key = false;
+                } else if let (true, Some((_, fk))) = (is(word, "on"), references) {
+                    let (start, end) = word_at(sql, end);
+                    let target = &sql[start..end];
+                    if is(target, "delete") || is(target, "update") {
+                        let (action, end) = reference_action(sql, end)?;
+                        if let Some(fk) = fk {
+                            if is(target, "delete") {
+                                actions[fk].0 = action;
+                            } else {
+                                actions[fk].1 = action;
+                            }
+                        }
+                        stripped.push_str(&sql[copied..i - word.len()]);
+                        copied = end;
+                        i = end;
                     }
                 }
-                column_infos.sort_by_key(|col| col.1);
-                let index_info = IndexInfo {
-                    id: 0,
-                    name: index_name,
-                    table_name,
-                    columns: column_infos,
-                    state: TableState::Public,
-                    primary: false,
-                    unique,
-                };
-                Ok(PlanNode::CreateIndex(CreateIndexPlan { index_info, table }))
+                prev_word = word;
+                continue;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    stripped.push_str(&sql[copied.min(sql.len())..]);
+    Ok((stripped, actions))
+}
+
+/// Cut `[GENERATED ALWAYS] AS (expr) [VIRTUAL | STORED]` off the column definitions of `sql`,
+/// and return the statement left with the generated columns in order, each by its name, the
+/// text of its expression and whether it is stored. A generated column is virtual by default.
+fn strip_generated_columns(sql: &str) -> MySQLResult<(String, Vec<(String, String, bool)>)> {
+    let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+    let bytes = sql.as_bytes();
+    let mut stripped = String::with_capacity(sql.len());
+    let mut generated = vec![];
+    let mut copied = 0;
+    let mut depth: usize = 0;
+    // Where the column definition being read starts, and the last two words read.
+    let mut definition = 0;
+    let mut words = [(0, 0); 2];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => i = quote_end(bytes, i),
+            b'(' => {
+                depth += 1;
+                if depth == 1 {
+                    definition = i + 1;
+                }
+            }
+            b')' => depth = depth.saturating_sub(1),
+            b',' if depth == 1 => definition = i + 1,
+            c if c.is_ascii_alphanumeric() || c == b'_' => {
+                let (start, end) = word_at(sql, i);
+                i = end;
+                if depth == 1
+                    && is(&sql[start..end], "as")
+                    && sql[end..].trim_start().starts_with('(')
+                {
+                    let open = end + sql[end..].find('(').unwrap();
+                    let close = match matching_paren(bytes, open) {
+                        Some(close) => close,
+                        None => return Err(MySQLError::UnsupportSQL),
+                    };
+                    let (kind_start, kind_end) = word_at(sql, close + 1);
+                    let kind = &sql[kind_start..kind_end];
+                    let (stored, end) = if is(kind, "stored") {
+                        (true, kind_end)
+                    } else if is(kind, "virtual") {
+                        (false, kind_end)
+                    } else {
+                        (false, close + 1)
+                    };
+                    let cut = match words {
+                        [(s1, e1), (s2, e2)]
+                            if is(&sql[s1..e1], "generated") && is(&sql[s2..e2], "always") =>
+                        {
+                            s1
+                        }
+                        _ => start,
+                    };
+                    let (column, _) = next_word(&sql[definition..]);
+                    generated.push((
+                        column.trim_matches('`').to_lowercase(),
+                        sql[open + 1..close].trim().to_string(),
+                        stored,
+                    ));
+                    stripped.push_str(&sql[copied..cut]);
+                    copied = end;
+                    i = end;
+                }
+                words = [words[1], (start, end)];
+                continue;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    stripped.push_str(&sql[copied..]);
+    Ok((stripped, generated))
+}
+
+/// The position of the quote which closes the quote at `pos` of `bytes`, a backslash escapes
+/// the next character in a string.
+fn quote_end(bytes: &[u8], pos: usize) -> usize {
+    let quote = bytes[pos];
+    let mut i = pos + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        if bytes[i] == b'\\' && quote != b'`' {
+            i += 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// The position of the `)` which closes the `(` at `open` of `bytes`.
+fn matching_paren(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => i = quote_end(bytes, i),
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
             }
-            None => Err(MySQLError::NoTable(table_name)),
+            _ => (),
         }
+        i += 1;
+    }
+    None
+}
+
+/// Parse an expression, which the parser only parses as a part of a statement.
+fn parse_expr(sql: &str) -> MySQLResult<Expr> {
+    let dialect = MySqlDialect {};
+    let statements = Parser::parse_sql(&dialect, &format!("SELECT {}", sql))?;
+    if let [Statement::Query(query)] = statements.as_slice() {
+        if let SetExpr::Select(select) = &query.body {
+            if let [SelectItem::UnnamedExpr(expr)] = select.projection.as_slice() {
+                if select.from.is_empty() {
+                    return Ok(expr.clone());
+                }
+            }
+        }
+    }
+    Err(MySQLError::UnsupportSQL)
+}
+
+/// Parse `RESTRICT | CASCADE | SET NULL | NO ACTION` from `pos` of `sql`, return the action and
+/// where it ends.
+fn reference_action(sql: &str, pos: usize) -> MySQLResult<(ReferenceAction, usize)> {
+    let is = |w: &str, expected: &str| w.eq_ignore_ascii_case(expected);
+    let (start, end) = word_at(sql, pos);
+    let first = &sql[start..end];
+    if is(first, "restrict") {
+        return Ok((ReferenceAction::Restrict, end));
+    }
+    if is(first, "cascade") {
+        return Ok((ReferenceAction::Cascade, end));
+    }
+    let (start, next_end) = word_at(sql, end);
+    let second = &sql[start..next_end];
+    if is(first, "set") && is(second, "null") {
+        Ok((ReferenceAction::SetNull, next_end))
+    } else if is(first, "no") && is(second, "action") {
+        Ok((ReferenceAction::NoAction, next_end))
+    } else {
+        Err(MySQLError::UnsupportSQL)
+    }
+}
+
+/// The word of letters, digits and underscores which starts at `pos` of `sql` after spaces.
+fn word_at(sql: &str, pos: usize) -> (usize, usize) {
+    let rest = &sql[pos..];
+    let start = pos + rest.len() - rest.trim_start().len();
+    let len = sql[start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(sql.len() - start);
+    (start, start + len)
+}
+
+/// Split the first word off `sql`, the rest starts at the next word.
+fn next_word(sql: &str) -> (&str, &str) {
+    let sql = sql.trim_start();
+    match sql.find(char::is_whitespace) {
+        Some(i) => (&sql[..i], sql[i..].trim_start()),
+        None => (sql, ""),
+    }
+}
+
+/// Qualify the tables read by `query` which have no database by `db`.
+fn qualify_tables(query: &mut Query, db: &str) {
+    fn qualify_factor(factor: &mut TableFactor, db: &str) {
+        match factor {
+            TableFactor::Table { name, .. } if name.0.len() == 1 => {
+                name.0.insert(0, Ident::new(db));
+            }
+            TableFactor::Derived { subquery, .. } => qualify_tables(subquery, db),
+            TableFactor::NestedJoin(table) => qualify_table(table, db),
+            _ => (),
+        }
+    }
+    fn qualify_table(table: &mut TableWithJoins, db: &str) {
+        qualify_factor(&mut table.relation, db);
+        for join in table.joins.iter_mut() {
+            qualify_factor(&mut join.relation, db);
+        }
+    }
+    fn qualify_body(body: &mut SetExpr, db: &str) {
+        match body {
+            SetExpr::Select(select) => {
+                for table in select.from.iter_mut() {
+                    qualify_table(table, db);
+                }
+            }
+            SetExpr::Query(query) => qualify_tables(query, db),
+            SetExpr::SetOperation { left, right, .. } => {
+                qualify_body(left, db);
+                qualify_body(right, db);
+            }
+            _ => (),
+        }
+    }
+    qualify_body(&mut query.body, db);
+}
+
+/// The table read by a plan which only filters and renames the columns of one table, with the
+/// column of the table which every plain column of the plan is.
+fn view_source(plan: &LogicalPlan) -> Option<(Arc<TableSource>, HashMap<usize, Arc<ColumnInfo>>)> {
+    match plan {
+        LogicalPlan::Scan(scan) if scan.table.system_table().is_none() => {
+            let columns = scan.columns.iter().map(|c| (c.id, c.column.clone()));
+            Some((scan.table.clone(), columns.collect()))
+        }
+        LogicalPlan::Filter { input, .. } | LogicalPlan::Sort { input, .. } => view_source(input),
+        LogicalPlan::Projection { input, columns, .. } => {
+            let (table, sources) = view_source(input)?;
+            let columns = columns
+                .iter()
+                .filter_map(|c| Some((c.id, sources.get(&c.id)?.clone())))
+                .collect();
+            Some((table, columns))
+        }
+        _ => None,
+    }
+}
+
+/// The name of a table written as `[db.]name`, whose parts may be quoted by backticks.
+fn object_name(name: &str) -> ObjectName {
+    ObjectName(
+        name.split('.')
+            .map(|part| Ident::new(part.trim_matches('`')))
+            .collect(),
+    )
+}
+
+/// Parse one column definition by the parser of `CREATE TABLE`.
+fn parse_column_def(sql: &str) -> MySQLResult<ColumnDef> {
+    let dialect = MySqlDialect {};
+    let mut statements = Parser::parse_sql(&dialect, &format!("CREATE TABLE t ({})", sql))?;
+    match (statements.len(), statements.pop()) {
+        (
+            1,
+            Some(Statement::CreateTable {
+                mut columns,
+                constraints,
+                ..
+            }),
+        ) if columns.len() == 1 && constraints.is_empty() => Ok(columns.pop().unwrap()),
+        _ => Err(MySQLError::UnsupportSQL),
     }
 }
 
@@ -184,15 +1594,13 @@ mod tests {
     use super::*;
     use crate::common::EncodeValue;
     use crate::mysql_driver::MysqlServerCore;
+    use crate::planner::ExplainRow;
     use crate::table::schema::{ColumnInfo, IndexInfo, IndexType, TableInfo, TableState};
-    use crate::table::TableSource;
+    use crate::table::DEFAULT_DATABASE;
     use sqlparser::ast::DataType;
+    use std::sync::Arc;
 
-    #[test]
-    fn test_build_point_get_plan() {
-        let core = MysqlServerCore::default();
-        let conn = core.create_connection();
-        let session = conn.get_session();
+    fn create_table_info(name: &str) -> TableInfo {
         let columns = vec![
             Arc::new(ColumnInfo {
                 id: 1,
@@ -203,6 +1611,9 @@ mod tests {
                 comment: "".to_string(),
                 key: IndexType::Primary,
                 not_null: false,
+                state: TableState::Public,
+                origin_default: EncodeValue::NULL,
+                generated: None,
             }),
             Arc::new(ColumnInfo {
                 id: 2,
@@ -213,41 +1624,275 @@ mod tests {
                 comment: "".to_string(),
                 key: IndexType::None,
                 not_null: false,
+                state: TableState::Public,
+                origin_default: EncodeValue::NULL,
+                generated: None,
             }),
         ];
-        let table = TableSource::new(Arc::new(TableInfo {
+        TableInfo {
             id: 1,
-            name: "sbtest".to_string(),
+            name: name.to_string(),
             indices: vec![Arc::new(IndexInfo {
                 id: 1,
                 name: "".to_string(),
-                table_name: "sbtest".to_string(),
+                table_name: name.to_string(),
                 columns: vec![("id".to_string(), 0)],
                 state: TableState::Public,
                 primary: true,
                 unique: false,
             })],
+            foreign_keys: vec![],
+            checks: vec![],
             columns,
             state: TableState::Public,
             pk_is_handle: true,
             max_column_id: 3,
             max_index_id: 1,
-            max_row_id: Arc::new(Default::default()),
+            row_id: Arc::new(AutoIdAllocator::new(0)),
+            auto_id: Arc::new(AutoIdAllocator::new(0)),
+            modify_count: Arc::new(Default::default()),
             update_ts: 0,
-        }));
+        }
+    }
+
+    fn create_session_with_table() -> SessionRef {
+        let core = MysqlServerCore::default();
+        let conn = core.create_connection();
+        let session = conn.get_session();
+        let mut guard = session.lock().unwrap();
+        guard.set_db(DEFAULT_DATABASE.to_string()).unwrap();
+        for name in ["sbtest", "sbtest2"].iter() {
+            guard
+                .add_table(DEFAULT_DATABASE, name.to_string(), create_table_info(name))
+                .unwrap();
+        }
+        drop(guard);
         session
-            .lock()
-            .unwrap()
-            .add_table("sbtest".to_string(), Arc::new(table));
+    }
+
+    fn explain_sql(session: SessionRef, sql: &str) -> Vec<ExplainRow> {
+        let plan_builder = PlanBuilder::create(session);
+        let plan = plan_builder.build_from_sql(sql).unwrap();
+        let mut rows = vec![];
+        plan.explain(0, &mut rows);
+        rows
+    }
+
+    #[test]
+    fn test_build_point_get_plan() {
+        let session = create_session_with_table();
         let plan_builder = PlanBuilder::create(session.clone());
         let plan = plan_builder
             .build_from_sql("select k from sbtest where id = 1;")
             .unwrap();
         match plan {
             PlanNode::PointGet(plan) => {
-                assert_eq!(plan.index_value, EncodeValue::Int(1));
+                assert_eq!(plan.index_values, vec![EncodeValue::Int(1)]);
             }
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn test_build_explain_plan() {
+        let session = create_session_with_table();
+        let plan_builder = PlanBuilder::create(session.clone());
+        let plan = plan_builder
+            .build_from_sql("explain select k from sbtest where id = 1;")
+            .unwrap();
+        let mut rows = vec![];
+        plan.explain(0, &mut rows);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].id, "  PointGet");
+        assert_eq!(rows[1].table, "sbtest");
+        assert_eq!(rows[1].index, "PRIMARY");
+        assert_eq!(rows[1].range, "id: [1]");
+    }
+
+    #[test]
+    fn test_build_join_plan() {
+        let session = create_session_with_table();
+        let rows = explain_sql(
+            session,
+            "select sbtest.id from sbtest join sbtest2 on sbtest.k = sbtest2.k where sbtest2.id > 10",
+        );
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].id, "Projection");
+        assert_eq!(rows[1].id, "  HashJoin");
+        assert_eq!(rows[2].id, "    TableScan");
+        assert_eq!(rows[2].table, "sbtest");
+        assert_eq!(rows[3].id, "    IndexScan");
+        assert_eq!(rows[3].table, "sbtest2");
+        assert_eq!(rows[3].index, "PRIMARY");
+        assert_eq!(rows[3].range, "id: (10,+inf]");
+    }
+
+    #[test]
+    fn test_outer_join_to_inner() {
+        let session = create_session_with_table();
+        let rows = explain_sql(
+            session,
+            "select * from sbtest left join sbtest2 on sbtest.id = sbtest2.id where sbtest2.k = 'a'",
+        );
+        assert_eq!(rows[1].id, "  HashJoin");
+        assert!(rows[1].filters.starts_with("inner join"));
+        assert_eq!(rows[3].table, "sbtest2");
+        assert_eq!(rows[3].filters, "=(sbtest2.k, \"a\")");
+    }
+
+    #[test]
+    fn test_build_aggregation_plan() {
+        let session = create_session_with_table();
+        let rows = explain_sql(
+            session.clone(),
+            "select k, count(*) as c from sbtest where id > 1 group by k having c > 1 order by c desc limit 10",
+        );
+        let ids: Vec<&str> = rows.iter().map(|r| r.id.trim()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "Projection",
+                "Limit",
+                "Sort",
+                "Filter",
+                "HashAgg",
+                "IndexScan"
+            ]
+        );
+        assert_eq!(rows[1].filters, "offset: 0, count: 10");
+        assert_eq!(rows[2].filters, "count(*) desc");
+        assert_eq!(rows[3].filters, ">(count(*), 1)");
+        assert_eq!(rows[4].filters, "group by: sbtest.k, funcs: count(*)");
+        assert_eq!(rows[5].range, "id: (1,+inf]");
+        // The aggregate returns a row without any input, so a constant is filtered after it.
+        let rows = explain_sql(session.clone(), "select count(*) from sbtest having 1 = 0");
+        let ids: Vec<&str> = rows.iter().map(|r| r.id.trim()).collect();
+        let filter = ids.iter().position(|id| *id == "Filter").unwrap();
+        let agg = ids.iter().position(|id| *id == "HashAgg").unwrap();
+        assert!(filter < agg);
+        let plan_builder = PlanBuilder::create(session);
+        match plan_builder.build_from_sql("select id, count(*) from sbtest group by k") {
+            Err(MySQLError::NotInGroupBy(_)) => (),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_build_prepared_plan() {
+        let session = create_session_with_table();
+        let stmt = PreparedStatement::prepare("select k from sbtest where id = ?").unwrap();
+        assert_eq!(stmt.param_count, 1);
+        for id in 1..3 {
+            let plan = PlanBuilder::create(session.clone())
+                .with_params(vec![EncodeValue::Int(id)])
+                .build_prepared_plan(&stmt)
+                .unwrap();
+            match plan {
+                PlanNode::PointGet(plan) => {
+                    assert_eq!(plan.index_values, vec![EncodeValue::Int(id)]);
+                }
+                _ => assert!(false),
+            }
+        }
+        let cache = session.lock().unwrap().get_plan_cache();
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+        assert!(PlanBuilder::create(session.clone())
+            .build_prepared_plan(&stmt)
+            .is_err());
+        let stmt = PreparedStatement::prepare("insert into sbtest (id, k) values (?, ?)").unwrap();
+        let plan = PlanBuilder::create(session)
+            .with_params(vec![EncodeValue::Int(1), EncodeValue::Bytes(b"a".to_vec())])
+            .build_prepared_plan(&stmt)
+            .unwrap();
+        match plan {
+            PlanNode::Insert(plan) => {
+                assert_eq!(plan.values[0][1], EncodeValue::Bytes(b"a".to_vec()));
+            }
+            _ => assert!(false),
+        }
+        let builder = PlanBuilder::create(session.clone());
+        assert!(builder
+            .build_prepared_schema(&stmt)
+            .unwrap()
+            .columns
+            .is_empty());
+        let stmt = PreparedStatement::prepare("select id, k + ? as v from sbtest").unwrap();
+        let schema = builder.build_prepared_schema(&stmt).unwrap();
+        let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "v"]);
+    }
+
+    #[test]
+    fn test_plan_cache() {
+        let session = create_session_with_table();
+        let cache = session.lock().unwrap().get_plan_cache();
+        for id in 1..4 {
+            let rows = explain_sql(
+                session.clone(),
+                &format!("select k from sbtest where id = {}", id),
+            );
+            assert_eq!(rows[0].range, format!("id: [{}]", id));
+        }
+        explain_sql(session.clone(), "select k from sbtest where id > 10");
+        assert_eq!(cache.hits(), 2);
+        assert_eq!(cache.misses(), 2);
+        session
+            .lock()
+            .unwrap()
+            .replace_table(
+                DEFAULT_DATABASE,
+                "sbtest".to_string(),
+                create_table_info("sbtest"),
+            )
+            .unwrap();
+        explain_sql(session, "select k from sbtest where id = 1");
+        assert_eq!(cache.hits(), 2);
+        assert_eq!(cache.misses(), 3);
+
+        let plan = PlanBuilder::create(session)
+            .build_from_sql("SHOW GLOBAL STATUS LIKE 'plan_cache_h%';")
+            .unwrap();
+        match plan {
+            PlanNode::Show(plan) => {
+                assert_eq!(
+                    plan.rows,
+                    vec![vec![
+                        EncodeValue::Bytes(b"plan_cache_hits".to_vec()),
+                        EncodeValue::Bytes(b"2".to_vec()),
+                    ]]
+                );
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_build_kill_plan() {
+        let core = MysqlServerCore::default();
+        let (a, b) = (core.create_connection(), core.create_connection());
+        let id = b.get_session().lock().unwrap().process().id;
+        let plan_builder = PlanBuilder::create(a.get_session());
+        match plan_builder.build_from_sql("SHOW PROCESSLIST").unwrap() {
+            PlanNode::Show(plan) => {
+                assert_eq!(plan.rows.len(), 2);
+                assert_eq!(plan.rows[1][0], EncodeValue::Int(id as i64));
+                assert_eq!(plan.rows[1][4], EncodeValue::Bytes(b"Sleep".to_vec()));
+            }
+            _ => assert!(false),
+        }
+        match plan_builder
+            .build_from_sql(&format!("kill query {};", id))
+            .unwrap()
+        {
+            PlanNode::Kill(plan) => assert!(plan.connection_id == id && plan.query_only),
+            _ => assert!(false),
+        }
+        assert!(plan_builder.build_from_sql("KILL abc").is_err());
+        drop(b);
+        match plan_builder.build_from_sql("SHOW PROCESSLIST").unwrap() {
+            PlanNode::Show(plan) => assert_eq!(plan.rows.len(), 1),
+            _ => assert!(false),
+        }
+    }
 }
@@ -0,0 +1,149 @@
+use crate::planner::logical_plan::LogicalPlan;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub const DEFAULT_PLAN_CACHE_CAPACITY: usize = 1000;
+
+/// A plan is only shared by statements with the same text after parameterizing, in the same
+/// database and with the same version of schema.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PlanCacheKey {
+    pub db: String,
+    pub sql: String,
+    pub schema_version: u64,
+}
+
+struct CachedPlan {
+    plan: LogicalPlan,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct LruPlans {
+    plans: HashMap<PlanCacheKey, CachedPlan>,
+    /// The key of every plan ordered by the time it is used last.
+    order: BTreeMap<u64, PlanCacheKey>,
+    clock: u64,
+}
+
+impl LruPlans {
+    fn touch(&mut self, key: &PlanCacheKey) -> Option<&CachedPlan> {
+        self.clock += 1;
+        let clock = self.clock;
+        let cached = self.plans.get_mut(key)?;
+        self.order.remove(&cached.last_used);
+        self.order.insert(clock, key.clone());
+        cached.last_used = clock;
+        Some(cached)
+    }
+
+    fn remove(&mut self, key: &PlanCacheKey) {
+        if let Some(cached) = self.plans.remove(key) {
+            self.order.remove(&cached.last_used);
+        }
+    }
+}
+
+/// A bounded LRU cache of optimized logical plans shared by all sessions. The parameters of a
+/// cached plan are bound for every execution, and the access paths are chosen again by the
+/// physical planner with the bound values.
+pub struct PlanCache {
+    capacity: usize,
+    plans: Mutex<LruPlans>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PlanCache {
+    pub fn new(capacity: usize) -> PlanCache {
+        PlanCache {
+            capacity,
+            plans: Mutex::new(LruPlans::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return a copy of the plan of `key`, a plan reading any table which has been changed is
+    /// removed.
+    pub fn get(&self, key: &PlanCacheKey) -> Option<LogicalPlan> {
+        let mut plans = self.plans.lock().unwrap();
+        let plan = match plans.touch(key).map(|cached| cached.plan.is_valid()) {
+            Some(true) => plans.plans.get(key).map(|cached| cached.plan.clone()),
+            Some(false) => {
+                plans.remove(key);
+                None
+            }
+            None => None,
+        };
+        if plan.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        plan
+    }
+
+    pub fn put(&self, key: PlanCacheKey, plan: LogicalPlan) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut plans = self.plans.lock().unwrap();
+        plans.remove(&key);
+        while plans.plans.len() >= self.capacity {
+            let oldest = match plans.order.iter().next() {
+                Some((_, key)) => key.clone(),
+                None => break,
+            };
+            plans.remove(&oldest);
+        }
+        plans.clock += 1;
+        let last_used = plans.clock;
+        plans.order.insert(last_used, key.clone());
+        plans.plans.insert(key, CachedPlan { plan, last_used });
+    }
+
+    pub fn len(&self) -> usize {
+        self.plans.lock().unwrap().plans.len()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(sql: &str) -> PlanCacheKey {
+        PlanCacheKey {
+            db: "test".to_string(),
+            sql: sql.to_string(),
+            schema_version: 1,
+        }
+    }
+
+    #[test]
+    fn test_plan_cache_evict() {
+        let cache = PlanCache::new(2);
+        cache.put(key("a"), LogicalPlan::Dual);
+        cache.put(key("b"), LogicalPlan::Dual);
+        assert!(cache.get(&key("a")).is_some());
+        cache.put(key("c"), LogicalPlan::Dual);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("c")).is_some());
+        let mut other_version = key("c");
+        other_version.schema_version = 2;
+        assert!(cache.get(&other_version).is_none());
+        assert_eq!(cache.hits(), 3);
+        assert_eq!(cache.misses(), 2);
+    }
+}
@@ -1,10 +1,550 @@
 use crate::common::EncodeValue;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::table::schema::{RecordEvaluator, TableInfo};
+use sqlparser::ast::{BinaryOperator, DataType, UnaryOperator};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
 
+/// A column referenced by an expression. `id` identifies the column in the whole query, and
+/// `index` is the offset of the column in the input row of the operator which evaluates the
+/// expression, it is resolved by the physical planner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnRef {
+    pub id: usize,
+    pub index: usize,
+    pub name: String,
+    pub data_type: DataType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
-    Eq {
+    Column(ColumnRef),
+    Value(EncodeValue),
+    BinaryOp {
+        op: BinaryOperator,
         left: Box<Expression>,
         right: Box<Expression>,
     },
-    Column(String),
-    Value(EncodeValue),
+    UnaryOp {
+        op: UnaryOperator,
+        expr: Box<Expression>,
+    },
+    IsNull(Box<Expression>),
+    IsNotNull(Box<Expression>),
+    /// The `?` placeholder of a prepared statement, the number is its position in the statement.
+    Parameter(usize),
+    /// `LAST_INSERT_ID()`, which is bound to the value of the session when the plan is executed
+    /// as the plan may be cached.
+    LastInsertId,
+}
+
+impl Expression {
+    pub fn binary(op: BinaryOperator, left: Expression, right: Expression) -> Expression {
+        Expression::BinaryOp {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /// Split a condition into expressions which are connected by `AND`.
+    pub fn split_conjunction(self, output: &mut Vec<Expression>) {
+        match self {
+            Expression::BinaryOp {
+                op: BinaryOperator::And,
+                left,
+                right,
+            } => {
+                left.split_conjunction(output);
+                right.split_conjunction(output);
+            }
+            e => output.push(e),
+        }
+    }
+
+    /// Ids of all columns referenced by this expression.
+    pub fn collect_columns(&self, output: &mut HashSet<usize>) {
+        match self {
+            Expression::Column(c) => {
+                output.insert(c.id);
+            }
+            Expression::Value(_) | Expression::Parameter(_) | Expression::LastInsertId => (),
+            Expression::BinaryOp { left, right, .. } => {
+                left.collect_columns(output);
+                right.collect_columns(output);
+            }
+            Expression::UnaryOp { expr, .. }
+            | Expression::IsNull(expr)
+            | Expression::IsNotNull(expr) => expr.collect_columns(output),
+        }
+    }
+
+    pub fn columns(&self) -> HashSet<usize> {
+        let mut cols = HashSet::default();
+        self.collect_columns(&mut cols);
+        cols
+    }
+
+    /// Whether the expression has a value which is only known when the plan is executed, which
+    /// is a parameter or `LAST_INSERT_ID()`.
+    pub fn has_parameter(&self) -> bool {
+        match self {
+            Expression::Parameter(_) | Expression::LastInsertId => true,
+            Expression::Column(_) | Expression::Value(_) => false,
+            Expression::BinaryOp { left, right, .. } => {
+                left.has_parameter() || right.has_parameter()
+            }
+            Expression::UnaryOp { expr, .. }
+            | Expression::IsNull(expr)
+            | Expression::IsNotNull(expr) => expr.has_parameter(),
+        }
+    }
+
+    /// Whether the expression can be evaluated while planning, an unbound parameter is not
+    /// constant.
+    pub fn is_constant(&self) -> bool {
+        self.columns().is_empty() && !self.has_parameter()
+    }
+
+    /// Replace every parameter by the value provided for it, and `LAST_INSERT_ID()` by
+    /// `last_insert_id`.
+    pub fn bind_parameters(
+        self,
+        params: &[EncodeValue],
+        last_insert_id: u64,
+    ) -> MySQLResult<Expression> {
+        match self {
+            Expression::Parameter(i) => params
+                .get(i)
+                .cloned()
+                .map(Expression::Value)
+                .ok_or_else(|| MySQLError::ParamMissMatch(i + 1, params.len())),
+            Expression::LastInsertId => {
+                Ok(Expression::Value(EncodeValue::Int(last_insert_id as i64)))
+            }
+            Expression::BinaryOp { op, left, right } => Ok(Expression::binary(
+                op,
+                left.bind_parameters(params, last_insert_id)?,
+                right.bind_parameters(params, last_insert_id)?,
+            )),
+            Expression::UnaryOp { op, expr } => Ok(Expression::UnaryOp {
+                op,
+                expr: Box::new(expr.bind_parameters(params, last_insert_id)?),
+            }),
+            Expression::IsNull(expr) => Ok(Expression::IsNull(Box::new(
+                expr.bind_parameters(params, last_insert_id)?,
+            ))),
+            Expression::IsNotNull(expr) => Ok(Expression::IsNotNull(Box::new(
+                expr.bind_parameters(params, last_insert_id)?,
+            ))),
+            e => Ok(e),
+        }
+    }
+
+    /// Resolve the offset of every column in the input row of the operator.
+    pub fn bind(&mut self, input: &[usize]) -> MySQLResult<()> {
+        match self {
+            Expression::Column(c) => match input.iter().position(|id| *id == c.id) {
+                Some(index) => {
+                    c.index = index;
+                    Ok(())
+                }
+                None => Err(MySQLError::MissColumn(c.name.clone())),
+            },
+            Expression::Value(_) | Expression::Parameter(_) | Expression::LastInsertId => Ok(()),
+            Expression::BinaryOp { left, right, .. } => {
+                left.bind(input)?;
+                right.bind(input)
+            }
+            Expression::UnaryOp { expr, .. }
+            | Expression::IsNull(expr)
+            | Expression::IsNotNull(expr) => expr.bind(input),
+        }
+    }
+
+    /// Replace the columns whose id is in `ids` by NULL.
+    pub fn replace_with_null(&self, ids: &HashSet<usize>) -> Expression {
+        match self {
+            Expression::Column(c) if ids.contains(&c.id) => Expression::Value(EncodeValue::NULL),
+            Expression::Column(_)
+            | Expression::Value(_)
+            | Expression::Parameter(_)
+            | Expression::LastInsertId => self.clone(),
+            Expression::BinaryOp { op, left, right } => Expression::binary(
+                op.clone(),
+                left.replace_with_null(ids),
+                right.replace_with_null(ids),
+            ),
+            Expression::UnaryOp { op, expr } => Expression::UnaryOp {
+                op: op.clone(),
+                expr: Box::new(expr.replace_with_null(ids)),
+            },
+            Expression::IsNull(expr) => Expression::IsNull(Box::new(expr.replace_with_null(ids))),
+            Expression::IsNotNull(expr) => {
+                Expression::IsNotNull(Box::new(expr.replace_with_null(ids)))
+            }
+        }
+    }
+
+    /// Replace every sub-expression equal to `from` by `to`.
+    pub fn replace_expr(self, from: &Expression, to: &Expression) -> Expression {
+        if self == *from {
+            return to.clone();
+        }
+        match self {
+            Expression::BinaryOp { op, left, right } => Expression::binary(
+                op,
+                left.replace_expr(from, to),
+                right.replace_expr(from, to),
+            ),
+            Expression::UnaryOp { op, expr } => Expression::UnaryOp {
+                op,
+                expr: Box::new(expr.replace_expr(from, to)),
+            },
+            Expression::IsNull(expr) => Expression::IsNull(Box::new(expr.replace_expr(from, to))),
+            Expression::IsNotNull(expr) => {
+                Expression::IsNotNull(Box::new(expr.replace_expr(from, to)))
+            }
+            e => e,
+        }
+    }
+
+    /// Evaluate every constant sub-expression.
+    pub fn fold_constant(self) -> MySQLResult<Expression> {
+        let expr = match self {
+            Expression::BinaryOp { op, left, right } => {
+                let left = left.fold_constant()?;
+                let right = right.fold_constant()?;
+                // `x AND false` and `x OR true` are constant even if `x` is not.
+                match (&op, &left, &right) {
+                    (BinaryOperator::And, Expression::Value(v), _)
+                    | (BinaryOperator::And, _, Expression::Value(v))
+                        if !v.is_null() && !v.is_true() =>
+                    {
+                        return Ok(Expression::Value(EncodeValue::Int(0)));
+                    }
+                    (BinaryOperator::Or, Expression::Value(v), _)
+                    | (BinaryOperator::Or, _, Expression::Value(v))
+                        if v.is_true() =>
+                    {
+                        return Ok(Expression::Value(EncodeValue::Int(1)));
+                    }
+                    // Other operators are NULL if any operand is NULL.
+                    (op, Expression::Value(v), _) | (op, _, Expression::Value(v))
+                        if v.is_null()
+                            && *op != BinaryOperator::And
+                            && *op != BinaryOperator::Or =>
+                    {
+                        return Ok(Expression::Value(EncodeValue::NULL));
+                    }
+                    _ => Expression::binary(op, left, right),
+                }
+            }
+            Expression::UnaryOp { op, expr } => Expression::UnaryOp {
+                op,
+                expr: Box::new(expr.fold_constant()?),
+            },
+            Expression::IsNull(expr) => Expression::IsNull(Box::new(expr.fold_constant()?)),
+            Expression::IsNotNull(expr) => Expression::IsNotNull(Box::new(expr.fold_constant()?)),
+            e => return Ok(e),
+        };
+        if expr.is_constant() {
+            Ok(Expression::Value(expr.eval(&[])?))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    pub fn eval(&self, row: &[EncodeValue]) -> MySQLResult<EncodeValue> {
+        match self {
+            Expression::Column(c) => row
+                .get(c.index)
+                .cloned()
+                .ok_or_else(|| MySQLError::MissColumn(c.name.clone())),
+            Expression::Value(v) => Ok(v.clone()),
+            Expression::Parameter(i) => Err(MySQLError::ParamMissMatch(i + 1, 0)),
+            Expression::LastInsertId => Err(MySQLError::UnsupportSQL),
+            Expression::BinaryOp { op, left, right } => {
+                let l = left.eval(row)?;
+                match op {
+                    BinaryOperator::And => {
+                        if !l.is_null() && !l.is_true() {
+                            return Ok(EncodeValue::Int(0));
+                        }
+                        let r = right.eval(row)?;
+                        if !r.is_null() && !r.is_true() {
+                            Ok(EncodeValue::Int(0))
+                        } else if l.is_null() || r.is_null() {
+                            Ok(EncodeValue::NULL)
+                        } else {
+                            Ok(EncodeValue::Int(1))
+                        }
+                    }
+                    BinaryOperator::Or => {
+                        if l.is_true() {
+                            return Ok(EncodeValue::Int(1));
+                        }
+                        let r = right.eval(row)?;
+                        if r.is_true() {
+                            Ok(EncodeValue::Int(1))
+                        } else if l.is_null() || r.is_null() {
+                            Ok(EncodeValue::NULL)
+                        } else {
+                            Ok(EncodeValue::Int(0))
+                        }
+                    }
+                    op => eval_binary(op, &l, &right.eval(row)?),
+                }
+            }
+            Expression::UnaryOp { op, expr } => eval_unary(op, expr.eval(row)?),
+            Expression::IsNull(expr) => Ok(bool_value(expr.eval(row)?.is_null())),
+            Expression::IsNotNull(expr) => Ok(bool_value(!expr.eval(row)?.is_null())),
+        }
+    }
+
+    /// The type of the value produced by this expression.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Expression::Column(c) => c.data_type.clone(),
+            Expression::Value(v) => match v {
+                EncodeValue::Int(_) => DataType::BigInt,
+                EncodeValue::Float(_) | EncodeValue::Double(_) => DataType::Double,
+                EncodeValue::Date(..) => DataType::Date,
+                EncodeValue::Time(..) => DataType::Time,
+                _ => DataType::String,
+            },
+            Expression::BinaryOp { op, left, right } => match op {
+                BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Modulo => {
+                    if is_integer_type(&left.data_type()) && is_integer_type(&right.data_type()) {
+                        DataType::BigInt
+                    } else {
+                        DataType::Double
+                    }
+                }
+                BinaryOperator::Divide => DataType::Double,
+                _ => DataType::BigInt,
+            },
+            Expression::UnaryOp { expr, .. } => match expr.data_type() {
+                DataType::Double => DataType::Double,
+                _ => DataType::BigInt,
+            },
+            Expression::IsNull(_) | Expression::IsNotNull(_) => DataType::BigInt,
+            Expression::Parameter(_) => DataType::String,
+            Expression::LastInsertId => DataType::BigInt,
+        }
+    }
+}
+
+/// The evaluator of a generated column. The columns of the expression are the ids of the
+/// columns of the table, they are bound to the offsets of the columns whenever it is evaluated,
+/// since the offsets change when other columns are dropped.
+#[derive(Debug)]
+pub struct ColumnEvaluator {
+    expr: Expression,
+}
+
+impl ColumnEvaluator {
+    pub fn new(expr: Expression) -> Self {
+        ColumnEvaluator { expr }
+    }
+}
+
+impl RecordEvaluator for ColumnEvaluator {
+    fn eval(&self, table: &TableInfo, record: &[EncodeValue]) -> MySQLResult<EncodeValue> {
+        let ids: Vec<usize> = table.columns.iter().map(|c| c.id as usize).collect();
+        let mut expr = self.expr.clone();
+        expr.bind(&ids)?;
+        expr.eval(record)
+    }
+}
+
+pub fn is_integer_type(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::SmallInt | DataType::Int | DataType::BigInt | DataType::Boolean => true,
+        _ => false,
+    }
+}
+
+fn bool_value(v: bool) -> EncodeValue {
+    if v {
+        EncodeValue::Int(1)
+    } else {
+        EncodeValue::Int(0)
+    }
+}
+
+pub(super) fn eval_unary(op: &UnaryOperator, v: EncodeValue) -> MySQLResult<EncodeValue> {
+    if v.is_null() {
+        return Ok(EncodeValue::NULL);
+    }
+    match op {
+        UnaryOperator::Not => Ok(bool_value(!v.is_true())),
+        UnaryOperator::Plus => Ok(v),
+        UnaryOperator::Minus => match v {
+            EncodeValue::Int(v) => Ok(EncodeValue::Int(-v)),
+            v => Ok(EncodeValue::Double(-v.as_f64().unwrap_or(0.0))),
+        },
+        _ => Err(MySQLError::UnsupportSQL),
+    }
+}
+
+pub(super) fn eval_binary(
+    op: &BinaryOperator,
+    l: &EncodeValue,
+    r: &EncodeValue,
+) -> MySQLResult<EncodeValue> {
+    if l.is_null() || r.is_null() {
+        return Ok(EncodeValue::NULL);
+    }
+    let ord = || l.compare(r).unwrap_or(Ordering::Equal);
+    let v = match op {
+        BinaryOperator::Eq => bool_value(ord() == Ordering::Equal),
+        BinaryOperator::NotEq => bool_value(ord() != Ordering::Equal),
+        BinaryOperator::Lt => bool_value(ord() == Ordering::Less),
+        BinaryOperator::LtEq => bool_value(ord() != Ordering::Greater),
+        BinaryOperator::Gt => bool_value(ord() == Ordering::Greater),
+        BinaryOperator::GtEq => bool_value(ord() != Ordering::Less),
+        BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply => {
+            if let (EncodeValue::Int(a), EncodeValue::Int(b)) = (l, r) {
+                let v = match op {
+                    BinaryOperator::Plus => a.checked_add(*b),
+                    BinaryOperator::Minus => a.checked_sub(*b),
+                    _ => a.checked_mul(*b),
+                };
+                return v
+                    .map(EncodeValue::Int)
+                    .ok_or_else(|| MySQLError::OutOfRange(format!("{} {} {}", a, op, b)));
+            }
+            let a = l.as_f64().unwrap_or(0.0);
+            let b = r.as_f64().unwrap_or(0.0);
+            EncodeValue::Double(match op {
+                BinaryOperator::Plus => a + b,
+                BinaryOperator::Minus => a - b,
+                _ => a * b,
+            })
+        }
+        BinaryOperator::Divide => {
+            let b = r.as_f64().unwrap_or(0.0);
+            if b == 0.0 {
+                EncodeValue::NULL
+            } else {
+                EncodeValue::Double(l.as_f64().unwrap_or(0.0) / b)
+            }
+        }
+        BinaryOperator::Modulo => match (l, r) {
+            (EncodeValue::Int(_), EncodeValue::Int(0)) => EncodeValue::NULL,
+            (EncodeValue::Int(a), EncodeValue::Int(b)) => EncodeValue::Int(a % b),
+            _ => {
+                let b = r.as_f64().unwrap_or(0.0);
+                if b == 0.0 {
+                    EncodeValue::NULL
+                } else {
+                    EncodeValue::Double(l.as_f64().unwrap_or(0.0) % b)
+                }
+            }
+        },
+        _ => return Err(MySQLError::UnsupportSQL),
+    };
+    Ok(v)
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Column(c) => write!(f, "{}", c.name),
+            Expression::Value(EncodeValue::Bytes(v)) => {
+                write!(f, "\"{}\"", String::from_utf8_lossy(v))
+            }
+            Expression::Value(v) => write!(f, "{}", String::from(v.clone())),
+            Expression::BinaryOp { op, left, right } => write!(f, "{}({}, {})", op, left, right),
+            Expression::UnaryOp { op, expr } => write!(f, "{}({})", op, expr),
+            Expression::IsNull(expr) => write!(f, "isnull({})", expr),
+            Expression::IsNotNull(expr) => write!(f, "not(isnull({}))", expr),
+            Expression::Parameter(_) => write!(f, "?"),
+            Expression::LastInsertId => write!(f, "last_insert_id()"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(id: usize, name: &str) -> Expression {
+        Expression::Column(ColumnRef {
+            id,
+            index: 0,
+            name: name.to_string(),
+            data_type: DataType::Int,
+        })
+    }
+
+    #[test]
+    fn test_fold_constant() {
+        let expr = Expression::binary(
+            BinaryOperator::Gt,
+            column(1, "a"),
+            Expression::binary(
+                BinaryOperator::Plus,
+                Expression::Value(EncodeValue::Int(1)),
+                Expression::Value(EncodeValue::Int(2)),
+            ),
+        );
+        let folded = expr.fold_constant().unwrap();
+        assert_eq!(
+            folded,
+            Expression::binary(
+                BinaryOperator::Gt,
+                column(1, "a"),
+                Expression::Value(EncodeValue::Int(3)),
+            )
+        );
+        let expr = Expression::binary(
+            BinaryOperator::And,
+            column(1, "a"),
+            Expression::binary(
+                BinaryOperator::Eq,
+                Expression::Value(EncodeValue::Int(1)),
+                Expression::Value(EncodeValue::Int(2)),
+            ),
+        );
+        assert_eq!(
+            expr.fold_constant().unwrap(),
+            Expression::Value(EncodeValue::Int(0))
+        );
+    }
+
+    #[test]
+    fn test_eval_with_null() {
+        let mut expr = Expression::binary(
+            BinaryOperator::Or,
+            Expression::binary(
+                BinaryOperator::Eq,
+                column(1, "a"),
+                Expression::Value(EncodeValue::Int(1)),
+            ),
+            Expression::IsNull(Box::new(column(2, "b"))),
+        );
+        expr.bind(&[1, 2]).unwrap();
+        let v = expr
+            .eval(&[EncodeValue::NULL, EncodeValue::Int(2)])
+            .unwrap();
+        assert_eq!(v, EncodeValue::NULL);
+        let v = expr
+            .eval(&[EncodeValue::Int(1), EncodeValue::NULL])
+            .unwrap();
+        assert_eq!(v, EncodeValue::Int(1));
+        let v = expr
+            .eval(&[EncodeValue::Int(3), EncodeValue::NULL])
+            .unwrap();
+        assert_eq!(v, EncodeValue::Int(1));
+        let v = expr
+            .eval(&[EncodeValue::Int(3), EncodeValue::Int(2)])
+            .unwrap();
+        assert_eq!(v, EncodeValue::Int(0));
+    }
 }
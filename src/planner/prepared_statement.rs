@@ -0,0 +1,236 @@
+use crate::common::EncodeValue;
+use crate::errors::{MySQLError, MySQLResult};
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::MySqlDialect;
+use sqlparser::parser::{Parser, ParserError};
+
+/// The parser does not know `?`, so every placeholder is replaced by a quoted identifier which
+/// users hardly write, such as `` `?0` ``.
+const PARAM_PREFIX: char = '?';
+
+/// A statement created by `COM_STMT_PREPARE`, it is parsed only once. `sql` is the text after
+/// replacing placeholders, it is the key of the plan in the plan cache.
+pub struct PreparedStatement {
+    pub sql: String,
+    pub statement: Statement,
+    pub param_count: usize,
+}
+
+impl PreparedStatement {
+    pub fn prepare(sql: &str) -> MySQLResult<PreparedStatement> {
+        let parameterized = parameterize(sql, false).ok_or_else(|| {
+            ParserError::ParserError(format!("can not replace the placeholders of: {}", sql))
+        })?;
+        let dialect = MySqlDialect {};
+        let mut stmts = Parser::parse_sql(&dialect, &parameterized.sql)?;
+        if stmts.len() != 1 {
+            return Err(MySQLError::PrepareMult(stmts.len() as u64));
+        }
+        Ok(PreparedStatement {
+            sql: parameterized.sql,
+            statement: stmts.pop().unwrap(),
+            param_count: parameterized.param_count,
+        })
+    }
+}
+
+/// The position of the parameter if `ident` is a placeholder.
+pub fn parameter_index(ident: &Ident) -> Option<usize> {
+    if ident.quote_style != Some('`') || !ident.value.starts_with(PARAM_PREFIX) {
+        return None;
+    }
+    ident.value[PARAM_PREFIX.len_utf8()..].parse::<usize>().ok()
+}
+
+/// SQL text whose parameters are replaced by placeholders which the parser accepts.
+pub struct ParameterizedSql {
+    pub sql: String,
+    /// The number of `?` in the original text.
+    pub param_count: usize,
+    /// The literals replaced by placeholders, in the order of their positions.
+    pub literals: Vec<EncodeValue>,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '@'
+}
+
+/// The position of the quote which closes the string or the quoted identifier starting at
+/// `start`, or the end of the text if it is not closed.
+fn quote_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == '\\' && quote != '`' {
+            i += 2;
+            continue;
+        }
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i;
+        }
+        i += 1;
+    }
+    chars.len() - 1
+}
+
+/// Replace every `?` which is not in a string, a quoted identifier or a comment by a
+/// placeholder. If `literals` is true, the numbers and the strings between the first `FROM` and
+/// `GROUP BY` or `ORDER BY` are replaced too, so that queries differing only in constants share
+/// the same text. Literals in the select list are kept because they decide the names and types
+/// of the result columns, and those after `GROUP` or `ORDER` because a number there may be a
+/// position in the select list.
+/// Return `None` if the literals can not be extracted exactly as the parser reads them, or if
+/// the text contains `?` while extracting literals.
+pub fn parameterize(sql: &str, literals: bool) -> Option<ParameterizedSql> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut output = String::with_capacity(sql.len());
+    let mut param_count = 0;
+    let mut values = vec![];
+    let mut after_from = false;
+    let mut i = 0;
+    let placeholder = |n: usize| format!("`{}{}`", PARAM_PREFIX, n);
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' | '`' => {
+                let end = quote_end(&chars, i);
+                let text: String = chars[i..=end].iter().collect();
+                if literals && after_from && c == '\'' {
+                    // Unclosed strings, escapes, charset introducers and hexadecimal strings
+                    // are not extracted.
+                    let prev_is_ident = output.chars().last().map_or(false, is_ident_char);
+                    if end == i || chars[end] != c || prev_is_ident {
+                        return None;
+                    }
+                    let value: String = chars[i + 1..end].iter().collect();
+                    if value.contains(|c| c == '\\' || c == '\'') {
+                        return None;
+                    }
+                    output.push_str(&placeholder(values.len()));
+                    values.push(EncodeValue::Bytes(value.into_bytes()));
+                } else {
+                    output.push_str(&text);
+                }
+                i = end + 1;
+            }
+            '#' | '-' | '/' => {
+                let end = if c == '#' || (c == '-' && chars.get(i + 1) == Some(&'-')) {
+                    chars[i..]
+                        .iter()
+                        .position(|c| *c == '\n')
+                        .map_or(chars.len(), |p| i + p + 1)
+                } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+                    (i + 2..chars.len())
+                        .find(|p| chars[*p] == '*' && chars.get(p + 1) == Some(&'/'))
+                        .map_or(chars.len(), |p| p + 2)
+                } else {
+                    i + 1
+                };
+                output.extend(chars[i..end].iter());
+                i = end;
+            }
+            '?' => {
+                if literals {
+                    return None;
+                }
+                output.push_str(&placeholder(param_count));
+                param_count += 1;
+                i += 1;
+            }
+            c if c.is_ascii_digit() && literals && after_from => {
+                let mut end = i;
+                while end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                if chars.get(end) == Some(&'.') {
+                    end += 1;
+                    while end < chars.len() && chars[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                // Such as `1e3`, `0x1f` and `t.1`.
+                if output.ends_with('.')
+                    || chars
+                        .get(end)
+                        .map_or(false, |c| is_ident_char(*c) || *c == '.')
+                {
+                    return None;
+                }
+                let text: String = chars[i..end].iter().collect();
+                let value = match text.parse::<i64>() {
+                    Ok(v) => EncodeValue::Int(v),
+                    Err(_) => EncodeValue::Double(text.parse::<f64>().ok()?),
+                };
+                output.push_str(&placeholder(values.len()));
+                values.push(value);
+                i = end;
+            }
+            c if is_ident_char(c) => {
+                let mut end = i;
+                while end < chars.len() && is_ident_char(chars[end]) {
+                    end += 1;
+                }
+                let word: String = chars[i..end].iter().collect();
+                if word.eq_ignore_ascii_case("from") {
+                    after_from = true;
+                } else if word.eq_ignore_ascii_case("group") || word.eq_ignore_ascii_case("order") {
+                    after_from = false;
+                }
+                output.push_str(&word);
+                i = end;
+            }
+            c => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    Some(ParameterizedSql {
+        sql: output,
+        param_count,
+        literals: values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameterize() {
+        let sql = parameterize(
+            "select * from t where a = ? and b = '?' -- ?\n and c > ?",
+            false,
+        )
+        .unwrap();
+        assert_eq!(sql.param_count, 2);
+        assert_eq!(
+            sql.sql,
+            "select * from t where a = `?0` and b = '?' -- ?\n and c > `?1`"
+        );
+        let stmt = PreparedStatement::prepare("insert into t values (?, 'a\\'?', ?)").unwrap();
+        assert_eq!(stmt.param_count, 2);
+        assert!(PreparedStatement::prepare("select ?; select ?").is_err());
+
+        let sql = parameterize("select 1, k from t1 where id = 10 and k >= 'a1.5'", true).unwrap();
+        assert_eq!(sql.sql, "select 1, k from t1 where id = `?0` and k >= `?1`");
+        assert_eq!(
+            sql.literals,
+            vec![EncodeValue::Int(10), EncodeValue::Bytes(b"a1.5".to_vec())]
+        );
+        let sql = parameterize("select k from t where id > 1.5", true).unwrap();
+        assert_eq!(sql.literals, vec![EncodeValue::Double(1.5)]);
+        assert!(parameterize("select k from t where id = 1e3", true).is_none());
+        assert!(parameterize("select k from t where k = 'a\\'b'", true).is_none());
+        assert!(parameterize("select k from t where k = ?", true).is_none());
+        let sql = parameterize("select k from t where id > 1 order by 1 limit 2", true).unwrap();
+        assert_eq!(
+            sql.sql,
+            "select k from t where id > `?0` order by 1 limit 2"
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use crate::planner::plan_expression::Expression;
+use crate::planner::{PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+
+pub struct ProjectionPlan {
+    pub input: Box<PlanNode>,
+    pub exprs: Vec<Expression>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for ProjectionPlan {
+    fn name(&self) -> &str {
+        "Projection"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.input.as_ref()]
+    }
+}
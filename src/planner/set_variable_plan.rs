@@ -0,0 +1,14 @@
+use crate::common::EncodeValue;
+use crate::planner::PlanInfo;
+
+/// `SET [SESSION] name = value`, the value is `None` for `DEFAULT`.
+pub struct SetVariablePlan {
+    pub name: String,
+    pub value: Option<EncodeValue>,
+}
+
+impl PlanInfo for SetVariablePlan {
+    fn name(&self) -> &str {
+        "SetVariable"
+    }
+}
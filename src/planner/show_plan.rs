@@ -0,0 +1,60 @@
+use crate::common::EncodeValue;
+use crate::planner::PlanInfo;
+use crate::table::schema::DataSchemaRef;
+
+/// The result of a `SHOW` statement, the rows are collected while planning.
+pub struct ShowPlan {
+    pub schema: DataSchemaRef,
+    pub rows: Vec<Vec<EncodeValue>>,
+}
+
+impl PlanInfo for ShowPlan {
+    fn name(&self) -> &str {
+        "Show"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.rows.len() as f64
+    }
+}
+
+/// Match `value` with a pattern of `LIKE` case-insensitively, `%` matches any string and `_`
+/// matches one character.
+pub fn like_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    // The position to retry from when the last `%` matches one more character.
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut v) = (0, 0);
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '_' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '%' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((bp, bv)) = backtrack {
+            backtrack = Some((bp, bv + 1));
+            p = bp + 1;
+            v = bv + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_match() {
+        assert!(like_match("plan%", "Plan_cache_hits"));
+        assert!(like_match("%cache%", "plan_cache_hits"));
+        assert!(like_match("a_c", "abc"));
+        assert!(!like_match("a_c", "abbc"));
+        assert!(like_match("%", ""));
+        assert!(!like_match("plan", "plan_cache"));
+    }
+}
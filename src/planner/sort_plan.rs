@@ -0,0 +1,47 @@
+use crate::planner::plan_expression::Expression;
+use crate::planner::{ExplainRow, PlanInfo, PlanNode};
+use crate::table::schema::DataSchemaRef;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SortItem {
+    pub expr: Expression,
+    pub asc: bool,
+}
+
+impl fmt::Display for SortItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.asc {
+            write!(f, "{}", self.expr)
+        } else {
+            write!(f, "{} desc", self.expr)
+        }
+    }
+}
+
+/// Sort all rows of the input by `items`, NULL is smaller than any other value.
+pub struct SortPlan {
+    pub input: Box<PlanNode>,
+    pub items: Vec<SortItem>,
+    pub schema: DataSchemaRef,
+    pub est_rows: f64,
+}
+
+impl PlanInfo for SortPlan {
+    fn name(&self) -> &str {
+        "Sort"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        let items: Vec<String> = self.items.iter().map(|i| i.to_string()).collect();
+        row.filters = items.join(", ");
+    }
+
+    fn children(&self) -> Vec<&PlanNode> {
+        vec![self.input.as_ref()]
+    }
+}
@@ -0,0 +1,32 @@
+use crate::planner::plan_expression::Expression;
+use crate::planner::point_get_plan::join_expressions;
+use crate::planner::{ExplainRow, PlanInfo};
+use crate::session::SessionRef;
+use crate::table::schema::DataSchemaRef;
+use crate::table::SystemTable;
+
+/// Read a table of `information_schema`, whose rows are built when the plan is executed.
+/// `filters` are bound to the columns of the table ordered by their offset, and `columns` are
+/// the columns returned.
+pub struct SystemTableScanPlan {
+    pub table: SystemTable,
+    pub columns: DataSchemaRef,
+    pub filters: Vec<Expression>,
+    pub est_rows: f64,
+    pub session: SessionRef,
+}
+
+impl PlanInfo for SystemTableScanPlan {
+    fn name(&self) -> &str {
+        "SystemTableScan"
+    }
+
+    fn est_rows(&self) -> f64 {
+        self.est_rows
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.table.name().to_string();
+        row.filters = join_expressions(&self.filters);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::planner::PlanInfo;
+use crate::table::schema::DataSchemaRef;
+
+/// Produce one empty row, it is the input of `SELECT` without `FROM`.
+pub struct TableDualPlan {
+    pub schema: DataSchemaRef,
+}
+
+impl PlanInfo for TableDualPlan {
+    fn name(&self) -> &str {
+        "TableDual"
+    }
+
+    fn est_rows(&self) -> f64 {
+        1.0
+    }
+}
@@ -0,0 +1,41 @@
+use crate::planner::{ExplainRow, PlanInfo};
+use crate::table::schema::ViewInfo;
+
+/// `CREATE [OR REPLACE] VIEW name [(column, ...)] AS query`, the query has been checked by
+/// planning it once.
+pub struct CreateViewPlan {
+    pub db: String,
+    pub view: ViewInfo,
+    pub or_replace: bool,
+}
+
+impl PlanInfo for CreateViewPlan {
+    fn name(&self) -> &str {
+        "CreateView"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        row.table = self.view.name.clone();
+    }
+}
+
+/// `DROP VIEW [IF EXISTS] name [, name] ...`, the views are resolved to their databases.
+pub struct DropViewPlan {
+    pub views: Vec<(String, String)>,
+    pub if_exists: bool,
+}
+
+impl PlanInfo for DropViewPlan {
+    fn name(&self) -> &str {
+        "DropView"
+    }
+
+    fn explain_info(&self, row: &mut ExplainRow) {
+        let names: Vec<String> = self
+            .views
+            .iter()
+            .map(|(db, name)| format!("{}.{}", db, name))
+            .collect();
+        row.table = names.join(", ");
+    }
+}
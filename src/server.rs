@@ -48,8 +48,9 @@ impl Server {
         let listener = tokio::net::TcpListener::bind(address.as_str()).await?;
         let port = listener.local_addr().unwrap().port();
         println!("listening on port: {}", port);
-        while let Ok((stream, _)) = listener.accept().await {
+        while let Ok((stream, addr)) = listener.accept().await {
             let conn = core.create_connection();
+            conn.set_client_address(addr);
             self.pool
                 .spawn(async move { MysqlIntermediary::run_on_tcp(conn, stream).await });
         }
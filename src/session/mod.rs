@@ -1,2 +1,6 @@
+mod process;
 mod session;
-pub use session::{Session, SessionRef};
+mod variables;
+pub use process::{ProcessList, ProcessRef};
+pub use session::{Session, SessionOptions, SessionRef};
+pub use variables::{SessionVariables, DEFAULT_MEM_QUOTA_QUERY};
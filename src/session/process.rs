@@ -0,0 +1,249 @@
+use crate::common::EncodeValue;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::oneshot;
+
+pub type ProcessRef = Arc<Process>;
+
+/// The characters of a statement shown by `SHOW PROCESSLIST` without `FULL`.
+const PROCESS_INFO_LENGTH: usize = 100;
+
+/// The connections of a server, by their ids.
+pub struct ProcessList {
+    next_id: AtomicU64,
+    processes: Mutex<HashMap<u64, ProcessRef>>,
+}
+
+impl ProcessList {
+    pub fn new() -> Arc<ProcessList> {
+        Arc::new(ProcessList {
+            next_id: AtomicU64::new(1),
+            processes: Mutex::new(HashMap::default()),
+        })
+    }
+
+    /// Give a new connection its id.
+    pub fn register(&self) -> ProcessRef {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let process = Arc::new(Process {
+            id,
+            state: Mutex::new(ProcessState {
+                since: Instant::now(),
+                host: String::new(),
+                db: String::new(),
+                statement: None,
+                state: "",
+                interrupted: false,
+                killed: false,
+                interrupt: None,
+            }),
+        });
+        self.processes.lock().unwrap().insert(id, process.clone());
+        process
+    }
+
+    pub fn unregister(&self, id: u64) {
+        self.processes.lock().unwrap().remove(&id);
+    }
+
+    pub fn get(&self, id: u64) -> Option<ProcessRef> {
+        self.processes.lock().unwrap().get(&id).cloned()
+    }
+
+    /// The processes ordered by their ids.
+    pub fn list(&self) -> Vec<ProcessRef> {
+        let mut processes: Vec<ProcessRef> =
+            self.processes.lock().unwrap().values().cloned().collect();
+        processes.sort_by_key(|p| p.id);
+        processes
+    }
+}
+
+/// A connection and the statement it is running.
+pub struct Process {
+    pub id: u64,
+    state: Mutex<ProcessState>,
+}
+
+struct ProcessState {
+    /// When the current statement started, or the previous one ended.
+    since: Instant,
+    /// The address of the client.
+    host: String,
+    db: String,
+    statement: Option<String>,
+    state: &'static str,
+    interrupted: bool,
+    /// The connection is closed before its next command.
+    killed: bool,
+    /// Interrupt the running statement.
+    interrupt: Option<oneshot::Sender<()>>,
+}
+
+/// A snapshot of a process as shown by `SHOW PROCESSLIST`.
+pub struct ProcessInfo {
+    pub id: u64,
+    pub host: String,
+    pub db: Option<String>,
+    pub command: &'static str,
+    pub time: u64,
+    pub state: &'static str,
+    pub info: Option<String>,
+}
+
+impl ProcessInfo {
+    /// The values of the columns of `SHOW PROCESSLIST`, the statement is truncated unless
+    /// `full`. The user is always empty, the handshake does not tell it to the server.
+    pub fn to_row(&self, full: bool) -> Vec<EncodeValue> {
+        let text = |v: &str| EncodeValue::Bytes(v.as_bytes().to_vec());
+        let info = self.info.as_ref().map(|info| {
+            if full {
+                info.clone()
+            } else {
+                info.chars().take(PROCESS_INFO_LENGTH).collect()
+            }
+        });
+        vec![
+            EncodeValue::Int(self.id as i64),
+            text(""),
+            text(&self.host),
+            self.db.as_deref().map_or(EncodeValue::NULL, text),
+            text(self.command),
+            EncodeValue::Int(self.time as i64),
+            text(self.state),
+            info.map_or(EncodeValue::NULL, |v| EncodeValue::Bytes(v.into_bytes())),
+        ]
+    }
+}
+
+impl Process {
+    /// Record the address of the client.
+    pub fn set_host(&self, host: &str) {
+        self.state.lock().unwrap().host = host.to_string();
+    }
+
+    /// Record the current database of the connection.
+    pub fn set_db(&self, db: &str) {
+        self.state.lock().unwrap().db = db.to_string();
+    }
+
+    /// Record the statement which starts running, the receiver is told once it is interrupted.
+    pub fn start(&self, statement: &str) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        let mut state = self.state.lock().unwrap();
+        state.since = Instant::now();
+        state.statement = Some(statement.to_string());
+        state.state = "executing";
+        state.interrupted = false;
+        state.interrupt = Some(tx);
+        rx
+    }
+
+    /// Return whether the statement which ends has been interrupted.
+    pub fn finish(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.since = Instant::now();
+        state.statement = None;
+        state.state = "";
+        state.interrupt = None;
+        state.interrupted
+    }
+
+    /// Interrupt the running statement, `reason` is shown as its state until it ends.
+    pub fn interrupt(&self, reason: &'static str) {
+        let mut state = self.state.lock().unwrap();
+        if state.statement.is_none() || state.interrupted {
+            return;
+        }
+        state.interrupted = true;
+        state.state = reason;
+        if let Some(tx) = state.interrupt.take() {
+            let _ = tx.send(());
+        }
+    }
+
+    /// Interrupt the running statement, and also close the connection unless `query_only`.
+    pub fn kill(&self, query_only: bool) {
+        if !query_only {
+            self.state.lock().unwrap().killed = true;
+        }
+        self.interrupt("killed");
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.state.lock().unwrap().killed
+    }
+
+    pub fn info(&self) -> ProcessInfo {
+        let state = self.state.lock().unwrap();
+        let command = if state.killed {
+            "Killed"
+        } else if state.statement.is_some() {
+            "Query"
+        } else {
+            "Sleep"
+        };
+        ProcessInfo {
+            id: self.id,
+            host: state.host.clone(),
+            db: Some(state.db.clone()).filter(|db| !db.is_empty()),
+            command,
+            time: state.since.elapsed().as_secs(),
+            state: state.state,
+            info: state.statement.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_process() {
+        let processes = ProcessList::new();
+        let a = processes.register();
+        let b = processes.register();
+        assert_eq!(
+            processes.list().iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // Killing an idle connection does not interrupt its next statement.
+        a.kill(true);
+        let mut rx = a.start("select 1");
+        assert!(rx.try_recv().is_err());
+        a.kill(true);
+        assert!(rx.try_recv().is_ok());
+        let info = a.info();
+        assert_eq!((info.command, info.state), ("Query", "killed"));
+        assert!(a.finish());
+        assert!(!a.is_killed());
+
+        let _rx = b.start("select 2");
+        b.kill(false);
+        assert!(b.is_killed());
+        assert_eq!(b.info().command, "Killed");
+        processes.unregister(b.id);
+        assert!(processes.get(b.id).is_none());
+
+        let c = processes.register();
+        c.set_host("127.0.0.1:3306");
+        c.set_db("test");
+        let statement = format!("select '{}'", "a".repeat(200));
+        let _rx = c.start(&statement);
+        let row = c.info().to_row(false);
+        assert_eq!(row[1], EncodeValue::Bytes(vec![]));
+        assert_eq!(row[2], EncodeValue::Bytes(b"127.0.0.1:3306".to_vec()));
+        assert_eq!(row[3], EncodeValue::Bytes(b"test".to_vec()));
+        assert_eq!(
+            row[7],
+            EncodeValue::Bytes(statement[..100].as_bytes().to_vec())
+        );
+        let row = c.info().to_row(true);
+        assert_eq!(row[7], EncodeValue::Bytes(statement.into_bytes()));
+        assert_eq!(a.info().to_row(true)[3], EncodeValue::NULL);
+    }
+}
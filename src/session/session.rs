@@ -1,28 +1,81 @@
-use crate::errors::MySQLResult;
-use crate::store::Transaction;
-use crate::table::schema::TableInfo;
+use super::{ProcessList, ProcessRef, SessionVariables};
+use crate::common::{EncodeValue, MemTrackerRef};
+use crate::errors::{MySQLError, MySQLResult};
+use crate::planner::{PlanCache, PreparedStatement};
+use crate::statistics::TableStatistics;
+use crate::store::{DeleteRangeWorker, Storage, Transaction};
+use crate::table::schema::{TableInfo, ViewInfo};
 use crate::table::table::TableSource;
-use crate::table::DBTableManager;
+use crate::table::{is_system_database, DBTableManager};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 
+/// What the sessions of a server share: the directory of temporary files, the defaults of the
+/// variables, the memory tracker of the server, its connections and the worker which deletes
+/// the data of dropped tables.
+#[derive(Clone)]
+pub struct SessionOptions {
+    pub tmp_dir: PathBuf,
+    pub variables: SessionVariables,
+    pub memory: MemTrackerRef,
+    pub processes: Arc<ProcessList>,
+    pub delete_ranges: Arc<DeleteRangeWorker>,
+}
+
 pub struct Session {
-    cache: HashMap<String, Arc<TableSource>>,
+    /// The tables read by the session, by their databases and names.
+    cache: HashMap<(String, String), Arc<TableSource>>,
     table_mgr: Arc<RwLock<DBTableManager>>,
+    plan_cache: Arc<PlanCache>,
+    /// The current database, it is empty if none is selected.
     db: String,
     transaction: Option<Box<dyn Transaction>>,
+    statements: HashMap<u32, Arc<PreparedStatement>>,
+    next_statement_id: u32,
+    variables: SessionVariables,
+    options: SessionOptions,
+    /// The memory held by the session, whose parent is the tracker of the server.
+    memory: MemTrackerRef,
+    /// The connection of the session in the process list of the server.
+    process: ProcessRef,
+    /// The first `AUTO_INCREMENT` value generated by the last `INSERT` which generated any,
+    /// which `LAST_INSERT_ID()` returns.
+    last_insert_id: u64,
+    /// The first `AUTO_INCREMENT` value generated by the current statement, or 0, which is sent
+    /// to the client with the result of the statement.
+    insert_id: u64,
+    /// The warnings of the last statement which may raise any, which `SHOW WARNINGS` lists.
+    warnings: Vec<MySQLError>,
     pub is_in_txn: bool,
 }
 
 pub type SessionRef = Arc<Mutex<Session>>;
 
 impl Session {
-    pub fn new(table_mgr: Arc<RwLock<DBTableManager>>) -> Session {
+    pub fn new(
+        table_mgr: Arc<RwLock<DBTableManager>>,
+        plan_cache: Arc<PlanCache>,
+        options: SessionOptions,
+    ) -> Session {
+        let variables = options.variables.clone();
+        let memory = options.memory.child("session", variables.mem_quota_session);
+        let process = options.processes.register();
         Session {
             table_mgr,
+            plan_cache,
             cache: HashMap::default(),
             db: "".to_string(),
             transaction: None,
+            statements: HashMap::default(),
+            next_statement_id: 0,
+            variables,
+            options,
+            memory,
+            process,
+            last_insert_id: 0,
+            insert_id: 0,
+            warnings: vec![],
             is_in_txn: false,
         }
     }
@@ -35,34 +88,260 @@ impl Session {
         self.transaction = Some(txn)
     }
 
-    pub fn add_table(&mut self, name: String, table_info: TableInfo) {
+    /// Whether the session holds a transaction across statements, whose writes are only
+    /// visible through the transaction itself.
+    pub fn has_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    pub fn variables(&self) -> &SessionVariables {
+        &self.variables
+    }
+
+    pub fn tmp_dir(&self) -> PathBuf {
+        self.options.tmp_dir.clone()
+    }
+
+    pub fn memory_tracker(&self) -> MemTrackerRef {
+        self.memory.clone()
+    }
+
+    pub fn process(&self) -> ProcessRef {
+        self.process.clone()
+    }
+
+    pub fn processes(&self) -> Arc<ProcessList> {
+        self.options.processes.clone()
+    }
+
+    pub fn delete_ranges(&self) -> Arc<DeleteRangeWorker> {
+        self.options.delete_ranges.clone()
+    }
+
+    pub fn last_insert_id(&self) -> u64 {
+        self.last_insert_id
+    }
+
+    pub fn set_last_insert_id(&mut self, id: u64) {
+        self.last_insert_id = id;
+        self.insert_id = id;
+    }
+
+    /// The first `AUTO_INCREMENT` value generated by the statement which has just finished.
+    pub fn take_insert_id(&mut self) -> u64 {
+        std::mem::take(&mut self.insert_id)
+    }
+
+    pub fn warnings(&self) -> &[MySQLError] {
+        &self.warnings
+    }
+
+    pub fn set_warnings(&mut self, warnings: Vec<MySQLError>) {
+        self.warnings = warnings;
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: Option<&EncodeValue>) -> MySQLResult<()> {
+        self.variables.set(name, value, &self.options.variables)?;
+        self.memory.set_limit(self.variables.mem_quota_session);
+        Ok(())
+    }
+
+    /// Register a prepared statement and return the id which the client uses to execute it.
+    pub fn add_prepared_statement(&mut self, stmt: PreparedStatement) -> u32 {
+        self.next_statement_id += 1;
+        self.statements
+            .insert(self.next_statement_id, Arc::new(stmt));
+        self.next_statement_id
+    }
+
+    pub fn get_prepared_statement(&self, id: u32) -> Option<Arc<PreparedStatement>> {
+        self.statements.get(&id).cloned()
+    }
+
+    pub fn remove_prepared_statement(&mut self, id: u32) {
+        self.statements.remove(&id);
+    }
+
+    pub fn create_database(&mut self, db: String) -> MySQLResult<()> {
+        if is_system_database(&db) {
+            return Err(MySQLError::DBExists(db));
+        }
+        self.table_mgr.write().unwrap().create_database(db)
+    }
+
+    /// Drop a database, the session has no current database afterwards if it is dropped. The
+    /// data of its tables is deleted from `storage` in background.
+    pub fn drop_database(&mut self, db: &str, storage: &Arc<dyn Storage>) -> MySQLResult<()> {
+        // The references between the tables of the database are dropped with them.
+        let names = self.table_mgr.read().unwrap().table_names(db)?;
+        let dropped: Vec<(String, String)> =
+            names.into_iter().map(|n| (db.to_string(), n)).collect();
+        for (db, name) in dropped.iter() {
+            self.check_referenced(db, name, &dropped)?;
+        }
+        let tables = self.table_mgr.write().unwrap().drop_database(db)?;
+        for table in tables.iter() {
+            self.delete_table_data(table, storage)?;
+        }
+        self.cache.retain(|(d, _), _| d != db);
+        if self.db == db {
+            self.process.set_db("");
+            self.db.clear();
+        }
+        Ok(())
+    }
+
+    pub fn add_table(&mut self, db: &str, name: String, table_info: TableInfo) -> MySQLResult<()> {
         let mut tables = self.table_mgr.write().unwrap();
-        let table = tables.add_table(name.clone(), table_info);
-        self.cache.insert(name, table);
+        let table = tables.add_table(db, name.clone(), table_info)?;
+        self.cache.insert((db.to_string(), name), table);
+        Ok(())
     }
 
-    pub fn replace_table(&mut self, name: String, table_info: TableInfo) {
+    pub fn replace_table(
+        &mut self,
+        db: &str,
+        name: String,
+        table_info: TableInfo,
+    ) -> MySQLResult<()> {
         let mut tables = self.table_mgr.write().unwrap();
-        let table = tables.replace_table(name.clone(), table_info);
-        self.cache.insert(name, table);
+        let table = tables.replace_table(db, name.clone(), table_info)?;
+        self.cache.insert((db.to_string(), name), table);
+        Ok(())
+    }
+
+    /// Drop a table, its data is deleted from `storage` in background.
+    pub fn drop_table(
+        &mut self,
+        db: &str,
+        name: &str,
+        storage: &Arc<dyn Storage>,
+    ) -> MySQLResult<()> {
+        let table = self.table_mgr.write().unwrap().drop_table(db, name)?;
+        self.cache.remove(&(db.to_string(), name.to_string()));
+        self.delete_table_data(&table, storage)
+    }
+
+    /// Fail if a foreign key of another table references `db.name` while `foreign_key_checks`
+    /// is on, as the rows it references would be removed. The tables in `ignored` are removed
+    /// together with it.
+    pub fn check_referenced(
+        &self,
+        db: &str,
+        name: &str,
+        ignored: &[(String, String)],
+    ) -> MySQLResult<()> {
+        if !self.variables().foreign_key_checks {
+            return Ok(());
+        }
+        match self
+            .table_mgr
+            .read()
+            .unwrap()
+            .find_reference(db, name, ignored)
+        {
+            Some(fk) => Err(MySQLError::RowIsReferenced(fk.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Empty a table by replacing it with a new one, the data of the old one is deleted from
+    /// `storage` in background.
+    pub fn truncate_table(
+        &mut self,
+        db: &str,
+        name: &str,
+        storage: &Arc<dyn Storage>,
+    ) -> MySQLResult<()> {
+        let table = self.table_mgr.write().unwrap().truncate_table(db, name)?;
+        self.cache.remove(&(db.to_string(), name.to_string()));
+        self.delete_table_data(&table, storage)
+    }
+
+    /// Move a table to another name, which may be in another database.
+    pub fn rename_table(
+        &mut self,
+        db: &str,
+        name: &str,
+        new_db: &str,
+        new_name: String,
+    ) -> MySQLResult<()> {
+        let mut tables = self.table_mgr.write().unwrap();
+        tables.rename_table(db, name, new_db, new_name)?;
+        self.cache.remove(&(db.to_string(), name.to_string()));
+        Ok(())
+    }
+
+    pub fn create_view(&mut self, db: &str, view: ViewInfo, or_replace: bool) -> MySQLResult<()> {
+        self.table_mgr
+            .write()
+            .unwrap()
+            .create_view(db, view, or_replace)
+    }
+
+    pub fn drop_view(&mut self, db: &str, name: &str) -> MySQLResult<()> {
+        self.table_mgr.write().unwrap().drop_view(db, name)
     }
 
-    pub fn get_table(&mut self, name: &String) -> Option<Arc<TableSource>> {
-        if let Some(table) = self.cache.get(name) {
+    pub fn get_view(&self, db: &str, name: &str) -> Option<Arc<ViewInfo>> {
+        self.table_mgr.read().unwrap().get_view(db, name)
+    }
+
+    /// Delete the data of a table, which is no longer in the catalog, in background.
+    pub fn delete_table_data(
+        &self,
+        table: &TableSource,
+        storage: &Arc<dyn Storage>,
+    ) -> MySQLResult<()> {
+        let range = table.table_range()?;
+        self.options.delete_ranges.schedule(storage.clone(), range);
+        Ok(())
+    }
+
+    pub fn get_table(&mut self, db: &str, name: &str) -> Option<Arc<TableSource>> {
+        let key = (db.to_string(), name.to_string());
+        if let Some(table) = self.cache.get(&key) {
             if table.is_valid() {
                 return Some(table.clone());
             }
         }
-        let table = self.table_mgr.read().unwrap().get_table(name);
-        if let Some(t) = table.as_ref() {
-            self.cache.insert(name.clone(), t.clone());
-        }
+        let table = self.table_mgr.read().unwrap().get_table(db, name);
+        match table.as_ref() {
+            Some(t) => self.cache.insert(key, t.clone()),
+            None => self.cache.remove(&key),
+        };
         table
     }
 
+    pub fn get_table_manager(&self) -> Arc<RwLock<DBTableManager>> {
+        self.table_mgr.clone()
+    }
+
+    pub fn get_plan_cache(&self) -> Arc<PlanCache> {
+        self.plan_cache.clone()
+    }
+
+    pub fn get_schema_version(&self) -> u64 {
+        self.table_mgr.read().unwrap().schema_version()
+    }
+
+    pub fn get_table_stats(&self, table_id: u64) -> Option<Arc<TableStatistics>> {
+        self.table_mgr.read().unwrap().get_stats(table_id)
+    }
+
+    pub fn update_table_stats(&mut self, stats: TableStatistics) {
+        self.table_mgr.write().unwrap().update_stats(stats);
+    }
+
+    /// Change the current database, which must exist.
     pub fn set_db(&mut self, name: String) -> MySQLResult<()> {
+        let exists =
+            is_system_database(&name) || self.table_mgr.read().unwrap().has_database(&name);
+        if !exists {
+            return Err(MySQLError::NoDB(name));
+        }
+        self.process.set_db(&name);
         self.db = name;
-        // TODO: get db info
         Ok(())
     }
 
@@ -70,3 +349,9 @@ impl Session {
         &self.db
     }
 }
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.options.processes.unregister(self.process.id);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::common::{EncodeValue, OomAction};
+use crate::errors::{MySQLError, MySQLResult};
+
+pub const DEFAULT_EXECUTOR_CONCURRENCY: usize = 4;
+pub const DEFAULT_MEM_QUOTA_QUERY: usize = 1 << 30;
+/// The largest value of `auto_increment_increment` and `auto_increment_offset`, as MySQL.
+const MAX_AUTO_INCREMENT_STEP: usize = 65535;
+/// The default `sql_mode` of MySQL 8.0.
+const DEFAULT_SQL_MODE: &str = "ONLY_FULL_GROUP_BY,STRICT_TRANS_TABLES,NO_ZERO_IN_DATE,\
+                                NO_ZERO_DATE,ERROR_FOR_DIVISION_BY_ZERO,NO_ENGINE_SUBSTITUTION";
+/// The modes which `sql_mode` accepts, only the strict modes change the behavior here.
+const SQL_MODES: &[&str] = &[
+    "ALLOW_INVALID_DATES",
+    "ANSI_QUOTES",
+    "ERROR_FOR_DIVISION_BY_ZERO",
+    "HIGH_NOT_PRECEDENCE",
+    "IGNORE_SPACE",
+    "NO_AUTO_VALUE_ON_ZERO",
+    "NO_BACKSLASH_ESCAPES",
+    "NO_DIR_IN_CREATE",
+    "NO_ENGINE_SUBSTITUTION",
+    "NO_UNSIGNED_SUBTRACTION",
+    "NO_ZERO_DATE",
+    "NO_ZERO_IN_DATE",
+    "ONLY_FULL_GROUP_BY",
+    "PAD_CHAR_TO_FULL_LENGTH",
+    "PIPES_AS_CONCAT",
+    "REAL_AS_FLOAT",
+    "STRICT_ALL_TABLES",
+    "STRICT_TRANS_TABLES",
+    "TIME_TRUNCATE_FRACTIONAL",
+];
+
+/// The system variables of a session, which are changed by `SET`. They are read when a
+/// statement is executed, so a change applies from the next statement.
+#[derive(Clone, Debug)]
+pub struct SessionVariables {
+    /// The difference between the `AUTO_INCREMENT` values allocated one after another.
+    pub auto_increment_increment: u64,
+    /// The value the `AUTO_INCREMENT` values start from, every value is this plus a multiple of
+    /// `auto_increment_increment`.
+    pub auto_increment_offset: u64,
+    /// Whether foreign keys are checked, it is turned off to load tables which reference each
+    /// other in any order.
+    pub foreign_key_checks: bool,
+    /// The milliseconds which a statement may run before it is interrupted, 0 means no limit.
+    pub max_execution_time: u64,
+    /// The number of workers which read the ranges of a scan and aggregate them concurrently,
+    /// every operator runs serially if it is 1.
+    pub executor_concurrency: usize,
+    /// The bytes which the operators of one query may hold, 0 means no limit.
+    pub mem_quota_query: usize,
+    /// The bytes which a session may hold, including the writes buffered by its transaction and
+    /// its running query, 0 means no limit.
+    pub mem_quota_session: usize,
+    /// What a query does when a memory quota is exceeded.
+    pub oom_action: OomAction,
+    /// The modes separated by commas, in upper case.
+    pub sql_mode: String,
+}
+
+impl Default for SessionVariables {
+    fn default() -> SessionVariables {
+        SessionVariables {
+            auto_increment_increment: 1,
+            auto_increment_offset: 1,
+            foreign_key_checks: true,
+            max_execution_time: 0,
+            executor_concurrency: DEFAULT_EXECUTOR_CONCURRENCY,
+            mem_quota_query: DEFAULT_MEM_QUOTA_QUERY,
+            mem_quota_session: 0,
+            oom_action: OomAction::Spill,
+            sql_mode: DEFAULT_SQL_MODE.to_string(),
+        }
+    }
+}
+
+impl SessionVariables {
+    /// Set a variable, or reset it to its value in `default` if `value` is `None`.
+    pub fn set(
+        &mut self,
+        name: &str,
+        value: Option<&EncodeValue>,
+        default: &SessionVariables,
+    ) -> MySQLResult<()> {
+        match name.to_lowercase().as_str() {
+            "auto_increment_increment" => {
+                self.auto_increment_increment = match value {
+                    Some(v) => auto_increment_step(name, v)?,
+                    None => default.auto_increment_increment,
+                }
+            }
+            "auto_increment_offset" => {
+                self.auto_increment_offset = match value {
+                    Some(v) => auto_increment_step(name, v)?,
+                    None => default.auto_increment_offset,
+                }
+            }
+            "foreign_key_checks" => {
+                self.foreign_key_checks = match value {
+                    Some(v) => switch(name, v)?,
+                    None => default.foreign_key_checks,
+                }
+            }
+            "max_execution_time" => {
+                self.max_execution_time = match value {
+                    Some(v) => non_negative_integer(name, v)? as u64,
+                    None => default.max_execution_time,
+                }
+            }
+            "naivedb_executor_concurrency" => {
+                self.executor_concurrency = match value {
+                    Some(v) => positive_integer(name, v)?,
+                    None => default.executor_concurrency,
+                }
+            }
+            "naivedb_mem_quota_query" => {
+                self.mem_quota_query = match value {
+                    Some(v) => non_negative_integer(name, v)?,
+                    None => default.mem_quota_query,
+                }
+            }
+            "naivedb_mem_quota_session" => {
+                self.mem_quota_session = match value {
+                    Some(v) => non_negative_integer(name, v)?,
+                    None => default.mem_quota_session,
+                }
+            }
+            "naivedb_mem_oom_action" => {
+                self.oom_action = match value {
+                    Some(v) => oom_action(name, v)?,
+                    None => default.oom_action,
+                }
+            }
+            "sql_mode" => {
+                self.sql_mode = match value {
+                    Some(v) => sql_mode(name, v)?,
+                    None => default.sql_mode.clone(),
+                }
+            }
+            _ => return Err(MySQLError::UnknownSystemVariable(name.to_string())),
+        }
+        Ok(())
+    }
+
+    /// The names and values of all variables, ordered by name.
+    pub fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "auto_increment_increment",
+                self.auto_increment_increment.to_string(),
+            ),
+            (
+                "auto_increment_offset",
+                self.auto_increment_offset.to_string(),
+            ),
+            (
+                "foreign_key_checks",
+                switch_name(self.foreign_key_checks).to_string(),
+            ),
+            ("max_execution_time", self.max_execution_time.to_string()),
+            (
+                "naivedb_executor_concurrency",
+                self.executor_concurrency.to_string(),
+            ),
+            ("naivedb_mem_oom_action", self.oom_action.name().to_string()),
+            ("naivedb_mem_quota_query", self.mem_quota_query.to_string()),
+            (
+                "naivedb_mem_quota_session",
+                self.mem_quota_session.to_string(),
+            ),
+            ("sql_mode", self.sql_mode.clone()),
+        ]
+    }
+
+    /// Whether a value which does not fit its column fails the statement, instead of being
+    /// adjusted with a warning. Every table is transactional, so both strict modes apply.
+    pub fn strict_mode(&self) -> bool {
+        self.sql_mode
+            .split(',')
+            .any(|m| m == "STRICT_TRANS_TABLES" || m == "STRICT_ALL_TABLES")
+    }
+}
+
+fn positive_integer(name: &str, value: &EncodeValue) -> MySQLResult<usize> {
+    match value {
+        EncodeValue::Int(v) if *v > 0 => Ok(*v as usize),
+        v => Err(wrong_value(name, v)),
+    }
+}
+
+fn non_negative_integer(name: &str, value: &EncodeValue) -> MySQLResult<usize> {
+    match value {
+        EncodeValue::Int(v) if *v >= 0 => Ok(*v as usize),
+        v => Err(wrong_value(name, v)),
+    }
+}
+
+fn auto_increment_step(name: &str, value: &EncodeValue) -> MySQLResult<u64> {
+    match positive_integer(name, value)? {
+        v if v <= MAX_AUTO_INCREMENT_STEP => Ok(v as u64),
+        _ => Err(wrong_value(name, value)),
+    }
+}
+
+/// A variable which is turned on by `ON` or 1, and off by `OFF` or 0.
+fn switch(name: &str, value: &EncodeValue) -> MySQLResult<bool> {
+    match value {
+        EncodeValue::Int(1) => Ok(true),
+        EncodeValue::Int(0) => Ok(false),
+        EncodeValue::Bytes(v) if v.eq_ignore_ascii_case(b"on") => Ok(true),
+        EncodeValue::Bytes(v) if v.eq_ignore_ascii_case(b"off") => Ok(false),
+        v => Err(wrong_value(name, v)),
+    }
+}
+
+fn switch_name(on: bool) -> &'static str {
+    if on {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+fn oom_action(name: &str, value: &EncodeValue) -> MySQLResult<OomAction> {
+    let action = match value {
+        EncodeValue::Bytes(v) => std::str::from_utf8(v).ok().and_then(OomAction::from_name),
+        _ => None,
+    };
+    action.ok_or_else(|| wrong_value(name, value))
+}
+
+fn sql_mode(name: &str, value: &EncodeValue) -> MySQLResult<String> {
+    let modes = match value {
+        EncodeValue::Bytes(v) => std::str::from_utf8(v).ok(),
+        _ => None,
+    };
+    let mut result: Vec<String> = vec![];
+    for mode in modes.ok_or_else(|| wrong_value(name, value))?.split(',') {
+        let mode = mode.trim().to_uppercase();
+        if mode.is_empty() || result.contains(&mode) {
+            continue;
+        }
+        if !SQL_MODES.contains(&mode.as_str()) {
+            return Err(wrong_value(name, value));
+        }
+        result.push(mode);
+    }
+    Ok(result.join(","))
+}
+
+fn wrong_value(name: &str, value: &EncodeValue) -> MySQLError {
+    MySQLError::WrongValueForVariable(name.to_string(), String::from(value.clone()))
+}
@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+
+/// A bucket of an equal-depth histogram, the bounds are values in memcomparable format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    pub lower: Vec<u8>,
+    pub upper: Vec<u8>,
+    /// The number of values in this bucket and all buckets before it.
+    pub count: u64,
+    /// The number of values which equal to `upper`.
+    pub repeats: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    pub buckets: Vec<Bucket>,
+}
+
+impl Histogram {
+    /// Build an equal-depth histogram from values which have been sorted. All the equal values
+    /// are put into the same bucket, so a bucket may hold more values than the others.
+    pub fn build(sorted: &[Vec<u8>], bucket_count: usize) -> Histogram {
+        let bucket_count = std::cmp::max(bucket_count, 1);
+        let depth = std::cmp::max((sorted.len() + bucket_count - 1) / bucket_count, 1) as u64;
+        let mut buckets: Vec<Bucket> = vec![];
+        let mut last_count = 0;
+        for v in sorted {
+            if let Some(last) = buckets.last_mut() {
+                if last.upper == *v {
+                    last.count += 1;
+                    last.repeats += 1;
+                    continue;
+                }
+                if last.count - last_count < depth {
+                    last.upper = v.clone();
+                    last.count += 1;
+                    last.repeats = 1;
+                    continue;
+                }
+                last_count = last.count;
+            }
+            buckets.push(Bucket {
+                lower: v.clone(),
+                upper: v.clone(),
+                count: last_count + 1,
+                repeats: 1,
+            });
+        }
+        Histogram { buckets }
+    }
+
+    pub fn total_count(&self) -> u64 {
+        self.buckets.last().map_or(0, |b| b.count)
+    }
+
+    /// Find the first bucket whose upper bound is not less than `value`.
+    fn locate(&self, value: &[u8]) -> Option<usize> {
+        let idx = match self
+            .buckets
+            .binary_search_by(|b| b.upper.as_slice().cmp(value))
+        {
+            Ok(idx) => idx,
+            Err(idx) => idx,
+        };
+        if idx < self.buckets.len() {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    fn prev_count(&self, idx: usize) -> u64 {
+        if idx == 0 {
+            0
+        } else {
+            self.buckets[idx - 1].count
+        }
+    }
+
+    /// Estimate the number of values which equal to `value`, return `None` if the value falls
+    /// into the middle of a bucket and the histogram can not tell the exact count.
+    pub fn equal_count(&self, value: &[u8]) -> Option<f64> {
+        match self.locate(value) {
+            None => Some(0.0),
+            Some(idx) => {
+                let bucket = &self.buckets[idx];
+                if bucket.upper.as_slice() == value {
+                    Some(bucket.repeats as f64)
+                } else if bucket.lower.as_slice() > value {
+                    Some(0.0)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Estimate the number of values which are less than `value`.
+    pub fn less_count(&self, value: &[u8]) -> f64 {
+        match self.locate(value) {
+            None => self.total_count() as f64,
+            Some(idx) => {
+                let bucket = &self.buckets[idx];
+                let prev = self.prev_count(idx);
+                match bucket.lower.as_slice().cmp(value) {
+                    Ordering::Greater | Ordering::Equal => prev as f64,
+                    Ordering::Less => {
+                        if bucket.upper.as_slice() == value {
+                            (bucket.count - bucket.repeats) as f64
+                        } else {
+                            // Assume the values are distributed uniformly in the bucket.
+                            prev as f64 + (bucket.count - bucket.repeats - prev) as f64 / 2.0
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Estimate the number of values in `[lower, upper)`.
+    pub fn range_count(&self, lower: &[u8], upper: &[u8]) -> f64 {
+        let count = self.less_count(upper) - self.less_count(lower);
+        if count < 0.0 {
+            0.0
+        } else {
+            count
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(data: &[u8]) -> Vec<Vec<u8>> {
+        data.iter().map(|v| vec![*v]).collect()
+    }
+
+    #[test]
+    fn test_build_histogram() {
+        let sorted = values(&[1, 1, 1, 2, 3, 4, 5, 5, 6, 7]);
+        let hist = Histogram::build(&sorted, 3);
+        assert_eq!(hist.total_count(), 10);
+        assert_eq!(hist.buckets.len(), 3);
+        assert_eq!(hist.buckets[0].lower, vec![1]);
+        assert_eq!(hist.buckets[0].upper, vec![2]);
+        assert_eq!(hist.buckets[0].count, 4);
+        assert_eq!(hist.buckets[0].repeats, 1);
+        assert_eq!(hist.buckets[1].lower, vec![3]);
+        assert_eq!(hist.buckets[1].upper, vec![5]);
+        assert_eq!(hist.buckets[1].count, 8);
+        assert_eq!(hist.buckets[1].repeats, 2);
+        assert_eq!(hist.buckets[2].lower, vec![6]);
+        assert_eq!(hist.buckets[2].upper, vec![7]);
+        assert_eq!(hist.buckets[2].count, 10);
+    }
+
+    #[test]
+    fn test_estimate_count() {
+        let sorted = values(&[1, 1, 1, 2, 3, 4, 5, 5, 6, 7]);
+        let hist = Histogram::build(&sorted, 3);
+        assert_eq!(hist.equal_count(&[2]), Some(1.0));
+        assert_eq!(hist.equal_count(&[5]), Some(2.0));
+        assert_eq!(hist.equal_count(&[0]), Some(0.0));
+        assert_eq!(hist.equal_count(&[9]), Some(0.0));
+        assert_eq!(hist.equal_count(&[4]), None);
+        assert_eq!(hist.less_count(&[0]), 0.0);
+        assert_eq!(hist.less_count(&[2]), 3.0);
+        assert_eq!(hist.less_count(&[3]), 4.0);
+        assert_eq!(hist.less_count(&[4]), 5.0);
+        assert_eq!(hist.less_count(&[5]), 6.0);
+        assert_eq!(hist.less_count(&[9]), 10.0);
+        assert_eq!(hist.range_count(&[2], &[6]), 5.0);
+    }
+}
@@ -0,0 +1,133 @@
+mod histogram;
+mod table_statistics;
+
+use crate::errors::{MySQLError, MySQLResult};
+use crate::store::{Storage, TransactionOptions};
+use crate::table::schema::DataSchema;
+use crate::table::{DBTableManager, TableSource};
+use crate::transaction::OptimisticTransactionContext;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+pub use histogram::{Bucket, Histogram};
+pub use table_statistics::{
+    ColumnStatistics, IndexStatistics, SampleCollector, TableStatistics, DEFAULT_SAMPLE_SIZE,
+};
+
+/// Analyze a table again once the modified rows reach this ratio of the analyzed rows.
+pub const AUTO_ANALYZE_RATIO: f64 = 0.5;
+/// Never analyze a table automatically before this number of rows have been modified.
+pub const AUTO_ANALYZE_MIN_MODIFY_COUNT: u64 = 1000;
+
+/// How long the automatic analyze of a table waits after it has failed before it runs again.
+pub const AUTO_ANALYZE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The number of records read from storage by one request of `ANALYZE TABLE`.
+const ANALYZE_BATCH_SIZE: usize = 1024;
+
+/// Scan the whole table and build statistics from a sample of its rows.
+pub async fn analyze_table(
+    storage: Arc<dyn Storage>,
+    table: &TableSource,
+) -> MySQLResult<TableStatistics> {
+    let opts = TransactionOptions::default();
+    let txn = storage.new_transaction(&opts).await?;
+    let mut ctx = OptimisticTransactionContext::new(txn);
+    let schema = DataSchema {
+        columns: table.meta().columns.clone(),
+    };
+    let (mut start, end) = table.record_range()?;
+    let mut collector = SampleCollector::new(DEFAULT_SAMPLE_SIZE);
+    loop {
+        let (rows, next) = table
+            .scan_record_batch(&mut ctx, &start, &end, ANALYZE_BATCH_SIZE, &schema)
+            .await?;
+        for row in rows {
+            collector.collect(row);
+        }
+        match next {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    TableStatistics::build(table.meta(), collector.seen(), collector.samples())
+}
+
+pub fn need_auto_analyze(stats: Option<&TableStatistics>, modify_count: u64) -> bool {
+    if modify_count < AUTO_ANALYZE_MIN_MODIFY_COUNT {
+        return false;
+    }
+    match stats {
+        Some(stats) => modify_count as f64 >= stats.row_count as f64 * AUTO_ANALYZE_RATIO,
+        None => true,
+    }
+}
+
+/// The state of the automatic analyze of a table, at most one runs at a time.
+#[derive(Debug, Default)]
+pub struct AutoAnalyze {
+    running: bool,
+    /// When the last analyze failed, the next one waits `AUTO_ANALYZE_BACKOFF` after it.
+    failed_at: Option<Instant>,
+    /// The error of the last failed analyze, until it is reported to a statement.
+    error: Option<MySQLError>,
+}
+
+impl AutoAnalyze {
+    fn can_start(&self, now: Instant) -> bool {
+        !self.running
+            && self
+                .failed_at
+                .map_or(true, |t| now >= t + AUTO_ANALYZE_BACKOFF)
+    }
+
+    fn finish(&mut self, error: Option<MySQLError>) {
+        self.running = false;
+        self.failed_at = error.as_ref().map(|_| Instant::now());
+        self.error = error;
+    }
+}
+
+/// Record rows modified by a committed statement, and refresh the statistics of the table in
+/// background if too many rows have changed since the last analyze.
+///
+/// An automatic analyze which fails keeps the modified rows counted, so that it is tried again
+/// by a later statement once `AUTO_ANALYZE_BACKOFF` has passed, and its error is returned to
+/// the next statement which modifies the table.
+pub fn record_modify_count(
+    table_mgr: Arc<RwLock<DBTableManager>>,
+    storage: Arc<dyn Storage>,
+    table: Arc<TableSource>,
+    rows: u64,
+) -> Option<MySQLError> {
+    let modify_count = &table.meta().modify_count;
+    let count = modify_count.fetch_add(rows, Ordering::SeqCst) + rows;
+    let (start, err) = {
+        let mut mgr = table_mgr.write().unwrap();
+        let stats = mgr.get_stats(table.id());
+        let state = mgr.auto_analyze_mut(table.id());
+        let start = need_auto_analyze(stats.as_deref(), count) && state.can_start(Instant::now());
+        state.running |= start;
+        (start, state.error.take())
+    };
+    if !start {
+        return err;
+    }
+    modify_count.fetch_sub(count, Ordering::SeqCst);
+    tokio::spawn(async move {
+        let result = analyze_table(storage, table.as_ref()).await;
+        let mut mgr = table_mgr.write().unwrap();
+        match result {
+            Ok(stats) => {
+                mgr.update_stats(stats);
+                mgr.auto_analyze_mut(table.id()).finish(None);
+            }
+            Err(e) => {
+                table.meta().modify_count.fetch_add(count, Ordering::SeqCst);
+                mgr.auto_analyze_mut(table.id()).finish(Some(e));
+            }
+        }
+    });
+    err
+}
@@ -0,0 +1,266 @@
+use super::histogram::Histogram;
+use crate::common::EncodeValue;
+use crate::errors::MySQLResult;
+use crate::table::schema::{ColumnInfo, IndexInfo, TableInfo};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_BUCKET_COUNT: usize = 64;
+pub const DEFAULT_SAMPLE_SIZE: usize = 10000;
+
+#[derive(Debug, Clone, Default)]
+pub struct ColumnStatistics {
+    pub histogram: Histogram,
+    /// The estimated number of distinct non-null values in the whole table.
+    pub ndv: u64,
+    /// The estimated number of null values in the whole table.
+    pub null_count: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexStatistics {
+    /// Histogram of the encoded index keys.
+    pub histogram: Histogram,
+    /// The estimated number of distinct index keys in the whole table.
+    pub ndv: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableStatistics {
+    pub table_id: u64,
+    pub row_count: u64,
+    pub sample_count: u64,
+    /// Statistics of every column, keyed by column id.
+    pub columns: HashMap<u64, ColumnStatistics>,
+    /// Statistics of every index, keyed by index id.
+    pub indices: HashMap<u64, IndexStatistics>,
+    /// The time of analyze in milliseconds since unix epoch.
+    pub version: u64,
+}
+
+impl TableStatistics {
+    /// Build statistics from rows sampled from a table, the values of each row are ordered as
+    /// `TableInfo::columns`.
+    pub fn build(
+        table: &TableInfo,
+        row_count: u64,
+        samples: &[Vec<EncodeValue>],
+    ) -> MySQLResult<TableStatistics> {
+        let mut stats = TableStatistics {
+            table_id: table.id,
+            row_count,
+            sample_count: samples.len() as u64,
+            columns: HashMap::default(),
+            indices: HashMap::default(),
+            version: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+        };
+        for col in table.columns.iter() {
+            let column_stats = stats.build_column(col.as_ref(), samples)?;
+            stats.columns.insert(col.id, column_stats);
+        }
+        for index in table.indices.iter() {
+            let index_stats = stats.build_index(table, index.as_ref(), samples)?;
+            stats.indices.insert(index.id, index_stats);
+        }
+        Ok(stats)
+    }
+
+    fn build_column(
+        &self,
+        col: &ColumnInfo,
+        samples: &[Vec<EncodeValue>],
+    ) -> MySQLResult<ColumnStatistics> {
+        let mut null_count = 0;
+        let mut values = Vec::with_capacity(samples.len());
+        for row in samples {
+            let v = &row[col.offset];
+            if v.is_null() {
+                null_count += 1;
+                continue;
+            }
+            let mut key = vec![];
+            v.encode_comparable(&mut key, &col.data_type)?;
+            values.push(key);
+        }
+        values.sort();
+        Ok(ColumnStatistics {
+            histogram: Histogram::build(&values, DEFAULT_BUCKET_COUNT),
+            ndv: self.estimate_ndv(&values),
+            null_count: (null_count as f64 * self.scale()) as u64,
+        })
+    }
+
+    fn build_index(
+        &self,
+        table: &TableInfo,
+        index: &IndexInfo,
+        samples: &[Vec<EncodeValue>],
+    ) -> MySQLResult<IndexStatistics> {
+        let mut values = Vec::with_capacity(samples.len());
+        for row in samples {
+            let mut key = vec![];
+            for (_, offset) in index.columns.iter() {
+                let col = table.columns[*offset].as_ref();
+                row[*offset].encode_comparable(&mut key, &col.data_type)?;
+            }
+            values.push(key);
+        }
+        values.sort();
+        Ok(IndexStatistics {
+            histogram: Histogram::build(&values, DEFAULT_BUCKET_COUNT),
+            ndv: self.estimate_ndv(&values),
+        })
+    }
+
+    /// The ratio between rows of the table and rows of the sample.
+    pub fn scale(&self) -> f64 {
+        if self.sample_count == 0 {
+            1.0
+        } else {
+            self.row_count as f64 / self.sample_count as f64
+        }
+    }
+
+    /// Estimate the number of distinct values of the whole table from sorted sample values by
+    /// the Duj1 estimator `n * d / (n - f1 + f1 * n / N)`.
+    fn estimate_ndv(&self, sorted: &[Vec<u8>]) -> u64 {
+        if sorted.is_empty() {
+            return 0;
+        }
+        let mut distinct = 0u64;
+        let mut once = 0u64;
+        let mut i = 0;
+        while i < sorted.len() {
+            let mut j = i + 1;
+            while j < sorted.len() && sorted[j] == sorted[i] {
+                j += 1;
+            }
+            distinct += 1;
+            if j - i == 1 {
+                once += 1;
+            }
+            i = j;
+        }
+        if self.sample_count >= self.row_count {
+            return distinct;
+        }
+        let n = sorted.len() as f64;
+        let total = self.row_count as f64 * (n / self.sample_count as f64);
+        let d = distinct as f64;
+        let f1 = once as f64;
+        let ndv = n * d / (n - f1 + f1 * n / total);
+        std::cmp::min(ndv.round() as u64, total as u64)
+    }
+
+    pub fn column(&self, col: &ColumnInfo) -> Option<&ColumnStatistics> {
+        self.columns.get(&col.id)
+    }
+
+    pub fn index(&self, index: &IndexInfo) -> Option<&IndexStatistics> {
+        self.indices.get(&index.id)
+    }
+
+    /// Estimate the number of rows whose column equals to `value`.
+    pub fn equal_row_count(&self, col: &ColumnInfo, value: &EncodeValue) -> MySQLResult<f64> {
+        let stats = match self.column(col) {
+            Some(stats) => stats,
+            None => return Ok(self.row_count as f64),
+        };
+        if value.is_null() {
+            return Ok(stats.null_count as f64);
+        }
+        let mut key = vec![];
+        value.encode_comparable(&mut key, &col.data_type)?;
+        match stats.histogram.equal_count(&key) {
+            Some(count) => Ok(count * self.scale()),
+            None => Ok(self.row_count as f64 / std::cmp::max(stats.ndv, 1) as f64),
+        }
+    }
+
+    /// Estimate the number of rows whose column is in `[lower, upper)`, an unbounded side is
+    /// passed as `None`.
+    pub fn range_row_count(
+        &self,
+        col: &ColumnInfo,
+        lower: Option<&EncodeValue>,
+        upper: Option<&EncodeValue>,
+    ) -> MySQLResult<f64> {
+        let stats = match self.column(col) {
+            Some(stats) => stats,
+            None => return Ok(self.row_count as f64),
+        };
+        let hist = &stats.histogram;
+        let low = match lower {
+            Some(v) => {
+                let mut key = vec![];
+                v.encode_comparable(&mut key, &col.data_type)?;
+                hist.less_count(&key)
+            }
+            None => 0.0,
+        };
+        let high = match upper {
+            Some(v) => {
+                let mut key = vec![];
+                v.encode_comparable(&mut key, &col.data_type)?;
+                hist.less_count(&key)
+            }
+            None => hist.total_count() as f64,
+        };
+        if high <= low {
+            return Ok(0.0);
+        }
+        Ok((high - low) * self.scale())
+    }
+}
+
+/// Collect a uniform sample of rows by reservoir sampling.
+pub struct SampleCollector {
+    samples: Vec<Vec<EncodeValue>>,
+    max_samples: usize,
+    seen: u64,
+    seed: u64,
+}
+
+impl SampleCollector {
+    pub fn new(max_samples: usize) -> SampleCollector {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        SampleCollector {
+            samples: Vec::with_capacity(max_samples),
+            max_samples,
+            seen: 0,
+            seed: seed | 1,
+        }
+    }
+
+    pub fn collect(&mut self, row: Vec<EncodeValue>) {
+        self.seen += 1;
+        if self.samples.len() < self.max_samples {
+            self.samples.push(row);
+            return;
+        }
+        let idx = self.next_random() % self.seen;
+        if (idx as usize) < self.max_samples {
+            self.samples[idx as usize] = row;
+        }
+    }
+
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn samples(&self) -> &[Vec<EncodeValue>] {
+        &self.samples
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+}
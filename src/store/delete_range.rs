@@ -0,0 +1,161 @@
+use crate::store::Storage;
+use std::cmp;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Builder;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::time::{sleep_until, Instant};
+
+/// How long a range whose deletion failed waits before it is deleted again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+struct DeleteRangeTask {
+    storage: Arc<dyn Storage>,
+    start: Vec<u8>,
+    end: Vec<u8>,
+    deadline: Instant,
+}
+
+/// The tasks are ordered by their deadlines, the task due first is the greatest so that a
+/// `BinaryHeap` pops it first.
+impl Ord for DeleteRangeTask {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+impl PartialOrd for DeleteRangeTask {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for DeleteRangeTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for DeleteRangeTask {}
+
+/// Deletes the key ranges of dropped and truncated tables on a thread of its own. A range is
+/// deleted `delay` after it is scheduled, so that the statements which were still reading the
+/// table when it was dropped can finish first. A range which fails to be deleted is retried
+/// every `RETRY_INTERVAL` until it succeeds, the failures are counted and the last error is
+/// kept. The ranges still pending when the server stops are left in storage.
+pub struct DeleteRangeWorker {
+    sender: UnboundedSender<DeleteRangeTask>,
+    delay: Duration,
+    pending: Arc<AtomicUsize>,
+    /// The number of failed deletions and the error of the last one.
+    failures: Arc<Mutex<(usize, Option<String>)>>,
+}
+
+impl DeleteRangeWorker {
+    pub fn new(delay: Duration) -> DeleteRangeWorker {
+        let (sender, mut receiver) = unbounded_channel::<DeleteRangeTask>();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let counter = pending.clone();
+        let failures = Arc::new(Mutex::new((0, None)));
+        let failed = failures.clone();
+        thread::Builder::new()
+            .name("delete-range".to_string())
+            .spawn(move || {
+                let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+                runtime.block_on(async move {
+                    // The ranges scheduled but not deleted yet, by their deadlines.
+                    let mut queue: BinaryHeap<DeleteRangeTask> = BinaryHeap::new();
+                    loop {
+                        let deadline = queue.peek().map_or_else(Instant::now, |t| t.deadline);
+                        tokio::select! {
+                            task = receiver.recv() => match task {
+                                Some(task) => queue.push(task),
+                                None => break,
+                            },
+                            _ = sleep_until(deadline), if !queue.is_empty() => {
+                                let mut task = queue.pop().unwrap();
+                                match task.storage.delete_range(&task.start, &task.end).await {
+                                    Ok(()) => {
+                                        counter.fetch_sub(1, Ordering::AcqRel);
+                                    }
+                                    Err(e) => {
+                                        let mut failed = failed.lock().unwrap();
+                                        failed.0 += 1;
+                                        failed.1 = Some(e.to_string());
+                                        task.deadline = Instant::now() + RETRY_INTERVAL;
+                                        queue.push(task);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                });
+            })
+            .unwrap();
+        DeleteRangeWorker {
+            sender,
+            delay,
+            pending,
+            failures,
+        }
+    }
+
+    /// Delete the keys of `storage` in `[start, end)` later.
+    pub fn schedule(&self, storage: Arc<dyn Storage>, range: (Vec<u8>, Vec<u8>)) {
+        let (start, end) = range;
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        let task = DeleteRangeTask {
+            storage,
+            start,
+            end,
+            deadline: Instant::now() + self.delay,
+        };
+        if self.sender.send(task).is_err() {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// The number of ranges scheduled but not deleted yet.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// The number of deletions which have failed, and the error of the last one.
+    pub fn failures(&self) -> (usize, Option<String>) {
+        self.failures.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MemStorage, TransactionOptions};
+    use tokio::runtime;
+
+    #[test]
+    fn test_delete_range() {
+        let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+        let r = runtime::Runtime::new().unwrap();
+        let mut txn = r
+            .block_on(storage.new_transaction(&TransactionOptions::default()))
+            .unwrap();
+        for key in [b"a", b"b", b"c", b"d"].iter() {
+            r.block_on(txn.put(*key, *key)).unwrap();
+        }
+        r.block_on(txn.commit()).unwrap();
+
+        let worker = DeleteRangeWorker::new(Duration::from_millis(10));
+        worker.schedule(storage.clone(), (b"b".to_vec(), b"d".to_vec()));
+        assert_eq!(worker.pending(), 1);
+        while worker.pending() > 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(worker.failures(), (0, None));
+        for (key, exists) in [(b"a", true), (b"b", false), (b"c", false), (b"d", true)].iter() {
+            assert_eq!(r.block_on(storage.get(*key)).unwrap().is_some(), *exists);
+        }
+    }
+}
@@ -1,11 +1,17 @@
+use crate::common::MemTrackerRef;
 use crate::errors::MySQLResult;
 use crate::store::{Storage, Transaction, TransactionOptions};
 use std::collections::BTreeMap;
+use std::mem::size_of;
+use std::ops::Bound;
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+/// The number of keys sampled to split a range.
+const SPLIT_SAMPLE_SIZE: usize = 1024;
+
 pub struct MemStorage {
     data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<Operation>>>>,
     last_commit_ts: AtomicU64,
@@ -30,38 +36,161 @@ pub struct MemTransaction {
     data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<Operation>>>>,
     cache: BTreeMap<Vec<u8>, Operation>,
     start_ts: u64,
+    /// The tracker which the bytes of `cache` are reserved in, they are released when the
+    /// transaction is dropped.
+    memory: Option<MemTrackerRef>,
+    cache_size: usize,
 }
 
 impl MemTransaction {
     pub fn new(
         data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<Operation>>>>,
         start_ts: u64,
+        memory: Option<MemTrackerRef>,
     ) -> MemTransaction {
         MemTransaction {
             data,
             cache: BTreeMap::default(),
             start_ts,
+            memory,
+            cache_size: 0,
+        }
+    }
+
+    /// Buffer a write, which fails without buffering it if a memory quota is exceeded.
+    fn write(&mut self, key: &[u8], op: Operation) -> MySQLResult<()> {
+        let size = write_size(key, &op);
+        if let Some(memory) = self.memory.as_ref() {
+            memory.try_consume(size)?;
+        }
+        self.cache_size += size;
+        if let Some(old) = self.cache.insert(key.to_vec(), op) {
+            let old_size = write_size(key, &old);
+            self.cache_size -= old_size;
+            if let Some(memory) = self.memory.as_ref() {
+                memory.release(old_size);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemTransaction {
+    fn drop(&mut self) {
+        if let Some(memory) = self.memory.as_ref() {
+            memory.release(self.cache_size);
+        }
+    }
+}
+
+fn write_size(key: &[u8], op: &Operation) -> usize {
+    let value = match op {
+        Operation::Put(v, _) => v.len(),
+        Operation::Delete(_) => 0,
+    };
+    size_of::<Vec<u8>>() + size_of::<Operation>() + key.len() + value
+}
+
+/// Find the newest version of a key which is visible for a reader at `ts`.
+fn visible_value(values: &[Operation], ts: u64) -> Option<&Vec<u8>> {
+    for op in values.iter().rev() {
+        match op {
+            Operation::Put(v, commit_ts) => {
+                if *commit_ts <= ts {
+                    return Some(v);
+                }
+            }
+            Operation::Delete(commit_ts) => {
+                if *commit_ts <= ts {
+                    return None;
+                }
+            }
         }
     }
+    None
 }
 
 #[async_trait]
 impl Storage for MemStorage {
     async fn get(&self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
         let data = self.data.lock().unwrap();
-        let ops = data.get(key);
-        if let Some(values) = ops {
-            match values.first() {
-                Some(Operation::Put(v, _)) => return Ok(Some(v.clone())),
-                _ => (),
+        let ts = self.last_commit_ts.load(Ordering::Acquire);
+        Ok(data
+            .get(key)
+            .and_then(|values| visible_value(values, ts))
+            .cloned())
+    }
+
+    async fn new_transaction(
+        &self,
+        opts: &TransactionOptions,
+    ) -> MySQLResult<Box<dyn Transaction>> {
+        let start_ts = self.last_commit_ts.load(Ordering::Acquire);
+        Ok(Box::new(MemTransaction::new(
+            self.data.clone(),
+            start_ts,
+            opts.memory.clone(),
+        )))
+    }
+
+    async fn new_snapshots(&self, count: usize) -> MySQLResult<Vec<Box<dyn Transaction>>> {
+        let start_ts = self.last_commit_ts.load(Ordering::Acquire);
+        Ok((0..count)
+            .map(|_| {
+                Box::new(MemTransaction::new(self.data.clone(), start_ts, None))
+                    as Box<dyn Transaction>
+            })
+            .collect())
+    }
+
+    /// Split the range by a sample of its keys. Every `stride`-th key is sampled, and the stride
+    /// is doubled whenever the sample grows too large, so the sample stays evenly spread.
+    async fn split_range(
+        &self,
+        start: &[u8],
+        end: &[u8],
+        count: usize,
+    ) -> MySQLResult<Vec<Vec<u8>>> {
+        if start >= end || count <= 1 {
+            return Ok(vec![]);
+        }
+        let data = self.data.lock().unwrap();
+        let range = (Bound::Included(start), Bound::Excluded(end));
+        let mut samples: Vec<&Vec<u8>> = vec![];
+        let mut stride = 1;
+        for (i, (key, _)) in data.range::<[u8], _>(range).enumerate() {
+            if i % stride != 0 {
+                continue;
+            }
+            samples.push(key);
+            if samples.len() >= 2 * SPLIT_SAMPLE_SIZE {
+                samples = samples.into_iter().step_by(2).collect();
+                stride *= 2;
+            }
+        }
+        let mut keys: Vec<Vec<u8>> = vec![];
+        if samples.is_empty() {
+            return Ok(keys);
+        }
+        for i in 1..count {
+            let key = samples[i * samples.len() / count];
+            if key.as_slice() > start && keys.last().map_or(true, |last| last < key) {
+                keys.push(key.clone());
             }
         }
-        Ok(None)
+        Ok(keys)
     }
 
-    async fn new_transaction(&self, _: &TransactionOptions) -> MySQLResult<Box<dyn Transaction>> {
-        let start_ts = self.last_commit_ts.load(Ordering::Acquire);
-        Ok(Box::new(MemTransaction::new(self.data.clone(), start_ts)))
+    /// Remove the keys with all their versions, the range is split off the tree and dropped.
+    async fn delete_range(&self, start: &[u8], end: &[u8]) -> MySQLResult<()> {
+        if start >= end {
+            return Ok(());
+        }
+        let mut data = self.data.lock().unwrap();
+        let mut deleted = data.split_off(start);
+        let mut rest = deleted.split_off(end);
+        data.append(&mut rest);
+        Ok(())
     }
 }
 
@@ -80,15 +209,11 @@ impl Transaction for MemTransaction {
     }
 
     async fn put(&mut self, key: &[u8], value: &[u8]) -> MySQLResult<()> {
-        self.cache
-            .insert(key.to_vec(), Operation::Put(value.to_vec(), self.start_ts));
-        Ok(())
+        self.write(key, Operation::Put(value.to_vec(), self.start_ts))
     }
 
     async fn delete(&mut self, key: &[u8]) -> MySQLResult<()> {
-        self.cache
-            .insert(key.to_vec(), Operation::Delete(self.start_ts));
-        Ok(())
+        self.write(key, Operation::Delete(self.start_ts))
     }
 
     async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
@@ -100,34 +225,148 @@ impl Transaction for MemTransaction {
             }
         }
         let data = self.data.lock().unwrap();
-        if let Some(values) = data.get(key) {
-            if values.is_empty() {
-                return Ok(None);
-            }
-            let idx = values.len();
-            while idx > 0 {
-                match &values[idx - 1] {
-                    Operation::Put(v, ts) => {
-                        if *ts <= self.start_ts {
-                            return Ok(Some(v.clone()));
-                        }
-                    }
-                    Operation::Delete(ts) => {
-                        if *ts <= self.start_ts {
-                            return Ok(None);
-                        }
-                    }
+        Ok(data
+            .get(key)
+            .and_then(|values| visible_value(values, self.start_ts))
+            .cloned())
+    }
+
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        if start >= end {
+            return Ok(vec![]);
+        }
+        let range = (Bound::Included(start), Bound::Excluded(end));
+        let data = self.data.lock().unwrap();
+        let start_ts = self.start_ts;
+        let mut committed = data
+            .range::<[u8], _>(range)
+            .filter_map(|(key, values)| visible_value(values, start_ts).map(|v| (key, Some(v))))
+            .peekable();
+        // The writes of this transaction overwrite the committed data.
+        let mut written = self
+            .cache
+            .range::<[u8], _>(range)
+            .map(|(key, op)| match op {
+                Operation::Put(v, _) => (key, Some(v)),
+                Operation::Delete(_) => (key, None),
+            })
+            .peekable();
+        let mut result = vec![];
+        while result.len() < limit {
+            let keys = (
+                committed.peek().map(|(key, _)| *key),
+                written.peek().map(|(key, _)| *key),
+            );
+            let next = match keys {
+                (Some(a), Some(b)) if a < b => committed.next(),
+                (Some(a), Some(b)) if a == b => {
+                    committed.next();
+                    written.next()
                 }
+                (_, Some(_)) => written.next(),
+                (Some(_), None) => committed.next(),
+                (None, None) => break,
+            };
+            if let Some((key, Some(value))) = next {
+                result.push((key.clone(), value.clone()));
             }
         }
-        Ok(None)
-    }
-
-    async fn scan(&mut self, _start: &[u8], _end: &[u8]) -> MySQLResult<Vec<Vec<u8>>> {
-        Ok(vec![])
+        Ok(result)
     }
 
     fn get_start_time(&self) -> u64 {
         self.start_ts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::MemTracker;
+    use crate::errors::MySQLError;
+    use tokio::runtime;
+
+    #[test]
+    fn test_scan_with_limit() {
+        let storage = MemStorage::new();
+        let r = runtime::Runtime::new().unwrap();
+        let opts = TransactionOptions::default();
+        let mut txn = r.block_on(storage.new_transaction(&opts)).unwrap();
+        for key in [b"a", b"b", b"c", b"d"].iter() {
+            r.block_on(txn.put(*key, *key)).unwrap();
+        }
+        r.block_on(txn.commit()).unwrap();
+
+        let mut txn = r.block_on(storage.new_transaction(&opts)).unwrap();
+        r.block_on(txn.delete(b"b")).unwrap();
+        r.block_on(txn.put(b"bb", b"x")).unwrap();
+        r.block_on(txn.put(b"c", b"y")).unwrap();
+        let kvs = r.block_on(txn.scan(b"a", b"z", 3)).unwrap();
+        assert_eq!(
+            kvs,
+            vec![
+                (b"a".to_vec(), b"a".to_vec()),
+                (b"bb".to_vec(), b"x".to_vec()),
+                (b"c".to_vec(), b"y".to_vec()),
+            ]
+        );
+        let kvs = r.block_on(txn.scan(b"c\0", b"z", 3)).unwrap();
+        assert_eq!(kvs, vec![(b"d".to_vec(), b"d".to_vec())]);
+    }
+
+    #[test]
+    fn test_split_range() {
+        let storage = MemStorage::new();
+        let r = runtime::Runtime::new().unwrap();
+        let mut txn = r
+            .block_on(storage.new_transaction(&TransactionOptions::default()))
+            .unwrap();
+        for i in 0..10000u32 {
+            r.block_on(txn.put(&i.to_be_bytes(), b"v")).unwrap();
+        }
+        r.block_on(txn.commit()).unwrap();
+        let (start, end) = (0u32.to_be_bytes(), 10000u32.to_be_bytes());
+        let keys = r.block_on(storage.split_range(&start, &end, 4)).unwrap();
+        assert_eq!(keys.len(), 3);
+        for (i, key) in keys.iter().enumerate() {
+            let mut k = [0u8; 4];
+            k.copy_from_slice(key);
+            let expected = (i as i64 + 1) * 2500;
+            assert!((u32::from_be_bytes(k) as i64 - expected).abs() < 100);
+        }
+        assert!(r
+            .block_on(storage.split_range(&end, b"\xff", 4))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_transaction_memory() {
+        let storage = MemStorage::new();
+        let r = runtime::Runtime::new().unwrap();
+        let session = MemTracker::root("session", 4 * write_size(b"a", &Operation::Delete(0)));
+        let opts = TransactionOptions {
+            pessimistic: false,
+            memory: Some(session.clone()),
+        };
+        let mut txn = r.block_on(storage.new_transaction(&opts)).unwrap();
+        r.block_on(txn.put(b"a", b"")).unwrap();
+        r.block_on(txn.put(b"b", b"")).unwrap();
+        // Writing a key again replaces its buffered write.
+        r.block_on(txn.delete(b"a")).unwrap();
+        r.block_on(txn.put(b"c", b"")).unwrap();
+        r.block_on(txn.put(b"d", b"")).unwrap();
+        match r.block_on(txn.put(b"e", b"")) {
+            Err(MySQLError::MemoryQuotaExceeded(label, _)) => assert_eq!(label, "session"),
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(r.block_on(txn.get(b"e")).unwrap().is_none());
+        drop(txn);
+        assert_eq!(session.consumed(), 0);
+    }
+}
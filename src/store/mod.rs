@@ -1,15 +1,23 @@
+use crate::common::MemTrackerRef;
 use crate::errors::MySQLResult;
 use async_trait::async_trait;
 
+mod delete_range;
 mod mem;
+mod stats;
 mod tikv;
 
+pub use delete_range::DeleteRangeWorker;
 pub use mem::MemStorage;
+pub use stats::{StatsStorage, StorageStats};
 pub use tikv::{TiKVConfig, TiKVStorage};
 
 #[derive(Default)]
 pub struct TransactionOptions {
     pub pessimistic: bool,
+    /// The tracker of the session, which the writes buffered by the transaction are reserved
+    /// in until it ends.
+    pub memory: Option<MemTrackerRef>,
 }
 
 #[async_trait]
@@ -17,6 +25,20 @@ pub trait Storage: Sync + Send {
     async fn get(&self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>>;
     async fn new_transaction(&self, opts: &TransactionOptions)
         -> MySQLResult<Box<dyn Transaction>>;
+    /// Start `count` transactions which read the same snapshot, so that the parts of a scan can
+    /// be read concurrently. They are only used to read.
+    async fn new_snapshots(&self, count: usize) -> MySQLResult<Vec<Box<dyn Transaction>>>;
+    /// Return at most `count - 1` ordered keys inside `(start, end)` which split the range into
+    /// parts holding similar amounts of data.
+    async fn split_range(
+        &self,
+        start: &[u8],
+        end: &[u8],
+        count: usize,
+    ) -> MySQLResult<Vec<Vec<u8>>>;
+    /// Remove every key in `[start, end)`. It is only called on the ranges of dropped tables,
+    /// which no statement reads or writes any more.
+    async fn delete_range(&self, start: &[u8], end: &[u8]) -> MySQLResult<()>;
 }
 
 #[async_trait]
@@ -25,6 +47,12 @@ pub trait Transaction: Send {
     async fn put(&mut self, key: &[u8], value: &[u8]) -> MySQLResult<()>;
     async fn delete(&mut self, key: &[u8]) -> MySQLResult<()>;
     async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>>;
-    async fn scan(&mut self, start: &[u8], end: &[u8]) -> MySQLResult<Vec<Vec<u8>>>;
+    /// Return at most `limit` visible key-value pairs in `[start, end)`, ordered by key.
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>>;
     fn get_start_time(&self) -> u64;
 }
@@ -0,0 +1,138 @@
+use crate::errors::MySQLResult;
+use crate::store::{Storage, Transaction, TransactionOptions};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counters of requests sent to the storage engine.
+#[derive(Debug, Default)]
+pub struct StorageStats {
+    pub get: AtomicU64,
+    pub scan: AtomicU64,
+    pub put: AtomicU64,
+    pub delete: AtomicU64,
+    pub commit: AtomicU64,
+}
+
+impl StorageStats {
+    pub fn total(&self) -> u64 {
+        self.get.load(Ordering::Relaxed)
+            + self.scan.load(Ordering::Relaxed)
+            + self.put.load(Ordering::Relaxed)
+            + self.delete.load(Ordering::Relaxed)
+            + self.commit.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Display for StorageStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "get:{}, scan:{}, put:{}, delete:{}, commit:{}",
+            self.get.load(Ordering::Relaxed),
+            self.scan.load(Ordering::Relaxed),
+            self.put.load(Ordering::Relaxed),
+            self.delete.load(Ordering::Relaxed),
+            self.commit.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// A wrapper of `Storage` which records every request into `StorageStats`.
+pub struct StatsStorage {
+    inner: Arc<dyn Storage>,
+    stats: Arc<StorageStats>,
+}
+
+impl StatsStorage {
+    pub fn new(inner: Arc<dyn Storage>, stats: Arc<StorageStats>) -> StatsStorage {
+        StatsStorage { inner, stats }
+    }
+}
+
+struct StatsTransaction {
+    inner: Box<dyn Transaction>,
+    stats: Arc<StorageStats>,
+}
+
+#[async_trait]
+impl Storage for StatsStorage {
+    async fn get(&self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
+        self.stats.get.fetch_add(1, Ordering::Relaxed);
+        self.inner.get(key).await
+    }
+
+    async fn new_transaction(
+        &self,
+        opts: &TransactionOptions,
+    ) -> MySQLResult<Box<dyn Transaction>> {
+        let inner = self.inner.new_transaction(opts).await?;
+        Ok(Box::new(StatsTransaction {
+            inner,
+            stats: self.stats.clone(),
+        }))
+    }
+
+    async fn new_snapshots(&self, count: usize) -> MySQLResult<Vec<Box<dyn Transaction>>> {
+        let snapshots = self.inner.new_snapshots(count).await?;
+        Ok(snapshots
+            .into_iter()
+            .map(|inner| {
+                Box::new(StatsTransaction {
+                    inner,
+                    stats: self.stats.clone(),
+                }) as Box<dyn Transaction>
+            })
+            .collect())
+    }
+
+    async fn split_range(
+        &self,
+        start: &[u8],
+        end: &[u8],
+        count: usize,
+    ) -> MySQLResult<Vec<Vec<u8>>> {
+        self.inner.split_range(start, end, count).await
+    }
+
+    async fn delete_range(&self, start: &[u8], end: &[u8]) -> MySQLResult<()> {
+        self.inner.delete_range(start, end).await
+    }
+}
+
+#[async_trait]
+impl Transaction for StatsTransaction {
+    async fn commit(&mut self) -> MySQLResult<()> {
+        self.stats.commit.fetch_add(1, Ordering::Relaxed);
+        self.inner.commit().await
+    }
+
+    async fn put(&mut self, key: &[u8], value: &[u8]) -> MySQLResult<()> {
+        self.stats.put.fetch_add(1, Ordering::Relaxed);
+        self.inner.put(key, value).await
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> MySQLResult<()> {
+        self.stats.delete.fetch_add(1, Ordering::Relaxed);
+        self.inner.delete(key).await
+    }
+
+    async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
+        self.stats.get.fetch_add(1, Ordering::Relaxed);
+        self.inner.get(key).await
+    }
+
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.stats.scan.fetch_add(1, Ordering::Relaxed);
+        self.inner.scan(start, end, limit).await
+    }
+
+    fn get_start_time(&self) -> u64 {
+        self.inner.get_start_time()
+    }
+}
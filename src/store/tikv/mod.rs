@@ -1,20 +1,32 @@
 mod config;
 
 use super::{Storage, Transaction};
-use crate::errors::MySQLResult;
+use crate::errors::{MySQLError, MySQLResult};
 use crate::store::TransactionOptions;
 use async_trait::async_trait;
 pub use config::TiKVConfig;
+use std::sync::Arc;
 use tikv_client::{
-    Timestamp, Transaction as KVTransaction, TransactionClient, TransactionOptions as KVTxnOpts,
+    Config, PdClient, PdRpcClient, Snapshot, Timestamp, Transaction as KVTransaction,
+    TransactionClient, TransactionOptions as KVTxnOpts,
 };
 
+/// The number of keys which one transaction of `delete_range` deletes.
+const DELETE_RANGE_BATCH_SIZE: u32 = 1024;
+
 struct TiKVTransaction {
     inner: KVTransaction,
 }
 
+/// A transaction which only reads a snapshot, it is used by the workers of a parallel scan.
+struct TiKVSnapshot {
+    inner: Snapshot,
+}
+
 pub struct TiKVStorage {
     client: TransactionClient,
+    /// Looks up the regions of keys, a scan is split at their boundaries.
+    pd: Arc<PdRpcClient>,
 }
 
 #[async_trait]
@@ -44,6 +56,70 @@ impl Storage for TiKVStorage {
         };
         Ok(Box::new(TiKVTransaction { inner: txn }))
     }
+
+    async fn new_snapshots(&self, count: usize) -> MySQLResult<Vec<Box<dyn Transaction>>> {
+        let ts = self.client.current_timestamp().await?;
+        Ok((0..count)
+            .map(|_| {
+                let inner = self
+                    .client
+                    .snapshot(ts.clone(), KVTxnOpts::new_optimistic());
+                Box::new(TiKVSnapshot { inner }) as Box<dyn Transaction>
+            })
+            .collect())
+    }
+
+    /// Split the range at the boundaries of the regions it covers, so that every part is read
+    /// from different regions. The regions are looked up in PD one after another, and only until
+    /// `count` parts are decided, so the last part holds every region left in the range.
+    async fn split_range(
+        &self,
+        start: &[u8],
+        end: &[u8],
+        count: usize,
+    ) -> MySQLResult<Vec<Vec<u8>>> {
+        if start >= end || count <= 1 {
+            return Ok(vec![]);
+        }
+        let mut boundaries: Vec<Vec<u8>> = vec![];
+        let mut key = start.to_vec();
+        while boundaries.len() + 1 < count {
+            let region = self.pd.region_for_key(&key.into()).await?;
+            let region_end: Vec<u8> = region.end_key().into();
+            // The last region ends with an empty key.
+            if region_end.is_empty() || region_end.as_slice() >= end {
+                break;
+            }
+            boundaries.push(region_end.clone());
+            key = region_end;
+        }
+        Ok(boundaries)
+    }
+
+    /// The transactional client has no `delete_range`, so the keys are deleted by transactions
+    /// of at most `DELETE_RANGE_BATCH_SIZE` keys each.
+    async fn delete_range(&self, start: &[u8], end: &[u8]) -> MySQLResult<()> {
+        let mut start = start.to_vec();
+        loop {
+            let mut txn = self.client.begin_optimistic().await?;
+            let keys: Vec<Vec<u8>> = txn
+                .scan_keys(start.clone()..end.to_vec(), DELETE_RANGE_BATCH_SIZE)
+                .await?
+                .map(|k| k.into())
+                .collect();
+            for key in keys.iter() {
+                txn.delete(key.clone()).await?;
+            }
+            txn.commit().await?;
+            match keys.last() {
+                Some(last) if keys.len() == DELETE_RANGE_BATCH_SIZE as usize => {
+                    start = last.clone();
+                    start.push(0);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -68,8 +144,50 @@ impl Transaction for TiKVTransaction {
         Ok(v)
     }
 
-    async fn scan(&mut self, _start: &[u8], _end: &[u8]) -> MySQLResult<Vec<Vec<u8>>> {
-        unimplemented!()
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let limit = limit.min(u32::MAX as usize) as u32;
+        let pairs = self.inner.scan(start.to_vec()..end.to_vec(), limit).await?;
+        Ok(pairs.map(|kv| (kv.0.into(), kv.1)).collect())
+    }
+
+    fn get_start_time(&self) -> u64 {
+        0
+    }
+}
+
+#[async_trait]
+impl Transaction for TiKVSnapshot {
+    async fn commit(&mut self) -> MySQLResult<()> {
+        Ok(())
+    }
+
+    async fn put(&mut self, _key: &[u8], _value: &[u8]) -> MySQLResult<()> {
+        Err(MySQLError::ReadOnlyTransaction)
+    }
+
+    async fn delete(&mut self, _key: &[u8]) -> MySQLResult<()> {
+        Err(MySQLError::ReadOnlyTransaction)
+    }
+
+    async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
+        let v = self.inner.get(key.to_vec()).await?;
+        Ok(v)
+    }
+
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let limit = limit.min(u32::MAX as usize) as u32;
+        let pairs = self.inner.scan(start.to_vec()..end.to_vec(), limit).await?;
+        Ok(pairs.map(|kv| (kv.0.into(), kv.1)).collect())
     }
 
     fn get_start_time(&self) -> u64 {
@@ -80,6 +198,10 @@ impl Transaction for TiKVTransaction {
 impl TiKVStorage {
     pub async fn create(config: &TiKVConfig) -> MySQLResult<TiKVStorage> {
         let client = TransactionClient::new(config.pd_address.clone()).await?;
-        Ok(TiKVStorage { client })
+        let pd = PdRpcClient::connect(&config.pd_address, &Config::default(), true).await?;
+        Ok(TiKVStorage {
+            client,
+            pd: Arc::new(pd),
+        })
     }
 }
@@ -0,0 +1,169 @@
+use crate::errors::{MySQLError, MySQLResult};
+use crate::store::{Storage, TransactionOptions};
+use byteorder::{BigEndian, ByteOrder};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// The number of values a server takes from the counter in storage at a time.
+const AUTO_ID_BATCH_SIZE: u64 = 100;
+/// The times the counter is read and written again when another server updates it first.
+const AUTO_ID_RETRY_TIMES: usize = 3;
+
+/// Allocates the values of the `AUTO_INCREMENT` column of a table. The counter in storage is
+/// the largest value any server may have handed out, every server takes a batch of values above
+/// it, so the values are unique between servers and restarts but not always consecutive.
+#[derive(Debug)]
+pub struct AutoIdAllocator {
+    /// The counter when it is not in storage yet, the first value is above it.
+    base: u64,
+    /// The last value handed out by this server and the end of the batch it has taken.
+    cache: Mutex<(u64, u64)>,
+}
+
+impl AutoIdAllocator {
+    pub fn new(base: u64) -> AutoIdAllocator {
+        AutoIdAllocator {
+            base,
+            cache: Mutex::new((base, base)),
+        }
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// Hand out the smallest value above all values handed out before which is `offset` plus a
+    /// multiple of `increment`, as the variables `auto_increment_offset` and
+    /// `auto_increment_increment` of MySQL. The counter is stored at `key`.
+    pub async fn alloc(
+        &self,
+        storage: &Arc<dyn Storage>,
+        key: &[u8],
+        increment: u64,
+        offset: u64,
+    ) -> MySQLResult<u64> {
+        let mut cache = self.cache.lock().await;
+        let next = next_value(cache.0, increment, offset);
+        if next <= cache.1 {
+            cache.0 = next;
+            return Ok(next);
+        }
+        let (last, end) = self
+            .update(storage, key, |last| {
+                let next = next_value(last, increment, offset);
+                next.max(last + AUTO_ID_BATCH_SIZE)
+            })
+            .await?;
+        let next = next_value(cache.0.max(last), increment, offset);
+        *cache = (next, end);
+        Ok(next)
+    }
+
+    /// Make sure the values handed out later are above `value`, which has been inserted
+    /// explicitly.
+    pub async fn rebase(
+        &self,
+        storage: &Arc<dyn Storage>,
+        key: &[u8],
+        value: u64,
+    ) -> MySQLResult<()> {
+        let mut cache = self.cache.lock().await;
+        if value <= cache.0 {
+            return Ok(());
+        }
+        if value > cache.1 {
+            self.update(storage, key, |last| last.max(value)).await?;
+            cache.1 = value;
+        }
+        cache.0 = value;
+        Ok(())
+    }
+
+    /// The counter in storage, the values handed out by any server are not above it.
+    pub async fn end(&self, storage: &Arc<dyn Storage>, key: &[u8]) -> MySQLResult<u64> {
+        Ok(storage
+            .get(key)
+            .await?
+            .map_or(self.base, |v| decode_counter(&v)))
+    }
+
+    /// Move the counter in storage to `f(counter)`, return the old and the new counter.
+    async fn update<F: Fn(u64) -> u64>(
+        &self,
+        storage: &Arc<dyn Storage>,
+        key: &[u8],
+        f: F,
+    ) -> MySQLResult<(u64, u64)> {
+        let mut result = Err(MySQLError::UnsupportSQL);
+        for _ in 0..AUTO_ID_RETRY_TIMES {
+            let mut txn = storage
+                .new_transaction(&TransactionOptions::default())
+                .await?;
+            let last = txn
+                .get(key)
+                .await?
+                .map_or(self.base, |v| decode_counter(&v));
+            let end = f(last);
+            let mut value = [0; 8];
+            BigEndian::write_u64(&mut value, end);
+            txn.put(key, &value).await?;
+            match txn.commit().await {
+                Ok(()) => return Ok((last, end)),
+                Err(e) => result = Err(e),
+            }
+        }
+        result
+    }
+}
+
+fn decode_counter(value: &[u8]) -> u64 {
+    BigEndian::read_u64(value)
+}
+
+/// The smallest value above `last` which is `offset` plus a multiple of `increment`.
+fn next_value(last: u64, increment: u64, offset: u64) -> u64 {
+    let increment = increment.max(1);
+    if last < offset {
+        return offset;
+    }
+    offset + ((last - offset) / increment + 1) * increment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemStorage;
+    use tokio::runtime;
+
+    #[test]
+    fn test_alloc_auto_id() {
+        let storage: Arc<dyn Storage> = Arc::new(MemStorage::new());
+        let r = runtime::Runtime::new().unwrap();
+        let key = b"t1m";
+        let allocator = AutoIdAllocator::new(0);
+        assert_eq!(r.block_on(allocator.alloc(&storage, key, 1, 1)).unwrap(), 1);
+        assert_eq!(r.block_on(allocator.alloc(&storage, key, 1, 1)).unwrap(), 2);
+        assert_eq!(
+            r.block_on(allocator.alloc(&storage, key, 10, 5)).unwrap(),
+            5
+        );
+        assert_eq!(
+            r.block_on(allocator.alloc(&storage, key, 10, 5)).unwrap(),
+            15
+        );
+        r.block_on(allocator.rebase(&storage, key, 500)).unwrap();
+        assert_eq!(
+            r.block_on(allocator.alloc(&storage, key, 1, 1)).unwrap(),
+            501
+        );
+
+        // Another server, or this one after a restart, goes on above the stored counter.
+        let other = AutoIdAllocator::new(0);
+        let end = r.block_on(other.end(&storage, key)).unwrap();
+        assert!(end >= 501);
+        assert_eq!(
+            r.block_on(other.alloc(&storage, key, 1, 1)).unwrap(),
+            end + 1
+        );
+    }
+}
@@ -1,8 +1,13 @@
+pub mod auto_id;
 pub mod decoder;
+pub mod range;
 pub mod schema;
+mod system_table;
 pub mod table;
 mod table_manager;
 
 pub use decoder::{DecoderRow, EncoderRow};
+pub use range::IndexRange;
+pub use system_table::{is_system_database, SystemTable, INFORMATION_SCHEMA, PERFORMANCE_SCHEMA};
 pub use table::TableSource;
-pub use table_manager::DBTableManager;
+pub use table_manager::{DBTableManager, DEFAULT_DATABASE};
@@ -0,0 +1,127 @@
+use crate::common::EncodeValue;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A range of the values of the first column of an index, the bool of a bound means whether it
+/// is inclusive.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct IndexRange {
+    pub low: Option<(EncodeValue, bool)>,
+    pub high: Option<(EncodeValue, bool)>,
+}
+
+impl IndexRange {
+    pub fn is_point(&self) -> bool {
+        match (&self.low, &self.high) {
+            (Some((l, true)), Some((h, true))) => l == h,
+            _ => false,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+
+    /// Whether no value is in the range.
+    pub fn is_empty(&self) -> bool {
+        match (&self.low, &self.high) {
+            (Some((l, l_inclusive)), Some((h, h_inclusive))) => match l.compare(h) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => !(*l_inclusive && *h_inclusive),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Narrow the range to the values greater than `value`.
+    pub fn restrict_low(&mut self, value: EncodeValue, inclusive: bool) {
+        if let Some((l, l_inclusive)) = &self.low {
+            match value.compare(l) {
+                Some(Ordering::Less) => return,
+                Some(Ordering::Equal) if !*l_inclusive => return,
+                _ => (),
+            }
+        }
+        self.low = Some((value, inclusive));
+    }
+
+    /// Narrow the range to the values less than `value`.
+    pub fn restrict_high(&mut self, value: EncodeValue, inclusive: bool) {
+        if let Some((h, h_inclusive)) = &self.high {
+            match value.compare(h) {
+                Some(Ordering::Greater) => return,
+                Some(Ordering::Equal) if !*h_inclusive => return,
+                _ => (),
+            }
+        }
+        self.high = Some((value, inclusive));
+    }
+}
+
+impl fmt::Display for IndexRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.low {
+            Some((v, true)) => write!(f, "[{}", String::from(v.clone()))?,
+            Some((v, false)) => write!(f, "({}", String::from(v.clone()))?,
+            None => write!(f, "[-inf")?,
+        }
+        match &self.high {
+            Some((v, true)) => write!(f, ",{}]", String::from(v.clone())),
+            Some((v, false)) => write!(f, ",{})", String::from(v.clone())),
+            None => write!(f, ",+inf]"),
+        }
+    }
+}
+
+/// The smallest key which is greater than all the keys with prefix `key`. Every key of a table
+/// starts with `t`, so the key never consists of `0xff` only.
+pub fn prefix_next(key: &[u8]) -> Vec<u8> {
+    let mut next = key.to_vec();
+    while let Some(last) = next.pop() {
+        if last < u8::MAX {
+            next.push(last + 1);
+            break;
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_next() {
+        assert_eq!(prefix_next(&[1, 2, 3]), vec![1, 2, 4]);
+        assert_eq!(prefix_next(&[1, 255, 255]), vec![2]);
+    }
+
+    #[test]
+    fn test_display_range() {
+        let range = IndexRange {
+            low: Some((EncodeValue::Int(1), false)),
+            high: None,
+        };
+        assert_eq!(range.to_string(), "(1,+inf]");
+        let mut range = IndexRange::default();
+        range.restrict_low(EncodeValue::Int(3), true);
+        range.restrict_high(EncodeValue::Int(3), true);
+        assert!(range.is_point());
+        assert_eq!(range.to_string(), "[3,3]");
+    }
+
+    #[test]
+    fn test_restrict_range() {
+        let mut range = IndexRange::default();
+        range.restrict_low(EncodeValue::Int(1), true);
+        range.restrict_low(EncodeValue::Int(1), false);
+        range.restrict_low(EncodeValue::Int(0), true);
+        range.restrict_high(EncodeValue::Int(10), true);
+        range.restrict_high(EncodeValue::Int(5), false);
+        assert_eq!(range.to_string(), "(1,5)");
+        assert!(!range.is_empty());
+        range.restrict_high(EncodeValue::Int(1), true);
+        assert!(range.is_empty());
+    }
+}
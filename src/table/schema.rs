@@ -1,12 +1,15 @@
 use crate::common::EncodeValue;
 use crate::errors::{MySQLError, MySQLResult};
+use crate::table::auto_id::AutoIdAllocator;
 use msql_srv::{Column, ColumnFlags, ColumnType};
 use sqlparser::ast::DataType;
-use sqlparser::ast::{ColumnDef, ColumnOption, Expr, Ident, ObjectName, TableConstraint, Value};
+use sqlparser::ast::{
+    ColumnDef, ColumnOption, Expr, Ident, ObjectName, Query, TableConstraint, Value,
+};
 use sqlparser::dialect::keywords;
 use sqlparser::tokenizer::{Token, Word};
-use std::fmt::Debug;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::fmt::{self, Debug};
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
 pub trait ValueGenerator: Send + Sync + Debug {
@@ -40,21 +43,33 @@ impl ValueGenerator for DefaultValueGenerator {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct AutoIncrementIdGenerator {
-    max_id: Arc<AtomicU64>,
+/// Computes the value of a generated column. It is built by the planner, the table does not
+/// know how to evaluate an expression.
+pub trait RecordEvaluator: Send + Sync + Debug {
+    /// `record` holds the values of all columns of `table` in their order.
+    fn eval(&self, table: &TableInfo, record: &[EncodeValue]) -> MySQLResult<EncodeValue>;
 }
 
-impl AutoIncrementIdGenerator {
-    pub fn new(max_id: Arc<AtomicU64>) -> Self {
-        AutoIncrementIdGenerator { max_id }
-    }
+/// The expression of a column defined by `AS (expr) {VIRTUAL | STORED}`. The value of a stored
+/// column is written with the row, a virtual one is computed whenever the row is read, but the
+/// entries of the indexes on it are written.
+#[derive(Debug, Clone)]
+pub struct GeneratedColumn {
+    pub expr: Expr,
+    pub stored: bool,
+    /// The columns read by `expr`, which can not be dropped or renamed.
+    pub columns: Vec<String>,
+    pub evaluator: Arc<dyn RecordEvaluator>,
 }
 
+/// Marks an `AUTO_INCREMENT` column. Its values are allocated by `InsertExecutor` from the
+/// `AutoIdAllocator` of the table, which has to read and write storage.
+#[derive(Debug, Clone)]
+pub struct AutoIncrementIdGenerator;
+
 impl ValueGenerator for AutoIncrementIdGenerator {
     fn generate(&self) -> EncodeValue {
-        let id = self.max_id.fetch_add(1, Ordering::SeqCst);
-        EncodeValue::Int(id as i64)
+        EncodeValue::NULL
     }
 
     fn name(&self) -> &str {
@@ -66,9 +81,16 @@ impl ValueGenerator for AutoIncrementIdGenerator {
     }
 }
 
+/// The state of a table, an index or a column. A column is added through `DeleteOnly` and
+/// `WriteOnly` before it becomes `Public`, and dropped through them in the reverse order, every
+/// step is a new version of the schema.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TableState {
     Tombstone,
+    /// Neither read nor written by statements.
+    DeleteOnly,
+    /// Written by statements but not read.
+    WriteOnly,
     Public,
 }
 
@@ -81,6 +103,18 @@ pub enum IndexType {
     Index,
 }
 
+impl IndexType {
+    /// The `Key` of a column as `DESCRIBE` shows it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexType::None => "",
+            IndexType::Primary => "PRI",
+            IndexType::Unique => "UNI",
+            IndexType::MultipleUnqiue | IndexType::Index => "MUL",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DataSchema {
     pub columns: Vec<Arc<ColumnInfo>>,
@@ -88,17 +122,145 @@ pub struct DataSchema {
 
 pub type DataSchemaRef = Arc<DataSchema>;
 
+impl DataSchema {
+    /// Build the schema of a result set which is not read from any table, such as the output of
+    /// `EXPLAIN`.
+    pub fn result_schema(fields: &[(&str, DataType)]) -> DataSchema {
+        let columns = fields
+            .iter()
+            .enumerate()
+            .map(|(offset, (name, data_type))| {
+                Arc::new(ColumnInfo::result_column(name, offset, data_type.clone()))
+            })
+            .collect();
+        DataSchema { columns }
+    }
+}
+
+/// What happens to the rows of the child table when the row they reference is deleted or
+/// updated, MySQL rejects the change for both `RESTRICT` and `NO ACTION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceAction {
+    Restrict,
+    NoAction,
+    Cascade,
+    SetNull,
+}
+
+impl ReferenceAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReferenceAction::Restrict => "RESTRICT",
+            ReferenceAction::NoAction => "NO ACTION",
+            ReferenceAction::Cascade => "CASCADE",
+            ReferenceAction::SetNull => "SET NULL",
+        }
+    }
+}
+
+/// A `FOREIGN KEY` of a table. The referenced columns must be the primary key or a unique
+/// index of the referenced table, so that a row can be checked by one point get.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    /// The columns of this table and their offsets.
+    pub columns: Vec<(String, usize)>,
+    pub ref_db: String,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    pub on_delete: ReferenceAction,
+    pub on_update: ReferenceAction,
+}
+
+/// The definition of the foreign key as `SHOW CREATE TABLE` of MySQL prints it.
+impl fmt::Display for ForeignKeyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns: Vec<&str> = self.columns.iter().map(|(c, _)| c.as_str()).collect();
+        write!(
+            f,
+            "CONSTRAINT `{}` FOREIGN KEY (`{}`) REFERENCES `{}` (`{}`)",
+            self.name,
+            columns.join("`, `"),
+            self.ref_table,
+            self.ref_columns.join("`, `")
+        )?;
+        if self.on_delete != ReferenceAction::NoAction {
+            write!(f, " ON DELETE {}", self.on_delete.name())?;
+        }
+        if self.on_update != ReferenceAction::NoAction {
+            write!(f, " ON UPDATE {}", self.on_update.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// A `CHECK` constraint of a table. A row violates it if `expr` is false on the row, NULL
+/// satisfies it as MySQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckInfo {
+    pub name: String,
+    pub expr: Expr,
+    /// The columns read by `expr`, which can not be dropped or renamed.
+    pub columns: Vec<String>,
+}
+
+/// The definition of the check as `SHOW CREATE TABLE` of MySQL prints it.
+impl fmt::Display for CheckInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CONSTRAINT `{}` CHECK ({})", self.name, self.expr)
+    }
+}
+
+/// A view, its query is expanded inline wherever the view is read. The tables of the query
+/// are qualified by their databases when the view is created, so the view reads the same
+/// tables from any database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewInfo {
+    pub name: String,
+    /// The names given to the columns by `CREATE VIEW`, the columns are named by the select
+    /// list if it is empty.
+    pub columns: Vec<String>,
+    pub query: Query,
+}
+
+impl ViewInfo {
+    /// The definition of the view as `SHOW CREATE VIEW` prints it.
+    pub fn create_statement(&self) -> String {
+        let mut sql = format!("CREATE VIEW `{}` ", self.name);
+        if !self.columns.is_empty() {
+            sql.push_str(&format!("(`{}`) ", self.columns.join("`, `")));
+        }
+        sql.push_str(&format!("AS {}", self.query));
+        sql
+    }
+}
+
+/// The character set and the collation of every string column.
+pub const DEFAULT_CHARSET: &str = "utf8mb4";
+pub const DEFAULT_COLLATION: &str = "utf8mb4_0900_ai_ci";
+
+/// The name of the primary key, by which `ALTER TABLE` drops it.
+pub const PRIMARY_INDEX_NAME: &str = "primary";
+
 #[derive(Debug, Clone)]
 pub struct TableInfo {
     pub id: u64,
     pub name: String,
     pub columns: Vec<Arc<ColumnInfo>>,
     pub indices: Vec<Arc<IndexInfo>>,
+    pub foreign_keys: Vec<Arc<ForeignKeyInfo>>,
+    pub checks: Vec<Arc<CheckInfo>>,
     pub state: TableState,
+    /// Whether the records are stored by the primary key, or by a hidden `_rowid` otherwise.
     pub pk_is_handle: bool,
     pub max_column_id: u64,
     pub max_index_id: u64,
-    pub max_row_id: Arc<AtomicU64>,
+    /// Allocates the hidden `_rowid` of a table without primary key.
+    pub row_id: Arc<AutoIdAllocator>,
+    /// Allocates the values of the `AUTO_INCREMENT` column.
+    pub auto_id: Arc<AutoIdAllocator>,
+    /// The number of rows modified since the statistics of this table were built.
+    pub modify_count: Arc<AtomicU64>,
     pub update_ts: u64,
 }
 
@@ -112,6 +274,10 @@ pub struct ColumnInfo {
     pub comment: String,
     pub key: IndexType,
     pub not_null: bool,
+    pub state: TableState,
+    /// The value of this column in the rows written before it was added.
+    pub origin_default: EncodeValue,
+    pub generated: Option<GeneratedColumn>,
 }
 
 impl Clone for ColumnInfo {
@@ -125,6 +291,9 @@ impl Clone for ColumnInfo {
             comment: self.comment.clone(),
             key: self.key,
             not_null: self.not_null,
+            state: self.state.clone(),
+            origin_default: self.origin_default.clone(),
+            generated: self.generated.clone(),
         }
     }
 }
@@ -136,7 +305,10 @@ impl PartialEq for ColumnInfo {
             && self.offset == other.offset
             && self.comment == other.comment
             && self.key == other.key
-            && self.not_null == other.not_null;
+            && self.not_null == other.not_null
+            && self.state == other.state
+            && self.generated.as_ref().map(|g| (&g.expr, g.stored))
+                == other.generated.as_ref().map(|g| (&g.expr, g.stored));
         if !eq {
             return false;
         }
@@ -164,6 +336,356 @@ pub struct IndexInfo {
 }
 
 impl TableInfo {
+    /// Start the `AUTO_INCREMENT` column and `_rowid` over from 1, as a table of a new id whose
+    /// counter is not in storage yet.
+    pub fn reset_auto_increment(&mut self) {
+        self.auto_id = Arc::new(AutoIdAllocator::new(0));
+        self.row_id = Arc::new(AutoIdAllocator::new(0));
+    }
+
+    /// The statement which creates this table, as `SHOW CREATE TABLE` of MySQL prints it. The
+    /// columns and indexes which are being added or dropped are left out.
+    pub fn create_statement(&self) -> String {
+        let mut lines: Vec<String> = self
+            .columns
+            .iter()
+            .filter(|c| c.state == TableState::Public)
+            .map(|c| c.definition())
+            .collect();
+        let quoted = |columns: &[(String, usize)]| {
+            let names: Vec<String> = columns.iter().map(|(c, _)| format!("`{}`", c)).collect();
+            names.join(",")
+        };
+        let indices = self
+            .indices
+            .iter()
+            .filter(|i| i.state == TableState::Public);
+        for index in indices.clone().filter(|i| i.primary) {
+            lines.push(format!("PRIMARY KEY ({})", quoted(&index.columns)));
+        }
+        for index in indices.filter(|i| !i.primary) {
+            let kind = if index.unique { "UNIQUE KEY" } else { "KEY" };
+            lines.push(format!(
+                "{} `{}` ({})",
+                kind,
+                index.name,
+                quoted(&index.columns)
+            ));
+        }
+        lines.extend(self.foreign_keys.iter().map(|fk| fk.to_string()));
+        lines.extend(self.checks.iter().map(|c| c.to_string()));
+        format!(
+            "CREATE TABLE `{}` (\n  {}\n) ENGINE=InnoDB DEFAULT CHARSET={}",
+            self.name,
+            lines.join(",\n  "),
+            DEFAULT_CHARSET
+        )
+    }
+
+    fn column_offset(&self, name: &str) -> MySQLResult<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name == name)
+            .ok_or(MySQLError::NoColumn)
+    }
+
+    /// Append a public column built from `def`. No row is rewritten, the rows written before
+    /// read the value which the column defaults to now.
+    pub fn add_column(&mut self, def: &ColumnDef) -> MySQLResult<()> {
+        let name = def.name.value.to_lowercase();
+        if self.columns.iter().any(|c| c.name == name) {
+            return Err(MySQLError::DupColumn(name));
+        }
+        let mut constraints = vec![];
+        let mut col = self.build_column(&mut constraints, self.columns.len(), def)?;
+        if !constraints.is_empty() || col.is_auto_increment() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        self.max_column_id += 1;
+        col.id = self.max_column_id;
+        if let Some(generator) = col.default_value.as_ref() {
+            col.origin_default = generator.generate();
+        }
+        self.columns.push(Arc::new(col));
+        Ok(())
+    }
+
+    /// Remove a column which no index covers, the columns after it move forward. Its values are
+    /// left in the rows, where nothing reads them since column ids are never reused.
+    pub fn drop_column(&mut self, name: &str) -> MySQLResult<()> {
+        let offset = self.column_offset(name)?;
+        if self
+            .indices
+            .iter()
+            .any(|index| index.columns.iter().any(|(c, _)| c == name))
+        {
+            return Err(MySQLError::DropIndexedColumn(name.to_string()));
+        }
+        if let Some(fk) = self
+            .foreign_keys
+            .iter()
+            .find(|fk| fk.columns.iter().any(|(c, _)| c == name))
+        {
+            return Err(MySQLError::DropForeignKeyColumn(
+                name.to_string(),
+                fk.name.clone(),
+            ));
+        }
+        self.check_not_in_checks(name)?;
+        self.check_not_generated_from(name)?;
+        if self.columns.len() == 1 {
+            return Err(MySQLError::DropAllColumns);
+        }
+        self.columns.remove(offset);
+        for col in self.columns[offset..].iter_mut() {
+            let mut moved = col.as_ref().clone();
+            moved.offset -= 1;
+            *col = Arc::new(moved);
+        }
+        self.indices = self
+            .indices
+            .iter()
+            .map(|index| {
+                let mut index = index.as_ref().clone();
+                for (_, o) in index.columns.iter_mut() {
+                    if *o > offset {
+                        *o -= 1;
+                    }
+                }
+                Arc::new(index)
+            })
+            .collect();
+        self.foreign_keys = self
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                let mut fk = fk.as_ref().clone();
+                for (_, o) in fk.columns.iter_mut() {
+                    if *o > offset {
+                        *o -= 1;
+                    }
+                }
+                Arc::new(fk)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Change the type, the default and `NOT NULL` of the column named by `def`. Return whether
+    /// the rows must be rewritten because the column is stored in another format.
+    pub fn modify_column(&mut self, def: &ColumnDef) -> MySQLResult<bool> {
+        let offset = self.column_offset(&def.name.value.to_lowercase())?;
+        let mut constraints = vec![];
+        let modified = self.build_column(&mut constraints, offset, def)?;
+        if !constraints.is_empty() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        let mut col = self.columns[offset].as_ref().clone();
+        let reorganize = !same_format(&col.data_type, &modified.data_type);
+        col.origin_default = col.origin_default.convert_to(&modified.data_type);
+        col.data_type = modified.data_type;
+        col.default_value = modified.default_value;
+        col.not_null = modified.not_null;
+        self.columns[offset] = Arc::new(col);
+        Ok(reorganize)
+    }
+
+    pub fn rename_column(&mut self, old: &str, new: &str) -> MySQLResult<()> {
+        let offset = self.column_offset(old)?;
+        if old != new && self.columns.iter().any(|c| c.name == new) {
+            return Err(MySQLError::DupColumn(new.to_string()));
+        }
+        if old != new {
+            self.check_not_in_checks(old)?;
+            self.check_not_generated_from(old)?;
+        }
+        let mut col = self.columns[offset].as_ref().clone();
+        col.name = new.to_string();
+        self.columns[offset] = Arc::new(col);
+        self.indices = self
+            .indices
+            .iter()
+            .map(|index| {
+                let mut index = index.as_ref().clone();
+                for (c, o) in index.columns.iter_mut() {
+                    if *o == offset {
+                        *c = new.to_string();
+                    }
+                }
+                Arc::new(index)
+            })
+            .collect();
+        self.foreign_keys = self
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                let mut fk = fk.as_ref().clone();
+                for (c, o) in fk.columns.iter_mut() {
+                    if *o == offset {
+                        *c = new.to_string();
+                    }
+                }
+                Arc::new(fk)
+            })
+            .collect();
+        Ok(())
+    }
+
+    fn check_not_in_checks(&self, column: &str) -> MySQLResult<()> {
+        match self
+            .checks
+            .iter()
+            .find(|check| check.columns.iter().any(|c| c == column))
+        {
+            Some(check) => Err(MySQLError::DependentByCheck(
+                check.name.clone(),
+                column.to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn check_not_generated_from(&self, column: &str) -> MySQLResult<()> {
+        let dependent = self.columns.iter().any(|c| {
+            c.generated.as_ref().map_or(false, |g| {
+                c.name != column && g.columns.iter().any(|n| n == column)
+            })
+        });
+        if dependent {
+            return Err(MySQLError::DependentByGenerated(column.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Add an index in the state of `index`, an unnamed one is named after its first column.
+    /// Return the name of the index.
+    pub fn add_index(&mut self, mut index: IndexInfo) -> MySQLResult<String> {
+        if index.primary && self.get_primary_index().is_some() {
+            return Err(MySQLError::MultiplePrimaryKey);
+        }
+        if self.indices.iter().any(|i| i.columns == index.columns) {
+            return Err(MySQLError::IndexExist);
+        }
+        if index.primary {
+            index.name = PRIMARY_INDEX_NAME.to_string();
+        } else if index.name.is_empty() {
+            let first = index.columns[0].0.clone();
+            index.name = first.clone();
+            let mut suffix = 2;
+            while self.indices.iter().any(|i| i.name == index.name) {
+                index.name = format!("{}_{}", first, suffix);
+                suffix += 1;
+            }
+        } else if self.indices.iter().any(|i| i.name == index.name) {
+            return Err(MySQLError::IndexExist);
+        }
+        self.max_index_id += 1;
+        index.id = self.max_index_id;
+        index.table_name = self.name.clone();
+        let name = index.name.clone();
+        self.indices.push(Arc::new(index));
+        self.update_column_keys();
+        Ok(name)
+    }
+
+    /// Remove an index and return it, so that its entries can be deleted.
+    pub fn drop_index(&mut self, name: &str) -> MySQLResult<Arc<IndexInfo>> {
+        let position = self
+            .indices
+            .iter()
+            .position(|i| i.name == name)
+            .ok_or(MySQLError::NoIndex)?;
+        let index = self.indices.remove(position);
+        self.update_column_keys();
+        Ok(index)
+    }
+
+    pub fn set_index_state(&mut self, name: &str, state: TableState) -> MySQLResult<()> {
+        let position = self
+            .indices
+            .iter()
+            .position(|i| i.name == name)
+            .ok_or(MySQLError::NoIndex)?;
+        let mut index = self.indices[position].as_ref().clone();
+        index.state = state;
+        self.indices[position] = Arc::new(index);
+        Ok(())
+    }
+
+    /// Compute the `key` of every column from the indexes which cover it: every column of the
+    /// primary key, and the first column of the other indexes, as `SHOW COLUMNS` of MySQL does.
+    pub fn update_column_keys(&mut self) {
+        let mut keys = vec![IndexType::None; self.columns.len()];
+        for index in self.indices.iter() {
+            for (i, (_, offset)) in index.columns.iter().enumerate() {
+                let key = if index.primary {
+                    IndexType::Primary
+                } else if i > 0 {
+                    continue;
+                } else if index.unique && index.columns.len() == 1 {
+                    IndexType::Unique
+                } else if index.unique {
+                    IndexType::MultipleUnqiue
+                } else {
+                    IndexType::Index
+                };
+                if key_priority(key) > key_priority(keys[*offset]) {
+                    keys[*offset] = key;
+                }
+            }
+        }
+        for (col, key) in self.columns.iter_mut().zip(keys) {
+            if col.key != key {
+                let mut changed = col.as_ref().clone();
+                changed.key = key;
+                *col = Arc::new(changed);
+            }
+        }
+    }
+
+    pub fn set_column_state(&mut self, name: &str, state: TableState) -> MySQLResult<()> {
+        let offset = self.column_offset(name)?;
+        let mut col = self.columns[offset].as_ref().clone();
+        col.state = state;
+        self.columns[offset] = Arc::new(col);
+        Ok(())
+    }
+
+    /// The public primary key or unique index whose columns are `columns` in order, which a
+    /// foreign key referencing them is checked by.
+    pub fn referenced_index(&self, columns: &[String]) -> Option<Arc<IndexInfo>> {
+        self.indices
+            .iter()
+            .find(|i| {
+                (i.primary || i.unique)
+                    && i.state == TableState::Public
+                    && i.columns.iter().map(|(c, _)| c).eq(columns.iter())
+            })
+            .cloned()
+    }
+
+    /// Set the actions of the foreign keys in the order they are defined, `SET NULL` is not
+    /// allowed on a `NOT NULL` column.
+    pub fn set_reference_actions(
+        &mut self,
+        actions: &[(ReferenceAction, ReferenceAction)],
+    ) -> MySQLResult<()> {
+        if actions.len() != self.foreign_keys.len() {
+            return Err(MySQLError::UnsupportSQL);
+        }
+        for (fk, (on_delete, on_update)) in self.foreign_keys.iter_mut().zip(actions) {
+            let set_null =
+                *on_delete == ReferenceAction::SetNull || *on_update == ReferenceAction::SetNull;
+            if set_null && fk.columns.iter().any(|(_, o)| self.columns[*o].not_null) {
+                return Err(MySQLError::CannotAddForeignKey(fk.name.clone()));
+            }
+            let fk = Arc::make_mut(fk);
+            fk.on_delete = *on_delete;
+            fk.on_update = *on_update;
+        }
+        Ok(())
+    }
+
     pub fn get_primary_index(&self) -> Option<Arc<IndexInfo>> {
         for i in self.indices.iter() {
             if i.primary {
@@ -188,14 +710,19 @@ impl TableInfo {
             name: table_name,
             columns: vec![],
             indices: vec![],
+            foreign_keys: vec![],
+            checks: vec![],
             state: TableState::Public,
             pk_is_handle: true,
             max_column_id: 0,
             max_index_id: 0,
             update_ts: 0,
-            max_row_id: Arc::new(AtomicU64::new(0)),
+            row_id: Arc::new(AutoIdAllocator::new(0)),
+            auto_id: Arc::new(AutoIdAllocator::new(0)),
+            modify_count: Arc::new(AtomicU64::new(0)),
         };
         table_info.build_columns_and_constraints(column_defs, constrains)?;
+        table_info.pk_is_handle = table_info.get_primary_index().is_some();
         Ok(table_info)
     }
 
@@ -212,27 +739,24 @@ impl TableInfo {
             cols.push(column);
             offset += 1;
         }
+        let mut foreign_keys = vec![];
+        let mut checks = vec![];
+        constraints.retain(|c| match c {
+            TableConstraint::ForeignKey { .. } => {
+                foreign_keys.push(c.clone());
+                false
+            }
+            TableConstraint::Check { .. } => {
+                checks.push(c.clone());
+                false
+            }
+            _ => true,
+        });
         for c in constraints.iter_mut() {
             match c {
-                TableConstraint::Unique {
-                    is_primary,
-                    columns,
-                    ..
-                } => {
+                TableConstraint::Unique { columns, .. } => {
                     for c in columns {
                         c.value = c.value.to_lowercase();
-                        let l = cols.len();
-                        for col in cols.iter_mut() {
-                            if col.name == c.value {
-                                if *is_primary {
-                                    col.key = IndexType::Primary;
-                                } else if l > 0 {
-                                    col.key = IndexType::MultipleUnqiue;
-                                } else {
-                                    col.key = IndexType::Unique;
-                                }
-                            }
-                        }
                     }
                 }
                 _ => return Err(MySQLError::UnsupportSQL),
@@ -243,12 +767,27 @@ impl TableInfo {
             col.id = self.max_column_id;
             self.columns.push(Arc::new(col));
         }
+        for constraint in foreign_keys {
+            let fk = self.build_foreign_key_info(constraint)?;
+            self.foreign_keys.push(Arc::new(fk));
+        }
+        for constraint in checks {
+            let check = self.build_check_info(constraint)?;
+            self.checks.push(Arc::new(check));
+        }
         for constriant in constraints {
             let mut index_info = self.build_index_info(constriant)?;
             self.max_index_id += 1;
             index_info.id = self.max_index_id;
             self.indices.push(Arc::new(index_info));
         }
+        // The columns of the primary key are `NOT NULL` even if they are not declared so.
+        if let Some(pk) = self.get_primary_index() {
+            for (_, offset) in pk.columns.iter() {
+                Arc::make_mut(&mut self.columns[*offset]).not_null = true;
+            }
+        }
+        self.update_column_keys();
         // TODO: Check constraints conflict and valid.
         Ok(())
     }
@@ -268,11 +807,13 @@ impl TableInfo {
             comment: "".to_string(),
             key: IndexType::None,
             not_null: false,
+            state: TableState::Public,
+            origin_default: EncodeValue::NULL,
+            generated: None,
         };
         for opt in col_def.options.iter() {
             match &opt.option {
                 ColumnOption::Unique { is_primary } => {
-                    col.key = IndexType::Primary;
                     constraints.push(TableConstraint::Unique {
                         name: None,
                         columns: vec![Ident {
@@ -285,15 +826,19 @@ impl TableInfo {
                 ColumnOption::NotNull => {
                     col.not_null = true;
                 }
+                ColumnOption::Check(expr) => {
+                    constraints.push(TableConstraint::Check {
+                        name: opt.name.clone(),
+                        expr: Box::new(expr.clone()),
+                    });
+                }
                 ColumnOption::DialectSpecific(others) => {
                     for word in others {
                         if let Token::Word(Word { value, keyword, .. }) = word {
                             if value.to_lowercase() == "auto_increment"
                                 && *keyword == keywords::Keyword::AUTO_INCREMENT
                             {
-                                col.default_value = Some(Box::new(AutoIncrementIdGenerator::new(
-                                    self.max_row_id.clone(),
-                                )));
+                                col.default_value = Some(Box::new(AutoIncrementIdGenerator));
                             }
                         }
                     }
@@ -326,7 +871,7 @@ impl TableInfo {
             name: "".to_string(),
             table_name: self.name.clone(),
             columns: vec![],
-            state: TableState::Tombstone,
+            state: TableState::Public,
             primary: false,
             unique: false,
         };
@@ -336,10 +881,13 @@ impl TableInfo {
                 name,
                 is_primary,
             } => {
-                if let Some(name) = name {
+                if is_primary {
+                    index_info.name = PRIMARY_INDEX_NAME.to_string();
+                } else if let Some(name) = name {
                     index_info.name = name.value.to_lowercase();
                 }
                 index_info.primary = is_primary;
+                index_info.unique = true;
                 for key in columns {
                     let mut index_col = self.columns[0].clone();
                     for col in self.columns.iter() {
@@ -361,6 +909,75 @@ impl TableInfo {
         Ok(index_info)
     }
 
+    /// Build a foreign key which references a table of the current database if `ref_db` is
+    /// empty, it is named `{table}_ibfk_{n}` as MySQL does if it has no name.
+    fn build_foreign_key_info(&self, constraint: TableConstraint) -> MySQLResult<ForeignKeyInfo> {
+        let (name, columns, foreign_table, referred_columns) = match constraint {
+            TableConstraint::ForeignKey {
+                name,
+                columns,
+                foreign_table,
+                referred_columns,
+                ..
+            } => (name, columns, foreign_table, referred_columns),
+            _ => return Err(MySQLError::UnsupportSQL),
+        };
+        let name = match name {
+            Some(name) => name.value.to_lowercase(),
+            None => format!("{}_ibfk_{}", self.name, self.foreign_keys.len() + 1),
+        };
+        if self.foreign_keys.iter().any(|fk| fk.name == name) {
+            return Err(MySQLError::CannotAddForeignKey(name));
+        }
+        let mut fk_columns = vec![];
+        for c in columns.iter() {
+            let c = c.value.to_lowercase();
+            let offset = self.column_offset(&c)?;
+            fk_columns.push((c, offset));
+        }
+        if fk_columns.is_empty() || fk_columns.len() != referred_columns.len() {
+            return Err(MySQLError::CannotAddForeignKey(name));
+        }
+        let (ref_db, ref_table) = match foreign_table.0.as_slice() {
+            [table] => ("".to_string(), table.value.to_lowercase()),
+            [db, table] => (db.value.to_lowercase(), table.value.to_lowercase()),
+            _ => return Err(MySQLError::UnsupportSQL),
+        };
+        Ok(ForeignKeyInfo {
+            name,
+            columns: fk_columns,
+            ref_db,
+            ref_table,
+            ref_columns: referred_columns
+                .iter()
+                .map(|c| c.value.to_lowercase())
+                .collect(),
+            on_delete: ReferenceAction::NoAction,
+            on_update: ReferenceAction::NoAction,
+        })
+    }
+
+    /// Build a check whose columns are left to be resolved, it is named `{table}_chk_{n}` as
+    /// MySQL does if it has no name.
+    fn build_check_info(&self, constraint: TableConstraint) -> MySQLResult<CheckInfo> {
+        let (name, expr) = match constraint {
+            TableConstraint::Check { name, expr } => (name, expr),
+            _ => return Err(MySQLError::UnsupportSQL),
+        };
+        let name = match name {
+            Some(name) => name.value.to_lowercase(),
+            None => format!("{}_chk_{}", self.name, self.checks.len() + 1),
+        };
+        if self.checks.iter().any(|c| c.name == name) {
+            return Err(MySQLError::DupCheck(name));
+        }
+        Ok(CheckInfo {
+            name,
+            expr: *expr,
+            columns: vec![],
+        })
+    }
+
     fn parse_column_string_value(
         &self,
         data_type: &DataType,
@@ -383,7 +1000,233 @@ impl TableInfo {
     }
 }
 
+fn key_priority(key: IndexType) -> u8 {
+    match key {
+        IndexType::None => 0,
+        IndexType::Index => 1,
+        IndexType::MultipleUnqiue => 2,
+        IndexType::Unique => 3,
+        IndexType::Primary => 4,
+    }
+}
+
+/// Whether the values of both types are stored as the same bytes, in records and in index keys.
+fn same_format(a: &DataType, b: &DataType) -> bool {
+    let bytes = |t: &DataType| matches!(t, DataType::Char(_) | DataType::String);
+    a == b || (bytes(a) && bytes(b))
+}
+
+/// The number in a string written to a numeric column, `None` if it is not a number.
+fn parse_number(value: &[u8]) -> Option<f64> {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+}
+
 impl ColumnInfo {
+    pub fn result_column(name: &str, offset: usize, data_type: DataType) -> ColumnInfo {
+        ColumnInfo {
+            id: offset as u64 + 1,
+            name: name.to_string(),
+            offset,
+            data_type,
+            default_value: None,
+            comment: "".to_string(),
+            key: IndexType::None,
+            not_null: false,
+            state: TableState::Public,
+            origin_default: EncodeValue::NULL,
+            generated: None,
+        }
+    }
+
+    pub fn is_auto_increment(&self) -> bool {
+        self.default_value
+            .as_ref()
+            .map_or(false, |v| v.name() == "AutoIncrementIdGenerator")
+    }
+
+    /// Whether this is a generated column which is not stored.
+    pub fn is_virtual(&self) -> bool {
+        self.generated.as_ref().map_or(false, |g| !g.stored)
+    }
+
+    /// Convert a value written to this column to its type. A NULL in a `NOT NULL` column, a
+    /// string longer than the column and a number out of its range or which is not a number
+    /// fail the statement in strict mode, otherwise the value is adjusted as MySQL does and the
+    /// error is appended to `warnings`.
+    pub fn convert_value(
+        &self,
+        value: EncodeValue,
+        strict: bool,
+        warnings: &mut Vec<MySQLError>,
+    ) -> MySQLResult<EncodeValue> {
+        let (value, error) = self.check_value(value);
+        match error {
+            Some(e) if strict => Err(e),
+            Some(e) => {
+                warnings.push(e);
+                Ok(value)
+            }
+            None => Ok(value),
+        }
+    }
+
+    /// The value which a `NOT NULL` column takes instead of NULL in non-strict mode.
+    pub fn implicit_default(&self) -> EncodeValue {
+        match &self.data_type {
+            DataType::Char(_) | DataType::Varchar(_) | DataType::String | DataType::Text => {
+                EncodeValue::Bytes(vec![])
+            }
+            data_type => EncodeValue::Int(0).convert_to(data_type),
+        }
+    }
+
+    fn check_value(&self, value: EncodeValue) -> (EncodeValue, Option<MySQLError>) {
+        match (&value, &self.data_type) {
+            (EncodeValue::NULL, _) if self.not_null => (
+                self.implicit_default(),
+                Some(MySQLError::BadNull(self.name.clone())),
+            ),
+            (EncodeValue::NULL, _) => (value, None),
+            (_, DataType::SmallInt) => self.check_integer(value, i16::MIN as i64, i16::MAX as i64),
+            (_, DataType::Int) => self.check_integer(value, i32::MIN as i64, i32::MAX as i64),
+            (_, DataType::BigInt) => self.check_integer(value, i64::MIN, i64::MAX),
+            (EncodeValue::Bytes(v), DataType::Double) if parse_number(v).is_none() => (
+                EncodeValue::Double(0.0),
+                Some(self.wrong_value("double", v)),
+            ),
+            (_, DataType::Char(len)) => self.check_length(value, len.unwrap_or(1)),
+            (_, DataType::Varchar(Some(len))) => self.check_length(value, *len),
+            _ => (value.convert_to(&self.data_type), None),
+        }
+    }
+
+    fn check_integer(
+        &self,
+        value: EncodeValue,
+        min: i64,
+        max: i64,
+    ) -> (EncodeValue, Option<MySQLError>) {
+        let v = match &value {
+            EncodeValue::Int(v) => *v as f64,
+            EncodeValue::Bytes(v) => match parse_number(v) {
+                Some(v) => v.round(),
+                None => return (EncodeValue::Int(0), Some(self.wrong_value("integer", v))),
+            },
+            v => v.as_f64().unwrap_or(0.0).round(),
+        };
+        let out_of_range = Some(MySQLError::DataOutOfRange(self.name.clone()));
+        if v < min as f64 {
+            (EncodeValue::Int(min), out_of_range)
+        } else if v > max as f64 {
+            (EncodeValue::Int(max), out_of_range)
+        } else {
+            (value.convert_to(&self.data_type), None)
+        }
+    }
+
+    /// The length of a string is counted by characters, as the columns are in `utf8mb4`.
+    fn check_length(&self, value: EncodeValue, len: u64) -> (EncodeValue, Option<MySQLError>) {
+        let bytes = match value.convert_to(&self.data_type) {
+            EncodeValue::Bytes(v) => v,
+            v => return (v, None),
+        };
+        let len = len as usize;
+        let too_long = match std::str::from_utf8(&bytes) {
+            Ok(s) => s.chars().count() > len,
+            Err(_) => bytes.len() > len,
+        };
+        if !too_long {
+            return (EncodeValue::Bytes(bytes), None);
+        }
+        let truncated = match std::str::from_utf8(&bytes) {
+            Ok(s) => s.chars().take(len).collect::<String>().into_bytes(),
+            Err(_) => bytes[..len].to_vec(),
+        };
+        (
+            EncodeValue::Bytes(truncated),
+            Some(MySQLError::DataTooLong(self.name.clone())),
+        )
+    }
+
+    fn wrong_value(&self, type_name: &str, value: &[u8]) -> MySQLError {
+        MySQLError::WrongValueForColumn(
+            type_name.to_string(),
+            String::from_utf8_lossy(value).to_string(),
+            self.name.clone(),
+        )
+    }
+
+    /// The type of this column as MySQL names it.
+    pub fn type_name(&self) -> String {
+        match &self.data_type {
+            DataType::Char(len) => format!("char({})", len.unwrap_or(1)),
+            DataType::Varchar(Some(len)) => format!("varchar({})", len),
+            DataType::Decimal(precision, scale) => format!(
+                "decimal({},{})",
+                precision.unwrap_or(10),
+                scale.unwrap_or(0)
+            ),
+            DataType::Boolean => "tinyint(1)".to_string(),
+            data_type => data_type.to_string().to_lowercase(),
+        }
+    }
+
+    /// The collation of this column, `None` if it is not a string.
+    pub fn collation(&self) -> Option<&'static str> {
+        match self.data_type {
+            DataType::Char(_) | DataType::Varchar(_) | DataType::String | DataType::Text => {
+                Some(DEFAULT_COLLATION)
+            }
+            _ => None,
+        }
+    }
+
+    /// The default value which is written when this column is left out, `None` if it is NULL
+    /// or allocated by `AUTO_INCREMENT`.
+    pub fn default_text(&self) -> Option<String> {
+        match self.default_value.as_ref().map(|g| g.generate()) {
+            Some(EncodeValue::NULL) | None => None,
+            Some(v) => Some(String::from(v)),
+        }
+    }
+
+    /// The `Extra` of this column as `DESCRIBE` shows it.
+    pub fn extra(&self) -> &'static str {
+        match &self.generated {
+            Some(g) if g.stored => "STORED GENERATED",
+            Some(_) => "VIRTUAL GENERATED",
+            None if self.is_auto_increment() => "auto_increment",
+            None => "",
+        }
+    }
+
+    /// The definition of this column in `SHOW CREATE TABLE`.
+    pub fn definition(&self) -> String {
+        let mut def = format!("`{}` {}", self.name, self.type_name());
+        if let Some(g) = &self.generated {
+            let kind = if g.stored { "STORED" } else { "VIRTUAL" };
+            def.push_str(&format!(" GENERATED ALWAYS AS ({}) {}", g.expr, kind));
+        }
+        if self.not_null {
+            def.push_str(" NOT NULL");
+        }
+        match self.default_text() {
+            _ if self.generated.is_some() => (),
+            Some(v) => def.push_str(&format!(" DEFAULT '{}'", v.replace('\'', "''"))),
+            None if !self.not_null => def.push_str(" DEFAULT NULL"),
+            None => (),
+        }
+        if self.is_auto_increment() {
+            def.push_str(" AUTO_INCREMENT");
+        }
+        if !self.comment.is_empty() {
+            def.push_str(&format!(" COMMENT '{}'", self.comment.replace('\'', "''")));
+        }
+        def
+    }
+
     pub fn to_mysql_column(&self) -> MySQLResult<Column> {
         let tp = match &self.data_type {
             DataType::Char(_) => ColumnType::MYSQL_TYPE_VARCHAR,
@@ -392,8 +1235,8 @@ impl ColumnInfo {
             DataType::Float(_) => ColumnType::MYSQL_TYPE_FLOAT,
             DataType::SmallInt => ColumnType::MYSQL_TYPE_SHORT,
             DataType::Int => ColumnType::MYSQL_TYPE_LONG,
-            DataType::BigInt => ColumnType::MYSQL_TYPE_LONG,
-            DataType::Double => ColumnType::MYSQL_TYPE_FLOAT,
+            DataType::BigInt => ColumnType::MYSQL_TYPE_LONGLONG,
+            DataType::Double => ColumnType::MYSQL_TYPE_DOUBLE,
             DataType::Boolean => ColumnType::MYSQL_TYPE_SHORT,
             DataType::Text => ColumnType::MYSQL_TYPE_VARCHAR,
             DataType::String => ColumnType::MYSQL_TYPE_VAR_STRING,
@@ -0,0 +1,440 @@
+use super::auto_id::AutoIdAllocator;
+use super::schema::{
+    ColumnInfo, IndexInfo, TableInfo, TableState, DEFAULT_CHARSET, DEFAULT_COLLATION,
+};
+use super::table::TableSource;
+use super::table_manager::DBTableManager;
+use crate::common::EncodeValue;
+use crate::session::Session;
+use sqlparser::ast::DataType;
+use std::sync::Arc;
+
+/// The database of the tables which describe the server.
+pub const INFORMATION_SCHEMA: &str = "information_schema";
+/// The database of the tables which describe the runtime state of the server.
+pub const PERFORMANCE_SCHEMA: &str = "performance_schema";
+
+/// The catalog which every database belongs to, as MySQL.
+const CATALOG: &str = "def";
+
+/// A table of `information_schema` or `performance_schema`. Nothing of it is stored, its rows
+/// are built from the catalog and the state of the server every time it is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemTable {
+    Processlist,
+    Schemata,
+    Tables,
+    Columns,
+    Statistics,
+    KeyColumnUsage,
+    SessionVariables,
+}
+
+/// Every system table, ordered by database and name.
+const SYSTEM_TABLES: [SystemTable; 7] = [
+    SystemTable::Columns,
+    SystemTable::KeyColumnUsage,
+    SystemTable::Processlist,
+    SystemTable::Schemata,
+    SystemTable::Statistics,
+    SystemTable::Tables,
+    SystemTable::SessionVariables,
+];
+
+/// Whether `db` is a database of system tables, which can not be created or dropped.
+pub fn is_system_database(db: &str) -> bool {
+    db == INFORMATION_SCHEMA || db == PERFORMANCE_SCHEMA
+}
+
+impl SystemTable {
+    pub fn from_name(db: &str, name: &str) -> Option<SystemTable> {
+        let name = name.to_lowercase();
+        SYSTEM_TABLES
+            .iter()
+            .find(|t| t.db() == db && t.name() == name)
+            .copied()
+    }
+
+    /// The tables of the system database `db`, ordered by name.
+    pub fn tables_of(db: &str) -> Vec<SystemTable> {
+        SYSTEM_TABLES
+            .iter()
+            .filter(|t| t.db() == db)
+            .copied()
+            .collect()
+    }
+
+    pub fn db(&self) -> &'static str {
+        match self {
+            SystemTable::SessionVariables => PERFORMANCE_SCHEMA,
+            _ => INFORMATION_SCHEMA,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemTable::Processlist => "processlist",
+            SystemTable::Schemata => "schemata",
+            SystemTable::Tables => "tables",
+            SystemTable::Columns => "columns",
+            SystemTable::Statistics => "statistics",
+            SystemTable::KeyColumnUsage => "key_column_usage",
+            SystemTable::SessionVariables => "session_variables",
+        }
+    }
+
+    fn columns(&self) -> Vec<(&'static str, DataType)> {
+        match self {
+            SystemTable::Processlist => vec![
+                ("id", DataType::BigInt),
+                ("user", DataType::String),
+                ("host", DataType::String),
+                ("db", DataType::String),
+                ("command", DataType::String),
+                ("time", DataType::BigInt),
+                ("state", DataType::String),
+                ("info", DataType::String),
+            ],
+            SystemTable::Schemata => vec![
+                ("catalog_name", DataType::String),
+                ("schema_name", DataType::String),
+                ("default_character_set_name", DataType::String),
+                ("default_collation_name", DataType::String),
+                ("sql_path", DataType::String),
+            ],
+            SystemTable::Tables => vec![
+                ("table_catalog", DataType::String),
+                ("table_schema", DataType::String),
+                ("table_name", DataType::String),
+                ("table_type", DataType::String),
+                ("engine", DataType::String),
+                ("version", DataType::BigInt),
+                ("row_format", DataType::String),
+                ("table_rows", DataType::BigInt),
+                ("auto_increment", DataType::BigInt),
+                ("table_collation", DataType::String),
+                ("table_comment", DataType::String),
+            ],
+            SystemTable::Columns => vec![
+                ("table_catalog", DataType::String),
+                ("table_schema", DataType::String),
+                ("table_name", DataType::String),
+                ("column_name", DataType::String),
+                ("ordinal_position", DataType::BigInt),
+                ("column_default", DataType::String),
+                ("is_nullable", DataType::String),
+                ("data_type", DataType::String),
+                ("character_maximum_length", DataType::BigInt),
+                ("numeric_precision", DataType::BigInt),
+                ("numeric_scale", DataType::BigInt),
+                ("character_set_name", DataType::String),
+                ("collation_name", DataType::String),
+                ("column_type", DataType::String),
+                ("column_key", DataType::String),
+                ("extra", DataType::String),
+                ("column_comment", DataType::String),
+            ],
+            SystemTable::Statistics => vec![
+                ("table_catalog", DataType::String),
+                ("table_schema", DataType::String),
+                ("table_name", DataType::String),
+                ("non_unique", DataType::BigInt),
+                ("index_schema", DataType::String),
+                ("index_name", DataType::String),
+                ("seq_in_index", DataType::BigInt),
+                ("column_name", DataType::String),
+                ("collation", DataType::String),
+                ("cardinality", DataType::BigInt),
+                ("nullable", DataType::String),
+                ("index_type", DataType::String),
+                ("comment", DataType::String),
+                ("is_visible", DataType::String),
+            ],
+            SystemTable::KeyColumnUsage => vec![
+                ("constraint_catalog", DataType::String),
+                ("constraint_schema", DataType::String),
+                ("constraint_name", DataType::String),
+                ("table_catalog", DataType::String),
+                ("table_schema", DataType::String),
+                ("table_name", DataType::String),
+                ("column_name", DataType::String),
+                ("ordinal_position", DataType::BigInt),
+                ("position_in_unique_constraint", DataType::BigInt),
+                ("referenced_table_schema", DataType::String),
+                ("referenced_table_name", DataType::String),
+                ("referenced_column_name", DataType::String),
+            ],
+            SystemTable::SessionVariables => vec![
+                ("variable_name", DataType::String),
+                ("variable_value", DataType::String),
+            ],
+        }
+    }
+
+    pub fn table_info(&self) -> TableInfo {
+        let columns: Vec<Arc<ColumnInfo>> = self
+            .columns()
+            .into_iter()
+            .enumerate()
+            .map(|(offset, (name, data_type))| {
+                Arc::new(ColumnInfo::result_column(name, offset, data_type))
+            })
+            .collect();
+        TableInfo {
+            id: 0,
+            name: self.name().to_string(),
+            max_column_id: columns.len() as u64,
+            columns,
+            indices: vec![],
+            foreign_keys: vec![],
+            checks: vec![],
+            state: TableState::Public,
+            pk_is_handle: false,
+            max_index_id: 0,
+            row_id: Arc::new(AutoIdAllocator::new(0)),
+            auto_id: Arc::new(AutoIdAllocator::new(0)),
+            modify_count: Arc::new(Default::default()),
+            update_ts: 0,
+        }
+    }
+
+    /// The rows seen by `session`, ordered as the columns of the table.
+    pub fn rows(&self, session: &Session) -> Vec<Vec<EncodeValue>> {
+        match self {
+            SystemTable::Processlist => session
+                .processes()
+                .list()
+                .into_iter()
+                .map(|p| p.info().to_row(true))
+                .collect(),
+            SystemTable::Schemata => databases(&session.get_table_manager().read().unwrap())
+                .into_iter()
+                .map(|db| {
+                    vec![
+                        text(CATALOG),
+                        text(&db),
+                        text(DEFAULT_CHARSET),
+                        text(DEFAULT_COLLATION),
+                        EncodeValue::NULL,
+                    ]
+                })
+                .collect(),
+            SystemTable::Tables => tables(session)
+                .into_iter()
+                .map(|(db, table)| {
+                    let system = table.system_table().is_some();
+                    let rows = session
+                        .get_table_stats(table.id())
+                        .filter(|_| !system)
+                        .map_or(EncodeValue::NULL, |s| EncodeValue::Int(s.row_count as i64));
+                    vec![
+                        text(CATALOG),
+                        text(&db),
+                        text(table.name()),
+                        text(if system { "SYSTEM VIEW" } else { "BASE TABLE" }),
+                        if system {
+                            EncodeValue::NULL
+                        } else {
+                            text("InnoDB")
+                        },
+                        EncodeValue::Int(10),
+                        text("Dynamic"),
+                        rows,
+                        EncodeValue::NULL,
+                        text(DEFAULT_COLLATION),
+                        text(""),
+                    ]
+                })
+                .collect(),
+            SystemTable::Columns => tables(session)
+                .into_iter()
+                .flat_map(|(db, table)| {
+                    let meta = table.meta();
+                    let columns = meta.columns.iter();
+                    let columns = columns.filter(|c| c.state == TableState::Public);
+                    columns
+                        .enumerate()
+                        .map(|(i, c)| column_row(&db, meta, i, c))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            SystemTable::Statistics => tables(session)
+                .into_iter()
+                .flat_map(|(db, table)| {
+                    let meta = table.meta();
+                    let indices = meta.indices.iter();
+                    indices
+                        .filter(|index| index.state == TableState::Public)
+                        .flat_map(|index| statistics_rows(&db, meta, index))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            SystemTable::KeyColumnUsage => tables(session)
+                .into_iter()
+                .flat_map(|(db, table)| key_column_usage_rows(&db, table.meta()))
+                .collect(),
+            SystemTable::SessionVariables => session
+                .variables()
+                .values()
+                .into_iter()
+                .map(|(name, value)| vec![text(name), EncodeValue::Bytes(value.into_bytes())])
+                .collect(),
+        }
+    }
+}
+
+fn text(s: &str) -> EncodeValue {
+    EncodeValue::Bytes(s.as_bytes().to_vec())
+}
+
+/// The names of all databases in alphabetical order, including the system databases.
+fn databases(table_mgr: &DBTableManager) -> Vec<String> {
+    let mut names = table_mgr.database_names();
+    names.push(INFORMATION_SCHEMA.to_string());
+    names.push(PERFORMANCE_SCHEMA.to_string());
+    names.sort();
+    names
+}
+
+/// Every table with its database, ordered by database and name.
+fn tables(session: &Session) -> Vec<(String, Arc<TableSource>)> {
+    let table_mgr = session.get_table_manager();
+    let table_mgr = table_mgr.read().unwrap();
+    let mut tables = vec![];
+    for db in databases(&table_mgr) {
+        if is_system_database(&db) {
+            for t in SystemTable::tables_of(&db) {
+                tables.push((db.clone(), Arc::new(TableSource::system(t))));
+            }
+            continue;
+        }
+        for name in table_mgr.table_names(&db).unwrap_or_default() {
+            if let Some(table) = table_mgr.get_table(&db, &name) {
+                tables.push((db.clone(), table));
+            }
+        }
+    }
+    tables
+}
+
+fn column_row(db: &str, table: &TableInfo, i: usize, c: &ColumnInfo) -> Vec<EncodeValue> {
+    let column_type = c.type_name();
+    let data_type = column_type.split('(').next().unwrap_or("");
+    let int = |v: Option<u64>| v.map_or(EncodeValue::NULL, |v| EncodeValue::Int(v as i64));
+    let (length, precision, scale) = match &c.data_type {
+        DataType::Char(len) => (Some(len.unwrap_or(1)), None, None),
+        DataType::Varchar(len) => (*len, None, None),
+        DataType::Text | DataType::String => (Some(65535), None, None),
+        DataType::SmallInt => (None, Some(5), Some(0)),
+        DataType::Int => (None, Some(10), Some(0)),
+        DataType::BigInt => (None, Some(19), Some(0)),
+        DataType::Double => (None, Some(22), None),
+        DataType::Decimal(p, s) => (None, Some(p.unwrap_or(10)), Some(s.unwrap_or(0))),
+        _ => (None, None, None),
+    };
+    let collation = c.collation();
+    vec![
+        text(CATALOG),
+        text(db),
+        text(&table.name),
+        text(&c.name),
+        EncodeValue::Int(i as i64 + 1),
+        c.default_text().map_or(EncodeValue::NULL, |v| text(&v)),
+        text(if c.not_null { "NO" } else { "YES" }),
+        text(data_type),
+        int(length),
+        int(precision),
+        int(scale),
+        collation.map_or(EncodeValue::NULL, |_| text(DEFAULT_CHARSET)),
+        collation.map_or(EncodeValue::NULL, text),
+        text(&column_type),
+        text(c.key.name()),
+        text(c.extra()),
+        text(&c.comment),
+    ]
+}
+
+/// The name of an index as MySQL shows it, the primary key is `PRIMARY`.
+fn index_name(index: &IndexInfo) -> String {
+    if index.primary {
+        "PRIMARY".to_string()
+    } else {
+        index.name.clone()
+    }
+}
+
+/// A row for every column of `index`.
+fn statistics_rows(db: &str, table: &TableInfo, index: &IndexInfo) -> Vec<Vec<EncodeValue>> {
+    index
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, offset))| {
+            let nullable = !table.columns[*offset].not_null;
+            vec![
+                text(CATALOG),
+                text(db),
+                text(&table.name),
+                EncodeValue::Int(if index.unique { 0 } else { 1 }),
+                text(db),
+                text(&index_name(index)),
+                EncodeValue::Int(i as i64 + 1),
+                text(name),
+                text("A"),
+                EncodeValue::NULL,
+                text(if nullable { "YES" } else { "" }),
+                text("BTREE"),
+                text(""),
+                text("YES"),
+            ]
+        })
+        .collect()
+}
+
+/// A row for every column of the primary key, the unique indexes and the foreign keys of
+/// `table`.
+fn key_column_usage_rows(db: &str, table: &TableInfo) -> Vec<Vec<EncodeValue>> {
+    let mut rows = vec![];
+    let unique = table
+        .indices
+        .iter()
+        .filter(|index| index.unique && index.state == TableState::Public);
+    for index in unique {
+        for (i, (name, _)) in index.columns.iter().enumerate() {
+            rows.push(vec![
+                text(CATALOG),
+                text(db),
+                text(&index_name(index)),
+                text(CATALOG),
+                text(db),
+                text(&table.name),
+                text(name),
+                EncodeValue::Int(i as i64 + 1),
+                EncodeValue::NULL,
+                EncodeValue::NULL,
+                EncodeValue::NULL,
+                EncodeValue::NULL,
+            ]);
+        }
+    }
+    for fk in table.foreign_keys.iter() {
+        let columns = fk.columns.iter().zip(fk.ref_columns.iter());
+        for (i, ((name, _), ref_name)) in columns.enumerate() {
+            rows.push(vec![
+                text(CATALOG),
+                text(db),
+                text(&fk.name),
+                text(CATALOG),
+                text(db),
+                text(&table.name),
+                text(name),
+                EncodeValue::Int(i as i64 + 1),
+                EncodeValue::Int(i as i64 + 1),
+                text(&fk.ref_db),
+                text(&fk.ref_table),
+                text(ref_name),
+            ]);
+        }
+    }
+    rows
+}
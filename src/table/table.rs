@@ -1,10 +1,14 @@
 use super::schema::*;
+use super::SystemTable;
 use crate::common::EncodeValue;
 use crate::errors::MySQLError;
 use crate::errors::MySQLResult;
+use crate::store::Storage;
 use crate::table::decoder::{get_handle_from_record_key, DecoderRow, EncoderRow};
+use crate::table::range::{prefix_next, IndexRange};
 use crate::transaction::TransactionContext;
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use sqlparser::ast::DataType;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -15,6 +19,7 @@ pub struct TableSource {
     column_map: HashMap<String, Arc<ColumnInfo>>,
     unique_index_map: HashMap<String, Arc<IndexInfo>>,
     valid: AtomicBool,
+    system: Option<SystemTable>,
 }
 
 impl TableSource {
@@ -22,7 +27,9 @@ impl TableSource {
         let mut column_map = HashMap::default();
         let mut unique_index_map = HashMap::default();
         for c in table.columns.iter() {
-            column_map.insert(c.name.clone(), c.clone());
+            if c.state == TableState::Public {
+                column_map.insert(c.name.clone(), c.clone());
+            }
         }
         for i in table.indices.iter() {
             if (i.unique || i.primary) && i.columns.len() == 1 {
@@ -35,9 +42,22 @@ impl TableSource {
             column_map,
             unique_index_map,
             valid: AtomicBool::new(true),
+            system: None,
+        }
+    }
+
+    /// A table of `information_schema`, which is never stored.
+    pub fn system(table: SystemTable) -> TableSource {
+        TableSource {
+            system: Some(table),
+            ..TableSource::new(Arc::new(table.table_info()))
         }
     }
 
+    pub fn system_table(&self) -> Option<SystemTable> {
+        self.system
+    }
+
     pub fn is_valid(&self) -> bool {
         self.valid.load(Ordering::Acquire)
     }
@@ -46,6 +66,18 @@ impl TableSource {
         self.valid.store(false, Ordering::Release);
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.meta.name
+    }
+
+    pub fn meta(&self) -> &TableInfo {
+        self.meta.as_ref()
+    }
+
     pub fn get_column(&self, name: &String) -> Option<Arc<ColumnInfo>> {
         self.column_map.get(name).map(|col| col.clone())
     }
@@ -58,15 +90,16 @@ impl TableSource {
         self.meta.as_ref().clone()
     }
 
+    /// Read a record by the values of all columns of the primary key.
     pub async fn read_record<W: TransactionContext>(
         &self,
         reader: &mut W,
         select_cols: &DataSchema,
-        handle: &EncodeValue,
+        values: &[EncodeValue],
     ) -> MySQLResult<Vec<EncodeValue>> {
         if let Some(primary_info) = self.meta.get_primary_index() {
             return self
-                .read_record_by_index(reader, primary_info.as_ref(), select_cols, handle)
+                .read_record_by_index(reader, primary_info.as_ref(), select_cols, values)
                 .await;
         }
         Err(MySQLError::NoIndex)
@@ -77,102 +110,384 @@ impl TableSource {
         reader: &mut W,
         primary_info: &IndexInfo,
         select_cols: &DataSchema,
-        handle: &EncodeValue,
+        values: &[EncodeValue],
     ) -> MySQLResult<Vec<EncodeValue>> {
-        let key = self.get_record_by_handle(primary_info, handle)?;
-        let value = reader.get(&key).await?;
-        let mut result = vec![];
-        match value {
-            Some(v) => {
-                let row = DecoderRow::from_bytes(v)?;
-                for col in select_cols.columns.iter() {
-                    match row.get_data(col.id as u32) {
-                        Some(Some(mut v)) => {
-                            result.push(EncodeValue::read_from(&mut v, &col.data_type)?);
-                        }
-                        _ => result.push(EncodeValue::NULL),
-                    }
+        let key = self.get_record_by_handle(primary_info, values)?;
+        match reader.get(&key).await? {
+            Some(v) => self.decode_record(v, select_cols),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Read a record by the encoded handle which is stored as the value of index entries.
+    pub async fn read_record_by_handle<W: TransactionContext>(
+        &self,
+        reader: &mut W,
+        select_cols: &DataSchema,
+        handle: &[u8],
+    ) -> MySQLResult<Option<Vec<EncodeValue>>> {
+        let key = self.record_key(handle)?;
+        match reader.get(&key).await? {
+            Some(v) => Ok(Some(self.decode_record(v, select_cols)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read at most `limit` records whose keys are in `[start, end)`. Return the records and
+    /// the key to continue the scan from, which is `None` once the range is exhausted.
+    pub async fn scan_record_batch<W: TransactionContext>(
+        &self,
+        reader: &mut W,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+        select_cols: &DataSchema,
+    ) -> MySQLResult<(Vec<Vec<EncodeValue>>, Option<Vec<u8>>)> {
+        let (kvs, next) = scan_batch(reader, start, end, limit).await?;
+        let mut rows = Vec::with_capacity(kvs.len());
+        for (_, v) in kvs {
+            rows.push(self.decode_record(v, select_cols)?);
+        }
+        Ok((rows, next))
+    }
+
+    /// Like `scan_record_batch`, but every record comes with its handle.
+    pub async fn scan_handle_record_batch<W: TransactionContext>(
+        &self,
+        reader: &mut W,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+        select_cols: &DataSchema,
+    ) -> MySQLResult<(Vec<(Vec<u8>, Vec<EncodeValue>)>, Option<Vec<u8>>)> {
+        let (kvs, next) = scan_batch(reader, start, end, limit).await?;
+        let mut rows = Vec::with_capacity(kvs.len());
+        for (k, v) in kvs {
+            let handle = get_handle_from_record_key(&k).to_vec();
+            rows.push((handle, self.decode_record(v, select_cols)?));
+        }
+        Ok((rows, next))
+    }
+
+    /// Read the handles of at most `limit` index entries whose keys are in `[start, end)`, and
+    /// the key to continue the scan from.
+    pub async fn scan_index_batch<W: TransactionContext>(
+        &self,
+        reader: &mut W,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<(Vec<Vec<u8>>, Option<Vec<u8>>)> {
+        let (kvs, next) = scan_batch(reader, start, end, limit).await?;
+        Ok((kvs.into_iter().map(|(_, handle)| handle).collect(), next))
+    }
+
+    /// The keys of the records if `index_info` is the primary key, or of the index entries
+    /// otherwise, whose first column of index is in `range`.
+    pub fn index_key_range(
+        &self,
+        index_info: &IndexInfo,
+        range: &IndexRange,
+    ) -> MySQLResult<(Vec<u8>, Vec<u8>)> {
+        let prefix = if index_info.primary {
+            self.record_prefix()?
+        } else {
+            self.index_prefix(index_info)?
+        };
+        self.encode_range(prefix, index_info, range)
+    }
+
+    /// The key range `[t{id}, t{id + 1})` which contains all records and indexes of this table.
+    pub fn table_range(&self) -> MySQLResult<(Vec<u8>, Vec<u8>)> {
+        let mut start = Vec::with_capacity(9);
+        start.push(b't');
+        start.write_u64::<LittleEndian>(self.id)?;
+        let end = prefix_next(&start);
+        Ok((start, end))
+    }
+
+    /// The key range `[t{id}r, t{id}s)` which contains all records of this table.
+    pub fn record_range(&self) -> MySQLResult<(Vec<u8>, Vec<u8>)> {
+        let start = self.record_prefix()?;
+        let end = prefix_next(&start);
+        Ok((start, end))
+    }
+
+    /// The key range which contains all entries of `index_info`.
+    pub fn index_range(&self, index_info: &IndexInfo) -> MySQLResult<(Vec<u8>, Vec<u8>)> {
+        let start = self.index_prefix(index_info)?;
+        let end = prefix_next(&start);
+        Ok((start, end))
+    }
+
+    /// The key `t{id}m` of the counter from which the `AUTO_INCREMENT` values are allocated, it
+    /// is in the range of the table so that it is deleted with the table.
+    pub fn auto_id_key(&self) -> MySQLResult<Vec<u8>> {
+        let mut key = Vec::with_capacity(10);
+        key.push(b't');
+        key.write_u64::<LittleEndian>(self.id)?;
+        key.push(b'm');
+        Ok(key)
+    }
+
+    /// The key `t{id}h` of the counter from which the hidden `_rowid` of a table without primary
+    /// key is allocated.
+    pub fn row_id_key(&self) -> MySQLResult<Vec<u8>> {
+        let mut key = Vec::with_capacity(10);
+        key.push(b't');
+        key.write_u64::<LittleEndian>(self.id)?;
+        key.push(b'h');
+        Ok(key)
+    }
+
+    /// Allocate the `_rowid` of a new record, or return `None` if the records are stored by the
+    /// primary key.
+    pub async fn alloc_row_id(&self, storage: &Arc<dyn Storage>) -> MySQLResult<Option<u64>> {
+        if self.meta.get_primary_index().is_some() {
+            return Ok(None);
+        }
+        let key = self.row_id_key()?;
+        Ok(Some(self.meta.row_id.alloc(storage, &key, 1, 1).await?))
+    }
+
+    /// The largest `_rowid` which any server may have allocated.
+    pub async fn row_id_end(&self, storage: &Arc<dyn Storage>) -> MySQLResult<u64> {
+        let key = self.row_id_key()?;
+        self.meta.row_id.end(storage, &key).await
+    }
+
+    /// Allocate a value of the `AUTO_INCREMENT` column, see `AutoIdAllocator::alloc`.
+    pub async fn alloc_auto_id(
+        &self,
+        storage: &Arc<dyn Storage>,
+        increment: u64,
+        offset: u64,
+    ) -> MySQLResult<u64> {
+        let key = self.auto_id_key()?;
+        self.meta
+            .auto_id
+            .alloc(storage, &key, increment, offset)
+            .await
+    }
+
+    /// Make the `AUTO_INCREMENT` values allocated later larger than `value`.
+    pub async fn rebase_auto_id(&self, storage: &Arc<dyn Storage>, value: u64) -> MySQLResult<()> {
+        let key = self.auto_id_key()?;
+        self.meta.auto_id.rebase(storage, &key, value).await
+    }
+
+    /// The largest `AUTO_INCREMENT` value which any server may have allocated.
+    pub async fn auto_id_end(&self, storage: &Arc<dyn Storage>) -> MySQLResult<u64> {
+        let key = self.auto_id_key()?;
+        self.meta.auto_id.end(storage, &key).await
+    }
+
+    fn encode_range(
+        &self,
+        prefix: Vec<u8>,
+        index_info: &IndexInfo,
+        range: &IndexRange,
+    ) -> MySQLResult<(Vec<u8>, Vec<u8>)> {
+        let col = self.meta.columns[index_info.columns[0].1].as_ref();
+        let start = match &range.low {
+            None => prefix.clone(),
+            Some((v, inclusive)) => {
+                let mut key = prefix.clone();
+                v.convert_to(&col.data_type)
+                    .encode_comparable(&mut key, &col.data_type)?;
+                if *inclusive {
+                    key
+                } else {
+                    prefix_next(&key)
                 }
             }
-            None => {
-                return Ok(vec![]);
+        };
+        let end = match &range.high {
+            None => prefix_next(&prefix),
+            Some((v, inclusive)) => {
+                let mut key = prefix;
+                v.convert_to(&col.data_type)
+                    .encode_comparable(&mut key, &col.data_type)?;
+                if *inclusive {
+                    prefix_next(&key)
+                } else {
+                    key
+                }
+            }
+        };
+        Ok((start, end))
+    }
+
+    /// Decode the values of `select_cols` from a stored record. The virtual columns are not
+    /// stored, if any of them is selected the whole record is decoded to compute them.
+    pub fn decode_record(
+        &self,
+        value: Vec<u8>,
+        select_cols: &DataSchema,
+    ) -> MySQLResult<Vec<EncodeValue>> {
+        let row = DecoderRow::from_bytes(value)?;
+        if !select_cols.columns.iter().any(|col| col.is_virtual()) {
+            return select_cols
+                .columns
+                .iter()
+                .map(|col| decode_column(&row, col))
+                .collect();
+        }
+        let mut record = self
+            .meta
+            .columns
+            .iter()
+            .map(|col| decode_column(&row, col))
+            .collect::<MySQLResult<Vec<_>>>()?;
+        for col in self.meta.columns.iter() {
+            if let Some(generated) = col.generated.as_ref().filter(|g| !g.stored) {
+                let value = generated.evaluator.eval(&self.meta, &record)?;
+                record[col.offset] = value.convert_to(&col.data_type);
+            }
+        }
+        let mut result = Vec::with_capacity(select_cols.columns.len());
+        for col in select_cols.columns.iter() {
+            match self.meta.columns.iter().find(|c| c.id == col.id) {
+                Some(c) => result.push(record[c.offset].clone()),
+                None => result.push(decode_column(&row, col)?),
             }
         }
         Ok(result)
     }
 
+    /// Read the handle of the record from an unique index by the values of all its columns.
     pub async fn read_handle_from_index<W: TransactionContext>(
         &self,
         reader: &mut W,
         index_info: &IndexInfo,
-        index: &EncodeValue,
-    ) -> MySQLResult<Option<EncodeValue>> {
+        values: &[EncodeValue],
+    ) -> MySQLResult<Option<Vec<u8>>> {
         let mut index_key = Vec::with_capacity(self.get_handle_size());
-        self.encode_index_key(&mut index_key, index_info, &[index.clone()])?;
-        match reader.get(&index_key).await? {
-            None => Ok(None),
-            Some(v) => {
-                let col = self.meta.columns[index_info.columns[0].1].as_ref();
-                Ok(Some(EncodeValue::read_from(
-                    &mut v.as_ref(),
-                    &col.data_type,
-                )?))
-            }
+        index_key.extend_from_slice(&self.index_prefix(index_info)?);
+        self.encode_index_values(&mut index_key, index_info, values)?;
+        reader.get(&index_key).await
+    }
+
+    /// Whether a row has `values` in the columns of `index_info`, which is the primary key or a
+    /// unique index.
+    pub async fn contains_key<W: TransactionContext>(
+        &self,
+        reader: &mut W,
+        index_info: &IndexInfo,
+        values: &[EncodeValue],
+    ) -> MySQLResult<bool> {
+        if index_info.primary {
+            let key = self.get_record_by_handle(index_info, values)?;
+            return Ok(reader.get(&key).await?.is_some());
         }
+        Ok(self
+            .read_handle_from_index(reader, index_info, values)
+            .await?
+            .is_some())
     }
 
+    /// Write a record in strict mode, see `build_record`.
     pub async fn add_record<W: TransactionContext>(
         &self,
         writer: &mut W,
         row: &mut EncoderRow,
         vcols: &[Arc<ColumnInfo>],
         values: Vec<EncodeValue>,
+        row_id: Option<u64>,
     ) -> MySQLResult<Vec<u8>> {
+        let record = self.build_record(vcols, values, true, &mut vec![])?;
+        self.write_record(writer, row, record, row_id).await
+    }
+
+    /// Arrange the values of the columns `vcols` as the order of columns in table, the columns
+    /// left out take their default values and the generated columns are computed, whatever is
+    /// given to them is ignored. The values are converted to the types of their columns, what
+    /// does not fit fails in `strict` mode or becomes a warning otherwise.
+    pub fn build_record(
+        &self,
+        vcols: &[Arc<ColumnInfo>],
+        values: Vec<EncodeValue>,
+        strict: bool,
+        warnings: &mut Vec<MySQLError>,
+    ) -> MySQLResult<Vec<EncodeValue>> {
         let mut offsets = vec![values.len(); self.meta.columns.len()];
         for i in 0..vcols.len() {
             offsets[vcols[i].offset] = i;
         }
-        let mut default_values = vec![];
+        // Arrange the values as the order of columns in table.
+        let mut record = Vec::with_capacity(self.meta.columns.len());
         for col in self.meta.columns.iter() {
+            if col.generated.is_some() {
+                record.push(EncodeValue::NULL);
+                continue;
+            }
             let idx = offsets[col.offset];
-            if idx >= values.len() {
-                if let Some(generator) = col.default_value.as_ref() {
-                    offsets[col.offset] = values.len() + default_values.len();
-                    default_values.push(generator.generate());
+            let value = if idx < values.len() {
+                values[idx].clone()
+            } else if col.state == TableState::DeleteOnly {
+                EncodeValue::NULL
+            } else if let Some(generator) = col.default_value.as_ref() {
+                generator.generate()
+            } else if col.state == TableState::WriteOnly || !col.not_null {
+                EncodeValue::NULL
+            } else if strict {
+                return Err(MySQLError::NoDefault(col.name.clone()));
+            } else {
+                warnings.push(MySQLError::NoDefault(col.name.clone()));
+                col.implicit_default()
+            };
+            if col.state == TableState::Public {
+                record.push(col.convert_value(value, strict, warnings)?);
+            } else {
+                record.push(value.convert_to(&col.data_type));
+            }
+        }
+        // A generated column reads the columns after it as well, it is computed once all of
+        // them are in the record. It may read the generated columns before it.
+        for col in self.meta.columns.iter() {
+            if let Some(generated) = col.generated.as_ref() {
+                let value = generated.evaluator.eval(&self.meta, &record)?;
+                record[col.offset] = if col.state == TableState::Public {
+                    col.convert_value(value, strict, warnings)?
                 } else {
-                    return Err(MySQLError::MissColumn(format!("Miss column {}", col.name)));
-                }
+                    value.convert_to(&col.data_type)
+                };
             }
         }
+        Ok(record)
+    }
 
-        let key = self.get_record_key(&values, &default_values, &offsets)?;
+    /// Write a record built by `build_record` and its index entries, return its handle. `row_id`
+    /// is the `_rowid` allocated for the record if the table has no primary key.
+    pub async fn write_record<W: TransactionContext>(
+        &self,
+        writer: &mut W,
+        row: &mut EncoderRow,
+        record: Vec<EncodeValue>,
+        row_id: Option<u64>,
+    ) -> MySQLResult<Vec<u8>> {
+        let key = self.get_record_key(&record, row_id)?;
         if writer.check_constants(&key).await? {
-            return Err(MySQLError::KeyExist);
+            return Err(match self.meta.get_primary_index() {
+                Some(pk) => duplicate_entry(&pk, &record),
+                None => MySQLError::DupEntry(row_id.unwrap_or(0).to_string(), "PRIMARY".into()),
+            });
         }
 
         for col in self.meta.columns.iter() {
-            let idx = offsets[col.offset];
-            if idx < values.len() {
-                row.append_column(col.id as u32, &values[idx], &col.data_type)?;
-            } else {
-                row.append_column(
-                    col.id as u32,
-                    &default_values[idx - values.len()],
-                    &col.data_type,
-                )?;
+            if col.state != TableState::DeleteOnly && !col.is_virtual() {
+                row.append_column(col.id as u32, &record[col.offset], &col.data_type)?;
             }
         }
 
         let handle = get_handle_from_record_key(&key);
 
-        let mut index_key = Vec::with_capacity(self.get_handle_size());
         for index in self.meta.indices.iter() {
-            if index.primary {
+            if index.primary || index.state == TableState::DeleteOnly {
                 continue;
             }
-            self.encode_index_key(&mut index_key, index.as_ref(), &values)?;
-            writer.write(&index_key, handle).await?;
-            index_key.clear();
+            self.add_index_entry(writer, index.as_ref(), handle, &record)
+                .await?;
         }
 
         let value = row.to_bytes()?;
@@ -180,57 +495,123 @@ impl TableSource {
         Ok(handle.to_vec())
     }
 
-    fn get_record_by_handle(&self, info: &IndexInfo, handle: &EncodeValue) -> MySQLResult<Vec<u8>> {
+    /// Write the entry of `index_info` for a record which is already stored, `record` holds the
+    /// values of all columns in the order of the table, with the virtual columns computed as
+    /// `decode_record` does. It fails if another record has the same values in a unique index.
+    pub async fn add_index_entry<W: TransactionContext>(
+        &self,
+        writer: &mut W,
+        index_info: &IndexInfo,
+        handle: &[u8],
+        record: &[EncodeValue],
+    ) -> MySQLResult<()> {
+        let mut index_key = Vec::with_capacity(self.get_handle_size());
+        let unique = self.encode_index_key(&mut index_key, index_info, record, handle)?;
+        if unique {
+            if let Some(other) = writer.get(&index_key).await? {
+                if other != handle {
+                    return Err(duplicate_entry(index_info, record));
+                }
+            }
+        }
+        writer.write(&index_key, handle).await
+    }
+
+    fn record_prefix(&self) -> MySQLResult<Vec<u8>> {
         let mut key = Vec::with_capacity(self.get_handle_size());
         key.push(b't');
         key.write_u64::<LittleEndian>(self.id)?;
         key.push(b'r');
-        let col = self.meta.columns[info.columns[0].1].as_ref();
-        handle.encode_comparable(&mut key, &col.data_type)?;
         Ok(key)
     }
 
-    fn get_record_key(
+    fn record_key(&self, handle: &[u8]) -> MySQLResult<Vec<u8>> {
+        let mut key = self.record_prefix()?;
+        key.extend_from_slice(handle);
+        Ok(key)
+    }
+
+    fn index_prefix(&self, index_info: &IndexInfo) -> MySQLResult<Vec<u8>> {
+        let mut key = Vec::with_capacity(self.get_handle_size());
+        key.push(b't');
+        key.write_u64::<LittleEndian>(self.id)?;
+        key.push(b'i');
+        key.write_u64::<BigEndian>(index_info.id)?;
+        Ok(key)
+    }
+
+    fn get_record_by_handle(
         &self,
+        info: &IndexInfo,
         values: &[EncodeValue],
-        default_values: &[EncodeValue],
-        offsets: &[usize],
     ) -> MySQLResult<Vec<u8>> {
-        if let Some(pk_index) = self.meta.get_primary_index() {
-            let mut key = Vec::with_capacity(self.get_handle_size());
-            key.push(b't');
-            key.write_u64::<LittleEndian>(self.id)?;
-            key.push(b'r');
-            for (_, offset) in pk_index.columns.iter() {
-                let col = self.meta.columns[*offset as usize].clone();
-                let idx = offsets[col.offset];
-                if idx < values.len() {
-                    values[idx].encode_comparable(&mut key, &col.data_type)?;
-                } else {
-                    default_values[idx - values.len()]
-                        .encode_comparable(&mut key, &col.data_type)?;
+        let mut key = self.record_prefix()?;
+        self.encode_index_values(&mut key, info, values)?;
+        Ok(key)
+    }
+
+    /// Append `values` converted to the types of the columns of `index_info` to `key`.
+    fn encode_index_values(
+        &self,
+        key: &mut Vec<u8>,
+        index_info: &IndexInfo,
+        values: &[EncodeValue],
+    ) -> MySQLResult<()> {
+        if values.len() != index_info.columns.len() {
+            return Err(MySQLError::NoIndex);
+        }
+        for ((_, offset), value) in index_info.columns.iter().zip(values) {
+            let col = self.meta.columns[*offset].as_ref();
+            value
+                .convert_to(&col.data_type)
+                .encode_comparable(key, &col.data_type)?;
+        }
+        Ok(())
+    }
+
+    /// The key of a new record, which is made of the columns of the primary key. A table
+    /// without primary key stores its records by a hidden `_rowid`, see `alloc_row_id`.
+    fn get_record_key(&self, record: &[EncodeValue], row_id: Option<u64>) -> MySQLResult<Vec<u8>> {
+        let mut key = self.record_prefix()?;
+        match self.meta.get_primary_index() {
+            Some(pk_index) => {
+                for (_, offset) in pk_index.columns.iter() {
+                    let col = self.meta.columns[*offset].as_ref();
+                    record[*offset].encode_comparable(&mut key, &col.data_type)?;
                 }
             }
-            return Ok(key);
+            None => {
+                let row_id = row_id.ok_or(MySQLError::UnsupportSQL)?;
+                EncodeValue::Int(row_id as i64).encode_comparable(&mut key, &DataType::BigInt)?;
+            }
         }
-        Err(MySQLError::NoIndex)
+        Ok(key)
     }
 
+    /// The key of an index entry is `t{table id}i{index id}{column values}`, the handle is
+    /// appended to the key of a non-unique index so that rows with the same values do not
+    /// overwrite each other. A unique index allows any rows with NULL in its columns, so their
+    /// keys carry the handle too. Return whether the key is unique to the values.
     fn encode_index_key(
         &self,
         index_key: &mut Vec<u8>,
         index_info: &IndexInfo,
-        values: &[EncodeValue],
-    ) -> MySQLResult<()> {
+        record: &[EncodeValue],
+        handle: &[u8],
+    ) -> MySQLResult<bool> {
         index_key.clear();
-        index_key.push(b't');
-        index_key.write_u64::<LittleEndian>(self.id)?;
-        index_key.push(b'i');
+        index_key.extend_from_slice(&self.index_prefix(index_info)?);
+        let mut has_null = false;
         for (_, offset) in index_info.columns.iter() {
-            let col = self.meta.columns[*offset as usize].clone();
-            values[col.offset].encode_comparable(index_key, &col.data_type)?;
+            let col = self.meta.columns[*offset].as_ref();
+            has_null |= record[*offset].is_null();
+            record[*offset].encode_comparable(index_key, &col.data_type)?;
         }
-        Ok(())
+        let unique = index_info.unique && !has_null;
+        if !unique {
+            index_key.extend_from_slice(handle);
+        }
+        Ok(unique)
     }
 
     fn get_handle_size(&self) -> usize {
@@ -238,10 +619,60 @@ impl TableSource {
     }
 }
 
+/// Decode the value of `col` from a stored record.
+fn decode_column(row: &DecoderRow, col: &ColumnInfo) -> MySQLResult<EncodeValue> {
+    match row.get_data(col.id as u32) {
+        Some(Some(mut v)) => EncodeValue::read_from(&mut v, &col.data_type),
+        Some(None) => Ok(EncodeValue::NULL),
+        // The record was written before the column was added.
+        None => Ok(col.origin_default.clone()),
+    }
+}
+
+/// Read at most `limit` key-value pairs in `[start, end)`, the key to continue from is the
+/// smallest key after the last one read.
+async fn scan_batch<W: TransactionContext>(
+    reader: &mut W,
+    start: &[u8],
+    end: &[u8],
+    limit: usize,
+) -> MySQLResult<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>)> {
+    if start >= end {
+        return Ok((vec![], None));
+    }
+    let kvs = reader.scan(start, end, limit).await?;
+    let next = match kvs.last() {
+        Some((key, _)) if kvs.len() >= limit => {
+            let mut next = key.clone();
+            next.push(0);
+            Some(next)
+        }
+        _ => None,
+    };
+    Ok((kvs, next))
+}
+
+/// The error of a record whose values in the columns of `index_info` are already taken, the
+/// values are joined by `-` and the primary key is named `PRIMARY` as MySQL does.
+fn duplicate_entry(index_info: &IndexInfo, record: &[EncodeValue]) -> MySQLError {
+    let values: Vec<String> = index_info
+        .columns
+        .iter()
+        .map(|(_, offset)| String::from(record[*offset].clone()))
+        .collect();
+    let name = if index_info.primary {
+        "PRIMARY".to_string()
+    } else {
+        index_info.name.clone()
+    };
+    MySQLError::DupEntry(values.join("-"), name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::common::EncodeValue;
+    use crate::table::auto_id::AutoIdAllocator;
     use sqlparser::ast::DataType;
     use tokio::runtime;
 
@@ -270,6 +701,21 @@ mod tests {
             assert_eq!(key.to_vec(), self.expected_key);
             Ok(self.expected_value.clone())
         }
+
+        async fn scan(
+            &mut self,
+            start: &[u8],
+            end: &[u8],
+            limit: usize,
+        ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+            Ok(self
+                .kvs
+                .iter()
+                .filter(|(k, _)| k.as_slice() >= start && k.as_slice() < end)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
     }
 
     fn create_table_source() -> TableSource {
@@ -299,6 +745,9 @@ mod tests {
                     comment: "".to_string(),
                     key,
                     not_null: false,
+                    state: TableState::Public,
+                    origin_default: EncodeValue::NULL,
+                    generated: None,
                 })
             })
             .collect();
@@ -314,12 +763,16 @@ mod tests {
                 primary: true,
                 unique: false,
             })],
+            foreign_keys: vec![],
+            checks: vec![],
             columns,
             state: TableState::Public,
             pk_is_handle: true,
             max_column_id: 5,
             max_index_id: 1,
-            max_row_id: Arc::new(Default::default()),
+            row_id: Arc::new(AutoIdAllocator::new(0)),
+            auto_id: Arc::new(AutoIdAllocator::new(0)),
+            modify_count: Arc::new(Default::default()),
             update_ts: 0,
         }))
     }
@@ -343,7 +796,7 @@ mod tests {
         ];
         let mut r = runtime::Runtime::new().unwrap();
         let handle = r
-            .block_on(table.add_record(&mut ctx, &mut row, &cols, values.clone()))
+            .block_on(table.add_record(&mut ctx, &mut row, &cols, values.clone(), None))
             .unwrap();
         assert_eq!(ctx.kvs.len(), 1);
         let value = ctx.kvs[0].1.clone();
@@ -360,7 +813,7 @@ mod tests {
                 &DataSchema {
                     columns: cols.clone(),
                 },
-                &EncodeValue::Int(1),
+                &[EncodeValue::Int(1)],
             ))
             .unwrap();
         assert_eq!(v, values);
@@ -370,7 +823,7 @@ mod tests {
                 &DataSchema {
                     columns: vec![cols[1].clone(), cols[4].clone(), cols[2].clone()],
                 },
-                &EncodeValue::Int(1),
+                &[EncodeValue::Int(1)],
             ))
             .unwrap();
         assert_eq!(
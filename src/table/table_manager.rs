@@ -1,37 +1,288 @@
-use crate::table::schema::TableInfo;
+use crate::errors::{MySQLError, MySQLResult};
+use crate::statistics::{AutoAnalyze, TableStatistics};
+use crate::table::schema::{ForeignKeyInfo, TableInfo, ViewInfo};
 use crate::table::table::TableSource;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// The database which a new server holds.
+pub const DEFAULT_DATABASE: &str = "test";
+
 pub struct DBTableManager {
-    tables: HashMap<String, Arc<TableSource>>,
+    /// The tables of every database by their names.
+    databases: HashMap<String, HashMap<String, Arc<TableSource>>>,
+    /// The views of every database by their names, a view can not have the name of a table.
+    views: HashMap<String, HashMap<String, Arc<ViewInfo>>>,
+    stats: HashMap<u64, Arc<TableStatistics>>,
+    /// The automatic analyzes of the tables in background, by table id.
+    auto_analyzes: HashMap<u64, AutoAnalyze>,
     max_table_id: u64,
+    /// Increased whenever a table is created or changed, plans cached with an old version are
+    /// never used again.
+    schema_version: u64,
 }
 
 impl DBTableManager {
     pub fn new() -> DBTableManager {
+        let mut databases = HashMap::default();
+        databases.insert(DEFAULT_DATABASE.to_string(), HashMap::default());
+        let mut views = HashMap::default();
+        views.insert(DEFAULT_DATABASE.to_string(), HashMap::default());
         DBTableManager {
             max_table_id: 0,
-            tables: HashMap::default(),
+            schema_version: 0,
+            databases,
+            views,
+            stats: HashMap::default(),
+            auto_analyzes: HashMap::default(),
+        }
+    }
+
+    pub fn has_database(&self, db: &str) -> bool {
+        self.databases.contains_key(db)
+    }
+
+    pub fn create_database(&mut self, db: String) -> MySQLResult<()> {
+        if self.databases.contains_key(&db) {
+            return Err(MySQLError::DBExists(db));
         }
+        self.views.insert(db.clone(), HashMap::default());
+        self.databases.insert(db, HashMap::default());
+        self.schema_version += 1;
+        Ok(())
     }
 
-    pub fn add_table(&mut self, name: String, mut table_info: TableInfo) -> Arc<TableSource> {
+    /// Remove a database with all its tables, which are returned so that their data can be
+    /// deleted.
+    pub fn drop_database(&mut self, db: &str) -> MySQLResult<Vec<Arc<TableSource>>> {
+        let tables = self
+            .databases
+            .remove(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?;
+        self.views.remove(db);
+        for table in tables.values() {
+            self.stats.remove(&table.id());
+            self.auto_analyzes.remove(&table.id());
+            table.invalid();
+        }
+        self.schema_version += 1;
+        Ok(tables.into_iter().map(|(_, table)| table).collect())
+    }
+
+    pub fn add_table(
+        &mut self,
+        db: &str,
+        name: String,
+        mut table_info: TableInfo,
+    ) -> MySQLResult<Arc<TableSource>> {
+        if self.get_view(db, &name).is_some() {
+            return Err(MySQLError::TableExists(name));
+        }
+        let tables = self
+            .databases
+            .get_mut(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?;
         self.max_table_id += 1;
         table_info.id = self.max_table_id;
+        self.schema_version += 1;
         let table = Arc::new(TableSource::new(Arc::new(table_info)));
-        self.tables.insert(name.clone(), table.clone());
-        table
+        tables.insert(name, table.clone());
+        Ok(table)
+    }
+
+    /// Reserve an id for a table which replaces another one later.
+    pub fn alloc_table_id(&mut self) -> u64 {
+        self.max_table_id += 1;
+        self.max_table_id
     }
-    pub fn replace_table(&mut self, name: String, table_info: TableInfo) -> Arc<TableSource> {
+
+    pub fn replace_table(
+        &mut self,
+        db: &str,
+        name: String,
+        table_info: TableInfo,
+    ) -> MySQLResult<Arc<TableSource>> {
+        let tables = self
+            .databases
+            .get_mut(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?;
         let table = Arc::new(TableSource::new(Arc::new(table_info)));
-        if let Some(t) = self.tables.insert(name.clone(), table.clone()) {
+        self.schema_version += 1;
+        if let Some(t) = tables.insert(name, table.clone()) {
             t.invalid();
         }
-        table
+        Ok(table)
+    }
+
+    /// Remove a table and return it, so that its data can be deleted.
+    pub fn drop_table(&mut self, db: &str, name: &str) -> MySQLResult<Arc<TableSource>> {
+        let table = self
+            .databases
+            .get_mut(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?
+            .remove(name)
+            .ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, name)))?;
+        self.stats.remove(&table.id());
+        self.auto_analyzes.remove(&table.id());
+        table.invalid();
+        self.schema_version += 1;
+        Ok(table)
+    }
+
+    /// Move a table to another name, which may be in another database. The table keeps its id,
+    /// so its data stays where it is.
+    pub fn rename_table(
+        &mut self,
+        db: &str,
+        name: &str,
+        new_db: &str,
+        new_name: String,
+    ) -> MySQLResult<()> {
+        let exists = self
+            .databases
+            .get(new_db)
+            .ok_or_else(|| MySQLError::NoDB(new_db.to_string()))?
+            .contains_key(&new_name);
+        if exists || self.get_view(new_db, &new_name).is_some() {
+            return Err(MySQLError::TableExists(new_name));
+        }
+        let old = self
+            .databases
+            .get_mut(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?
+            .remove(name)
+            .ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, name)))?;
+        let mut table_info = old.clone_meta();
+        table_info.name = new_name.clone();
+        old.invalid();
+        let table = Arc::new(TableSource::new(Arc::new(table_info)));
+        self.databases
+            .get_mut(new_db)
+            .unwrap()
+            .insert(new_name, table);
+        self.schema_version += 1;
+        Ok(())
+    }
+
+    /// Replace a table by an empty one with a new id and a new `AUTO_INCREMENT` counter. The
+    /// old table is returned so that its data can be deleted.
+    pub fn truncate_table(&mut self, db: &str, name: &str) -> MySQLResult<Arc<TableSource>> {
+        let old = self
+            .get_table(db, name)
+            .ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, name)))?;
+        let mut table_info = old.clone_meta();
+        table_info.reset_auto_increment();
+        table_info.modify_count = Arc::new(Default::default());
+        self.add_table(db, name.to_string(), table_info)?;
+        self.stats.remove(&old.id());
+        self.auto_analyzes.remove(&old.id());
+        old.invalid();
+        Ok(old)
+    }
+
+    pub fn get_table(&self, db: &str, name: &str) -> Option<Arc<TableSource>> {
+        self.databases.get(db)?.get(name).cloned()
+    }
+
+    /// A foreign key of another table which references the table `db.name`, the tables in
+    /// `ignored` are left out.
+    pub fn find_reference(
+        &self,
+        db: &str,
+        name: &str,
+        ignored: &[(String, String)],
+    ) -> Option<Arc<ForeignKeyInfo>> {
+        for (child_db, tables) in self.databases.iter() {
+            for (child, table) in tables.iter() {
+                if (child_db == db && child == name)
+                    || ignored.iter().any(|(d, n)| d == child_db && n == child)
+                {
+                    continue;
+                }
+                let foreign_keys = table.meta().foreign_keys.iter();
+                if let Some(fk) = foreign_keys.find(|fk| fk.ref_db == db && fk.ref_table == name) {
+                    return Some(fk.clone());
+                }
+            }
+        }
+        None
+    }
+
+    /// Create a view, or replace the view of the same name if `or_replace`.
+    pub fn create_view(&mut self, db: &str, view: ViewInfo, or_replace: bool) -> MySQLResult<()> {
+        let is_table = self
+            .databases
+            .get(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?
+            .contains_key(&view.name);
+        let views = self.views.get_mut(db).unwrap();
+        if is_table || (views.contains_key(&view.name) && !or_replace) {
+            return Err(MySQLError::TableExists(view.name));
+        }
+        views.insert(view.name.clone(), Arc::new(view));
+        self.schema_version += 1;
+        Ok(())
+    }
+
+    pub fn drop_view(&mut self, db: &str, name: &str) -> MySQLResult<()> {
+        if self.get_table(db, name).is_some() {
+            return Err(MySQLError::NotView(format!("{}.{}", db, name)));
+        }
+        self.views
+            .get_mut(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?
+            .remove(name)
+            .ok_or_else(|| MySQLError::NoTable(format!("{}.{}", db, name)))?;
+        self.schema_version += 1;
+        Ok(())
+    }
+
+    pub fn get_view(&self, db: &str, name: &str) -> Option<Arc<ViewInfo>> {
+        self.views.get(db)?.get(name).cloned()
+    }
+
+    /// The names of the views of `db` in alphabetical order.
+    pub fn view_names(&self, db: &str) -> MySQLResult<Vec<String>> {
+        let views = self
+            .views
+            .get(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?;
+        let mut names: Vec<String> = views.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// The names of the databases in alphabetical order.
+    pub fn database_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.databases.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// The names of the tables of `db` in alphabetical order.
+    pub fn table_names(&self, db: &str) -> MySQLResult<Vec<String>> {
+        let tables = self
+            .databases
+            .get(db)
+            .ok_or_else(|| MySQLError::NoDB(db.to_string()))?;
+        let mut names: Vec<String> = tables.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn schema_version(&self) -> u64 {
+        self.schema_version
+    }
+
+    pub fn update_stats(&mut self, stats: TableStatistics) {
+        self.stats.insert(stats.table_id, Arc::new(stats));
+    }
+
+    pub fn get_stats(&self, table_id: u64) -> Option<Arc<TableStatistics>> {
+        self.stats.get(&table_id).cloned()
     }
 
-    pub fn get_table(&self, name: &String) -> Option<Arc<TableSource>> {
-        self.tables.get(name).map(|t| t.clone())
+    pub fn auto_analyze_mut(&mut self, table_id: u64) -> &mut AutoAnalyze {
+        self.auto_analyzes.entry(table_id).or_default()
     }
 }
@@ -20,7 +20,7 @@ impl TransactionContext for AutoCommitContext {
     }
 
     async fn write(&mut self, key: &[u8], value: &[u8]) -> MySQLResult<()> {
-        let opts = TransactionOptions { pessimistic: false };
+        let opts = TransactionOptions::default();
         let mut txn = self.storage.new_transaction(&opts).await?;
         txn.put(key, value).await?;
         txn.commit().await
@@ -33,4 +33,15 @@ impl TransactionContext for AutoCommitContext {
     async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
         self.storage.get(key).await
     }
+
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let opts = TransactionOptions::default();
+        let mut txn = self.storage.new_transaction(&opts).await?;
+        txn.scan(start, end, limit).await
+    }
 }
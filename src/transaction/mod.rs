@@ -12,4 +12,10 @@ pub trait TransactionContext: Send {
     async fn write(&mut self, key: &[u8], value: &[u8]) -> MySQLResult<()>;
     async fn commit(&mut self) -> MySQLResult<()>;
     async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>>;
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>>;
 }
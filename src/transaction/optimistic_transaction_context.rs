@@ -33,4 +33,13 @@ impl TransactionContext for OptimisticTransactionContext {
     async fn get(&mut self, key: &[u8]) -> MySQLResult<Option<Vec<u8>>> {
         self.txn.get(key).await
     }
+
+    async fn scan(
+        &mut self,
+        start: &[u8],
+        end: &[u8],
+        limit: usize,
+    ) -> MySQLResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.txn.scan(start, end, limit).await
+    }
 }